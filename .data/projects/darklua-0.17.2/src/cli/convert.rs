@@ -3,9 +3,11 @@ use crate::cli::{CommandResult, GlobalOptions};
 use anstyle::Style;
 use clap::Args;
 use darklua_core::{DarkluaError, Resources};
-use std::{ffi::OsStr, path::PathBuf, str::FromStr, time::Instant};
+use serde::Serialize;
+use std::{ffi::OsStr, fs, path::PathBuf, str::FromStr, time::Instant};
 
 use super::error::CliError;
+use super::utils::{decode_message_pack, parse_delimited_values};
 
 #[derive(Debug, Args)]
 pub struct Options {
@@ -13,9 +15,12 @@ pub struct Options {
     input: PathBuf,
     /// Path where to write the Lua file
     output: Option<PathBuf>,
-    /// Data format ('json', 'yaml' or 'toml')
+    /// Data format (json, yaml, toml, csv, tsv, txt or msgpack)
     #[arg(short, long)]
     format: Option<DataFormat>,
+    /// Export a Luau type with this name and freeze the data tables
+    #[arg(long)]
+    export_type: Option<String>,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -23,6 +28,10 @@ enum DataFormat {
     Json,
     Yaml,
     Toml,
+    Csv,
+    Tsv,
+    Text,
+    MessagePack,
 }
 
 impl FromStr for DataFormat {
@@ -33,8 +42,12 @@ impl FromStr for DataFormat {
             "json" | "json5" => Ok(Self::Json),
             "yml" | "yaml" => Ok(Self::Yaml),
             "toml" => Ok(Self::Toml),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            "txt" | "text" => Ok(Self::Text),
+            "msgpack" | "mpk" => Ok(Self::MessagePack),
             _ => Err(format!(
-                "invalid data format '{}' (possible options are: 'json', 'json5', 'yml' or 'toml')",
+                "invalid data format '{}' (possible options are: 'json', 'json5', 'yml', 'toml', 'csv', 'tsv', 'txt' or 'msgpack')",
                 format
             )),
         }
@@ -51,8 +64,6 @@ pub fn run(options: &Options, _: &GlobalOptions) -> CommandResult {
 fn convert_data(options: &Options) -> Result<(), DarkluaError> {
     let resources = Resources::from_file_system();
 
-    let input = resources.get(&options.input).map_err(DarkluaError::from)?;
-
     let format = options
         .format
         .ok_or_else(|| DarkluaError::custom("unable to find data format"))
@@ -78,16 +89,48 @@ fn convert_data(options: &Options) -> Result<(), DarkluaError> {
 
     let convert_start_time = Instant::now();
 
+    let export_type = options.export_type.as_deref();
+
+    let read_input = || resources.get(&options.input).map_err(DarkluaError::from);
+
     let lua_code = match format {
-        DataFormat::Json => darklua_core::convert_data(
-            json5::from_str::<serde_json::Value>(&input).map_err(DarkluaError::from)?,
+        DataFormat::Json => convert(
+            json5::from_str::<serde_json::Value>(&read_input()?).map_err(DarkluaError::from)?,
+            export_type,
         ),
-        DataFormat::Yaml => darklua_core::convert_data(
-            serde_yaml::from_str::<serde_yaml::Value>(&input).map_err(DarkluaError::from)?,
+        DataFormat::Yaml => convert(
+            serde_yaml::from_str::<serde_yaml::Value>(&read_input()?)
+                .map_err(DarkluaError::from)?,
+            export_type,
         ),
-        DataFormat::Toml => darklua_core::convert_data(
-            toml::from_str::<toml::Value>(&input).map_err(DarkluaError::from)?,
+        DataFormat::Toml => convert(
+            toml::from_str::<toml::Value>(&read_input()?).map_err(DarkluaError::from)?,
+            export_type,
         ),
+        DataFormat::Csv => convert(
+            read_delimited_values(&read_input()?, b',', "csv")?,
+            export_type,
+        ),
+        DataFormat::Tsv => convert(
+            read_delimited_values(&read_input()?, b'\t', "tsv")?,
+            export_type,
+        ),
+        DataFormat::Text => convert(read_input()?, export_type),
+        DataFormat::MessagePack => {
+            let input = fs::read(&options.input).map_err(|err| {
+                DarkluaError::custom(format!(
+                    "unable to read `{}`: {}",
+                    options.input.display(),
+                    err
+                ))
+            })?;
+            convert(
+                decode_message_pack(&input).map_err(|err| {
+                    DarkluaError::custom(format!("unable to read msgpack data: {}", err))
+                })?,
+                export_type,
+            )
+        }
     }?;
 
     let convert_duration = durationfmt::to_string(convert_start_time.elapsed());
@@ -113,3 +156,20 @@ fn convert_data(options: &Options) -> Result<(), DarkluaError> {
 
     Ok(())
 }
+
+fn convert(value: impl Serialize, export_type: Option<&str>) -> Result<String, DarkluaError> {
+    if let Some(type_name) = export_type {
+        darklua_core::convert_data_to_typed_module(value, type_name)
+    } else {
+        darklua_core::convert_data(value)
+    }
+}
+
+fn read_delimited_values(
+    input: &str,
+    delimiter: u8,
+    label: &'static str,
+) -> Result<serde_json::Value, DarkluaError> {
+    parse_delimited_values(input, delimiter)
+        .map_err(|err| DarkluaError::custom(format!("unable to read {} data: {}", label, err)))
+}
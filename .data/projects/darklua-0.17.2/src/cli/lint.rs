@@ -0,0 +1,280 @@
+use crate::cli::error::CliError;
+use crate::cli::utils::maybe_plural;
+use crate::cli::{CommandResult, GlobalOptions};
+
+use clap::Args;
+use darklua_core::lint::{Diagnostic, Lint, LintConfiguration, LintLevel, Linter};
+use darklua_core::{DarkluaError, Resources};
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const DEFAULT_CONFIG_PATHS: [&str; 2] = [".darklua-lint.json", ".darklua-lint.json5"];
+
+#[derive(Debug, Args)]
+pub struct Options {
+    /// Path to the lua file or directory to lint.
+    input_path: PathBuf,
+    /// Path to the lint configuration file. If not provided, darklua will attempt
+    /// to read `.darklua-lint.json` or `.darklua-lint.json5` from the working directory.
+    #[arg(long, short)]
+    config: Option<PathBuf>,
+    /// Output format ('human', 'json' or 'sarif').
+    #[arg(long, short, default_value = "human")]
+    format: OutputFormat,
+    /// Disable a lint (can be specified multiple times).
+    #[arg(long, value_name = "LINT")]
+    allow: Vec<Lint>,
+    /// Report a lint as a warning (can be specified multiple times).
+    #[arg(long, value_name = "LINT")]
+    warn: Vec<Lint>,
+    /// Report a lint as an error, which makes the command fail (can be specified multiple times).
+    #[arg(long, value_name = "LINT")]
+    deny: Vec<Lint>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum OutputFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(format!(
+                "invalid output format '{}' (possible options are: 'human', 'json' or 'sarif')",
+                format
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileDiagnostic<'a> {
+    path: String,
+    #[serde(flatten)]
+    diagnostic: &'a Diagnostic,
+}
+
+pub fn run(options: &Options, _global: &GlobalOptions) -> CommandResult {
+    log::debug!("running `lint`: {:?}", options);
+
+    let resources = Resources::from_file_system();
+
+    let configuration = read_configuration(&resources, options).map_err(|err| {
+        eprintln!("an error happened: {}", err);
+        CliError::new(1)
+    })?;
+
+    let linter = Linter::new(configuration);
+
+    let mut files: Vec<_> = resources.collect_work(&options.input_path).collect();
+    files.sort();
+
+    let mut results = Vec::new();
+    let mut has_errors = false;
+
+    for path in files.iter() {
+        let code = resources.get(path).map_err(|err| {
+            eprintln!("an error happened: {}", DarkluaError::from(err));
+            CliError::new(1)
+        })?;
+
+        match linter.lint(&code) {
+            Ok(diagnostics) => results.push((path.as_path(), diagnostics)),
+            Err(err) => {
+                eprintln!("unable to parse `{}`: {}", path.display(), err);
+                has_errors = true;
+            }
+        }
+    }
+
+    match options.format {
+        OutputFormat::Human => print_human(&results),
+        OutputFormat::Json => print_json(&results),
+        OutputFormat::Sarif => print_sarif(&results),
+    }
+
+    has_errors |= results
+        .iter()
+        .flat_map(|(_, diagnostics)| diagnostics.iter())
+        .any(|diagnostic| diagnostic.level() == LintLevel::Error);
+
+    if has_errors {
+        Err(CliError::new(1))
+    } else {
+        Ok(())
+    }
+}
+
+fn read_configuration(
+    resources: &Resources,
+    options: &Options,
+) -> Result<LintConfiguration, DarkluaError> {
+    let config_path = match &options.config {
+        Some(path) => Some(path.as_path()),
+        None => DEFAULT_CONFIG_PATHS
+            .iter()
+            .map(Path::new)
+            .find(|path| resources.exists(path).unwrap_or(false)),
+    };
+
+    let mut configuration = match config_path {
+        Some(path) => {
+            let content = resources.get(path)?;
+            log::info!("using lint configuration file `{}`", path.display());
+            json5::from_str(&content).map_err(|err| {
+                DarkluaError::custom(format!(
+                    "invalid lint configuration file `{}`: {}",
+                    path.display(),
+                    err
+                ))
+            })?
+        }
+        None => LintConfiguration::default(),
+    };
+
+    for (lints, level) in [
+        (&options.allow, LintLevel::Allow),
+        (&options.warn, LintLevel::Warn),
+        (&options.deny, LintLevel::Error),
+    ] {
+        for lint in lints {
+            configuration.set_level(*lint, level);
+        }
+    }
+
+    Ok(configuration)
+}
+
+fn display_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn print_human(results: &[(&Path, Vec<Diagnostic>)]) {
+    let mut warning_count = 0;
+    let mut error_count = 0;
+
+    for (path, diagnostics) in results {
+        for diagnostic in diagnostics {
+            match diagnostic.level() {
+                LintLevel::Error => error_count += 1,
+                LintLevel::Warn => warning_count += 1,
+                LintLevel::Allow => {}
+            }
+
+            let location = diagnostic
+                .span()
+                .map(|span| format!(":{}:{}", span.line(), span.column()))
+                .unwrap_or_default();
+
+            println!(
+                "{}{}: {}[{}]: {}",
+                display_path(path),
+                location,
+                diagnostic.level(),
+                diagnostic.lint(),
+                diagnostic.message()
+            );
+        }
+    }
+
+    let file_count = results.len();
+    println!(
+        "linted {} file{}: {} error{}, {} warning{}",
+        file_count,
+        maybe_plural(file_count),
+        error_count,
+        maybe_plural(error_count),
+        warning_count,
+        maybe_plural(warning_count),
+    );
+}
+
+fn print_json(results: &[(&Path, Vec<Diagnostic>)]) {
+    let diagnostics: Vec<_> = results
+        .iter()
+        .flat_map(|(path, diagnostics)| {
+            diagnostics.iter().map(move |diagnostic| FileDiagnostic {
+                path: display_path(path),
+                diagnostic,
+            })
+        })
+        .collect();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&diagnostics).expect("diagnostics should serialize")
+    );
+}
+
+fn print_sarif(results: &[(&Path, Vec<Diagnostic>)]) {
+    let rules: Vec<_> = Lint::ALL
+        .iter()
+        .map(|lint| {
+            json!({
+                "id": lint.name(),
+                "shortDescription": { "text": lint.description() },
+            })
+        })
+        .collect();
+
+    let sarif_results: Vec<_> = results
+        .iter()
+        .flat_map(|(path, diagnostics)| {
+            diagnostics.iter().map(move |diagnostic| {
+                let mut physical_location = json!({
+                    "artifactLocation": { "uri": display_path(path) },
+                });
+                if let Some(span) = diagnostic.span() {
+                    physical_location["region"] = json!({
+                        "startLine": span.line(),
+                        "startColumn": span.column(),
+                        "endLine": span.end_line(),
+                        "endColumn": span.end_column(),
+                    });
+                }
+
+                json!({
+                    "ruleId": diagnostic.lint().name(),
+                    "level": match diagnostic.level() {
+                        LintLevel::Error => "error",
+                        LintLevel::Warn => "warning",
+                        LintLevel::Allow => "none",
+                    },
+                    "message": { "text": diagnostic.message() },
+                    "locations": [{ "physicalLocation": physical_location }],
+                })
+            })
+        })
+        .collect();
+
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "darklua",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://darklua.com",
+                    "rules": rules,
+                }
+            },
+            "results": sarif_results,
+        }],
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&sarif).expect("sarif report should serialize")
+    );
+}
@@ -1,5 +1,6 @@
 pub mod convert;
 pub mod error;
+pub mod lint;
 pub mod minify;
 pub mod process;
 pub mod utils;
@@ -39,8 +40,14 @@ pub enum Command {
     /// If no configuration is passed, darklua will attempt to read
     /// `.darklua.json` or `darklua.json5` from the working directory.
     Process(process::Options),
-    /// Convert a data file [json, json5, yaml, toml] into a Lua file
+    /// Convert a data file [json, json5, yaml, toml, csv, tsv, txt, msgpack] into a Lua file
     Convert(convert::Options),
+    /// Report common mistakes in lua files
+    ///
+    /// Lints can be configured using a configuration file. If no
+    /// configuration is passed, darklua will attempt to read
+    /// `.darklua-lint.json` or `.darklua-lint.json5` from the working directory.
+    Lint(lint::Options),
 }
 
 impl Command {
@@ -49,6 +56,7 @@ impl Command {
             Command::Minify(options) => minify::run(options, global_options),
             Command::Process(options) => process::run(options, global_options),
             Command::Convert(options) => convert::run(options, global_options),
+            Command::Lint(options) => lint::run(options, global_options),
         }
     }
 }
@@ -23,6 +23,13 @@ pub struct Options {
     /// This will override the format given by the configuration file.
     #[arg(long)]
     format: Option<LuaFormat>,
+    /// Generate a source map (`.map` file) next to each output file.
+    #[arg(long)]
+    source_map: bool,
+    /// Store the generated code in this directory and reuse it for files that did not
+    /// change since the last run.
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
     /// Watch files and directories for changes and automatically re-run
     #[arg(long, short)]
     watch: bool,
@@ -79,6 +86,15 @@ impl Options {
                 LuaFormat::RetainLines => GeneratorParameters::RetainLines,
             })
         }
+
+        if self.source_map {
+            process_options = process_options.with_source_map();
+        }
+
+        if let Some(cache_dir) = self.cache_dir.as_ref() {
+            process_options = process_options.with_cache_directory(cache_dir);
+        }
+
         process_options
     }
 }
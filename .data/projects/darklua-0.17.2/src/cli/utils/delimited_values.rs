@@ -0,0 +1,145 @@
+use csv::{ReaderBuilder, StringRecord};
+use serde_json::{Map, Value};
+
+use super::maybe_plural;
+
+/// Parse delimited values (like CSV or TSV) into an array of rows, where each row
+/// is a table that maps the column names (read from the first line) to the cell values.
+///
+/// Cells can be quoted with `"` to contain the delimiter or new lines, and a quote is
+/// escaped by doubling it (`""`). All values are kept as strings.
+pub fn parse_delimited_values(content: &str, delimiter: u8) -> Result<Value, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let header = reader.headers().map_err(|err| err.to_string())?.clone();
+
+    for (index, column) in header.iter().enumerate() {
+        if header.iter().take(index).any(|previous| previous == column) {
+            return Err(format!("duplicated column name `{}`", column));
+        }
+    }
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(index, record)| {
+            let record = record.map_err(|err| err.to_string())?;
+
+            if record.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} value{} but the header has {} column{}",
+                    index + 1,
+                    record.len(),
+                    maybe_plural(record.len()),
+                    header.len(),
+                    maybe_plural(header.len()),
+                ));
+            }
+
+            Ok(Value::Object(record_to_object(&header, &record)))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+fn record_to_object(header: &StringRecord, record: &StringRecord) -> Map<String, Value> {
+    header
+        .iter()
+        .zip(record.iter())
+        .map(|(column, value)| (column.to_owned(), Value::String(value.to_owned())))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn parse_empty_content() {
+        assert_eq!(parse_delimited_values("", b','), Ok(json!([])));
+    }
+
+    #[test]
+    fn parse_header_only() {
+        assert_eq!(parse_delimited_values("name,value\n", b','), Ok(json!([])));
+    }
+
+    #[test]
+    fn parse_rows() {
+        assert_eq!(
+            parse_delimited_values("name,value\na,1\nb,2\n", b','),
+            Ok(json!([{ "name": "a", "value": "1" }, { "name": "b", "value": "2" }]))
+        );
+    }
+
+    #[test]
+    fn parse_rows_without_trailing_new_line() {
+        assert_eq!(
+            parse_delimited_values("name,value\r\na,1\r\nb,2", b','),
+            Ok(json!([{ "name": "a", "value": "1" }, { "name": "b", "value": "2" }]))
+        );
+    }
+
+    #[test]
+    fn parse_tab_separated_values() {
+        assert_eq!(
+            parse_delimited_values("name\tvalue\na,b\t1\n", b'\t'),
+            Ok(json!([{ "name": "a,b", "value": "1" }]))
+        );
+    }
+
+    #[test]
+    fn parse_quoted_values() {
+        assert_eq!(
+            parse_delimited_values("name,value\n\"a, \"\"b\"\"\",\"1\n2\"\n", b','),
+            Ok(json!([{ "name": "a, \"b\"", "value": "1\n2" }]))
+        );
+    }
+
+    #[test]
+    fn parse_empty_values() {
+        assert_eq!(
+            parse_delimited_values("name,value\n,\n", b','),
+            Ok(json!([{ "name": "", "value": "" }]))
+        );
+    }
+
+    #[test]
+    fn skip_empty_lines() {
+        assert_eq!(
+            parse_delimited_values("name\n\na\n\n", b','),
+            Ok(json!([{ "name": "a" }]))
+        );
+    }
+
+    #[test]
+    fn error_on_missing_values() {
+        assert_eq!(
+            parse_delimited_values("name,value\na\n", b','),
+            Err("row 1 has 1 value but the header has 2 columns".to_owned())
+        );
+    }
+
+    #[test]
+    fn error_on_duplicated_columns() {
+        assert_eq!(
+            parse_delimited_values("name,name\na,b\n", b','),
+            Err("duplicated column name `name`".to_owned())
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_reads_until_the_end() {
+        assert_eq!(
+            parse_delimited_values("name\n\"a\n", b','),
+            Ok(json!([{ "name": "a\n" }]))
+        );
+    }
+}
@@ -8,7 +8,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use darklua_core::{Options, Resources, WorkerTree};
+use darklua_core::{FileChange, IncrementalSession, Resources};
 use notify::{EventKind, RecursiveMode};
 use notify_debouncer_full::{new_debouncer, DebounceEventResult, DebouncedEvent};
 
@@ -27,10 +27,9 @@ enum WatcherSignal {
 
 pub struct FileWatcher {
     input_path: PathBuf,
-    resources: Resources,
     sender: Sender<WatcherSignal>,
     receiver: Option<Receiver<WatcherSignal>>,
-    worker_tree: Option<WorkerTree>,
+    session: IncrementalSession,
     process_option: ProcessOptions,
     extra_file_watch: HashSet<PathBuf>,
     links_file_watch: HashSet<(PathBuf, PathBuf)>,
@@ -41,12 +40,15 @@ impl FileWatcher {
     pub fn new(process_option: &ProcessOptions) -> Self {
         let (sender, receiver) = mpsc::channel();
 
+        let session_option = process_option.clone();
+
         Self {
             input_path: process_option.input_path.clone(),
-            resources: Resources::from_file_system(),
             sender,
             receiver: Some(receiver),
-            worker_tree: None,
+            session: IncrementalSession::new(Resources::from_file_system(), move || {
+                session_option.get_process_options()
+            }),
             process_option: process_option.clone(),
             extra_file_watch: Default::default(),
             links_file_watch: Default::default(),
@@ -54,33 +56,33 @@ impl FileWatcher {
         }
     }
 
-    fn run_worker_tree(&mut self) {
-        let options = self.build_options();
-
+    fn run_session(&mut self, changes: Vec<FileChange>) {
         let process_start_time = Instant::now();
 
-        if let Some(worker_tree) = self.worker_tree.as_mut() {
-            log_darklua_error(worker_tree.process(&self.resources, options), || ());
-        } else {
-            self.worker_tree = log_darklua_error(
-                darklua_core::process(&self.resources, options).map(Some),
-                || None,
-            );
+        let update = self.session.apply_changes(changes);
+
+        for output in update.rewritten_outputs() {
+            log::debug!("file watcher: wrote '{}'", output.display());
+        }
+        for output in update.removed_outputs() {
+            log::debug!("file watcher: removed '{}'", output.display());
         }
 
-        if let Some(worker_tree) = self.worker_tree.as_mut() {
-            report_process("processed", worker_tree, process_start_time.elapsed()).ok();
+        let worker_tree = self.session.worker_tree();
+
+        if worker_tree.collect_errors().is_empty() {
+            for err in update.errors() {
+                log::error!("{}", err);
+            }
         }
 
-        self.update_extra_file_watch();
-    }
+        report_process("processed", worker_tree, process_start_time.elapsed()).ok();
 
-    fn build_options(&self) -> Options {
-        self.process_option.get_process_options()
+        self.update_extra_file_watch();
     }
 
     pub fn start(mut self) -> CommandResult {
-        self.run_worker_tree();
+        self.run_session(Vec::new());
         self.setup_ctrl_exit()?;
 
         let receiver = self
@@ -105,8 +107,8 @@ impl FileWatcher {
             move |events: DebounceEventResult| match events {
                 Ok(events) => {
                     log::debug!("changes detected, re-running process");
-                    self.process_events(events);
-                    self.run_worker_tree();
+                    let changes = self.process_events(events);
+                    self.run_session(changes);
                 }
                 Err(errors) => {
                     for err in errors {
@@ -240,18 +242,14 @@ impl FileWatcher {
         Ok(())
     }
 
-    fn process_events(&mut self, events: Vec<DebouncedEvent>) {
+    fn process_events(&mut self, events: Vec<DebouncedEvent>) -> Vec<FileChange> {
+        let mut changes = Vec::new();
+
         if events.is_empty() {
-            return;
+            return changes;
         }
         let current_path = self.current_working_path.as_ref();
 
-        let worker_tree = if let Some(worker_tree) = self.worker_tree.as_mut() {
-            worker_tree
-        } else {
-            return;
-        };
-
         log::debug!("file watch has detected changes");
 
         let mut has_created = false;
@@ -259,7 +257,7 @@ impl FileWatcher {
         for event in events {
             let links = &self.links_file_watch;
 
-            let mut paths_iterator = event.event.paths.iter().map(|path| {
+            let paths_iterator = event.event.paths.iter().map(|path| {
                 links
                     .iter()
                     .find_map(|(link_location, link_path)| {
@@ -270,6 +268,7 @@ impl FileWatcher {
                         current_path.and_then(|current_path| path.strip_prefix(current_path).ok())
                     })
                     .unwrap_or(path)
+                    .to_path_buf()
             });
 
             if log::log_enabled!(log::Level::Trace) {
@@ -288,62 +287,48 @@ impl FileWatcher {
             }
 
             match event.kind {
-                EventKind::Any => {
+                EventKind::Any | EventKind::Create(_) => {
                     for path in paths_iterator {
                         has_created = true;
-                        worker_tree.source_changed(path);
+                        changes.push(FileChange::Created(path));
                     }
                 }
-                EventKind::Create(_create_kind) => {
-                    has_created = paths_iterator.next().is_some();
-                }
                 EventKind::Modify(_modify_kind) => {
-                    for path in paths_iterator {
-                        worker_tree.source_changed(path);
-                    }
+                    changes.extend(paths_iterator.map(FileChange::Modified));
                 }
                 EventKind::Remove(_remove_kind) => {
-                    for path in paths_iterator {
-                        worker_tree.remove_source(path);
-                    }
+                    changes.extend(paths_iterator.map(FileChange::Removed));
                 }
                 EventKind::Access(_) | EventKind::Other => {}
             }
         }
 
         if has_created {
-            self.worker_collect_work();
             self.update_links();
         }
-    }
 
-    fn worker_collect_work(&mut self) {
-        let options = self.build_options();
-        if let Some(worker_tree) = self.worker_tree.as_mut() {
-            log_darklua_error(worker_tree.collect_work(&self.resources, &options), || ());
-        }
+        changes
     }
 
     fn update_extra_file_watch(&mut self) {
-        if let Some(worker_tree) = self.worker_tree.as_ref() {
-            let files: HashSet<_> = worker_tree
-                .iter_external_dependencies()
-                .map(ToOwned::to_owned)
-                .collect();
-
-            diff_sets(
-                &files,
-                &self.extra_file_watch,
-                |new_file| {
-                    self.send_watch_signal(new_file);
-                },
-                |last_file| {
-                    self.send_unwatch_signal(last_file);
-                },
-            );
+        let files: HashSet<_> = self
+            .session
+            .iter_external_dependencies()
+            .map(ToOwned::to_owned)
+            .collect();
 
-            self.extra_file_watch = files;
-        }
+        diff_sets(
+            &files,
+            &self.extra_file_watch,
+            |new_file| {
+                self.send_watch_signal(new_file);
+            },
+            |last_file| {
+                self.send_unwatch_signal(last_file);
+            },
+        );
+
+        self.extra_file_watch = files;
     }
 
     fn update_links(&mut self) {
@@ -442,14 +427,3 @@ fn iter_all_links(location: PathBuf) -> impl Iterator<Item = PathBuf> {
         }
     })
 }
-
-fn log_darklua_error<T>(
-    result: Result<T, darklua_core::DarkluaError>,
-    else_result: impl Fn() -> T,
-) -> T {
-    result
-        .inspect_err(|err| {
-            log::error!("{}", err);
-        })
-        .unwrap_or_else(|_| else_result())
-}
@@ -0,0 +1,287 @@
+use std::{convert::TryFrom, fmt, io::Cursor};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::SerializeMap,
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A value decoded from MessagePack data.
+///
+/// Unlike JSON, map keys can be any value (like numbers or booleans), so the maps
+/// are stored as a list of key-value pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessagePackValue {
+    Nil,
+    Boolean(bool),
+    Integer(i64),
+    UnsignedInteger(u64),
+    Float(f64),
+    String(String),
+    Binary(Vec<u8>),
+    Array(Vec<MessagePackValue>),
+    Map(Vec<(MessagePackValue, MessagePackValue)>),
+}
+
+impl Serialize for MessagePackValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Nil => serializer.serialize_unit(),
+            Self::Boolean(value) => serializer.serialize_bool(*value),
+            Self::Integer(value) => serializer.serialize_i64(*value),
+            Self::UnsignedInteger(value) => serializer.serialize_u64(*value),
+            Self::Float(value) => serializer.serialize_f64(*value),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Binary(value) => serializer.serialize_bytes(value),
+            Self::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Self::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MessagePackValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(MessagePackValueVisitor)
+    }
+}
+
+struct MessagePackValueVisitor;
+
+impl<'de> Visitor<'de> for MessagePackValueVisitor {
+    type Value = MessagePackValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a MessagePack value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::Nil)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::Nil)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::Boolean(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(match i64::try_from(value) {
+            Ok(value) => MessagePackValue::Integer(value),
+            Err(_) => MessagePackValue::UnsignedInteger(value),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::Float(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::String(value.to_owned()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(MessagePackValue::Binary(value.to_vec()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(MessagePackValue::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(MessagePackValue::Map(entries))
+    }
+
+    // extension types are given as newtype structs by `rmp_serde`
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, _: D) -> Result<Self::Value, D::Error> {
+        Err(de::Error::custom("extension types are not supported"))
+    }
+}
+
+/// The maximum nesting of arrays and maps, to avoid overflowing the stack on crafted data
+/// when decoding the value or when converting it to Lua code later.
+const MAX_DEPTH: usize = 128;
+
+/// Decode a single MessagePack value. Extension types are not supported.
+pub fn decode_message_pack(data: &[u8]) -> Result<MessagePackValue, String> {
+    let mut deserializer = rmp_serde::Deserializer::new(Cursor::new(data));
+    deserializer.set_max_depth(MAX_DEPTH);
+
+    let value = MessagePackValue::deserialize(&mut deserializer).map_err(|err| err.to_string())?;
+
+    if deserializer.position() != data.len() as u64 {
+        return Err(format!(
+            "unexpected data after the first value (at byte {})",
+            deserializer.position()
+        ));
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_positive_fixint() {
+        assert_eq!(
+            decode_message_pack(&[0x2a]),
+            Ok(MessagePackValue::Integer(42))
+        );
+    }
+
+    #[test]
+    fn decode_negative_fixint() {
+        assert_eq!(
+            decode_message_pack(&[0xff]),
+            Ok(MessagePackValue::Integer(-1))
+        );
+    }
+
+    #[test]
+    fn decode_nil_and_booleans() {
+        assert_eq!(
+            decode_message_pack(&[0x93, 0xc0, 0xc2, 0xc3]),
+            Ok(MessagePackValue::Array(vec![
+                MessagePackValue::Nil,
+                MessagePackValue::Boolean(false),
+                MessagePackValue::Boolean(true),
+            ]))
+        );
+    }
+
+    #[test]
+    fn decode_integers() {
+        assert_eq!(
+            decode_message_pack(&[
+                0x94, 0xcc, 0xff, 0xcd, 0x01, 0x00, 0xd0, 0x80, 0xd1, 0xff, 0x00
+            ]),
+            Ok(MessagePackValue::Array(vec![
+                MessagePackValue::Integer(255),
+                MessagePackValue::Integer(256),
+                MessagePackValue::Integer(-128),
+                MessagePackValue::Integer(-256),
+            ]))
+        );
+    }
+
+    #[test]
+    fn decode_large_unsigned_integer() {
+        assert_eq!(
+            decode_message_pack(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            Ok(MessagePackValue::UnsignedInteger(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn decode_floats() {
+        assert_eq!(
+            decode_message_pack(&[
+                0x92, 0xca, 0x3f, 0xc0, 0x00, 0x00, 0xcb, 0x40, 0x09, 0x21, 0xfb, 0x54, 0x44, 0x2d,
+                0x18
+            ]),
+            Ok(MessagePackValue::Array(vec![
+                MessagePackValue::Float(1.5),
+                MessagePackValue::Float(std::f64::consts::PI),
+            ]))
+        );
+    }
+
+    #[test]
+    fn decode_strings() {
+        assert_eq!(
+            decode_message_pack(&[0x92, 0xa2, b'h', b'i', 0xd9, 0x03, b'a', b'b', b'c']),
+            Ok(MessagePackValue::Array(vec![
+                MessagePackValue::String("hi".to_owned()),
+                MessagePackValue::String("abc".to_owned()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn decode_binary() {
+        assert_eq!(
+            decode_message_pack(&[0xc4, 0x02, 0x00, 0xff]),
+            Ok(MessagePackValue::Binary(vec![0x00, 0xff]))
+        );
+    }
+
+    #[test]
+    fn decode_map() {
+        assert_eq!(
+            decode_message_pack(&[0x82, 0xa1, b'a', 0x01, 0x02, 0xc3]),
+            Ok(MessagePackValue::Map(vec![
+                (
+                    MessagePackValue::String("a".to_owned()),
+                    MessagePackValue::Integer(1)
+                ),
+                (
+                    MessagePackValue::Integer(2),
+                    MessagePackValue::Boolean(true)
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn error_on_truncated_data() {
+        assert_eq!(
+            decode_message_pack(&[0xa3, b'a']),
+            Err("IO error while reading data: unexpected end of file".to_owned())
+        );
+    }
+
+    #[test]
+    fn error_on_trailing_data() {
+        assert_eq!(
+            decode_message_pack(&[0xc0, 0xc0]),
+            Err("unexpected data after the first value (at byte 1)".to_owned())
+        );
+    }
+
+    #[test]
+    fn error_on_extension() {
+        assert_eq!(
+            decode_message_pack(&[0xd4, 0x01, 0x00]),
+            Err("extension types are not supported".to_owned())
+        );
+    }
+
+    #[test]
+    fn error_on_deeply_nested_arrays() {
+        let mut data = vec![0x91; 100_000];
+        data.push(0xc0);
+
+        assert_eq!(
+            decode_message_pack(&data),
+            Err("depth limit exceeded".to_owned())
+        );
+    }
+}
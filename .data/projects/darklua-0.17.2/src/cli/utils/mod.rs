@@ -1,11 +1,15 @@
+mod delimited_values;
 #[cfg(not(target_arch = "wasm32"))]
 mod file_watcher;
+mod message_pack;
 
 use std::time::Duration;
 
 use darklua_core::WorkerTree;
+pub use delimited_values::parse_delimited_values;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_watcher::FileWatcher;
+pub use message_pack::decode_message_pack;
 
 pub fn maybe_plural(count: usize) -> &'static str {
     if count > 1 {
@@ -0,0 +1,194 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::Xxh3;
+
+use super::{Configuration, DarkluaError, Resources};
+
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// The result of a previous process of a file, restored from the build cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_map: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dependencies: Vec<CachedDependency>,
+}
+
+impl CacheEntry {
+    pub(crate) fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub(crate) fn source_map(&self) -> Option<&str> {
+        self.source_map.as_deref()
+    }
+
+    pub(crate) fn iter_dependencies(&self) -> impl Iterator<Item = &Path> {
+        self.dependencies
+            .iter()
+            .map(|dependency| dependency.path.as_path())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedDependency {
+    path: PathBuf,
+    hash: String,
+}
+
+/// An on-disk cache that stores the generated code of each processed file.
+///
+/// Entries are stored in the cache directory as JSON files, named after a hash of the
+/// source path, the output path, the source content and the configuration. Each entry
+/// also stores the hashes of the files the source depends on (like bundled modules), which
+/// are compared to the current files before restoring it.
+#[derive(Debug)]
+pub(crate) struct BuildCache {
+    directory: PathBuf,
+    configuration_hash: u64,
+}
+
+impl BuildCache {
+    pub(crate) fn new(directory: impl Into<PathBuf>, configuration: &Configuration) -> Self {
+        let configuration_data = serde_json::to_vec(configuration).ok().unwrap_or_default();
+
+        let mut hasher = Xxh3::new();
+        hasher.update(&CACHE_FORMAT_VERSION.to_le_bytes());
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update(&configuration_data);
+
+        Self {
+            directory: directory.into(),
+            configuration_hash: hasher.digest(),
+        }
+    }
+
+    /// Returns the location of the cache entry for the given source, output and source content.
+    pub(crate) fn entry_path(&self, source: &Path, output: &Path, content: &str) -> PathBuf {
+        let mut hasher = Xxh3::new();
+        hasher.update(&self.configuration_hash.to_le_bytes());
+        hash_path(&mut hasher, source);
+        hash_path(&mut hasher, output);
+        hasher.update(content.as_bytes());
+
+        self.directory
+            .join(format!("{:016x}.json", hasher.digest()))
+    }
+
+    pub(crate) fn get(&self, resources: &Resources, entry_path: &Path) -> Option<CacheEntry> {
+        if !resources.is_file(entry_path).unwrap_or_default() {
+            log::trace!("no cache entry found at `{}`", entry_path.display());
+            return None;
+        }
+
+        let entry: CacheEntry = match resources
+            .get(entry_path)
+            .map_err(|err| DarkluaError::from(err).to_string())
+            .and_then(|data| serde_json::from_str(&data).map_err(|err| err.to_string()))
+        {
+            Ok(entry) => entry,
+            Err(err) => {
+                log::warn!(
+                    "unable to read cache entry `{}`: {}",
+                    entry_path.display(),
+                    err
+                );
+                return None;
+            }
+        };
+
+        for dependency in entry.dependencies.iter() {
+            let current_hash = resources
+                .get(&dependency.path)
+                .ok()
+                .map(|content| hash_content(&content));
+
+            if current_hash.as_ref() != Some(&dependency.hash) {
+                log::trace!(
+                    "cache entry `{}` is outdated because `{}` changed",
+                    entry_path.display(),
+                    dependency.path.display()
+                );
+                return None;
+            }
+        }
+
+        Some(entry)
+    }
+
+    pub(crate) fn insert(
+        &self,
+        resources: &Resources,
+        entry_path: &Path,
+        code: &str,
+        source_map: Option<String>,
+        dependencies: &HashSet<PathBuf>,
+    ) {
+        let mut dependencies: Vec<_> = dependencies.iter().collect();
+        dependencies.sort();
+
+        let mut cached_dependencies = Vec::with_capacity(dependencies.len());
+
+        for path in dependencies {
+            match resources.get(path) {
+                Ok(dependency_content) => {
+                    cached_dependencies.push(CachedDependency {
+                        path: path.to_path_buf(),
+                        hash: hash_content(&dependency_content),
+                    });
+                }
+                Err(_) => {
+                    log::trace!(
+                        "skip cache entry `{}` because dependency `{}` can't be read",
+                        entry_path.display(),
+                        path.display()
+                    );
+                    return;
+                }
+            }
+        }
+
+        let entry = CacheEntry {
+            code: code.to_owned(),
+            source_map,
+            dependencies: cached_dependencies,
+        };
+
+        let result = serde_json::to_string(&entry)
+            .map_err(|err| err.to_string())
+            .and_then(|data| {
+                resources
+                    .write(entry_path, &data)
+                    .map_err(|err| DarkluaError::from(err).to_string())
+            });
+
+        match result {
+            Ok(()) => {
+                log::trace!("write cache entry `{}`", entry_path.display());
+            }
+            Err(err) => {
+                log::warn!(
+                    "unable to write cache entry `{}`: {}",
+                    entry_path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+fn hash_path(hasher: &mut Xxh3, path: &Path) {
+    let path = path.to_string_lossy();
+    hasher.update(&(path.len() as u64).to_le_bytes());
+    hasher.update(path.as_bytes());
+}
+
+fn hash_content(content: &str) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(content.as_bytes()))
+}
@@ -7,7 +7,10 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    generator::{DenseLuaGenerator, LuaGenerator, ReadableLuaGenerator, TokenBasedLuaGenerator},
+    generator::{
+        DenseLuaGenerator, LuaGenerator, ReadableLuaGenerator, SourceMap, SourceMapBuilder,
+        TokenBasedLuaGenerator,
+    },
     nodes::Block,
     rules::{
         bundle::{BundleRequireMode, Bundler},
@@ -32,6 +35,8 @@ pub struct Configuration {
     generator: GeneratorParameters,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     bundle: Option<BundleConfiguration>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    source_map: bool,
     #[serde(default, skip)]
     location: Option<PathBuf>,
 }
@@ -43,6 +48,7 @@ impl Configuration {
             rules: Vec::new(),
             generator: GeneratorParameters::default(),
             bundle: None,
+            source_map: false,
             location: None,
         }
     }
@@ -74,6 +80,19 @@ impl Configuration {
         self
     }
 
+    /// Enables or disables the generation of a source map next to each output file.
+    #[inline]
+    pub fn with_source_map(mut self, enabled: bool) -> Self {
+        self.source_map = enabled;
+        self
+    }
+
+    /// Enables or disables the generation of a source map next to each output file.
+    #[inline]
+    pub fn set_source_map(&mut self, enabled: bool) {
+        self.source_map = enabled;
+    }
+
     /// Sets the location of this configuration.
     #[inline]
     pub fn with_location(mut self, location: impl Into<PathBuf>) -> Self {
@@ -94,7 +113,11 @@ impl Configuration {
 
     #[inline]
     pub(crate) fn build_parser(&self) -> Parser {
-        self.generator.build_parser()
+        if self.source_map {
+            Parser::default().preserve_tokens()
+        } else {
+            self.generator.build_parser()
+        }
     }
 
     #[inline]
@@ -102,6 +125,22 @@ impl Configuration {
         self.generator.generate_lua(block, code)
     }
 
+    #[inline]
+    pub(crate) fn has_source_map(&self) -> bool {
+        self.source_map
+    }
+
+    #[inline]
+    pub(crate) fn generate_lua_with_source_map(
+        &self,
+        block: &Block,
+        code: &str,
+        source_map: SourceMapBuilder,
+    ) -> (String, Option<SourceMap>) {
+        self.generator
+            .generate_lua_with_source_map(block, code, source_map)
+    }
+
     pub(crate) fn bundle(&self) -> Option<Bundler> {
         if let Some(bundle_config) = self.bundle.as_ref() {
             let bundler = Bundler::new(
@@ -133,6 +172,7 @@ impl Default for Configuration {
             rules: get_default_rules(),
             generator: Default::default(),
             bundle: None,
+            source_map: false,
             location: None,
         }
     }
@@ -224,6 +264,33 @@ impl GeneratorParameters {
         }
     }
 
+    fn generate_lua_with_source_map(
+        &self,
+        block: &Block,
+        code: &str,
+        source_map: SourceMapBuilder,
+    ) -> (String, Option<SourceMap>) {
+        match self {
+            Self::RetainLines => {
+                let mut generator = TokenBasedLuaGenerator::new(code).with_source_map(source_map);
+                generator.write_block(block);
+                generator.into_string_with_source_map()
+            }
+            Self::Dense { column_span } => {
+                let mut generator =
+                    DenseLuaGenerator::new(*column_span).with_source_map(source_map);
+                generator.write_block(block);
+                generator.into_string_with_source_map()
+            }
+            Self::Readable { column_span } => {
+                let mut generator =
+                    ReadableLuaGenerator::new(*column_span).with_source_map(source_map);
+                generator.write_block(block);
+                generator.into_string_with_source_map()
+            }
+        }
+    }
+
     fn build_parser(&self) -> Parser {
         match self {
             Self::RetainLines => Parser::default().preserve_tokens(),
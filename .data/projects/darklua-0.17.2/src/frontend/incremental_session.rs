@@ -0,0 +1,209 @@
+use std::path::{Path, PathBuf};
+
+use super::{DarkluaError, DarkluaResult, Options, Resources, WorkerTree};
+
+/// A change to a file, used to notify an [`IncrementalSession`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    /// A file or a directory was created.
+    Created(PathBuf),
+    /// The content of a file was modified.
+    Modified(PathBuf),
+    /// A file or a directory was removed.
+    Removed(PathBuf),
+}
+
+impl FileChange {
+    /// Returns the path of the file affected by this change.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Created(path) | Self::Modified(path) | Self::Removed(path) => path,
+        }
+    }
+}
+
+/// The result of a single run of an [`IncrementalSession`].
+///
+/// It only contains the files affected by that run: sources that were already processed
+/// and did not change are not listed.
+#[derive(Debug, Default)]
+pub struct IncrementalUpdate {
+    rewritten: Vec<(PathBuf, PathBuf)>,
+    removed: Vec<PathBuf>,
+    errors: Vec<DarkluaError>,
+}
+
+impl IncrementalUpdate {
+    /// Returns an iterator over the outputs that were written during this run.
+    ///
+    /// Source maps written next to these outputs are not listed.
+    pub fn rewritten_outputs(&self) -> impl Iterator<Item = &Path> {
+        self.rewritten.iter().map(|(_, output)| output.as_path())
+    }
+
+    /// Returns an iterator over the sources that were processed successfully during this run.
+    pub fn rewritten_sources(&self) -> impl Iterator<Item = &Path> {
+        self.rewritten.iter().map(|(source, _)| source.as_path())
+    }
+
+    /// Returns an iterator over the output files that were removed during this run,
+    /// because their source was removed.
+    pub fn removed_outputs(&self) -> impl Iterator<Item = &Path> {
+        self.removed.iter().map(PathBuf::as_path)
+    }
+
+    /// Returns the errors that happened during this run.
+    pub fn errors(&self) -> &[DarkluaError] {
+        &self.errors
+    }
+
+    /// Returns `true` if any error happened during this run.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Returns `true` if nothing was written, removed or failed during this run.
+    pub fn is_empty(&self) -> bool {
+        self.rewritten.is_empty() && self.removed.is_empty() && self.errors.is_empty()
+    }
+
+    pub(crate) fn push_rewritten(&mut self, source: PathBuf, output: PathBuf) {
+        self.rewritten.push((source, output));
+    }
+
+    pub(crate) fn push_removed(&mut self, path: PathBuf) {
+        self.removed.push(path);
+    }
+
+    pub(crate) fn push_error(&mut self, error: DarkluaError) {
+        self.errors.push(error);
+    }
+}
+
+/// A processing session that keeps its state between runs, so that only the files
+/// affected by a change are processed again.
+///
+/// The session owns a [`WorkerTree`]: after the first run, each call to
+/// [`apply_changes`](IncrementalSession::apply_changes) invalidates the changed sources
+/// (and the sources that depend on them) and processes them again. The returned
+/// [`IncrementalUpdate`] tells exactly which outputs were rewritten or removed.
+///
+/// Since processing consumes the [`Options`], the session is created with a function
+/// that builds the options for each run.
+///
+/// # Example
+///
+/// ```rust
+/// # use darklua_core::{FileChange, IncrementalSession, Options, Resources};
+/// let resources = Resources::from_memory();
+/// resources.write("src/a.lua", "return 1").unwrap();
+/// resources.write("src/b.lua", "return 2").unwrap();
+///
+/// let mut session =
+///     IncrementalSession::new(resources.clone(), || Options::new("src").with_output("out"));
+///
+/// let update = session.process();
+/// assert_eq!(update.rewritten_outputs().count(), 2);
+///
+/// resources.write("src/a.lua", "return 3").unwrap();
+/// let update = session.apply_changes([FileChange::Modified("src/a.lua".into())]);
+///
+/// assert_eq!(
+///     update.rewritten_outputs().collect::<Vec<_>>(),
+///     vec![std::path::Path::new("out/a.lua")]
+/// );
+/// ```
+pub struct IncrementalSession {
+    resources: Resources,
+    options: Box<dyn Fn() -> Options + Send>,
+    worker_tree: WorkerTree,
+    needs_collect: bool,
+}
+
+impl IncrementalSession {
+    /// Creates a new session from the given resources and a function that builds the
+    /// options used for each run.
+    pub fn new(resources: Resources, options: impl Fn() -> Options + Send + 'static) -> Self {
+        Self {
+            resources,
+            options: Box::new(options),
+            worker_tree: WorkerTree::default(),
+            needs_collect: true,
+        }
+    }
+
+    /// Processes every source that is not up to date.
+    ///
+    /// The first call processes all the sources found from the input of the options.
+    pub fn process(&mut self) -> IncrementalUpdate {
+        let mut update = IncrementalUpdate::default();
+
+        if let Err(err) = self.run(&mut update) {
+            update.push_error(err);
+        }
+
+        update
+    }
+
+    /// Notifies the session of file changes and processes the affected sources.
+    pub fn apply_changes(
+        &mut self,
+        changes: impl IntoIterator<Item = FileChange>,
+    ) -> IncrementalUpdate {
+        for change in changes {
+            log::trace!("incremental session change: {:?}", change);
+            match change {
+                FileChange::Created(path) => {
+                    self.needs_collect = true;
+                    self.worker_tree.source_changed(path);
+                }
+                FileChange::Modified(path) => {
+                    self.worker_tree.source_changed(path);
+                }
+                FileChange::Removed(path) => {
+                    self.worker_tree.remove_source(path);
+                }
+            }
+        }
+
+        self.process()
+    }
+
+    /// Returns the worker tree of the session.
+    pub fn worker_tree(&self) -> &WorkerTree {
+        &self.worker_tree
+    }
+
+    /// Returns the resources used by the session.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Returns an iterator over the files that are not processed but that are used by the
+    /// processed sources (for example, a JSON file that gets bundled). Changes to these files
+    /// should also be reported to the session.
+    pub fn iter_external_dependencies(&self) -> impl Iterator<Item = &Path> {
+        self.worker_tree.iter_external_dependencies()
+    }
+
+    fn run(&mut self, update: &mut IncrementalUpdate) -> DarkluaResult<()> {
+        if self.needs_collect {
+            let options = (self.options)();
+            self.worker_tree.collect_work(&self.resources, &options)?;
+            self.needs_collect = false;
+        }
+
+        self.worker_tree
+            .process_with_update(&self.resources, (self.options)(), update)
+    }
+}
+
+impl std::fmt::Debug for IncrementalSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncrementalSession")
+            .field("resources", &self.resources)
+            .field("worker_tree", &self.worker_tree)
+            .field("needs_collect", &self.needs_collect)
+            .finish_non_exhaustive()
+    }
+}
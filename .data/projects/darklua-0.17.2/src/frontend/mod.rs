@@ -1,7 +1,11 @@
+mod build_cache;
 mod configuration;
 mod error;
+mod incremental_session;
 mod options;
+mod process_output;
 mod resources;
+mod typed_module;
 mod utils;
 mod work_cache;
 mod work_item;
@@ -10,13 +14,18 @@ mod worker_tree;
 
 pub use configuration::{BundleConfiguration, Configuration, GeneratorParameters};
 pub use error::{DarkluaError, DarkluaResult};
+pub use incremental_session::{FileChange, IncrementalSession, IncrementalUpdate};
 pub use options::Options;
+pub use process_output::ProcessOutput;
 pub use resources::Resources;
 use serde::Serialize;
+pub use typed_module::convert_data_to_typed_module;
 use work_item::WorkItem;
 use worker::Worker;
 pub use worker_tree::WorkerTree;
 
+use std::{collections::HashMap, ffi::OsStr, path::PathBuf};
+
 use crate::{
     generator::{DenseLuaGenerator, LuaGenerator},
     nodes::{Block, ReturnStatement},
@@ -73,3 +82,62 @@ pub fn process(resources: &Resources, options: Options) -> DarkluaResult<WorkerT
 
     Ok(worker_tree)
 }
+
+/// Process in-memory sources with the given configuration.
+///
+/// This function runs the same process as [`process`], but on sources provided as
+/// a map of paths to content instead of reading them from a [`Resources`]. Only sources
+/// with a `.lua` or `.luau` extension are processed: the others are still available to
+/// rules (for example, when bundling a JSON file).
+///
+/// The generated code, the errors and the external dependencies of each source are
+/// returned as data, so nothing is ever written to the file system.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::{collections::HashMap, path::PathBuf};
+/// # use darklua_core::{process_sources, Configuration, rules::{RemoveEmptyDo, Rule}};
+/// let mut sources = HashMap::new();
+/// sources.insert(PathBuf::from("src/main.lua"), "do end return true".to_owned());
+///
+/// let remove_empty_do: Box<dyn Rule> = Box::new(RemoveEmptyDo::default());
+/// let config = Configuration::empty().with_rule(remove_empty_do);
+///
+/// let output = process_sources(sources, config);
+///
+/// assert!(!output.has_errors());
+/// assert_eq!(output.code("src/main.lua"), Some("return true"));
+/// ```
+pub fn process_sources(
+    sources: HashMap<PathBuf, String>,
+    configuration: Configuration,
+) -> ProcessOutput {
+    let resources = Resources::from_memory();
+    let mut worker_tree = WorkerTree::default();
+    let mut output = ProcessOutput::default();
+
+    for (path, content) in sources {
+        if let Err(err) = resources.write(&path, &content) {
+            output.push_error(err.into());
+            continue;
+        }
+
+        if matches!(
+            path.extension().and_then(OsStr::to_str),
+            Some("lua") | Some("luau")
+        ) {
+            worker_tree.add_source(path, None);
+        }
+    }
+
+    let options = Options::new(PathBuf::new()).with_configuration(configuration);
+
+    if let Err(err) = worker_tree.process(&resources, options) {
+        output.push_error(err);
+    }
+
+    worker_tree.write_output(&resources, &mut output);
+
+    output
+}
@@ -12,6 +12,8 @@ pub struct Options {
     config_generator_override: Option<GeneratorParameters>,
     output: Option<PathBuf>,
     fail_fast: bool,
+    source_map: bool,
+    cache_directory: Option<PathBuf>,
 }
 
 impl Options {
@@ -23,6 +25,8 @@ impl Options {
             config: None,
             output: None,
             fail_fast: false,
+            source_map: false,
+            cache_directory: None,
             config_generator_override: None,
         }
     }
@@ -53,6 +57,25 @@ impl Options {
         self
     }
 
+    /// Enables source map generation.
+    ///
+    /// This will generate a source map next to each output file, even if the
+    /// configuration file does not enable it.
+    pub fn with_source_map(mut self) -> Self {
+        self.source_map = true;
+        self
+    }
+
+    /// Sets the directory where the build cache is stored.
+    ///
+    /// When a cache directory is set, the generated code of each file is saved in it. Files
+    /// that did not change since they were cached (including their dependencies and the
+    /// configuration) are restored from the cache instead of being processed again.
+    pub fn with_cache_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.cache_directory = Some(directory.into());
+        self
+    }
+
     /// Sets a generator override for the configuration.
     ///
     /// This will override any generator settings in the configuration file.
@@ -76,6 +99,16 @@ impl Options {
         self.fail_fast
     }
 
+    /// Checks if source map generation is enabled.
+    pub fn should_generate_source_map(&self) -> bool {
+        self.source_map
+    }
+
+    /// Gets the build cache directory, if set.
+    pub fn cache_directory(&self) -> Option<&Path> {
+        self.cache_directory.as_ref().map(AsRef::as_ref)
+    }
+
     /// Gets the configuration file path, if set.
     pub fn configuration_path(&self) -> Option<&Path> {
         self.config_path.as_ref().map(AsRef::as_ref)
@@ -0,0 +1,114 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::utils::normalize_path;
+
+use super::DarkluaError;
+
+/// The result of processing in-memory sources with [`process_sources`](super::process_sources).
+///
+/// It contains the generated code for each successfully processed source, the errors
+/// that happened while processing and the external files each source depends on.
+#[derive(Debug, Default)]
+pub struct ProcessOutput {
+    code: HashMap<PathBuf, String>,
+    source_maps: HashMap<PathBuf, String>,
+    errors: Vec<DarkluaError>,
+    dependencies: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+impl ProcessOutput {
+    pub(crate) fn insert_code(&mut self, path: PathBuf, code: String) {
+        self.code.insert(path, code);
+    }
+
+    pub(crate) fn insert_source_map(&mut self, path: PathBuf, source_map: String) {
+        self.source_maps.insert(path, source_map);
+    }
+
+    pub(crate) fn push_error(&mut self, error: DarkluaError) {
+        self.errors.push(error);
+    }
+
+    pub(crate) fn insert_dependencies(
+        &mut self,
+        path: PathBuf,
+        dependencies: impl IntoIterator<Item = PathBuf>,
+    ) {
+        self.dependencies
+            .entry(path)
+            .or_default()
+            .extend(dependencies);
+    }
+
+    /// Returns the generated code for the given source path, if it was processed successfully.
+    pub fn code(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.code
+            .get(&normalize_path(path.as_ref()))
+            .map(String::as_str)
+    }
+
+    /// Returns an iterator over all source paths and their generated code.
+    pub fn iter_code(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.code
+            .iter()
+            .map(|(path, code)| (path.as_path(), code.as_str()))
+    }
+
+    /// Returns the source map (as JSON) generated for the given source path, if the
+    /// configuration enables source maps.
+    pub fn source_map(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.source_maps
+            .get(&normalize_path(path.as_ref()))
+            .map(String::as_str)
+    }
+
+    /// Consumes the output and returns the generated code for each source path.
+    pub fn into_code(self) -> HashMap<PathBuf, String> {
+        self.code
+    }
+
+    /// Returns the errors that occurred during processing.
+    pub fn errors(&self) -> &[DarkluaError] {
+        &self.errors
+    }
+
+    /// Checks if any error occurred during processing.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Returns the external files that the given source path depends on.
+    ///
+    /// External dependencies are files read by rules (like required modules when
+    /// bundling) while processing the source.
+    pub fn dependencies(&self, path: impl AsRef<Path>) -> impl Iterator<Item = &Path> {
+        self.dependencies
+            .get(&normalize_path(path.as_ref()))
+            .into_iter()
+            .flat_map(|dependencies| dependencies.iter().map(PathBuf::as_path))
+    }
+
+    /// Returns an iterator over all external dependencies discovered during processing.
+    pub fn iter_external_dependencies(&self) -> impl Iterator<Item = &Path> {
+        let mut all_dependencies: Vec<_> = self
+            .dependencies
+            .values()
+            .flat_map(|dependencies| dependencies.iter().map(PathBuf::as_path))
+            .collect();
+        all_dependencies.sort();
+        all_dependencies.dedup();
+        all_dependencies.into_iter()
+    }
+
+    /// Converts the output into a result, returning the errors if any occurred.
+    pub fn result(self) -> Result<HashMap<PathBuf, String>, Vec<DarkluaError>> {
+        if self.errors.is_empty() {
+            Ok(self.code)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
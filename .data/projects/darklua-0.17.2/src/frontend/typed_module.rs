@@ -0,0 +1,400 @@
+use serde::Serialize;
+
+use crate::{
+    generator::{DenseLuaGenerator, LuaGenerator},
+    nodes::{
+        ArrayType, Block, Expression, FieldExpression, FunctionCall, Identifier,
+        LocalAssignStatement, OptionalType, ReturnStatement, StringType, TableEntry,
+        TableExpression, TableIndexerType, TableLiteralPropertyType, TablePropertyType, TableType,
+        Type, TypeDeclarationStatement, TypeName, TypedIdentifier, UnionType,
+    },
+    process::{to_expression, utils::is_valid_identifier},
+};
+
+use super::DarkluaError;
+
+const DATA_VARIABLE_NAME: &str = "data";
+
+/// Convert serializable data into a typed Luau module.
+///
+/// Like [`convert_data`](crate::convert_data), this function converts a value into a
+/// module that returns the data. The module also exports a type (named with the given
+/// `type_name`) that is derived from the shape of the data, and each table is frozen
+/// using `table.freeze`.
+///
+/// When the elements of an array (or the values of a map) do not have the same shape,
+/// their types are merged: a field that is not always present becomes optional and
+/// values of different types become a union.
+///
+/// # Example
+///
+/// ```rust
+/// # use serde::Serialize;
+/// # use darklua_core::convert_data_to_typed_module;
+/// #[derive(Serialize)]
+/// struct ExampleData {
+///     name: String,
+///     value: i32,
+/// }
+///
+/// let config = ExampleData {
+///     name: "test".to_string(),
+///     value: 42,
+/// };
+///
+/// let luau_code = convert_data_to_typed_module(config, "Config").unwrap();
+///
+/// assert_eq!(
+///     luau_code,
+///     "export type Config={name:string,value:number}\
+///     local data:Config=table.freeze({\n\
+///     name='test',value=42})return data"
+/// );
+/// ```
+pub fn convert_data_to_typed_module(
+    value: impl Serialize,
+    type_name: &str,
+) -> Result<String, DarkluaError> {
+    if !is_valid_identifier(type_name) {
+        return Err(DarkluaError::custom(format!(
+            "invalid type name `{}` (it must be a valid identifier)",
+            type_name
+        )));
+    }
+
+    let expression = to_expression(&value).map_err(DarkluaError::from)?;
+
+    let data_type = DataShape::from_expression(&expression).into_type();
+
+    let block = Block::default()
+        .with_statement(TypeDeclarationStatement::new(type_name, data_type).export())
+        .with_statement(
+            LocalAssignStatement::from_variable(
+                TypedIdentifier::new(DATA_VARIABLE_NAME).with_type(TypeName::new(type_name)),
+            )
+            .with_value(freeze_tables(expression)),
+        )
+        .with_last_statement(
+            ReturnStatement::default().with_expression(Identifier::new(DATA_VARIABLE_NAME)),
+        );
+
+    let mut generator = DenseLuaGenerator::default();
+    generator.write_block(&block);
+    Ok(generator.into_string())
+}
+
+fn freeze_tables(expression: Expression) -> Expression {
+    match expression {
+        Expression::Table(mut table) => {
+            let entries = std::mem::take(table.mutate_entries())
+                .into_iter()
+                .map(|entry| match entry {
+                    TableEntry::Field(mut field) => {
+                        let value = std::mem::replace(field.mutate_value(), Expression::nil());
+                        *field.mutate_value() = freeze_tables(value);
+                        TableEntry::Field(field)
+                    }
+                    TableEntry::Index(mut index) => {
+                        let value = std::mem::replace(index.mutate_value(), Expression::nil());
+                        *index.mutate_value() = freeze_tables(value);
+                        TableEntry::Index(index)
+                    }
+                    TableEntry::Value(value) => TableEntry::Value(Box::new(freeze_tables(*value))),
+                })
+                .collect();
+
+            FunctionCall::from_prefix(FieldExpression::new(Identifier::new("table"), "freeze"))
+                .with_argument(TableExpression::new(entries))
+                .into()
+        }
+        _ => expression,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecordField {
+    name: String,
+    shape: DataShape,
+    optional: bool,
+}
+
+/// The shape of a value, used to build the type of the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DataShape {
+    Nil,
+    Boolean,
+    Number,
+    String,
+    Unknown,
+    EmptyTable,
+    Array(Box<DataShape>),
+    Record(Vec<RecordField>),
+    Map(Box<DataShape>, Box<DataShape>),
+    Union(Vec<DataShape>),
+}
+
+impl DataShape {
+    fn from_expression(expression: &Expression) -> Self {
+        match expression {
+            Expression::Nil(_) => Self::Nil,
+            Expression::True(_) | Expression::False(_) => Self::Boolean,
+            Expression::Number(_)
+            | Expression::Unary(_)
+            | Expression::Binary(_)
+            | Expression::Field(_) => Self::Number,
+            // byte arrays are serialized into a `string.char(...)` call
+            Expression::String(_) | Expression::InterpolatedString(_) | Expression::Call(_) => {
+                Self::String
+            }
+            Expression::Table(table) => Self::from_table(table),
+            Expression::Parenthese(parenthese) => {
+                Self::from_expression(parenthese.inner_expression())
+            }
+            Expression::TypeCast(type_cast) => Self::from_expression(type_cast.get_expression()),
+            Expression::Function(_)
+            | Expression::Identifier(_)
+            | Expression::If(_)
+            | Expression::Index(_)
+            | Expression::VariableArguments(_) => Self::Unknown,
+        }
+    }
+
+    fn from_table(table: &TableExpression) -> Self {
+        if table.is_empty() {
+            return Self::EmptyTable;
+        }
+
+        let mut fields: Vec<RecordField> = Vec::new();
+        let mut values: Option<DataShape> = None;
+        let mut keys: Option<DataShape> = None;
+        let mut is_record = true;
+        let mut is_array = true;
+
+        for entry in table.iter_entries() {
+            let (key, value) = match entry {
+                TableEntry::Field(field) => {
+                    is_array = false;
+                    let name = field.get_field().get_name().to_owned();
+                    let value = Self::from_expression(field.get_value());
+                    push_record_field(&mut fields, name, value.clone());
+                    (Self::String, value)
+                }
+                TableEntry::Index(index) => {
+                    is_array = false;
+                    let value = Self::from_expression(index.get_value());
+                    match index.get_key() {
+                        Expression::String(string) => match string.get_string_value() {
+                            Some(name) => {
+                                push_record_field(&mut fields, name.to_owned(), value.clone());
+                            }
+                            None => {
+                                is_record = false;
+                            }
+                        },
+                        _ => {
+                            is_record = false;
+                        }
+                    }
+                    (Self::from_expression(index.get_key()), value)
+                }
+                TableEntry::Value(value) => {
+                    is_record = false;
+                    (Self::Number, Self::from_expression(value))
+                }
+            };
+
+            keys = Some(merge_optional(keys, key));
+            values = Some(merge_optional(values, value));
+        }
+
+        let values = values.unwrap_or(Self::Unknown);
+
+        if is_array {
+            Self::Array(Box::new(values))
+        } else if is_record {
+            Self::Record(fields)
+        } else {
+            Self::Map(Box::new(keys.unwrap_or(Self::Unknown)), Box::new(values))
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        if self == other {
+            return self;
+        }
+
+        match (self, other) {
+            (Self::Unknown, _) | (_, Self::Unknown) => Self::Unknown,
+            (Self::Union(mut shapes), Self::Union(other_shapes)) => {
+                for shape in other_shapes {
+                    insert_in_union(&mut shapes, shape);
+                }
+                Self::Union(shapes)
+            }
+            (Self::Union(mut shapes), other) | (other, Self::Union(mut shapes)) => {
+                insert_in_union(&mut shapes, other);
+                Self::Union(shapes)
+            }
+            (Self::EmptyTable, Self::Array(shape)) | (Self::Array(shape), Self::EmptyTable) => {
+                Self::Array(shape)
+            }
+            (Self::EmptyTable, Self::Map(key, value))
+            | (Self::Map(key, value), Self::EmptyTable) => Self::Map(key, value),
+            (Self::EmptyTable, Self::Record(fields)) | (Self::Record(fields), Self::EmptyTable) => {
+                Self::Record(
+                    fields
+                        .into_iter()
+                        .map(|field| RecordField {
+                            optional: true,
+                            ..field
+                        })
+                        .collect(),
+                )
+            }
+            (Self::Array(shape), Self::Array(other_shape)) => {
+                Self::Array(Box::new(shape.merge(*other_shape)))
+            }
+            (Self::Map(key, value), Self::Map(other_key, other_value)) => Self::Map(
+                Box::new(key.merge(*other_key)),
+                Box::new(value.merge(*other_value)),
+            ),
+            (Self::Record(fields), Self::Record(other_fields)) => {
+                Self::Record(merge_records(fields, other_fields))
+            }
+            (shape, other) => Self::Union(vec![shape, other]),
+        }
+    }
+
+    fn can_merge_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::EmptyTable, Self::Array(_) | Self::Map(..) | Self::Record(_))
+            | (Self::Array(_) | Self::Map(..) | Self::Record(_), Self::EmptyTable) => true,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    fn into_type(self) -> Type {
+        match self {
+            Self::Nil => Type::nil(),
+            Self::Boolean => TypeName::new("boolean").into(),
+            Self::Number => TypeName::new("number").into(),
+            Self::String => TypeName::new("string").into(),
+            Self::Unknown => TypeName::new("any").into(),
+            Self::EmptyTable => TableType::default().into(),
+            Self::Array(shape) => ArrayType::new(shape.into_type()).into(),
+            Self::Record(fields) => fields
+                .into_iter()
+                .fold(TableType::default(), |table_type, field| {
+                    let field_type = if field.optional {
+                        into_optional(field.shape.into_type())
+                    } else {
+                        field.shape.into_type()
+                    };
+
+                    if is_valid_identifier(&field.name) {
+                        table_type.with_property(TablePropertyType::new(field.name, field_type))
+                    } else {
+                        table_type.with_property(TableLiteralPropertyType::new(
+                            StringType::from_value(field.name),
+                            field_type,
+                        ))
+                    }
+                })
+                .into(),
+            Self::Map(key, value) => TableType::default()
+                .with_indexer_type(TableIndexerType::new(key.into_type(), value.into_type()))
+                .into(),
+            Self::Union(shapes) => {
+                let is_optional = shapes.contains(&Self::Nil);
+
+                let mut types: Vec<_> = shapes
+                    .into_iter()
+                    .filter(|shape| *shape != Self::Nil)
+                    .map(Self::into_type)
+                    .collect();
+
+                let union_type = match types.len() {
+                    0 => Type::nil(),
+                    1 => types.pop().unwrap(),
+                    _ => UnionType::from(types).into(),
+                };
+
+                if is_optional {
+                    into_optional(union_type)
+                } else {
+                    union_type
+                }
+            }
+        }
+    }
+}
+
+fn merge_optional(shape: Option<DataShape>, other: DataShape) -> DataShape {
+    match shape {
+        Some(shape) => shape.merge(other),
+        None => other,
+    }
+}
+
+fn insert_in_union(shapes: &mut Vec<DataShape>, shape: DataShape) {
+    if let Some(existing) = shapes
+        .iter_mut()
+        .find(|existing| existing.can_merge_with(&shape))
+    {
+        let current = std::mem::replace(existing, DataShape::Nil);
+        *existing = current.merge(shape);
+    } else {
+        shapes.push(shape);
+    }
+}
+
+fn push_record_field(fields: &mut Vec<RecordField>, name: String, shape: DataShape) {
+    if let Some(field) = fields.iter_mut().find(|field| field.name == name) {
+        let current = std::mem::replace(&mut field.shape, DataShape::Nil);
+        field.shape = current.merge(shape);
+    } else {
+        fields.push(RecordField {
+            name,
+            shape,
+            optional: false,
+        });
+    }
+}
+
+fn merge_records(fields: Vec<RecordField>, mut other_fields: Vec<RecordField>) -> Vec<RecordField> {
+    let mut merged = Vec::with_capacity(fields.len().max(other_fields.len()));
+
+    for field in fields {
+        if let Some(index) = other_fields
+            .iter()
+            .position(|other| other.name == field.name)
+        {
+            let other = other_fields.remove(index);
+            merged.push(RecordField {
+                name: field.name,
+                shape: field.shape.merge(other.shape),
+                optional: field.optional || other.optional,
+            });
+        } else {
+            merged.push(RecordField {
+                optional: true,
+                ..field
+            });
+        }
+    }
+
+    merged.extend(other_fields.into_iter().map(|field| RecordField {
+        optional: true,
+        ..field
+    }));
+
+    merged
+}
+
+fn into_optional(r#type: Type) -> Type {
+    match r#type {
+        Type::Nil(_) | Type::Optional(_) => r#type,
+        Type::Union(_) => OptionalType::new(r#type.in_parentheses()).into(),
+        _ => OptionalType::new(r#type).into(),
+    }
+}
@@ -12,6 +12,7 @@ pub(crate) struct Progress {
     block: Block,
     next_rule: usize,
     required: Vec<PathBuf>,
+    depends_on_work: bool,
     duration: Timer,
 }
 
@@ -21,6 +22,7 @@ impl Progress {
             block,
             next_rule: 0,
             required: Vec::new(),
+            depends_on_work: false,
             duration: Timer::now(),
         }
     }
@@ -29,6 +31,14 @@ impl Progress {
         self.required = required_content;
     }
 
+    pub(crate) fn set_depends_on_work(&mut self) {
+        self.depends_on_work = true;
+    }
+
+    pub(crate) fn depends_on_work(&self) -> bool {
+        self.depends_on_work
+    }
+
     pub(crate) fn next_rule(&self) -> usize {
         self.next_rule
     }
@@ -1,6 +1,7 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::{
+    build_cache::BuildCache,
     configuration::Configuration,
     resources::Resources,
     utils::maybe_plural,
@@ -10,6 +11,7 @@ use super::{
 };
 
 use crate::{
+    generator::SourceMapBuilder,
     nodes::Block,
     rules::{bundle::Bundler, ContextBuilder, Rule, RuleConfiguration},
     utils::{normalize_path, Timer},
@@ -18,12 +20,21 @@ use crate::{
 
 const DEFAULT_CONFIG_PATHS: [&str; 2] = [".darklua.json", ".darklua.json5"];
 
+/// Returns the path of the source map associated with an output file (the output
+/// path with an additional `.map` extension).
+pub(crate) fn get_source_map_path(output: &Path) -> PathBuf {
+    let mut source_map_path = output.as_os_str().to_owned();
+    source_map_path.push(".map");
+    PathBuf::from(source_map_path)
+}
+
 #[derive(Debug)]
 pub(crate) struct Worker<'a> {
     resources: &'a Resources,
     cache: WorkCache<'a>,
     configuration: Configuration,
     cached_bundler: Option<Bundler>,
+    build_cache: Option<BuildCache>,
 }
 
 impl<'a> Worker<'a> {
@@ -33,6 +44,7 @@ impl<'a> Worker<'a> {
             cache: WorkCache::new(resources),
             configuration: Configuration::default(),
             cached_bundler: None,
+            build_cache: None,
         }
     }
 
@@ -102,6 +114,16 @@ impl<'a> Worker<'a> {
             self.configuration.set_generator(generator.clone());
         }
 
+        if options.should_generate_source_map() {
+            log::trace!("override configuration to generate source maps");
+            self.configuration.set_source_map(true);
+        }
+
+        if let Some(directory) = options.cache_directory() {
+            log::debug!("using build cache at `{}`", directory.display());
+            self.build_cache = Some(BuildCache::new(directory, &self.configuration));
+        }
+
         log::trace!(
             "configuration setup in {}",
             configuration_setup_timer.duration_label()
@@ -123,10 +145,14 @@ impl<'a> Worker<'a> {
     pub(crate) fn advance_work(&mut self, work_item: &mut WorkItem) -> DarkluaResult<()> {
         match &work_item.status {
             WorkStatus::NotStarted => {
-                let source_display = work_item.source().display();
+                let source_display = work_item.source().display().to_string();
 
                 let content = self.resources.get(work_item.source())?;
 
+                if self.restore_from_cache(work_item, &content)? {
+                    return Ok(());
+                }
+
                 let parser = self.configuration.build_parser();
 
                 log::debug!("beginning work on `{}`", source_display);
@@ -222,6 +248,7 @@ impl<'a> Worker<'a> {
                     .iter()
                     .all(|path| self.cache.contains(path))
                 {
+                    progress.set_depends_on_work();
                     let parser = self.configuration.build_parser();
                     for path in required_content.iter() {
                         let block = self.cache.get_block(path, &parser)?;
@@ -311,9 +338,28 @@ impl<'a> Worker<'a> {
 
         let generator_timer = Timer::now();
 
-        let lua_code = self
-            .configuration
-            .generate_lua(progress.block(), &work_progress.content);
+        let output = work_item.data.output();
+
+        let (lua_code, source_map) = if self.configuration.has_source_map() {
+            let mut source_map_builder = SourceMapBuilder::new(&normalized_source)
+                .with_original_code(&work_progress.content);
+
+            if let Some(file_name) = output.file_name() {
+                source_map_builder =
+                    source_map_builder.with_file(file_name.to_string_lossy().to_string());
+            }
+
+            self.configuration.generate_lua_with_source_map(
+                progress.block(),
+                &work_progress.content,
+                source_map_builder,
+            )
+        } else {
+            let lua_code = self
+                .configuration
+                .generate_lua(progress.block(), &work_progress.content);
+            (lua_code, None)
+        };
 
         let generator_time = generator_timer.duration_label();
         log::debug!(
@@ -322,7 +368,39 @@ impl<'a> Worker<'a> {
             generator_time,
         );
 
-        self.resources.write(work_item.data.output(), &lua_code)?;
+        self.resources.write(output, &lua_code)?;
+
+        let source_map = source_map.map(|mut source_map| {
+            if let Some(output_directory) = output.parent() {
+                source_map.make_sources_relative_to(output_directory);
+            }
+            source_map.to_json()
+        });
+
+        if let Some(source_map) = source_map.as_ref() {
+            let source_map_path = get_source_map_path(output);
+            log::trace!("write source map at `{}`", source_map_path.display());
+            self.resources.write(&source_map_path, source_map)?;
+        }
+
+        if let Some(build_cache) = self.build_cache.as_ref() {
+            if progress.depends_on_work() {
+                log::trace!(
+                    "skip caching `{}` because it depends on other processed files",
+                    source_display
+                );
+            } else {
+                let entry_path =
+                    build_cache.entry_path(&normalized_source, output, &work_progress.content);
+                build_cache.insert(
+                    self.resources,
+                    &entry_path,
+                    &lua_code,
+                    source_map,
+                    &work_item.external_file_dependencies,
+                );
+            }
+        }
 
         self.cache
             .link_source_to_output(normalized_source, work_item.data.output());
@@ -331,12 +409,55 @@ impl<'a> Worker<'a> {
         Ok(())
     }
 
+    fn restore_from_cache(
+        &mut self,
+        work_item: &mut WorkItem,
+        content: &str,
+    ) -> DarkluaResult<bool> {
+        let build_cache = match self.build_cache.as_ref() {
+            Some(build_cache) => build_cache,
+            None => return Ok(false),
+        };
+
+        let normalized_source = normalize_path(work_item.source());
+        let output = work_item.data.output();
+        let entry_path = build_cache.entry_path(&normalized_source, output, content);
+
+        let entry = match build_cache.get(self.resources, &entry_path) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        self.resources.write(output, entry.code())?;
+
+        if let Some(source_map) = entry.source_map() {
+            self.resources
+                .write(get_source_map_path(output), source_map)?;
+        }
+
+        work_item
+            .external_file_dependencies
+            .extend(entry.iter_dependencies().map(Path::to_path_buf));
+
+        self.cache
+            .link_source_to_output(normalized_source, work_item.data.output());
+
+        log::debug!(
+            "restored `{}` from build cache",
+            work_item.source().display()
+        );
+
+        work_item.status = WorkStatus::done();
+        Ok(true)
+    }
+
     fn create_rule_context<'block, 'src>(
         &self,
         source: &Path,
         original_code: &'src str,
     ) -> ContextBuilder<'block, 'a, 'src> {
-        let builder = ContextBuilder::new(normalize_path(source), self.resources, original_code);
+        let builder = ContextBuilder::new(normalize_path(source), self.resources, original_code)
+            .with_source_map(self.configuration.has_source_map());
         if let Some(project_location) = self.configuration.location() {
             builder.with_project_location(project_location)
         } else {
@@ -13,8 +13,8 @@ use crate::{
 };
 
 use super::{
-    normalize_path, work_item::WorkStatus, Configuration, DarkluaResult, Options, Resources,
-    WorkItem, Worker,
+    normalize_path, work_item::WorkStatus, worker::get_source_map_path, Configuration,
+    DarkluaResult, IncrementalUpdate, Options, ProcessOutput, Resources, WorkItem, Worker,
 };
 
 /// A structure that manages the processing of Lua/Luau files and their dependencies.
@@ -30,6 +30,7 @@ pub struct WorkerTree {
     external_dependencies: HashMap<PathBuf, HashSet<NodeIndex>>,
     remove_files: Vec<PathBuf>,
     last_configuration_hash: Option<u64>,
+    source_map_enabled: bool,
 }
 
 impl WorkerTree {
@@ -101,7 +102,18 @@ impl WorkerTree {
     ///
     /// This method performs the actual processing of work items in topological order,
     /// respecting dependencies between files.
-    pub fn process(&mut self, resources: &Resources, mut options: Options) -> DarkluaResult<()> {
+    pub fn process(&mut self, resources: &Resources, options: Options) -> DarkluaResult<()> {
+        self.process_with_update(resources, options, &mut IncrementalUpdate::default())
+    }
+
+    /// Same as [`process`](WorkerTree::process), but records the outputs written or removed
+    /// and the errors that happened in the given update.
+    pub(crate) fn process_with_update(
+        &mut self,
+        resources: &Resources,
+        mut options: Options,
+        update: &mut IncrementalUpdate,
+    ) -> DarkluaResult<()> {
         clear_luau_configuration_cache();
 
         if !self.remove_files.is_empty() {
@@ -113,8 +125,13 @@ impl WorkerTree {
             );
             for path in self.remove_files.drain(..) {
                 log::trace!("remove file {}", path.display());
-                if let Err(err) = resources.remove(path).map_err(DarkluaError::from) {
-                    log::warn!("failed to remove resource: {}", err);
+                // removing a file that does not exist succeeds, so check
+                // beforehand to only report outputs that were really removed
+                let existed = resources.exists(&path).unwrap_or_default();
+                match resources.remove(&path).map_err(DarkluaError::from) {
+                    Ok(()) if existed => update.push_removed(path),
+                    Ok(()) => {}
+                    Err(err) => log::warn!("failed to remove resource: {}", err),
                 }
             }
         }
@@ -122,16 +139,19 @@ impl WorkerTree {
         let mut worker = Worker::new(resources);
         worker.setup_worker(&mut options)?;
 
+        self.source_map_enabled = worker.configuration().has_source_map();
+
         if self.has_configuration_changed(worker.configuration()) {
             log::debug!("configuration change detected");
             self.reset();
         }
 
-        let total_not_done = self
+        let pending_nodes: Vec<_> = self
             .graph
-            .node_weights()
-            .filter(|work_item| !work_item.status.is_done())
-            .count();
+            .node_indices()
+            .filter(|node_index| !self.graph[*node_index].status.is_done())
+            .collect();
+        let total_not_done = pending_nodes.len();
 
         if total_not_done == 0 {
             return Ok(());
@@ -237,6 +257,23 @@ impl WorkerTree {
 
         log::info!("executed work in {}", work_timer.duration_label());
 
+        for node_index in pending_nodes {
+            if let Some(work_item) = self.graph.node_weight(node_index) {
+                match &work_item.status {
+                    WorkStatus::NotStarted | WorkStatus::InProgress(_) => {}
+                    WorkStatus::Done(Ok(())) => {
+                        update.push_rewritten(
+                            work_item.source().to_path_buf(),
+                            work_item.data.output().to_path_buf(),
+                        );
+                    }
+                    WorkStatus::Done(Err(err)) => {
+                        update.push_error(err.clone());
+                    }
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -278,6 +315,37 @@ impl WorkerTree {
             .count()
     }
 
+    pub(crate) fn write_output(&self, resources: &Resources, output: &mut ProcessOutput) {
+        for work_item in self.graph.node_weights() {
+            output.insert_dependencies(
+                work_item.source().to_path_buf(),
+                work_item.external_file_dependencies.iter().cloned(),
+            );
+
+            match &work_item.status {
+                WorkStatus::NotStarted | WorkStatus::InProgress(_) => {}
+                WorkStatus::Done(Ok(())) => {
+                    match resources.get(work_item.data.output()) {
+                        Ok(code) => {
+                            output.insert_code(work_item.source().to_path_buf(), code);
+                        }
+                        Err(err) => {
+                            output.push_error(err.into());
+                        }
+                    }
+                    if let Ok(source_map) =
+                        resources.get(get_source_map_path(work_item.data.output()))
+                    {
+                        output.insert_source_map(work_item.source().to_path_buf(), source_map);
+                    }
+                }
+                WorkStatus::Done(Err(err)) => {
+                    output.push_error(err.clone());
+                }
+            }
+        }
+    }
+
     /// Returns an iterator over all external dependencies.
     pub fn iter_external_dependencies(&self) -> impl Iterator<Item = &Path> {
         self.external_dependencies
@@ -342,6 +410,10 @@ impl WorkerTree {
                 self.remove_files
                     .push(root_item.data.output().to_path_buf());
             }
+            if self.source_map_enabled {
+                self.remove_files
+                    .push(get_source_map_path(root_item.data.output()));
+            }
 
             self.restart_work(node_index);
 
@@ -365,6 +437,10 @@ impl WorkerTree {
                         self.remove_files
                             .push(work_item.data.output().to_path_buf());
                     }
+                    if self.source_map_enabled {
+                        self.remove_files
+                            .push(get_source_map_path(work_item.data.output()));
+                    }
                 }
             }
         }
@@ -1,4 +1,4 @@
-use crate::generator::{utils, LuaGenerator};
+use crate::generator::{utils, LuaGenerator, SourceMap, SourceMapBuilder};
 use crate::nodes;
 
 /// This implementation of [LuaGenerator](trait.LuaGenerator.html) attempts to produce Lua code as
@@ -9,6 +9,7 @@ pub struct DenseLuaGenerator {
     current_line_length: usize,
     output: String,
     last_push_length: usize,
+    source_map: Option<SourceMapBuilder>,
 }
 
 impl DenseLuaGenerator {
@@ -20,6 +21,29 @@ impl DenseLuaGenerator {
             current_line_length: 0,
             output: String::new(),
             last_push_length: 0,
+            source_map: None,
+        }
+    }
+
+    /// Enables source map generation: each token written by the generator is
+    /// recorded into the given builder.
+    pub fn with_source_map(mut self, source_map: SourceMapBuilder) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Consumes the generator and produce the code with its source map, if
+    /// source map generation was enabled.
+    pub fn into_string_with_source_map(self) -> (String, Option<SourceMap>) {
+        let output = self.output;
+        let source_map = self.source_map.map(|source_map| source_map.build(&output));
+        (output, source_map)
+    }
+
+    /// Records a mapping for the given token at the content that was just pushed.
+    fn add_mapping(&mut self, token: Option<&nodes::Token>) {
+        if let (Some(source_map), Some(token)) = (self.source_map.as_mut(), token) {
+            source_map.add_token_mapping(self.output.len() - self.last_push_length, token);
         }
     }
 
@@ -47,6 +71,7 @@ impl DenseLuaGenerator {
         if self.fits_on_current_line(1) {
             self.raw_push_char(character);
         } else {
+            let last_push_start = self.output.len() - self.last_push_length;
             let last_push_content = self.get_last_push_str().to_owned();
             (0..self.last_push_length).for_each(|_| {
                 self.output.pop();
@@ -63,6 +88,9 @@ impl DenseLuaGenerator {
             }
 
             self.output.push('\n');
+            if let Some(source_map) = self.source_map.as_mut() {
+                source_map.move_mappings(last_push_start, self.output.len());
+            }
             self.output.push_str(&last_push_content);
             self.output.push(character);
             self.last_push_length += 1;
@@ -218,6 +246,7 @@ impl DenseLuaGenerator {
 
     fn write_typed_identifier(&mut self, typed_identifier: &nodes::TypedIdentifier) {
         self.push_str(typed_identifier.get_name());
+        self.add_mapping(typed_identifier.get_token());
 
         if let Some(r#type) = typed_identifier.get_type() {
             self.push_char(':');
@@ -637,16 +666,19 @@ impl LuaGenerator for DenseLuaGenerator {
         self.write_type(statement.get_type());
     }
 
-    fn write_false_expression(&mut self, _token: &Option<nodes::Token>) {
+    fn write_false_expression(&mut self, token: &Option<nodes::Token>) {
         self.push_str("false");
+        self.add_mapping(token.as_ref());
     }
 
-    fn write_true_expression(&mut self, _token: &Option<nodes::Token>) {
+    fn write_true_expression(&mut self, token: &Option<nodes::Token>) {
         self.push_str("true");
+        self.add_mapping(token.as_ref());
     }
 
-    fn write_nil_expression(&mut self, _token: &Option<nodes::Token>) {
+    fn write_nil_expression(&mut self, token: &Option<nodes::Token>) {
         self.push_str("nil");
+        self.add_mapping(token.as_ref());
     }
 
     fn write_variable_arguments_expression(&mut self, _token: &Option<nodes::Token>) {
@@ -861,6 +893,7 @@ impl LuaGenerator for DenseLuaGenerator {
                 ));
             }
         }
+        self.add_mapping(number.get_token());
     }
 
     fn write_tuple_arguments(&mut self, arguments: &nodes::TupleArguments) {
@@ -888,6 +921,7 @@ impl LuaGenerator for DenseLuaGenerator {
         } else {
             self.push_str(&result);
         }
+        self.add_mapping(string.get_token());
     }
 
     fn write_interpolated_string(
@@ -919,6 +953,7 @@ impl LuaGenerator for DenseLuaGenerator {
 
     fn write_identifier(&mut self, identifier: &nodes::Identifier) {
         self.push_str(identifier.get_name());
+        self.add_mapping(identifier.get_token());
     }
 
     fn write_parenthese(&mut self, parenthese: &nodes::ParentheseExpression) {
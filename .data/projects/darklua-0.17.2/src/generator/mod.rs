@@ -3,11 +3,13 @@
 
 mod dense;
 mod readable;
+mod source_map;
 mod token_based;
 pub(crate) mod utils;
 
 pub use dense::DenseLuaGenerator;
 pub use readable::ReadableLuaGenerator;
+pub use source_map::{SourceMap, SourceMapBuilder};
 pub use token_based::TokenBasedLuaGenerator;
 
 use crate::nodes;
@@ -1,4 +1,4 @@
-use crate::generator::{utils, LuaGenerator};
+use crate::generator::{utils, LuaGenerator, SourceMap, SourceMapBuilder};
 use crate::nodes;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -64,6 +64,7 @@ pub struct ReadableLuaGenerator {
     output: String,
     last_push_length: usize,
     can_add_new_line_stack: Vec<bool>,
+    source_map: Option<SourceMapBuilder>,
 }
 
 impl ReadableLuaGenerator {
@@ -75,10 +76,33 @@ impl ReadableLuaGenerator {
             current_indentation: 0,
             output: String::new(),
             last_push_length: 0,
+            source_map: None,
             can_add_new_line_stack: Vec::new(),
         }
     }
 
+    /// Enables source map generation: each token written by the generator is
+    /// recorded into the given builder.
+    pub fn with_source_map(mut self, source_map: SourceMapBuilder) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Consumes the generator and produce the code with its source map, if
+    /// source map generation was enabled.
+    pub fn into_string_with_source_map(self) -> (String, Option<SourceMap>) {
+        let output = self.output;
+        let source_map = self.source_map.map(|source_map| source_map.build(&output));
+        (output, source_map)
+    }
+
+    /// Records a mapping for the given token at the content that was just pushed.
+    fn add_mapping(&mut self, token: Option<&nodes::Token>) {
+        if let (Some(source_map), Some(token)) = (self.source_map.as_mut(), token) {
+            source_map.add_token_mapping(self.output.len() - self.last_push_length, token);
+        }
+    }
+
     #[inline]
     fn can_add_new_line(&self) -> bool {
         self.can_add_new_line_stack.last().copied().unwrap_or(true)
@@ -369,6 +393,7 @@ impl ReadableLuaGenerator {
 
     fn write_typed_identifier(&mut self, typed_identifier: &nodes::TypedIdentifier) {
         self.push_str(typed_identifier.get_name());
+        self.add_mapping(typed_identifier.get_token());
 
         if let Some(r#type) = typed_identifier.get_type() {
             self.push_char(':');
@@ -871,16 +896,19 @@ impl LuaGenerator for ReadableLuaGenerator {
         self.write_type(statement.get_type());
     }
 
-    fn write_false_expression(&mut self, _token: &Option<nodes::Token>) {
+    fn write_false_expression(&mut self, token: &Option<nodes::Token>) {
         self.push_str("false");
+        self.add_mapping(token.as_ref());
     }
 
-    fn write_true_expression(&mut self, _token: &Option<nodes::Token>) {
+    fn write_true_expression(&mut self, token: &Option<nodes::Token>) {
         self.push_str("true");
+        self.add_mapping(token.as_ref());
     }
 
-    fn write_nil_expression(&mut self, _token: &Option<nodes::Token>) {
+    fn write_nil_expression(&mut self, token: &Option<nodes::Token>) {
         self.push_str("nil");
+        self.add_mapping(token.as_ref());
     }
 
     fn write_variable_arguments_expression(&mut self, _token: &Option<nodes::Token>) {
@@ -1114,6 +1142,7 @@ impl LuaGenerator for ReadableLuaGenerator {
 
     fn write_number(&mut self, number: &nodes::NumberExpression) {
         self.push_str(&utils::write_number(number));
+        self.add_mapping(number.get_token());
     }
 
     fn write_string(&mut self, string: &nodes::StringExpression) {
@@ -1123,6 +1152,7 @@ impl LuaGenerator for ReadableLuaGenerator {
         } else {
             self.push_str(&result);
         }
+        self.add_mapping(string.get_token());
     }
 
     fn write_interpolated_string(
@@ -1154,6 +1184,7 @@ impl LuaGenerator for ReadableLuaGenerator {
 
     fn write_identifier(&mut self, identifier: &nodes::Identifier) {
         self.push_str(identifier.get_name());
+        self.add_mapping(identifier.get_token());
     }
 
     fn write_parenthese(&mut self, parenthese: &nodes::ParentheseExpression) {
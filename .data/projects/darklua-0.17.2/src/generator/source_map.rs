@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::nodes::Token;
+
+const BASE64_CHARACTERS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mapping {
+    generated_offset: usize,
+    source_index: usize,
+    original_line: usize,
+    original_column: usize,
+}
+
+/// A builder that collects the positions of the tokens written by a
+/// [`LuaGenerator`](super::LuaGenerator) to produce a [`SourceMap`].
+///
+/// The generators record a mapping each time they write a node that has a token
+/// associated with it. This means that the original code must be parsed with a parser
+/// that preserves tokens (see [`Parser::preserve_tokens`](crate::Parser::preserve_tokens)).
+#[derive(Debug, Clone)]
+pub struct SourceMapBuilder {
+    file: Option<String>,
+    sources: Vec<PathBuf>,
+    source_indexes: HashMap<PathBuf, usize>,
+    line_offsets: Vec<usize>,
+    mappings: Vec<Mapping>,
+}
+
+impl SourceMapBuilder {
+    /// Creates a new builder where tokens without an explicit source are mapped to the
+    /// given `source` path.
+    pub fn new(source: impl Into<PathBuf>) -> Self {
+        let source = source.into();
+        Self {
+            file: None,
+            sources: vec![source.clone()],
+            source_indexes: HashMap::from([(source, 0)]),
+            line_offsets: Vec::new(),
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Sets the name of the generated file.
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Provides the original code of the main source, which is used to compute the
+    /// original column of tokens that still reference it.
+    pub fn with_original_code(mut self, code: &str) -> Self {
+        self.line_offsets = iter_line_offsets(code).collect();
+        self
+    }
+
+    pub(crate) fn add_token_mapping(&mut self, generated_offset: usize, token: &Token) {
+        let line_number = match token.get_original_line_number() {
+            Some(line_number) if line_number > 0 => line_number,
+            _ => return,
+        };
+
+        // tokens from other sources (like bundled modules) do not reference the
+        // processed code anymore, so only the column they kept can be used
+        let (source_index, original_column) = match token.get_source() {
+            Some(source) => (
+                self.get_source_index(source),
+                token.get_column().unwrap_or(0),
+            ),
+            None => (
+                0,
+                token
+                    .get_start_offset()
+                    .and_then(|start| {
+                        self.line_offsets
+                            .get(line_number - 1)
+                            .map(|line_start| start.saturating_sub(*line_start))
+                    })
+                    .or_else(|| token.get_column())
+                    .unwrap_or(0),
+            ),
+        };
+
+        self.mappings.push(Mapping {
+            generated_offset,
+            source_index,
+            original_line: line_number - 1,
+            original_column,
+        });
+    }
+
+    /// Moves the mappings starting at `from_offset` in the generated code to `to_offset`.
+    ///
+    /// This is used by generators that rewrite content that was already pushed.
+    pub(crate) fn move_mappings(&mut self, from_offset: usize, to_offset: usize) {
+        for mapping in self.mappings.iter_mut().rev() {
+            if mapping.generated_offset < from_offset {
+                break;
+            }
+            mapping.generated_offset = mapping.generated_offset - from_offset + to_offset;
+        }
+    }
+
+    fn get_source_index(&mut self, source: &Path) -> usize {
+        if let Some(index) = self.source_indexes.get(source) {
+            *index
+        } else {
+            let index = self.sources.len();
+            self.sources.push(source.to_path_buf());
+            self.source_indexes.insert(source.to_path_buf(), index);
+            index
+        }
+    }
+
+    /// Consumes the builder and creates the source map for the given generated code.
+    pub fn build(mut self, generated_code: &str) -> SourceMap {
+        self.mappings
+            .sort_by_key(|mapping| mapping.generated_offset);
+        self.mappings
+            .dedup_by_key(|mapping| mapping.generated_offset);
+
+        let mut mappings = String::new();
+
+        let mut current_offset = 0;
+        let mut generated_line = 0;
+        let mut generated_column = 0;
+
+        let mut previous_generated_line = 0;
+        let mut previous_generated_column = 0;
+        let mut previous_source_index = 0;
+        let mut previous_original_line = 0;
+        let mut previous_original_column = 0;
+
+        for mapping in self.mappings.iter() {
+            let offset = mapping.generated_offset.min(generated_code.len());
+
+            if let Some(content) = generated_code.get(current_offset..offset) {
+                for character in content.chars() {
+                    if character == '\n' {
+                        generated_line += 1;
+                        generated_column = 0;
+                    } else {
+                        generated_column += character.len_utf16();
+                    }
+                }
+                current_offset = offset;
+            }
+
+            if generated_line != previous_generated_line {
+                for _ in previous_generated_line..generated_line {
+                    mappings.push(';');
+                }
+                previous_generated_line = generated_line;
+                previous_generated_column = 0;
+            } else if !mappings.is_empty() && !mappings.ends_with(';') {
+                mappings.push(',');
+            }
+
+            encode_vlq(
+                generated_column as i64 - previous_generated_column as i64,
+                &mut mappings,
+            );
+            encode_vlq(
+                mapping.source_index as i64 - previous_source_index as i64,
+                &mut mappings,
+            );
+            encode_vlq(
+                mapping.original_line as i64 - previous_original_line as i64,
+                &mut mappings,
+            );
+            encode_vlq(
+                mapping.original_column as i64 - previous_original_column as i64,
+                &mut mappings,
+            );
+
+            previous_generated_column = generated_column;
+            previous_source_index = mapping.source_index;
+            previous_original_line = mapping.original_line;
+            previous_original_column = mapping.original_column;
+        }
+
+        SourceMap {
+            version: 3,
+            file: self.file,
+            sources: self
+                .sources
+                .iter()
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+                .collect(),
+            names: Vec::new(),
+            mappings,
+        }
+    }
+}
+
+/// A [Source Map v3](https://sourcemaps.info/spec.html) that links positions of generated
+/// code to the original sources.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceMap {
+    version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    sources: Vec<String>,
+    names: Vec<String>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Returns the name of the generated file, if any.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Returns an iterator over the source paths.
+    pub fn iter_sources(&self) -> impl Iterator<Item = &str> {
+        self.sources.iter().map(String::as_str)
+    }
+
+    /// Returns the encoded mappings.
+    pub fn mappings(&self) -> &str {
+        &self.mappings
+    }
+
+    /// Rewrites the source paths so that they are relative to the given directory.
+    pub fn make_sources_relative_to(&mut self, directory: &Path) {
+        for source in self.sources.iter_mut() {
+            if let Some(relative) = pathdiff::diff_paths(Path::new(source.as_str()), directory) {
+                *source = relative.to_string_lossy().replace('\\', "/");
+            }
+        }
+    }
+
+    /// Serializes the source map to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("source map should serialize to json")
+    }
+}
+
+fn iter_line_offsets(code: &str) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(
+        code.bytes()
+            .enumerate()
+            .filter_map(|(index, byte)| (byte == b'\n').then_some(index + 1)),
+    )
+}
+
+fn encode_vlq(value: i64, output: &mut String) {
+    let mut vlq = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+
+    loop {
+        let mut digit = (vlq & 0b11111) as usize;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        output.push(BASE64_CHARACTERS[digit] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn encode(value: i64) -> String {
+        let mut output = String::new();
+        encode_vlq(value, &mut output);
+        output
+    }
+
+    #[test]
+    fn encode_vlq_zero() {
+        assert_eq!(encode(0), "A");
+    }
+
+    #[test]
+    fn encode_vlq_one() {
+        assert_eq!(encode(1), "C");
+    }
+
+    #[test]
+    fn encode_vlq_minus_one() {
+        assert_eq!(encode(-1), "D");
+    }
+
+    #[test]
+    fn encode_vlq_sixteen() {
+        assert_eq!(encode(16), "gB");
+    }
+
+    #[test]
+    fn encode_vlq_large_value() {
+        assert_eq!(encode(123456), "gkxH");
+    }
+
+    #[test]
+    fn build_empty_source_map() {
+        let source_map = SourceMapBuilder::new("src/main.lua")
+            .with_file("main.lua")
+            .build("");
+
+        assert_eq!(
+            source_map.to_json(),
+            r#"{"version":3,"file":"main.lua","sources":["src/main.lua"],"names":[],"mappings":""}"#
+        );
+    }
+
+    #[test]
+    fn build_mappings_on_multiple_lines() {
+        let code = "local a = 1\nreturn a";
+        let mut builder = SourceMapBuilder::new("src/main.lua").with_original_code(code);
+
+        builder.add_token_mapping(6, &Token::new_with_line(6, 7, 1));
+        builder.add_token_mapping(19, &Token::new_with_line(19, 20, 2));
+
+        assert_eq!(builder.build(code).mappings(), "MAAM;OACC");
+    }
+
+    #[test]
+    fn ignore_tokens_without_line_number() {
+        let mut builder = SourceMapBuilder::new("src/main.lua");
+
+        builder.add_token_mapping(0, &Token::from_content("a"));
+
+        assert_eq!(builder.build("a").mappings(), "");
+    }
+
+    #[test]
+    fn tokens_from_other_sources_are_added_to_sources() {
+        let mut builder = SourceMapBuilder::new("src/main.lua");
+
+        let mut token = Token::from_position(crate::nodes::Position::line_number("a", 3));
+        token.set_source(Arc::from(Path::new("src/module.lua")));
+        builder.add_token_mapping(0, &token);
+
+        let source_map = builder.build("a");
+
+        assert_eq!(
+            source_map.iter_sources().collect::<Vec<_>>(),
+            vec!["src/main.lua", "src/module.lua"]
+        );
+        assert_eq!(source_map.mappings(), "ACEA");
+    }
+
+    #[test]
+    fn make_sources_relative_to_directory() {
+        let mut source_map = SourceMapBuilder::new("src/main.lua").build("");
+
+        source_map.make_sources_relative_to(Path::new("out"));
+
+        assert_eq!(
+            source_map.iter_sources().collect::<Vec<_>>(),
+            vec!["../src/main.lua"]
+        );
+    }
+}
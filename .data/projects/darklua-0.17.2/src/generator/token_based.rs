@@ -1,7 +1,7 @@
 use std::iter;
 
 use crate::{
-    generator::{utils, LuaGenerator},
+    generator::{utils, LuaGenerator, SourceMap, SourceMapBuilder},
     nodes::*,
 };
 
@@ -13,6 +13,7 @@ pub struct TokenBasedLuaGenerator<'a> {
     output: String,
     currently_commenting: bool,
     current_line: usize,
+    source_map: Option<SourceMapBuilder>,
 }
 
 impl<'a> TokenBasedLuaGenerator<'a> {
@@ -22,9 +23,25 @@ impl<'a> TokenBasedLuaGenerator<'a> {
             output: String::new(),
             currently_commenting: false,
             current_line: 1,
+            source_map: None,
         }
     }
 
+    /// Enables source map generation: each token written by the generator is
+    /// recorded into the given builder.
+    pub fn with_source_map(mut self, source_map: SourceMapBuilder) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Consumes the generator and produce the code with its source map, if
+    /// source map generation was enabled.
+    pub fn into_string_with_source_map(self) -> (String, Option<SourceMap>) {
+        let output = self.output;
+        let source_map = self.source_map.map(|source_map| source_map.build(&output));
+        (output, source_map)
+    }
+
     fn push_str(&mut self, string: &str) {
         self.current_line += utils::count_new_lines(string.as_bytes());
         self.output.push_str(string);
@@ -89,6 +106,10 @@ impl<'a> TokenBasedLuaGenerator<'a> {
                 }
             }
 
+            if let Some(source_map) = self.source_map.as_mut() {
+                source_map.add_token_mapping(self.output.len(), token);
+            }
+
             self.push_str(content);
         }
 
@@ -56,6 +56,7 @@
 mod ast_converter;
 mod frontend;
 pub mod generator;
+pub mod lint;
 pub mod nodes;
 mod parser;
 pub mod process;
@@ -63,7 +64,8 @@ pub mod rules;
 mod utils;
 
 pub use frontend::{
-    convert_data, process, BundleConfiguration, Configuration, DarkluaError, GeneratorParameters,
-    Options, Resources, WorkerTree,
+    convert_data, convert_data_to_typed_module, process, process_sources, BundleConfiguration,
+    Configuration, DarkluaError, FileChange, GeneratorParameters, IncrementalSession,
+    IncrementalUpdate, Options, ProcessOutput, Resources, WorkerTree,
 };
 pub use parser::{Parser, ParserError};
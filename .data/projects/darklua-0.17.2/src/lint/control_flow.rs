@@ -0,0 +1,159 @@
+use crate::nodes::*;
+use crate::process::{Evaluator, NodeProcessor};
+
+use super::{Lint, LintFinding};
+
+/// Reports unreachable code and conditions that are always truthy.
+#[derive(Debug, Default)]
+pub(crate) struct ControlFlowLinter {
+    evaluator: Evaluator,
+    findings: Vec<LintFinding>,
+}
+
+impl ControlFlowLinter {
+    pub(crate) fn into_findings(self) -> Vec<LintFinding> {
+        self.findings
+    }
+
+    fn push_unreachable(&mut self, token: Option<&Token>) {
+        self.findings.push(LintFinding::new(
+            Lint::UnreachableCode,
+            "unreachable code",
+            token,
+        ));
+    }
+
+    fn check_condition(&mut self, condition: &Expression, token: Option<&Token>) {
+        if self.evaluator.evaluate(condition).is_truthy() == Some(true) {
+            self.findings.push(LintFinding::new(
+                Lint::AlwaysTrueCondition,
+                "condition is always truthy",
+                token,
+            ));
+        }
+    }
+}
+
+fn block_always_exits(block: &Block) -> bool {
+    block.get_last_statement().is_some() || block.iter_statements().any(statement_always_exits)
+}
+
+fn statement_always_exits(statement: &Statement) -> bool {
+    match statement {
+        Statement::Do(do_statement) => block_always_exits(do_statement.get_block()),
+        Statement::If(if_statement) => {
+            if_statement
+                .get_else_block()
+                .map(block_always_exits)
+                .unwrap_or(false)
+                && if_statement
+                    .iter_branches()
+                    .all(|branch| block_always_exits(branch.get_block()))
+        }
+        _ => false,
+    }
+}
+
+fn statement_token(statement: &Statement) -> Option<&Token> {
+    match statement {
+        Statement::Assign(assign) => assign
+            .get_variables()
+            .first()
+            .and_then(variable_token)
+            .or_else(|| assign.get_tokens().map(|tokens| &tokens.equal)),
+        Statement::Do(do_statement) => do_statement.get_tokens().map(|tokens| &tokens.r#do),
+        Statement::Call(call) => prefix_token(call.get_prefix()),
+        Statement::CompoundAssign(assign) => variable_token(assign.get_variable()),
+        Statement::Function(function) => function.get_tokens().map(|tokens| &tokens.function),
+        Statement::GenericFor(generic_for) => generic_for.get_tokens().map(|tokens| &tokens.r#for),
+        Statement::If(if_statement) => if_statement.get_tokens().map(|tokens| &tokens.r#if),
+        Statement::LocalAssign(local_assign) => {
+            local_assign.get_tokens().map(|tokens| &tokens.local)
+        }
+        Statement::LocalFunction(local_function) => {
+            local_function.get_tokens().map(|tokens| &tokens.local)
+        }
+        Statement::NumericFor(numeric_for) => numeric_for.get_tokens().map(|tokens| &tokens.r#for),
+        Statement::Repeat(repeat) => repeat.get_tokens().map(|tokens| &tokens.repeat),
+        Statement::While(while_statement) => {
+            while_statement.get_tokens().map(|tokens| &tokens.r#while)
+        }
+        Statement::TypeDeclaration(type_declaration) => type_declaration
+            .get_tokens()
+            .map(|tokens| tokens.export.as_ref().unwrap_or(&tokens.r#type)),
+    }
+}
+
+fn last_statement_token(statement: &LastStatement) -> Option<&Token> {
+    match statement {
+        LastStatement::Break(token) | LastStatement::Continue(token) => token.as_ref(),
+        LastStatement::Return(return_statement) => {
+            return_statement.get_tokens().map(|tokens| &tokens.r#return)
+        }
+    }
+}
+
+fn variable_token(variable: &Variable) -> Option<&Token> {
+    match variable {
+        Variable::Identifier(identifier) => identifier.get_token(),
+        Variable::Field(field) => prefix_token(field.get_prefix()),
+        Variable::Index(index) => prefix_token(index.get_prefix()),
+    }
+}
+
+fn prefix_token(prefix: &Prefix) -> Option<&Token> {
+    match prefix {
+        Prefix::Call(call) => prefix_token(call.get_prefix()),
+        Prefix::Field(field) => prefix_token(field.get_prefix()),
+        Prefix::Identifier(identifier) => identifier.get_token(),
+        Prefix::Index(index) => prefix_token(index.get_prefix()),
+        Prefix::Parenthese(parenthese) => parenthese
+            .get_tokens()
+            .map(|tokens| &tokens.left_parenthese),
+    }
+}
+
+impl NodeProcessor for ControlFlowLinter {
+    fn process_block(&mut self, block: &mut Block) {
+        let mut exits = false;
+
+        for statement in block.iter_statements() {
+            if exits {
+                self.push_unreachable(statement_token(statement));
+                return;
+            }
+            exits = statement_always_exits(statement);
+        }
+
+        if exits {
+            if let Some(last_statement) = block.get_last_statement() {
+                self.push_unreachable(last_statement_token(last_statement));
+            }
+        }
+    }
+
+    fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
+        for (index, branch) in if_statement.iter_branches().enumerate() {
+            let token = if index == 0 {
+                if_statement.get_tokens().map(|tokens| &tokens.r#if)
+            } else {
+                branch.get_tokens().map(|tokens| &tokens.elseif)
+            };
+            self.check_condition(branch.get_condition(), token);
+        }
+    }
+
+    fn process_if_expression(&mut self, if_expression: &mut IfExpression) {
+        self.check_condition(
+            if_expression.get_condition(),
+            if_expression.get_tokens().map(|tokens| &tokens.r#if),
+        );
+
+        for branch in if_expression.iter_branches() {
+            self.check_condition(
+                branch.get_condition(),
+                branch.get_tokens().map(|tokens| &tokens.elseif),
+            );
+        }
+    }
+}
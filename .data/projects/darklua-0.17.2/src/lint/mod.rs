@@ -0,0 +1,448 @@
+//! Static analysis of Lua code to report common mistakes.
+//!
+//! The [`Linter`] parses code and runs a set of [`Lint`] on it. Each lint can be
+//! configured with a [`LintLevel`] using a [`LintConfiguration`].
+//!
+//! ```rust
+//! use darklua_core::lint::{Lint, LintConfiguration, LintLevel, Linter};
+//!
+//! let linter = Linter::new(
+//!     LintConfiguration::default().with_level(Lint::GlobalAssignment, LintLevel::Error),
+//! );
+//!
+//! let diagnostics = linter.lint("value = true").expect("unable to parse code");
+//!
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(diagnostics[0].lint(), Lint::GlobalAssignment);
+//! assert_eq!(diagnostics[0].level(), LintLevel::Error);
+//! ```
+
+mod control_flow;
+mod scope_lints;
+mod span;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::nodes::{Block, Token};
+use crate::process::{DefaultVisitor, NodeVisitor, ScopeVisitor};
+use crate::{Parser, ParserError};
+
+use control_flow::ControlFlowLinter;
+use scope_lints::ScopeLinter;
+pub use span::Span;
+
+/// The available lints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lint {
+    /// A local variable or local function that is never read.
+    UnusedVariable,
+    /// A local variable that has the same name as another variable from an enclosing scope.
+    ShadowedLocal,
+    /// An assignment to a variable that is not declared as a local.
+    GlobalAssignment,
+    /// Statements that can never run because the code before them always exits the block.
+    UnreachableCode,
+    /// An `if` branch condition that always evaluates to a truthy value.
+    AlwaysTrueCondition,
+}
+
+impl Lint {
+    /// All the available lints.
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariable,
+        Lint::ShadowedLocal,
+        Lint::GlobalAssignment,
+        Lint::UnreachableCode,
+        Lint::AlwaysTrueCondition,
+    ];
+
+    /// Returns the name of the lint, as used in configuration files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused_variable",
+            Lint::ShadowedLocal => "shadowed_local",
+            Lint::GlobalAssignment => "global_assignment",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::AlwaysTrueCondition => "always_true_condition",
+        }
+    }
+
+    /// Returns a short description of what the lint reports.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "local variable or local function that is never read",
+            Lint::ShadowedLocal => "local variable that shadows a variable of an enclosing scope",
+            Lint::GlobalAssignment => "assignment to a variable that is not declared as local",
+            Lint::UnreachableCode => "code that can never be executed",
+            Lint::AlwaysTrueCondition => "condition that always evaluates to a truthy value",
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lint::ALL
+            .iter()
+            .find(|lint| lint.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "invalid lint name `{}` (possible options are: {})",
+                    s,
+                    Lint::ALL
+                        .iter()
+                        .map(|lint| format!("`{}`", lint.name()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// The level at which a lint is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    /// The lint is not reported.
+    Allow,
+    /// The lint is reported as a warning.
+    Warn,
+    /// The lint is reported as an error.
+    Error,
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warning"),
+            LintLevel::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Configures the level of each lint.
+///
+/// Lints that are not configured are reported as warnings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfiguration {
+    #[serde(default, deserialize_with = "deserialize_lint_levels")]
+    lints: BTreeMap<Lint, LintLevel>,
+}
+
+// json5 can not deserialize enums as map keys, so the keys are parsed from strings
+fn deserialize_lint_levels<'de, D>(deserializer: D) -> Result<BTreeMap<Lint, LintLevel>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, LintLevel>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, level)| {
+            name.parse::<Lint>()
+                .map(|lint| (lint, level))
+                .map_err(serde::de::Error::custom)
+        })
+        .collect()
+}
+
+impl LintConfiguration {
+    /// Sets the level of a lint.
+    pub fn with_level(mut self, lint: Lint, level: LintLevel) -> Self {
+        self.set_level(lint, level);
+        self
+    }
+
+    /// Sets the level of a lint.
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.lints.insert(lint, level);
+    }
+
+    /// Returns the level of a lint.
+    pub fn get_level(&self, lint: Lint) -> LintLevel {
+        self.lints.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    fn is_enabled(&self, lint: Lint) -> bool {
+        self.get_level(lint) != LintLevel::Allow
+    }
+}
+
+/// A problem reported by a lint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    lint: Lint,
+    level: LintLevel,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// Returns the lint that produced this diagnostic.
+    pub fn lint(&self) -> Lint {
+        self.lint
+    }
+
+    /// Returns the level of this diagnostic.
+    pub fn level(&self) -> LintLevel {
+        self.level
+    }
+
+    /// Returns the message describing the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the location of the problem in the source code, if known.
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LintFinding {
+    lint: Lint,
+    message: String,
+    token: Option<Token>,
+}
+
+impl LintFinding {
+    pub(crate) fn new(lint: Lint, message: impl Into<String>, token: Option<&Token>) -> Self {
+        Self {
+            lint,
+            message: message.into(),
+            token: token.cloned(),
+        }
+    }
+}
+
+/// Runs the configured lints on Lua code.
+#[derive(Debug, Clone, Default)]
+pub struct Linter {
+    configuration: LintConfiguration,
+}
+
+impl Linter {
+    /// Creates a new linter with the given configuration.
+    pub fn new(configuration: LintConfiguration) -> Self {
+        Self { configuration }
+    }
+
+    /// Parses the given code and returns the diagnostics sorted by their location.
+    pub fn lint(&self, code: &str) -> Result<Vec<Diagnostic>, ParserError> {
+        let mut block = Parser::default().preserve_tokens().parse(code)?;
+        Ok(self.lint_block(&mut block, code))
+    }
+
+    /// Returns the diagnostics for a block that was parsed from the given code. To
+    /// get accurate spans, the block must be parsed with a parser that preserves tokens.
+    pub fn lint_block(&self, block: &mut Block, code: &str) -> Vec<Diagnostic> {
+        let mut scope_linter = ScopeLinter::default();
+        ScopeVisitor::visit_block(block, &mut scope_linter);
+
+        let mut control_flow_linter = ControlFlowLinter::default();
+        DefaultVisitor::visit_block(block, &mut control_flow_linter);
+
+        let line_offsets = span::line_offsets(code);
+
+        let mut diagnostics: Vec<_> = scope_linter
+            .into_findings()
+            .into_iter()
+            .chain(control_flow_linter.into_findings())
+            .filter(|finding| self.configuration.is_enabled(finding.lint))
+            .map(|finding| Diagnostic {
+                lint: finding.lint,
+                level: self.configuration.get_level(finding.lint),
+                message: finding.message,
+                span: finding
+                    .token
+                    .as_ref()
+                    .and_then(|token| Span::from_token(token, code, &line_offsets)),
+            })
+            .collect();
+
+        diagnostics.sort_by_key(|diagnostic| {
+            diagnostic
+                .span
+                .as_ref()
+                .map(|span| (span.line(), span.column()))
+        });
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lint(code: &str) -> Vec<(Lint, String)> {
+        Linter::default()
+            .lint(code)
+            .expect("unable to parse code")
+            .into_iter()
+            .map(|diagnostic| (diagnostic.lint(), diagnostic.message().to_owned()))
+            .collect()
+    }
+
+    fn lint_names(code: &str) -> Vec<Lint> {
+        lint(code).into_iter().map(|(lint, _)| lint).collect()
+    }
+
+    macro_rules! test_lints {
+        ($($name:ident ($code:literal) => [$($lint:ident),* $(,)?]),* $(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    pretty_assertions::assert_eq!(
+                        lint_names($code),
+                        vec![$(Lint::$lint),*],
+                        "unexpected diagnostics for `{}`",
+                        $code,
+                    );
+                }
+            )*
+        };
+    }
+
+    test_lints!(
+        empty_code("") => [],
+        used_local("local a = 1 print(a)") => [],
+        unused_local("local a = 1") => [UnusedVariable],
+        unused_local_with_underscore_prefix("local _a = 1") => [],
+        unused_underscore("local _ = 1") => [],
+        local_only_assigned("local a a = 1") => [UnusedVariable],
+        local_used_in_field_assignment("local a = {} a.b = 1") => [],
+        local_used_in_index_assignment("local a = {} a[1] = 1") => [],
+        unused_local_function("local function f() end") => [UnusedVariable],
+        used_local_function("local function f() end f()") => [],
+        unused_parameter("return function(a) end") => [],
+        unused_for_identifier("for i = 1, 10 do end") => [],
+        local_used_in_nested_function("local a = 1 return function() return a end") => [],
+        local_used_in_typeof("local a = 1 type A = typeof(a)") => [],
+        shadowed_local("local a = 1 do local a = 2 print(a) end print(a)") => [ShadowedLocal],
+        shadowed_parameter("return function(a) local a = a return a end") => [ShadowedLocal],
+        redeclared_local_in_same_scope("local a = 1 print(a) local a = 2 print(a)") => [ShadowedLocal],
+        shadowed_underscore("local _ = 1 do local _ = 2 end") => [],
+        global_assignment("value = true") => [GlobalAssignment],
+        global_function_statement("function run() end") => [GlobalAssignment],
+        global_field_function_statement("local M = {} function M.run() end return M") => [],
+        local_assignment("local value value = true return value") => [],
+        local_function_statement("local run function run() end return run") => [],
+        compound_assignment_to_global("count += 1") => [GlobalAssignment],
+        unreachable_after_do_return("do return end print('unreachable')") => [UnreachableCode],
+        unreachable_after_if_else_return(
+            "local a = ... if a then return 1 else return 2 end print(a)"
+        ) => [UnreachableCode],
+        reachable_after_if_without_else(
+            "local a = ... if a then return 1 end print(a)"
+        ) => [],
+        unreachable_after_do_break_in_loop("while true do do break end print('x') end") => [UnreachableCode],
+        unreachable_last_statement("do return end return 1") => [UnreachableCode],
+        always_true_if("if true then end") => [AlwaysTrueCondition],
+        always_true_elseif("local a = ... if a then elseif 'ok' then end") => [AlwaysTrueCondition],
+        always_true_if_expression("return if 1 then 'a' else 'b'") => [AlwaysTrueCondition],
+        unknown_condition("local a = ... if a then end") => [],
+        always_false_condition("if false then end") => [],
+        while_true_is_allowed("while true do break end") => [],
+    );
+
+    #[test]
+    fn unused_variable_message() {
+        pretty_assertions::assert_eq!(
+            lint("local value = 1"),
+            vec![(
+                Lint::UnusedVariable,
+                "variable `value` is never used".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn allowed_lints_are_not_reported() {
+        let linter = Linter::new(
+            LintConfiguration::default().with_level(Lint::UnusedVariable, LintLevel::Allow),
+        );
+
+        assert!(linter.lint("local a = 1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn diagnostics_have_spans() {
+        let diagnostics = Linter::default()
+            .lint("local a = 1\nlocal value = a\n")
+            .unwrap();
+
+        pretty_assertions::assert_eq!(diagnostics.len(), 1);
+        let span = diagnostics[0].span().expect("span should be defined");
+        pretty_assertions::assert_eq!(
+            (
+                span.line(),
+                span.column(),
+                span.end_line(),
+                span.end_column()
+            ),
+            (2, 7, 2, 12)
+        );
+    }
+
+    #[test]
+    fn diagnostics_are_sorted_by_location() {
+        let diagnostics = Linter::default()
+            .lint("local b = 1\nlocal a = 2\n")
+            .unwrap();
+
+        pretty_assertions::assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.span().unwrap().line())
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn parse_lint_from_name() {
+        for lint in Lint::ALL {
+            pretty_assertions::assert_eq!(lint.name().parse::<Lint>(), Ok(lint));
+        }
+    }
+
+    #[test]
+    fn parse_invalid_lint_name() {
+        assert!("unknown".parse::<Lint>().is_err());
+    }
+
+    #[test]
+    fn deserialize_configuration_with_invalid_lint_name() {
+        assert!(json5::from_str::<LintConfiguration>("{ lints: { unknown: 'error' } }").is_err());
+    }
+
+    #[test]
+    fn deserialize_configuration() {
+        let configuration: LintConfiguration =
+            json5::from_str("{ lints: { unused_variable: 'error', shadowed_local: 'allow' } }")
+                .unwrap();
+
+        pretty_assertions::assert_eq!(
+            configuration,
+            LintConfiguration::default()
+                .with_level(Lint::UnusedVariable, LintLevel::Error)
+                .with_level(Lint::ShadowedLocal, LintLevel::Allow)
+        );
+    }
+}
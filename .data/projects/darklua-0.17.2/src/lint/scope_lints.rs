@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+
+use crate::nodes::*;
+use crate::process::{NodeProcessor, Scope};
+
+use super::{Lint, LintFinding};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VariableKind {
+    Local,
+    LocalFunction,
+    Parameter,
+}
+
+#[derive(Debug)]
+struct LocalVariable {
+    name: String,
+    token: Option<Token>,
+    kind: VariableKind,
+    used: bool,
+}
+
+/// Tracks local variables to report unused variables, shadowed locals and
+/// assignments to global variables. It must be used with the `ScopeVisitor`.
+#[derive(Debug, Default)]
+pub(crate) struct ScopeLinter {
+    scopes: Vec<Vec<LocalVariable>>,
+    pending_local_tokens: Vec<VecDeque<Option<Token>>>,
+    next_identifier_is_assigned: bool,
+    findings: Vec<LintFinding>,
+}
+
+impl ScopeLinter {
+    pub(crate) fn into_findings(self) -> Vec<LintFinding> {
+        self.findings
+    }
+
+    fn find_variable(&mut self, name: &str) -> Option<&mut LocalVariable> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|variable| variable.name == name)
+    }
+
+    fn declare(&mut self, name: &str, token: Option<Token>, kind: VariableKind) {
+        if kind != VariableKind::Parameter
+            && !is_ignored(name)
+            && self.find_variable(name).is_some()
+        {
+            self.findings.push(LintFinding::new(
+                Lint::ShadowedLocal,
+                format!(
+                    "variable `{}` shadows another variable with the same name",
+                    name
+                ),
+                token.as_ref(),
+            ));
+        }
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(LocalVariable {
+                name: name.to_owned(),
+                token,
+                kind,
+                used: false,
+            });
+        }
+    }
+}
+
+fn is_ignored(name: &str) -> bool {
+    name.starts_with('_')
+}
+
+impl Scope for ScopeLinter {
+    fn push(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn pop(&mut self) {
+        for variable in self.scopes.pop().unwrap_or_default() {
+            if variable.used || is_ignored(&variable.name) {
+                continue;
+            }
+            let message = match variable.kind {
+                VariableKind::Local => format!("variable `{}` is never used", variable.name),
+                VariableKind::LocalFunction => {
+                    format!("local function `{}` is never used", variable.name)
+                }
+                VariableKind::Parameter => continue,
+            };
+            self.findings.push(LintFinding::new(
+                Lint::UnusedVariable,
+                message,
+                variable.token.as_ref(),
+            ));
+        }
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare(identifier, None, VariableKind::Parameter);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self", None, VariableKind::Parameter);
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        let token = match self.pending_local_tokens.last_mut() {
+            Some(tokens) => {
+                let token = tokens.pop_front().flatten();
+                if tokens.is_empty() {
+                    self.pending_local_tokens.pop();
+                }
+                token
+            }
+            None => None,
+        };
+        self.declare(identifier, token, VariableKind::Local);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        let token = function.get_identifier().get_token().cloned();
+        self.declare(
+            function.get_identifier().get_name(),
+            token,
+            VariableKind::LocalFunction,
+        );
+    }
+}
+
+impl NodeProcessor for ScopeLinter {
+    fn process_local_assign_statement(&mut self, statement: &mut LocalAssignStatement) {
+        // values are visited before the variables are inserted, so nested local
+        // assignments are stacked on top of this one
+        self.pending_local_tokens.push(
+            statement
+                .iter_variables()
+                .map(|variable| variable.get_token().cloned())
+                .collect(),
+        );
+    }
+
+    fn process_function_statement(&mut self, statement: &mut FunctionStatement) {
+        let name = statement.get_name();
+        self.next_identifier_is_assigned = name.get_field_names().is_empty() && !name.has_method();
+    }
+
+    fn process_variable(&mut self, variable: &mut Variable) {
+        self.next_identifier_is_assigned = matches!(variable, Variable::Identifier(_));
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let is_assigned = std::mem::take(&mut self.next_identifier_is_assigned);
+
+        match self.find_variable(identifier.get_name()) {
+            Some(variable) => {
+                if !is_assigned {
+                    variable.used = true;
+                }
+            }
+            None => {
+                if is_assigned {
+                    self.findings.push(LintFinding::new(
+                        Lint::GlobalAssignment,
+                        format!("assignment to global variable `{}`", identifier.get_name()),
+                        identifier.get_token(),
+                    ));
+                }
+            }
+        }
+    }
+}
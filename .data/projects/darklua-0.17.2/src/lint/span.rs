@@ -0,0 +1,120 @@
+use serde::Serialize;
+
+use crate::nodes::Token;
+
+/// A location in the source code. Lines and columns start at 1 and the end
+/// column is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl Span {
+    pub(crate) fn from_token(token: &Token, code: &str, line_offsets: &[usize]) -> Option<Self> {
+        let line = token.get_line_number()?;
+
+        match (token.get_start_offset(), token.get_end_offset()) {
+            (Some(start), Some(end)) => {
+                let (line, column) = position_at(code, line_offsets, start);
+                let (end_line, end_column) = position_at(code, line_offsets, end);
+                Some(Self {
+                    line,
+                    column,
+                    end_line,
+                    end_column,
+                })
+            }
+            _ => Some(Self {
+                line,
+                column: 1,
+                end_line: line,
+                end_column: 1 + token.read(code).chars().count(),
+            }),
+        }
+    }
+
+    /// The line where the span starts.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column where the span starts.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The line where the span ends.
+    pub fn end_line(&self) -> usize {
+        self.end_line
+    }
+
+    /// The column where the span ends (exclusive).
+    pub fn end_column(&self) -> usize {
+        self.end_column
+    }
+}
+
+pub(crate) fn line_offsets(code: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(
+            code.bytes()
+                .enumerate()
+                .filter_map(|(index, byte)| (byte == b'\n').then_some(index + 1)),
+        )
+        .collect()
+}
+
+fn position_at(code: &str, line_offsets: &[usize], offset: usize) -> (usize, usize) {
+    let line_index = match line_offsets.binary_search(&offset) {
+        Ok(index) => index,
+        Err(index) => index.saturating_sub(1),
+    };
+    let line_start = line_offsets.get(line_index).copied().unwrap_or(0);
+    let column = code
+        .get(line_start..offset)
+        .map(|content| content.chars().count())
+        .unwrap_or(0);
+
+    (line_index + 1, column + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn position_at_start_of_code() {
+        let code = "local a";
+        assert_eq!(position_at(code, &line_offsets(code), 0), (1, 1));
+    }
+
+    #[test]
+    fn position_at_second_line() {
+        let code = "local a\nlocal b";
+        assert_eq!(position_at(code, &line_offsets(code), 14), (2, 7));
+    }
+
+    #[test]
+    fn position_at_line_start() {
+        let code = "local a\nlocal b";
+        assert_eq!(position_at(code, &line_offsets(code), 8), (2, 1));
+    }
+
+    #[test]
+    fn span_from_token_without_offsets() {
+        let token = Token::from_position(crate::nodes::Position::line_number("value", 3));
+
+        assert_eq!(
+            Span::from_token(&token, "", &[0]),
+            Some(Span {
+                line: 3,
+                column: 1,
+                end_line: 3,
+                end_column: 6,
+            })
+        );
+    }
+}
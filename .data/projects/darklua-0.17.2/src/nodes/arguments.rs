@@ -267,17 +267,24 @@ impl Arguments {
     }
 
     /// Replaces referenced tokens with the actual content from the source code.
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
         match self {
-            Arguments::Tuple(tuple) => tuple.replace_referenced_tokens(code),
+            Arguments::Tuple(tuple) => tuple.replace_referenced_tokens(code, record_origin),
             Arguments::String(_) | Arguments::Table(_) => {}
         }
     }
 
     /// Shifts token line numbers by the specified amount.
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
         match self {
-            Arguments::Tuple(tuple) => tuple.shift_token_line(amount),
+            Arguments::Tuple(tuple) => tuple.shift_token_line(amount, record_origin),
+            Arguments::String(_) | Arguments::Table(_) => {}
+        }
+    }
+
+    pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
+        match self {
+            Arguments::Tuple(tuple) => tuple.set_token_source(source),
             Arguments::String(_) | Arguments::Table(_) => {}
         }
     }
@@ -190,17 +190,30 @@ impl InterpolationSegment {
         }
     }
 
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
         match self {
-            InterpolationSegment::String(segment) => segment.replace_referenced_tokens(code),
-            InterpolationSegment::Value(segment) => segment.replace_referenced_tokens(code),
+            InterpolationSegment::String(segment) => {
+                segment.replace_referenced_tokens(code, record_origin)
+            }
+            InterpolationSegment::Value(segment) => {
+                segment.replace_referenced_tokens(code, record_origin)
+            }
+        }
+    }
+
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
+        match self {
+            InterpolationSegment::String(segment) => {
+                segment.shift_token_line(amount, record_origin)
+            }
+            InterpolationSegment::Value(segment) => segment.shift_token_line(amount, record_origin),
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
+    pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
         match self {
-            InterpolationSegment::String(segment) => segment.shift_token_line(amount),
-            InterpolationSegment::Value(segment) => segment.shift_token_line(amount),
+            InterpolationSegment::String(segment) => segment.set_token_source(source),
+            InterpolationSegment::Value(segment) => segment.set_token_source(source),
         }
     }
 
@@ -350,19 +350,31 @@ impl NumberExpression {
         }
     }
 
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
         match self {
-            NumberExpression::Decimal(number) => number.replace_referenced_tokens(code),
-            NumberExpression::Hex(number) => number.replace_referenced_tokens(code),
-            NumberExpression::Binary(number) => number.replace_referenced_tokens(code),
+            NumberExpression::Decimal(number) => {
+                number.replace_referenced_tokens(code, record_origin)
+            }
+            NumberExpression::Hex(number) => number.replace_referenced_tokens(code, record_origin),
+            NumberExpression::Binary(number) => {
+                number.replace_referenced_tokens(code, record_origin)
+            }
+        }
+    }
+
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
+        match self {
+            NumberExpression::Decimal(number) => number.shift_token_line(amount, record_origin),
+            NumberExpression::Hex(number) => number.shift_token_line(amount, record_origin),
+            NumberExpression::Binary(number) => number.shift_token_line(amount, record_origin),
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
+    pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
         match self {
-            NumberExpression::Decimal(number) => number.shift_token_line(amount),
-            NumberExpression::Hex(number) => number.shift_token_line(amount),
-            NumberExpression::Binary(number) => number.shift_token_line(amount),
+            NumberExpression::Decimal(number) => number.set_token_source(source),
+            NumberExpression::Hex(number) => number.set_token_source(source),
+            NumberExpression::Binary(number) => number.set_token_source(source),
         }
     }
 
@@ -208,18 +208,26 @@ impl TableEntry {
         }
     }
 
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
         match self {
-            TableEntry::Field(entry) => entry.replace_referenced_tokens(code),
-            TableEntry::Index(entry) => entry.replace_referenced_tokens(code),
+            TableEntry::Field(entry) => entry.replace_referenced_tokens(code, record_origin),
+            TableEntry::Index(entry) => entry.replace_referenced_tokens(code, record_origin),
             TableEntry::Value(_) => {}
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
         match self {
-            TableEntry::Field(entry) => entry.shift_token_line(amount),
-            TableEntry::Index(entry) => entry.shift_token_line(amount),
+            TableEntry::Field(entry) => entry.shift_token_line(amount, record_origin),
+            TableEntry::Index(entry) => entry.shift_token_line(amount, record_origin),
+            TableEntry::Value(_) => {}
+        }
+    }
+
+    pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
+        match self {
+            TableEntry::Field(entry) => entry.set_token_source(source),
+            TableEntry::Index(entry) => entry.set_token_source(source),
             TableEntry::Value(_) => {}
         }
     }
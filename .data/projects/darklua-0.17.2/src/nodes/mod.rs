@@ -64,34 +64,50 @@ macro_rules! impl_token_fns {
             )*)?
         }
 
-        pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+        pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
             $(
-                self.$field.replace_referenced_tokens(code);
+                self.$field.replace_referenced_tokens(code, record_origin);
             )*
             $($(
                 for token in self.$iter_field.iter_mut() {
-                    token.replace_referenced_tokens(code);
+                    token.replace_referenced_tokens(code, record_origin);
                 }
             )*)?
             $($(
                 for token in self.$iter_flatten_field.iter_mut().flatten() {
-                    token.replace_referenced_tokens(code);
+                    token.replace_referenced_tokens(code, record_origin);
                 }
             )*)?
         }
 
-        pub(crate) fn shift_token_line(&mut self, amount: isize) {
+        pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
             $(
-                self.$field.shift_token_line(amount);
+                self.$field.shift_token_line(amount, record_origin);
             )*
             $($(
                 for token in self.$iter_field.iter_mut() {
-                    token.shift_token_line(amount);
+                    token.shift_token_line(amount, record_origin);
                 }
             )*)?
             $($(
                 for token in self.$iter_flatten_field.iter_mut().flatten() {
-                    token.shift_token_line(amount);
+                    token.shift_token_line(amount, record_origin);
+                }
+            )*)?
+        }
+
+        pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
+            $(
+                self.$field.set_token_source(source);
+            )*
+            $($(
+                for token in self.$iter_field.iter_mut() {
+                    token.set_token_source(source);
+                }
+            )*)?
+            $($(
+                for token in self.$iter_flatten_field.iter_mut().flatten() {
+                    token.set_token_source(source);
                 }
             )*)?
         }
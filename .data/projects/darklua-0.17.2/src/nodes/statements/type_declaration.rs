@@ -197,54 +197,81 @@ impl TypeDeclarationStatement {
         }
     }
 
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
-        self.name.replace_referenced_tokens(code);
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
+        self.name.replace_referenced_tokens(code, record_origin);
         if let Some(tokens) = &mut self.tokens {
-            tokens.replace_referenced_tokens(code);
+            tokens.replace_referenced_tokens(code, record_origin);
         }
         if let Some(parameters) = self.generic_parameters.as_mut() {
-            parameters.replace_referenced_tokens(code);
+            parameters.replace_referenced_tokens(code, record_origin);
 
             for parameter in parameters {
                 match parameter {
                     GenericParameterMutRef::TypeVariable(variable) => {
-                        variable.replace_referenced_tokens(code);
+                        variable.replace_referenced_tokens(code, record_origin);
                     }
                     GenericParameterMutRef::TypeVariableWithDefault(variable_with_default) => {
-                        variable_with_default.replace_referenced_tokens(code);
+                        variable_with_default.replace_referenced_tokens(code, record_origin);
                     }
                     GenericParameterMutRef::GenericTypePack(_) => {}
                     GenericParameterMutRef::GenericTypePackWithDefault(
                         generic_pack_with_default,
                     ) => {
-                        generic_pack_with_default.replace_referenced_tokens(code);
+                        generic_pack_with_default.replace_referenced_tokens(code, record_origin);
                     }
                 }
             }
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
-        self.name.shift_token_line(amount);
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
+        self.name.shift_token_line(amount, record_origin);
         if let Some(tokens) = &mut self.tokens {
-            tokens.shift_token_line(amount);
+            tokens.shift_token_line(amount, record_origin);
         }
         if let Some(parameters) = self.generic_parameters.as_mut() {
-            parameters.shift_token_line(amount);
+            parameters.shift_token_line(amount, record_origin);
 
             for parameter in parameters {
                 match parameter {
                     GenericParameterMutRef::TypeVariable(variable) => {
-                        variable.shift_token_line(amount);
+                        variable.shift_token_line(amount, record_origin);
                     }
                     GenericParameterMutRef::TypeVariableWithDefault(variable_with_default) => {
-                        variable_with_default.shift_token_line(amount);
+                        variable_with_default.shift_token_line(amount, record_origin);
                     }
                     GenericParameterMutRef::GenericTypePack(_) => {}
                     GenericParameterMutRef::GenericTypePackWithDefault(
                         generic_pack_with_default,
                     ) => {
-                        generic_pack_with_default.shift_token_line(amount);
+                        generic_pack_with_default.shift_token_line(amount, record_origin);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
+        self.name.set_token_source(source);
+        if let Some(tokens) = &mut self.tokens {
+            tokens.set_token_source(source);
+        }
+        if let Some(parameters) = self.generic_parameters.as_mut() {
+            parameters.set_token_source(source);
+
+            for parameter in parameters {
+                match parameter {
+                    GenericParameterMutRef::TypeVariable(variable) => {
+                        variable.set_token_source(source);
+                    }
+                    GenericParameterMutRef::TypeVariableWithDefault(variable_with_default) => {
+                        variable_with_default.set_token_source(source);
+                    }
+                    GenericParameterMutRef::GenericTypePack(_) => {}
+                    GenericParameterMutRef::GenericTypePackWithDefault(
+                        generic_pack_with_default,
+                    ) => {
+                        generic_pack_with_default.set_token_source(source);
                     }
                 }
             }
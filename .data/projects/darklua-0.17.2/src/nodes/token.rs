@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt, path::Path, sync::Arc};
 
 /// Represents a position in the source code.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -113,11 +113,47 @@ impl Trivia {
 }
 
 /// Represents a token in the source code with its position and associated comments or whitespaces.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct Token {
     position: Position,
     leading_trivia: Vec<Trivia>,
     trailing_trivia: Vec<Trivia>,
+    origin: Option<Box<TokenOrigin>>,
+}
+
+// The origin of a token only matters for source maps, so it is not compared
+// and only shown when it is recorded
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Token");
+        debug
+            .field("position", &self.position)
+            .field("leading_trivia", &self.leading_trivia)
+            .field("trailing_trivia", &self.trailing_trivia);
+        if let Some(origin) = &self.origin {
+            debug.field("origin", origin);
+        }
+        debug.finish()
+    }
+}
+
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.leading_trivia == other.leading_trivia
+            && self.trailing_trivia == other.trailing_trivia
+    }
+}
+
+impl Eq for Token {}
+
+/// Information about the original code of a token, used to generate source maps.
+/// It is boxed in tokens because most of them never need it.
+#[derive(Clone, Debug, Default)]
+struct TokenOrigin {
+    source: Option<Arc<Path>>,
+    column: Option<usize>,
+    line_shift: isize,
 }
 
 impl Token {
@@ -132,6 +168,7 @@ impl Token {
             },
             leading_trivia: Vec::new(),
             trailing_trivia: Vec::new(),
+            origin: None,
         }
     }
 
@@ -143,6 +180,7 @@ impl Token {
             },
             leading_trivia: Vec::new(),
             trailing_trivia: Vec::new(),
+            origin: None,
         }
     }
 
@@ -152,6 +190,7 @@ impl Token {
             position,
             leading_trivia: Vec::new(),
             trailing_trivia: Vec::new(),
+            origin: None,
         }
     }
 
@@ -240,6 +279,51 @@ impl Token {
         }
     }
 
+    /// Returns the path of the file where the token comes from, if it is not
+    /// the file being processed (for example, when the token was bundled from
+    /// another module).
+    pub fn get_source(&self) -> Option<&Path> {
+        self.origin
+            .as_ref()
+            .and_then(|origin| origin.source.as_deref())
+    }
+
+    pub(crate) fn set_source(&mut self, source: Arc<Path>) {
+        self.mutate_origin().source = Some(source);
+    }
+
+    fn mutate_origin(&mut self) -> &mut TokenOrigin {
+        self.origin.get_or_insert_with(Default::default)
+    }
+
+    /// Returns the line number of the token before any line shifting was applied
+    /// to it (for example, when bundling modules), if available.
+    pub(crate) fn get_original_line_number(&self) -> Option<usize> {
+        let line_shift = self.origin.as_ref().map_or(0, |origin| origin.line_shift);
+        self.get_line_number()
+            .map(|line_number| line_number.saturating_add_signed(-line_shift))
+    }
+
+    /// Returns the column where the token started in its original code, when the
+    /// token was created from a position that referenced that code.
+    pub(crate) fn get_column(&self) -> Option<usize> {
+        self.origin.as_ref().and_then(|origin| origin.column)
+    }
+
+    pub(crate) fn get_start_offset(&self) -> Option<usize> {
+        match &self.position {
+            Position::LineNumberReference { start, .. } => Some(*start),
+            Position::LineNumber { .. } | Position::Any { .. } => None,
+        }
+    }
+
+    pub(crate) fn get_end_offset(&self) -> Option<usize> {
+        match &self.position {
+            Position::LineNumberReference { end, .. } => Some(*end),
+            Position::LineNumber { .. } | Position::Any { .. } => None,
+        }
+    }
+
     /// Replaces the token's content with new content while preserving line number information.
     pub fn replace_with_content<IntoCowStr: Into<Cow<'static, str>>>(
         &mut self,
@@ -281,13 +365,19 @@ impl Token {
             .retain(|trivia| trivia.kind() != TriviaKind::Comment || filter(trivia));
     }
 
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
         if let Position::LineNumberReference {
             start,
             end,
             line_number,
         } = self.position
         {
+            if record_origin {
+                if let Some(before) = code.get(..start) {
+                    self.mutate_origin().column =
+                        Some(before.rfind('\n').map_or(start, |index| start - index - 1));
+                }
+            }
             self.position = Position::LineNumber {
                 line_number,
                 content: code
@@ -320,11 +410,20 @@ impl Token {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
+    pub(crate) fn set_token_source(&mut self, source: &Arc<Path>) {
+        if self.get_source().is_none() {
+            self.set_source(source.clone());
+        }
+    }
+
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
         match &mut self.position {
             Position::LineNumberReference { line_number, .. }
             | Position::LineNumber { line_number, .. } => {
                 *line_number = line_number.saturating_add_signed(amount);
+                if record_origin {
+                    self.mutate_origin().line_shift += amount;
+                }
             }
             Position::Any { .. } => {}
         }
@@ -349,4 +448,42 @@ mod test {
 
         assert_eq!("true", token.read(""));
     }
+
+    #[test]
+    fn replace_referenced_tokens_without_recording_origin() {
+        let code = "local a = 1\nreturn true";
+        let mut token = Token::new_with_line(19, 23, 2);
+
+        token.replace_referenced_tokens(code, false);
+        token.shift_token_line(3, false);
+
+        assert!(token.origin.is_none());
+        assert_eq!(Some(5), token.get_original_line_number());
+        assert_eq!(None, token.get_column());
+    }
+
+    #[test]
+    fn replace_referenced_tokens_recording_origin() {
+        let code = "local a = 1\nreturn true";
+        let mut token = Token::new_with_line(19, 23, 2);
+
+        token.replace_referenced_tokens(code, true);
+        token.shift_token_line(3, true);
+
+        assert_eq!(Some(5), token.get_line_number());
+        assert_eq!(Some(2), token.get_original_line_number());
+        assert_eq!(Some(7), token.get_column());
+    }
+
+    #[test]
+    fn tokens_with_different_origins_are_equal() {
+        let code = "return true";
+        let mut token = Token::new_with_line(7, 11, 1);
+        let mut other = token.clone();
+
+        token.replace_referenced_tokens(code, true);
+        other.replace_referenced_tokens(code, false);
+
+        assert_eq!(token, other);
+    }
 }
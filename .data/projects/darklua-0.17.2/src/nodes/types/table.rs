@@ -236,19 +236,33 @@ impl TableEntryType {
         }
     }
 
-    pub(crate) fn replace_referenced_tokens(&mut self, code: &str) {
+    pub(crate) fn replace_referenced_tokens(&mut self, code: &str, record_origin: bool) {
         match self {
-            TableEntryType::Property(property) => property.replace_referenced_tokens(code),
-            TableEntryType::Literal(literal) => literal.replace_referenced_tokens(code),
-            TableEntryType::Indexer(indexer) => indexer.replace_referenced_tokens(code),
+            TableEntryType::Property(property) => {
+                property.replace_referenced_tokens(code, record_origin)
+            }
+            TableEntryType::Literal(literal) => {
+                literal.replace_referenced_tokens(code, record_origin)
+            }
+            TableEntryType::Indexer(indexer) => {
+                indexer.replace_referenced_tokens(code, record_origin)
+            }
+        }
+    }
+
+    pub(crate) fn shift_token_line(&mut self, amount: isize, record_origin: bool) {
+        match self {
+            TableEntryType::Property(property) => property.shift_token_line(amount, record_origin),
+            TableEntryType::Literal(literal) => literal.shift_token_line(amount, record_origin),
+            TableEntryType::Indexer(indexer) => indexer.shift_token_line(amount, record_origin),
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: isize) {
+    pub(crate) fn set_token_source(&mut self, source: &std::sync::Arc<std::path::Path>) {
         match self {
-            TableEntryType::Property(property) => property.shift_token_line(amount),
-            TableEntryType::Literal(literal) => literal.shift_token_line(amount),
-            TableEntryType::Indexer(indexer) => indexer.shift_token_line(amount),
+            TableEntryType::Property(property) => property.set_token_source(source),
+            TableEntryType::Literal(literal) => literal.set_token_source(source),
+            TableEntryType::Indexer(indexer) => indexer.set_token_source(source),
         }
     }
 
@@ -0,0 +1,364 @@
+use std::collections::HashSet;
+
+use crate::nodes::*;
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+const ASSERT_FUNCTION_NAME: &str = "assert";
+const TYPEOF_FUNCTION_NAME: &str = "typeof";
+
+/// Returns the list of `typeof` results that match a type, or `None` if the
+/// type can't be verified at runtime with `typeof`.
+fn get_runtime_types(r#type: &Type) -> Option<Vec<&'static str>> {
+    let mut runtime_types = Vec::new();
+    collect_runtime_types(r#type, &mut runtime_types)?;
+    Some(runtime_types)
+}
+
+fn collect_runtime_types(r#type: &Type, runtime_types: &mut Vec<&'static str>) -> Option<()> {
+    match r#type {
+        Type::Name(type_name) => {
+            if type_name.has_type_parameters() {
+                return None;
+            }
+            let runtime_type = match type_name.get_type_name().get_name().as_str() {
+                "boolean" => "boolean",
+                "buffer" => "buffer",
+                "number" => "number",
+                "string" => "string",
+                "thread" => "thread",
+                _ => return None,
+            };
+            push_runtime_type(runtime_types, runtime_type);
+        }
+        Type::True(_) | Type::False(_) => push_runtime_type(runtime_types, "boolean"),
+        Type::Nil(_) => push_runtime_type(runtime_types, "nil"),
+        Type::String(_) => push_runtime_type(runtime_types, "string"),
+        Type::Parenthese(parenthese) => {
+            collect_runtime_types(parenthese.get_inner_type(), runtime_types)?;
+        }
+        Type::Optional(optional) => {
+            collect_runtime_types(optional.get_inner_type(), runtime_types)?;
+            push_runtime_type(runtime_types, "nil");
+        }
+        Type::Union(union) => {
+            for r#type in union.iter_types() {
+                collect_runtime_types(r#type, runtime_types)?;
+            }
+        }
+        Type::Field(_)
+        | Type::Array(_)
+        | Type::Table(_)
+        | Type::TypeOf(_)
+        | Type::Function(_)
+        | Type::Intersection(_) => return None,
+    }
+    Some(())
+}
+
+fn push_runtime_type(runtime_types: &mut Vec<&'static str>, runtime_type: &'static str) {
+    if !runtime_types.contains(&runtime_type) {
+        runtime_types.push(runtime_type);
+    }
+}
+
+fn create_type_check(name: &str, runtime_type: &str) -> Expression {
+    if runtime_type == "nil" {
+        BinaryExpression::new(
+            BinaryOperator::Equal,
+            Identifier::new(name),
+            Expression::nil(),
+        )
+        .into()
+    } else {
+        BinaryExpression::new(
+            BinaryOperator::Equal,
+            FunctionCall::from_name(TYPEOF_FUNCTION_NAME).with_argument(Identifier::new(name)),
+            StringExpression::from_value(runtime_type),
+        )
+        .into()
+    }
+}
+
+fn create_assertion(parameter: &TypedIdentifier) -> Option<Statement> {
+    let runtime_types = get_runtime_types(parameter.get_type()?)?;
+    let name = parameter.get_name();
+
+    let condition = runtime_types
+        .iter()
+        .map(|runtime_type| create_type_check(name, runtime_type))
+        .reduce(|condition, check| {
+            BinaryExpression::new(BinaryOperator::Or, condition, check).into()
+        })?;
+
+    let message = format!(
+        "invalid type for parameter '{}' (expected {})",
+        name,
+        runtime_types.join(" or ")
+    );
+
+    Some(
+        FunctionCall::from_name(ASSERT_FUNCTION_NAME)
+            .with_argument(condition)
+            .with_argument(StringExpression::from_value(message))
+            .into(),
+    )
+}
+
+fn insert_assertions(parameters: &[TypedIdentifier], block: &mut Block) {
+    for (index, statement) in parameters.iter().filter_map(create_assertion).enumerate() {
+        block.insert_statement(index, statement);
+    }
+}
+
+fn insert_function_expression_assertions(expression: &mut Expression) {
+    if let Expression::Function(function) = expression {
+        let parameters = function.get_parameters().clone();
+        insert_assertions(&parameters, function.mutate_block());
+    }
+}
+
+#[derive(Debug, Default)]
+struct AllFunctionsProcessor;
+
+impl NodeProcessor for AllFunctionsProcessor {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let parameters = function.get_parameters().clone();
+        insert_assertions(&parameters, function.mutate_block());
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        let parameters = function.get_parameters().clone();
+        insert_assertions(&parameters, function.mutate_block());
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        let parameters = function.get_parameters().clone();
+        insert_assertions(&parameters, function.mutate_block());
+    }
+}
+
+/// Processes the functions that can be reached from outside of their scope: functions
+/// declared with a `function` statement and functions assigned to a field or an index.
+#[derive(Debug, Default)]
+struct PublicFunctionsProcessor;
+
+impl NodeProcessor for PublicFunctionsProcessor {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let parameters = function.get_parameters().clone();
+        insert_assertions(&parameters, function.mutate_block());
+    }
+
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        let public_values: Vec<_> = assign
+            .iter_variables()
+            .map(|variable| !matches!(variable, Variable::Identifier(_)))
+            .collect();
+
+        for (value, is_public) in assign.iter_mut_values().zip(public_values) {
+            if is_public {
+                insert_function_expression_assertions(value);
+            }
+        }
+    }
+}
+
+/// Returns the names of the variables that are exported by the module, either
+/// because they are returned or assigned to a field or an index.
+fn collect_exported_names(block: &Block) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    let mut push_identifier = |expression: &Expression| {
+        if let Expression::Identifier(identifier) = expression {
+            names.insert(identifier.get_name().to_owned());
+        }
+    };
+
+    for statement in block.iter_statements() {
+        if let Statement::Assign(assign) = statement {
+            for (variable, value) in assign.iter_variables().zip(assign.iter_values()) {
+                if !matches!(variable, Variable::Identifier(_)) {
+                    push_identifier(value);
+                }
+            }
+        }
+    }
+
+    if let Some(LastStatement::Return(statement)) = block.get_last_statement() {
+        for expression in statement.iter_expressions() {
+            match expression {
+                Expression::Table(table) => {
+                    for entry in table.iter_entries() {
+                        match entry {
+                            TableEntry::Field(field) => push_identifier(field.get_value()),
+                            TableEntry::Index(index) => push_identifier(index.get_value()),
+                            TableEntry::Value(value) => push_identifier(value),
+                        }
+                    }
+                }
+                _ => push_identifier(expression),
+            }
+        }
+    }
+
+    names
+}
+
+fn process_exported_functions(block: &mut Block) {
+    let exported_names = collect_exported_names(block);
+
+    for statement in block.iter_mut_statements() {
+        match statement {
+            Statement::LocalFunction(function) if exported_names.contains(function.get_name()) => {
+                let parameters = function.get_parameters().clone();
+                insert_assertions(&parameters, function.mutate_block());
+            }
+            Statement::LocalAssign(local_assign) => {
+                let exported_values: Vec<_> = local_assign
+                    .iter_variables()
+                    .map(|variable| exported_names.contains(variable.get_name()))
+                    .collect();
+
+                for (value, is_exported) in local_assign.iter_mut_values().zip(exported_values) {
+                    if is_exported {
+                        insert_function_expression_assertions(value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(LastStatement::Return(statement)) = block.mutate_last_statement() {
+        for expression in statement.iter_mut_expressions() {
+            match expression {
+                Expression::Table(table) => {
+                    for entry in table.iter_mut_entries() {
+                        match entry {
+                            TableEntry::Field(field) => {
+                                insert_function_expression_assertions(field.mutate_value())
+                            }
+                            TableEntry::Index(index) => {
+                                insert_function_expression_assertions(index.mutate_value())
+                            }
+                            TableEntry::Value(value) => {
+                                insert_function_expression_assertions(value)
+                            }
+                        }
+                    }
+                }
+                _ => insert_function_expression_assertions(expression),
+            }
+        }
+    }
+}
+
+pub const ADD_TYPE_ASSERTIONS_RULE_NAME: &str = "add_type_assertions";
+
+/// A rule that converts function parameter type annotations into runtime assertions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AddTypeAssertions {
+    only_public_functions: bool,
+}
+
+impl AddTypeAssertions {
+    pub fn with_only_public_functions(mut self, only_public_functions: bool) -> Self {
+        self.only_public_functions = only_public_functions;
+        self
+    }
+}
+
+impl FlawlessRule for AddTypeAssertions {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        if self.only_public_functions {
+            process_exported_functions(block);
+            let mut processor = PublicFunctionsProcessor;
+            DefaultVisitor::visit_block(block, &mut processor);
+        } else {
+            let mut processor = AllFunctionsProcessor;
+            DefaultVisitor::visit_block(block, &mut processor);
+        }
+    }
+}
+
+impl RuleConfiguration for AddTypeAssertions {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "only_public_functions" => {
+                    self.only_public_functions = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        ADD_TYPE_ASSERTIONS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.only_public_functions {
+            properties.insert("only_public_functions".to_owned(), true.into());
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> AddTypeAssertions {
+        AddTypeAssertions::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_add_type_assertions", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_only_public_functions() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_only_public_functions(true));
+
+        assert_json_snapshot!("add_type_assertions_only_public_functions", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'add_type_assertions',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_only_public_functions_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'add_type_assertions',
+            only_public_functions: 'yes',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "boolean value expected for field 'only_public_functions'"
+        );
+    }
+}
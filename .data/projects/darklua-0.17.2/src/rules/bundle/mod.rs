@@ -1,6 +1,7 @@
 pub(crate) mod path_require_mode;
 mod rename_type_declaration;
 mod require_mode;
+mod token_source;
 
 use std::path::Path;
 
@@ -12,6 +13,7 @@ use crate::Parser;
 
 pub(crate) use rename_type_declaration::RenameTypeDeclarationProcessor;
 pub use require_mode::BundleRequireMode;
+pub(crate) use token_source::SetTokenSourceProcessor;
 use wax::Pattern;
 
 pub const BUNDLER_RULE_NAME: &str = "bundler";
@@ -119,7 +121,7 @@ impl RuleConfiguration for Bundler {
     }
 }
 
-const DEFAULT_MODULE_IDENTIFIER: &str = "__DARKLUA_BUNDLE_MODULES";
+pub(crate) const DEFAULT_MODULE_IDENTIFIER: &str = "__DARKLUA_BUNDLE_MODULES";
 
 #[cfg(test)]
 mod test {
@@ -24,7 +24,7 @@ use crate::rules::{
 use crate::utils::Timer;
 use crate::{DarkluaError, Resources};
 
-use super::BundleOptions;
+use super::{BundleOptions, SetTokenSourceProcessor};
 
 pub(crate) enum RequiredResource {
     Block(Block),
@@ -42,6 +42,7 @@ struct RequirePathProcessor<'a, 'b, 'resources, PathLocatorImpl> {
     require_stack: Vec<PathBuf>,
     skip_module_paths: HashSet<PathBuf>,
     resources: &'resources Resources,
+    source_map: bool,
     errors: Vec<String>,
 }
 
@@ -62,12 +63,16 @@ impl<'a, 'b, 'resources, PathLocatorImpl: PathLocator>
             options,
             identifier_tracker: IdentifierTracker::new(),
             path_locator,
-            module_definitions: BuildModuleDefinitions::new(options.modules_identifier()),
+            module_definitions: BuildModuleDefinitions::new(
+                options.modules_identifier(),
+                context.has_source_map(),
+            ),
             source: context.current_path().to_path_buf(),
             module_cache: Default::default(),
             require_stack: Default::default(),
             skip_module_paths: Default::default(),
             resources: context.resources(),
+            source_map: context.has_source_map(),
             errors: Vec::new(),
         }
     }
@@ -206,7 +211,9 @@ impl<'a, 'b, 'resources, PathLocatorImpl: PathLocator>
 
                     if self.options.parser().is_preserving_tokens() {
                         log::trace!("replacing token references of {}", path.display());
-                        let context = ContextBuilder::new(path, self.resources, &content).build();
+                        let context = ContextBuilder::new(path, self.resources, &content)
+                            .with_source_map(self.source_map)
+                            .build();
                         // run `replace_referenced_tokens` rule to avoid generating invalid code
                         // when using the token-based generator
                         let replace_tokens = ReplaceReferencedTokens::default();
@@ -220,6 +227,13 @@ impl<'a, 'b, 'resources, PathLocatorImpl: PathLocator>
                             path.display(),
                             apply_replace_tokens_timer.duration_label()
                         );
+
+                        if self.source_map {
+                            DefaultVisitor::visit_block(
+                                &mut block,
+                                &mut SetTokenSourceProcessor::new(path),
+                            );
+                        }
                     }
 
                     let current_source = mem::replace(&mut self.source, path.to_path_buf());
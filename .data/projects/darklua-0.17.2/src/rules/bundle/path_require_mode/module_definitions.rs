@@ -41,13 +41,16 @@ impl ModuleDefinition {
 const BUNDLE_MODULES_VARIABLE_CACHE_FIELD: &str = "cache";
 
 impl BuildModuleDefinitions {
-    pub(crate) fn new(modules_identifier: impl Into<String>) -> Self {
+    pub(crate) fn new(modules_identifier: impl Into<String>, record_origin: bool) -> Self {
         let modules_identifier = modules_identifier.into();
         Self {
             modules_identifier: modules_identifier.clone(),
             module_definitions: Default::default(),
             module_name_permutator: identifier_permutator(),
-            rename_type_declaration: RenameTypeDeclarationProcessor::new(modules_identifier),
+            rename_type_declaration: RenameTypeDeclarationProcessor::new(
+                modules_identifier,
+                record_origin,
+            ),
         }
     }
 
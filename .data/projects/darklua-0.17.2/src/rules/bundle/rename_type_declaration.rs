@@ -25,12 +25,14 @@ pub(crate) struct RenameTypeDeclarationProcessor {
     permutator: CharPermutator,
     modules_identifier: String,
     hoist_types: bool,
+    /// keep track of the original lines of hoisted types, for source maps
+    record_origin: bool,
     type_lines: isize,
     type_declarations: Vec<Statement>,
 }
 
 impl RenameTypeDeclarationProcessor {
-    pub(crate) fn new(modules_identifier: String) -> Self {
+    pub(crate) fn new(modules_identifier: String, record_origin: bool) -> Self {
         Self {
             suffix: "__DARKLUA_TYPE_".into(),
             renamed_types: Default::default(),
@@ -40,6 +42,7 @@ impl RenameTypeDeclarationProcessor {
             permutator: identifier_permutator(),
             modules_identifier,
             hoist_types: true,
+            record_origin,
             type_lines: 0,
             type_declarations: Default::default(),
         }
@@ -195,8 +198,10 @@ impl NodePostProcessor for RenameTypeDeclarationProcessor {
                 let last_line = lines::statement_total(&current);
                 let total = 1 + last_line.saturating_sub(first_line) as isize;
 
-                let mut shift_processor =
-                    ShiftTokenLineProcessor::new(1 + self.type_lines - first_line as isize);
+                let mut shift_processor = ShiftTokenLineProcessor::new(
+                    1 + self.type_lines - first_line as isize,
+                    self.record_origin,
+                );
                 DefaultVisitor::visit_statement(&mut current, &mut shift_processor);
 
                 self.type_lines += total;
@@ -0,0 +1,242 @@
+use std::{path::Path, sync::Arc};
+
+use crate::nodes::*;
+use crate::process::NodeProcessor;
+
+/// A processor that marks every token with the path of the file it comes from.
+///
+/// This is used when bundling to keep track of the module that each token was
+/// read from, so that source maps can point back to the original files.
+#[derive(Debug)]
+pub(crate) struct SetTokenSourceProcessor {
+    source: Arc<Path>,
+}
+
+impl SetTokenSourceProcessor {
+    pub(crate) fn new(source: &Path) -> Self {
+        Self {
+            source: Arc::from(source),
+        }
+    }
+}
+
+impl NodeProcessor for SetTokenSourceProcessor {
+    fn process_block(&mut self, block: &mut Block) {
+        block.set_token_source(&self.source);
+    }
+
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        call.set_token_source(&self.source);
+        call.mutate_arguments().set_token_source(&self.source);
+    }
+
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        assign.set_token_source(&self.source);
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        assign.set_token_source(&self.source);
+    }
+
+    fn process_do_statement(&mut self, statement: &mut DoStatement) {
+        statement.set_token_source(&self.source);
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        function.set_token_source(&self.source);
+    }
+
+    fn process_generic_for_statement(&mut self, generic_for: &mut GenericForStatement) {
+        generic_for.set_token_source(&self.source);
+    }
+
+    fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
+        if_statement.set_token_source(&self.source);
+    }
+
+    fn process_last_statement(&mut self, statement: &mut LastStatement) {
+        match statement {
+            LastStatement::Break(token) | LastStatement::Continue(token) => {
+                if let Some(token) = token {
+                    token.set_token_source(&self.source);
+                }
+            }
+            LastStatement::Return(statement) => statement.set_token_source(&self.source),
+        }
+    }
+
+    fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
+        assign.set_token_source(&self.source);
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        function.set_token_source(&self.source);
+    }
+
+    fn process_numeric_for_statement(&mut self, numeric_for: &mut NumericForStatement) {
+        numeric_for.set_token_source(&self.source);
+    }
+
+    fn process_repeat_statement(&mut self, repeat: &mut RepeatStatement) {
+        repeat.set_token_source(&self.source);
+    }
+
+    fn process_while_statement(&mut self, statement: &mut WhileStatement) {
+        statement.set_token_source(&self.source);
+    }
+
+    fn process_type_declaration(&mut self, type_declaration: &mut TypeDeclarationStatement) {
+        type_declaration.set_token_source(&self.source);
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::False(token)
+            | Expression::Nil(token)
+            | Expression::True(token)
+            | Expression::VariableArguments(token) => {
+                if let Some(token) = token {
+                    token.set_token_source(&self.source)
+                }
+            }
+            Expression::Binary(_)
+            | Expression::Call(_)
+            | Expression::Field(_)
+            | Expression::Function(_)
+            | Expression::Identifier(_)
+            | Expression::If(_)
+            | Expression::Index(_)
+            | Expression::Number(_)
+            | Expression::Parenthese(_)
+            | Expression::String(_)
+            | Expression::InterpolatedString(_)
+            | Expression::Table(_)
+            | Expression::Unary(_)
+            | Expression::TypeCast(_) => {}
+        }
+    }
+
+    fn process_binary_expression(&mut self, binary: &mut BinaryExpression) {
+        binary.set_token_source(&self.source);
+    }
+
+    fn process_field_expression(&mut self, field: &mut FieldExpression) {
+        field.set_token_source(&self.source);
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        function.set_token_source(&self.source);
+    }
+
+    fn process_if_expression(&mut self, if_expression: &mut IfExpression) {
+        if_expression.set_token_source(&self.source);
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        identifier.set_token_source(&self.source);
+    }
+
+    fn process_index_expression(&mut self, index: &mut IndexExpression) {
+        index.set_token_source(&self.source);
+    }
+
+    fn process_number_expression(&mut self, number: &mut NumberExpression) {
+        number.set_token_source(&self.source);
+    }
+
+    fn process_parenthese_expression(&mut self, expression: &mut ParentheseExpression) {
+        expression.set_token_source(&self.source);
+    }
+
+    fn process_string_expression(&mut self, string: &mut StringExpression) {
+        string.set_token_source(&self.source);
+    }
+
+    fn process_interpolated_string_expression(
+        &mut self,
+        string: &mut InterpolatedStringExpression,
+    ) {
+        string.set_token_source(&self.source);
+    }
+
+    fn process_table_expression(&mut self, table: &mut TableExpression) {
+        table.set_token_source(&self.source);
+    }
+
+    fn process_unary_expression(&mut self, unary: &mut UnaryExpression) {
+        unary.set_token_source(&self.source);
+    }
+
+    fn process_type_cast_expression(&mut self, type_cast: &mut TypeCastExpression) {
+        type_cast.set_token_source(&self.source);
+    }
+
+    fn process_prefix_expression(&mut self, _: &mut Prefix) {}
+
+    fn process_type(&mut self, r#type: &mut Type) {
+        match r#type {
+            Type::True(token) | Type::False(token) | Type::Nil(token) => {
+                if let Some(token) = token {
+                    token.set_token_source(&self.source);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process_type_name(&mut self, type_name: &mut TypeName) {
+        type_name.set_token_source(&self.source);
+    }
+
+    fn process_type_field(&mut self, type_field: &mut TypeField) {
+        type_field.set_token_source(&self.source);
+    }
+
+    fn process_string_type(&mut self, string_type: &mut StringType) {
+        string_type.set_token_source(&self.source);
+    }
+
+    fn process_array_type(&mut self, array: &mut ArrayType) {
+        array.set_token_source(&self.source);
+    }
+
+    fn process_table_type(&mut self, table: &mut TableType) {
+        table.set_token_source(&self.source);
+    }
+
+    fn process_expression_type(&mut self, expression_type: &mut ExpressionType) {
+        expression_type.set_token_source(&self.source);
+    }
+
+    fn process_parenthese_type(&mut self, parenthese_type: &mut ParentheseType) {
+        parenthese_type.set_token_source(&self.source);
+    }
+
+    fn process_function_type(&mut self, function_type: &mut FunctionType) {
+        function_type.set_token_source(&self.source);
+    }
+
+    fn process_optional_type(&mut self, optional: &mut OptionalType) {
+        optional.set_token_source(&self.source);
+    }
+
+    fn process_intersection_type(&mut self, intersection: &mut IntersectionType) {
+        intersection.set_token_source(&self.source);
+    }
+
+    fn process_union_type(&mut self, union: &mut UnionType) {
+        union.set_token_source(&self.source);
+    }
+
+    fn process_type_pack(&mut self, type_pack: &mut TypePack) {
+        type_pack.set_token_source(&self.source);
+    }
+
+    fn process_generic_type_pack(&mut self, generic_type_pack: &mut GenericTypePack) {
+        generic_type_pack.set_token_source(&self.source);
+    }
+
+    fn process_variadic_type_pack(&mut self, variadic_type_pack: &mut VariadicTypePack) {
+        variadic_type_pack.set_token_source(&self.source);
+    }
+}
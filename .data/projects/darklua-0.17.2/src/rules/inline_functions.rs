@@ -0,0 +1,942 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use crate::nodes::*;
+use crate::process::{
+    DefaultPostVisitor, DefaultVisitor, Evaluator, NodePostProcessor, NodePostVisitor,
+    NodeProcessor, NodeVisitor, Scope, ScopePostVisitor, ScopeVisitor,
+};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+use crate::utils::{expressions_as_statement, preserve_arguments_side_effects, ScopedHashMap};
+
+const DEFAULT_MAX_STATEMENTS: usize = 3;
+
+/// Identifies a call to a local variable: the identifier of the local and the
+/// index of the call among all the calls made to that local.
+type CallSite = (usize, usize);
+
+/// Gives each local variable declaration a unique identifier and counts the direct
+/// calls made to each of them. Since the declarations and the calls are visited in
+/// the same order, the identifiers match between the analysis and the inlining passes.
+#[derive(Debug, Default)]
+struct LocalTracker {
+    locals: ScopedHashMap<String, usize>,
+    next_id: usize,
+    calls: HashMap<usize, usize>,
+}
+
+impl LocalTracker {
+    fn declare(&mut self, name: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.locals.insert(name.to_owned(), id);
+        id
+    }
+
+    fn resolve(&self, name: &str) -> Option<usize> {
+        self.locals.get(&name.to_owned()).copied()
+    }
+
+    fn register_call(&mut self, call: &FunctionCall) -> Option<CallSite> {
+        if call.has_method() {
+            return None;
+        }
+
+        let id = match call.get_prefix() {
+            Prefix::Identifier(identifier) => self.resolve(identifier.get_name())?,
+            _ => return None,
+        };
+
+        let count = self.calls.entry(id).or_default();
+        let index = *count;
+        *count += 1;
+
+        Some((id, index))
+    }
+}
+
+impl Scope for LocalTracker {
+    fn push(&mut self) {
+        self.locals.push();
+    }
+
+    fn pop(&mut self) {
+        self.locals.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.declare(identifier);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.declare(function.get_name());
+    }
+}
+
+/// Looks for what would change the behavior of a function body once moved out of
+/// its function: a `return` statement nested in a block or an assignment to one
+/// of the parameters.
+#[derive(Debug, Default)]
+struct FunctionBodyInspector {
+    parameters: Vec<String>,
+    function_depth: usize,
+    has_nested_return: bool,
+    reassigns_parameter: bool,
+}
+
+impl FunctionBodyInspector {
+    fn new(parameters: Vec<String>) -> Self {
+        Self {
+            parameters,
+            ..Default::default()
+        }
+    }
+
+    fn inspect(&mut self, block: &mut Block) {
+        for statement in block.iter_mut_statements() {
+            DefaultPostVisitor::visit_statement(statement, self);
+        }
+
+        if let Some(LastStatement::Return(statement)) = block.mutate_last_statement() {
+            for expression in statement.iter_mut_expressions() {
+                DefaultPostVisitor::visit_expression(expression, self);
+            }
+        }
+    }
+
+    fn is_parameter(&self, name: &str) -> bool {
+        self.parameters.iter().any(|parameter| parameter == name)
+    }
+}
+
+impl NodeProcessor for FunctionBodyInspector {
+    fn process_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.function_depth += 1;
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        if name.get_field_names().is_empty()
+            && !name.has_method()
+            && self.is_parameter(name.get_name().get_name())
+        {
+            self.reassigns_parameter = true;
+        }
+        self.function_depth += 1;
+    }
+
+    fn process_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.function_depth += 1;
+    }
+
+    fn process_variable(&mut self, variable: &mut Variable) {
+        if let Variable::Identifier(identifier) = variable {
+            if self.is_parameter(identifier.get_name()) {
+                self.reassigns_parameter = true;
+            }
+        }
+    }
+
+    fn process_last_statement(&mut self, statement: &mut LastStatement) {
+        if self.function_depth == 0 && matches!(statement, LastStatement::Return(_)) {
+            self.has_nested_return = true;
+        }
+    }
+}
+
+impl NodePostProcessor for FunctionBodyInspector {
+    fn process_after_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.function_depth -= 1;
+    }
+
+    fn process_after_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.function_depth -= 1;
+    }
+
+    fn process_after_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.function_depth -= 1;
+    }
+}
+
+/// Verifies that an expression can be copied outside of its function: it must not
+/// declare new variables or refer to the variable arguments of its function.
+#[derive(Debug)]
+struct ExpressionInspector {
+    inlinable: bool,
+}
+
+impl Default for ExpressionInspector {
+    fn default() -> Self {
+        Self { inlinable: true }
+    }
+}
+
+impl NodeProcessor for ExpressionInspector {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        if matches!(
+            expression,
+            Expression::Function(_) | Expression::VariableArguments(_)
+        ) {
+            self.inlinable = false;
+        }
+    }
+}
+
+/// Collects the identifiers used in a block that are not declared inside of it.
+#[derive(Debug, Default)]
+struct FreeIdentifiers {
+    scopes: Vec<HashSet<String>>,
+    names: Vec<String>,
+}
+
+impl FreeIdentifiers {
+    fn declare(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_owned());
+        }
+    }
+
+    fn is_declared(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+}
+
+impl Scope for FreeIdentifiers {
+    fn push(&mut self) {
+        self.scopes.push(HashSet::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.declare(identifier);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.declare(function.get_name());
+    }
+}
+
+impl NodeProcessor for FreeIdentifiers {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let name = identifier.get_name();
+        if !self.is_declared(name) && !self.names.iter().any(|free| free == name) {
+            self.names.push(name.to_owned());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionBodyKind {
+    /// The function returns a single expression without side effects
+    PureExpression,
+    /// The function returns a single expression
+    Expression,
+    /// The function can only be inlined where the call is a statement
+    Statements,
+}
+
+#[derive(Debug)]
+struct FunctionCandidate {
+    parameters_len: usize,
+    kind: FunctionBodyKind,
+    /// The identifiers that the function uses without declaring them, associated
+    /// with the local variable they refer to where the function is declared
+    free_identifiers: Vec<(String, Option<usize>)>,
+}
+
+#[derive(Debug)]
+struct InlineAnalysis {
+    tracker: LocalTracker,
+    max_statements: usize,
+    evaluator: Evaluator,
+    candidates: HashMap<usize, FunctionCandidate>,
+    reads: HashMap<usize, usize>,
+    reassigned: HashSet<usize>,
+    next_is_assignment: bool,
+    /// The calls that can be inlined, associated with the local variables that
+    /// must never be reassigned for the inlining to be valid
+    inlinable_calls: HashMap<CallSite, Vec<usize>>,
+}
+
+impl std::ops::Deref for InlineAnalysis {
+    type Target = LocalTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tracker
+    }
+}
+
+impl std::ops::DerefMut for InlineAnalysis {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tracker
+    }
+}
+
+impl InlineAnalysis {
+    fn new(max_statements: usize) -> Self {
+        Self {
+            tracker: LocalTracker::default(),
+            max_statements,
+            evaluator: Evaluator::default(),
+            candidates: HashMap::new(),
+            reads: HashMap::new(),
+            reassigned: HashSet::new(),
+            next_is_assignment: false,
+            inlinable_calls: HashMap::new(),
+        }
+    }
+
+    fn analyze_function(
+        &self,
+        id: usize,
+        function: &mut LocalFunctionStatement,
+    ) -> Option<FunctionCandidate> {
+        if function.is_variadic() || function.get_generic_parameters().is_some() {
+            return None;
+        }
+
+        let block = function.get_block();
+        let last_statement_len = match block.get_last_statement() {
+            None => 0,
+            Some(LastStatement::Return(_)) => 1,
+            Some(LastStatement::Break(_)) | Some(LastStatement::Continue(_)) => return None,
+        };
+        if block.statements_len() + last_statement_len > self.max_statements {
+            return None;
+        }
+
+        let kind = match block.get_last_statement() {
+            Some(LastStatement::Return(statement))
+                if block.statements_len() == 0 && statement.len() == 1 =>
+            {
+                let mut expression = statement.iter_expressions().next()?.clone();
+                let mut inspector = ExpressionInspector::default();
+                DefaultVisitor::visit_expression(&mut expression, &mut inspector);
+
+                if !inspector.inlinable {
+                    FunctionBodyKind::Statements
+                } else if self.evaluator.has_side_effects(&expression) {
+                    FunctionBodyKind::Expression
+                } else {
+                    FunctionBodyKind::PureExpression
+                }
+            }
+            _ => FunctionBodyKind::Statements,
+        };
+
+        let parameters: Vec<String> = function
+            .iter_parameters()
+            .map(|parameter| parameter.get_name().to_owned())
+            .collect();
+
+        let mut inspector = FunctionBodyInspector::new(parameters.clone());
+        inspector.inspect(function.mutate_block());
+        if inspector.has_nested_return || inspector.reassigns_parameter {
+            return None;
+        }
+
+        let mut collector = FreeIdentifiers::default();
+        collector.push();
+        for parameter in parameters.iter() {
+            collector.declare(parameter);
+        }
+        ScopeVisitor::visit_block(function.mutate_block(), &mut collector);
+
+        let mut free_identifiers = Vec::new();
+        for name in collector.names {
+            let resolved = self.resolve(&name);
+            if resolved == Some(id) {
+                // recursive functions can't be inlined
+                return None;
+            }
+            // when the function calls another function that gets inlined, the
+            // identifiers of that other function are moved along
+            if let Some(candidate) = resolved.and_then(|resolved| self.candidates.get(&resolved)) {
+                free_identifiers.extend(candidate.free_identifiers.iter().cloned());
+            }
+            free_identifiers.push((name, resolved));
+        }
+
+        Some(FunctionCandidate {
+            parameters_len: parameters.len(),
+            kind,
+            free_identifiers,
+        })
+    }
+
+    fn analyze_call(&mut self, call: &FunctionCall, is_statement: bool) {
+        let call_site = match self.tracker.register_call(call) {
+            Some(call_site) => call_site,
+            None => return,
+        };
+
+        let candidate = match self.candidates.get(&call_site.0) {
+            Some(candidate) => candidate,
+            None => return,
+        };
+
+        let same_identifiers = candidate
+            .free_identifiers
+            .iter()
+            .all(|(name, id)| self.resolve(name) == *id);
+
+        if !same_identifiers {
+            return;
+        }
+
+        let requirements = if is_statement {
+            Some(Vec::new())
+        } else {
+            match candidate.kind {
+                FunctionBodyKind::PureExpression => {
+                    self.get_substitution_requirements(call.get_arguments(), candidate, true)
+                }
+                FunctionBodyKind::Expression => {
+                    self.get_substitution_requirements(call.get_arguments(), candidate, false)
+                }
+                FunctionBodyKind::Statements => None,
+            }
+        };
+
+        if let Some(requirements) = requirements {
+            self.inlinable_calls.insert(call_site, requirements);
+        }
+    }
+
+    /// Verifies that the arguments can be substituted to the parameters of a function
+    /// without evaluating them more than once or in a different order. Returns the
+    /// local variables that must never be reassigned.
+    fn get_substitution_requirements(
+        &self,
+        arguments: &Arguments,
+        candidate: &FunctionCandidate,
+        is_pure: bool,
+    ) -> Option<Vec<usize>> {
+        let mut values = arguments.clone().to_expressions();
+        let extra_values = if values.len() > candidate.parameters_len {
+            values.split_off(candidate.parameters_len)
+        } else {
+            Vec::new()
+        };
+
+        let extra_arguments = Arguments::from(TupleArguments::new(extra_values));
+        if !preserve_arguments_side_effects(&self.evaluator, &extra_arguments).is_empty() {
+            return None;
+        }
+
+        let mut requirements = Vec::new();
+
+        for value in values.iter() {
+            match value {
+                Expression::False(_)
+                | Expression::Nil(_)
+                | Expression::Number(_)
+                | Expression::String(_)
+                | Expression::True(_) => {}
+                Expression::Identifier(identifier) => {
+                    // when the function body has side effects, the identifier is only
+                    // substituted if its value can't change while the body is evaluated
+                    if !is_pure {
+                        requirements.push(self.resolve(identifier.get_name())?);
+                    }
+                }
+                _ => return None,
+            }
+        }
+
+        Some(requirements)
+    }
+
+    fn into_inlining(self) -> Option<Inlining> {
+        let reads = &self.reads;
+        let calls = &self.tracker.calls;
+        let count = |map: &HashMap<usize, usize>, id: &usize| map.get(id).copied().unwrap_or(0);
+
+        let inlinable_functions: HashSet<usize> = self
+            .candidates
+            .keys()
+            .filter(|id| count(reads, id) == count(calls, id))
+            .copied()
+            .collect();
+
+        let reassigned = &self.reassigned;
+        let calls_to_inline: HashSet<CallSite> = self
+            .inlinable_calls
+            .into_iter()
+            .filter(|((id, _), requirements)| {
+                inlinable_functions.contains(id)
+                    && !requirements.iter().any(|local| reassigned.contains(local))
+            })
+            .map(|(call_site, _)| call_site)
+            .collect();
+
+        if calls_to_inline.is_empty() {
+            return None;
+        }
+
+        let removed_functions = inlinable_functions
+            .into_iter()
+            .filter(|id| {
+                let inlined = calls_to_inline
+                    .iter()
+                    .filter(|(function, _)| function == id)
+                    .count();
+                inlined != 0 && inlined == count(calls, id)
+            })
+            .collect();
+
+        Some(Inlining {
+            calls: calls_to_inline,
+            removed_functions,
+        })
+    }
+}
+
+impl NodeProcessor for InlineAnalysis {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        if name.get_field_names().is_empty() && !name.has_method() {
+            self.next_is_assignment = true;
+        }
+    }
+
+    fn process_variable(&mut self, variable: &mut Variable) {
+        if let Variable::Identifier(_) = variable {
+            self.next_is_assignment = true;
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let is_assignment = mem::take(&mut self.next_is_assignment);
+
+        if let Some(id) = self.resolve(identifier.get_name()) {
+            *self.reads.entry(id).or_default() += 1;
+
+            if is_assignment {
+                self.reassigned.insert(id);
+            }
+        }
+    }
+}
+
+impl NodePostProcessor for InlineAnalysis {
+    fn process_after_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        if let Some(id) = self.resolve(function.get_name()) {
+            if let Some(candidate) = self.analyze_function(id, function) {
+                self.candidates.insert(id, candidate);
+            }
+        }
+    }
+
+    fn process_after_statement(&mut self, statement: &mut Statement) {
+        if let Statement::Call(call) = statement {
+            self.analyze_call(call, true);
+        }
+    }
+
+    fn process_after_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Call(call) = expression {
+            self.analyze_call(call, false);
+        }
+    }
+
+    fn process_after_prefix_expression(&mut self, prefix: &mut Prefix) {
+        if let Prefix::Call(call) = prefix {
+            self.analyze_call(call, false);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inlining {
+    calls: HashSet<CallSite>,
+    removed_functions: HashSet<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct InlinedFunction {
+    parameters: Vec<TypedIdentifier>,
+    block: Block,
+}
+
+/// Replaces the parameters of an inlined function with their arguments.
+struct ParameterSubstitution {
+    arguments: HashMap<String, Expression>,
+}
+
+impl NodeProcessor for ParameterSubstitution {}
+
+impl NodePostProcessor for ParameterSubstitution {
+    fn process_after_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier(identifier) = expression {
+            if let Some(argument) = self.arguments.get(identifier.get_name()) {
+                *expression = argument.clone();
+            }
+        }
+    }
+
+    fn process_after_prefix_expression(&mut self, prefix: &mut Prefix) {
+        if let Prefix::Identifier(identifier) = prefix {
+            if let Some(argument) = self.arguments.get(identifier.get_name()) {
+                *prefix = argument.clone().into();
+            }
+        }
+    }
+}
+
+struct InlineFunctionsProcessor {
+    tracker: LocalTracker,
+    evaluator: Evaluator,
+    inlining: Inlining,
+    functions: HashMap<usize, InlinedFunction>,
+    /// For each block being visited, tells if its local function statements must
+    /// be removed
+    removed_statements: Vec<Vec<bool>>,
+    mutated: bool,
+}
+
+impl std::ops::Deref for InlineFunctionsProcessor {
+    type Target = LocalTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tracker
+    }
+}
+
+impl std::ops::DerefMut for InlineFunctionsProcessor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tracker
+    }
+}
+
+impl InlineFunctionsProcessor {
+    fn new(inlining: Inlining) -> Self {
+        Self {
+            tracker: LocalTracker::default(),
+            evaluator: Evaluator::default(),
+            inlining,
+            functions: HashMap::new(),
+            removed_statements: Vec::new(),
+            mutated: false,
+        }
+    }
+
+    fn get_inlined_function(&mut self, call: &FunctionCall) -> Option<InlinedFunction> {
+        let call_site = self.tracker.register_call(call)?;
+
+        if self.inlining.calls.contains(&call_site) {
+            self.functions.get(&call_site.0).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn inline_statement(&self, function: InlinedFunction, arguments: &Arguments) -> Statement {
+        let InlinedFunction {
+            parameters,
+            mut block,
+        } = function;
+
+        let returned_values: Vec<_> = match block.take_last_statement() {
+            Some(LastStatement::Return(statement)) => statement
+                .into_iter_expressions()
+                .filter(|value| self.evaluator.has_side_effects(value))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        if block.is_empty() && returned_values.is_empty() {
+            return expressions_as_statement(preserve_arguments_side_effects(
+                &self.evaluator,
+                arguments,
+            ));
+        }
+
+        let mut values = arguments.clone().to_expressions();
+        let extra_values = if values.len() > parameters.len() {
+            values.split_off(parameters.len())
+        } else {
+            Vec::new()
+        };
+        let extra_values = preserve_arguments_side_effects(
+            &self.evaluator,
+            &Arguments::from(TupleArguments::new(extra_values)),
+        );
+
+        let mut statements = Vec::new();
+
+        if parameters.is_empty() {
+            if !extra_values.is_empty() {
+                statements.push(expressions_as_statement(extra_values));
+            }
+        } else {
+            values.extend(extra_values);
+            statements.push(LocalAssignStatement::new(parameters, values).into());
+        }
+
+        statements.extend(block.take_statements());
+
+        if !returned_values.is_empty() {
+            statements.push(expressions_as_statement(returned_values));
+        }
+
+        DoStatement::new(Block::new(statements, None)).into()
+    }
+
+    fn inline_expression(function: InlinedFunction, arguments: &Arguments) -> Option<Expression> {
+        let mut expression = match function.block.get_last_statement() {
+            Some(LastStatement::Return(statement)) => statement.iter_expressions().next()?.clone(),
+            _ => return None,
+        };
+
+        let mut values = arguments.clone().to_expressions().into_iter();
+        let arguments = function
+            .parameters
+            .iter()
+            .map(|parameter| {
+                (
+                    parameter.get_name().to_owned(),
+                    values.next().unwrap_or_else(Expression::nil),
+                )
+            })
+            .collect();
+
+        DefaultPostVisitor::visit_expression(
+            &mut expression,
+            &mut ParameterSubstitution { arguments },
+        );
+
+        Some(expression)
+    }
+
+    fn inline_call_expression(&mut self, call: &FunctionCall) -> Option<Expression> {
+        let function = self.get_inlined_function(call)?;
+        let expression = Self::inline_expression(function, call.get_arguments())?;
+        self.mutated = true;
+        Some(expression)
+    }
+}
+
+impl NodeProcessor for InlineFunctionsProcessor {
+    fn process_block(&mut self, _: &mut Block) {
+        self.removed_statements.push(Vec::new());
+    }
+}
+
+impl NodePostProcessor for InlineFunctionsProcessor {
+    fn process_after_block(&mut self, block: &mut Block) {
+        let removed_statements = self.removed_statements.pop().unwrap_or_default();
+
+        if removed_statements.contains(&true) {
+            let mut removed_statements = removed_statements.into_iter();
+            block.filter_statements(|statement| match statement {
+                Statement::LocalFunction(_) => !removed_statements.next().unwrap_or(false),
+                _ => true,
+            });
+        }
+    }
+
+    fn process_after_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        if let Some(id) = self.resolve(function.get_name()) {
+            let is_inlined = self
+                .inlining
+                .calls
+                .iter()
+                .any(|(function, _)| *function == id);
+
+            if is_inlined {
+                self.functions.insert(
+                    id,
+                    InlinedFunction {
+                        parameters: function.get_parameters().clone(),
+                        block: function.get_block().clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn process_after_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::LocalFunction(function) => {
+                let is_removed = self
+                    .resolve(function.get_name())
+                    .map(|id| self.inlining.removed_functions.contains(&id))
+                    .unwrap_or(false);
+
+                if let Some(removed_statements) = self.removed_statements.last_mut() {
+                    removed_statements.push(is_removed);
+                }
+            }
+            Statement::Call(call) => {
+                if let Some(function) = self.get_inlined_function(call) {
+                    *statement = self.inline_statement(function, call.get_arguments());
+                    self.mutated = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process_after_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Call(call) = expression {
+            if let Some(inlined) = self.inline_call_expression(call) {
+                *expression = inlined;
+            }
+        }
+    }
+
+    fn process_after_prefix_expression(&mut self, prefix: &mut Prefix) {
+        if let Prefix::Call(call) = prefix {
+            if let Some(inlined) = self.inline_call_expression(call) {
+                *prefix = inlined.into();
+            }
+        }
+    }
+}
+
+pub const INLINE_FUNCTIONS_RULE_NAME: &str = "inline_functions";
+
+/// A rule that inlines small local functions at their call sites, when the
+/// functions are only called directly.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InlineFunctions {
+    max_statements: usize,
+}
+
+impl Default for InlineFunctions {
+    fn default() -> Self {
+        Self {
+            max_statements: DEFAULT_MAX_STATEMENTS,
+        }
+    }
+}
+
+impl InlineFunctions {
+    pub fn with_max_statements(mut self, max_statements: usize) -> Self {
+        self.max_statements = max_statements;
+        self
+    }
+}
+
+impl FlawlessRule for InlineFunctions {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        loop {
+            let mut analysis = InlineAnalysis::new(self.max_statements);
+            ScopePostVisitor::visit_block(block, &mut analysis);
+
+            let inlining = match analysis.into_inlining() {
+                Some(inlining) => inlining,
+                None => break,
+            };
+
+            let mut processor = InlineFunctionsProcessor::new(inlining);
+            ScopePostVisitor::visit_block(block, &mut processor);
+
+            if !processor.mutated {
+                break;
+            }
+        }
+    }
+}
+
+impl RuleConfiguration for InlineFunctions {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "max_statements" => {
+                    self.max_statements = value.expect_usize(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        INLINE_FUNCTIONS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.max_statements != DEFAULT_MAX_STATEMENTS {
+            properties.insert("max_statements".to_owned(), self.max_statements.into());
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> InlineFunctions {
+        InlineFunctions::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_inline_functions", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_max_statements() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_max_statements(5));
+
+        assert_json_snapshot!("inline_functions_with_max_statements", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'inline_functions',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_max_statements_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'inline_functions',
+            max_statements: true,
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unsigned integer expected for field 'max_statements'"
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! or behavior while preserving functionality. Each rule implements the [`Rule`] trait and can
 //! be configured through properties.
 
+mod add_type_assertions;
 mod append_text_comment;
 pub mod bundle;
 mod call_parens;
@@ -18,8 +19,10 @@ mod filter_early_return;
 mod global_function_to_assign;
 mod group_local;
 mod inject_value;
+mod inline_functions;
 mod method_def;
 mod no_local_function;
+mod propagate_constants;
 mod remove_assertions;
 mod remove_call_match;
 mod remove_comments;
@@ -39,9 +42,11 @@ mod replace_referenced_tokens;
 pub(crate) mod require;
 mod rule_property;
 mod shift_token_line;
+mod tree_shake;
 mod unused_if_branch;
 mod unused_while;
 
+pub use add_type_assertions::*;
 pub use append_text_comment::*;
 pub use call_parens::*;
 pub use compute_expression::*;
@@ -55,8 +60,10 @@ pub use filter_early_return::*;
 pub use global_function_to_assign::*;
 pub use group_local::*;
 pub use inject_value::*;
+pub use inline_functions::*;
 pub use method_def::*;
 pub use no_local_function::*;
+pub use propagate_constants::*;
 pub use remove_assertions::*;
 pub use remove_comments::*;
 pub use remove_compound_assign::*;
@@ -75,6 +82,7 @@ pub(crate) use replace_referenced_tokens::*;
 pub use require::PathRequireMode;
 pub use rule_property::*;
 pub(crate) use shift_token_line::*;
+pub use tree_shake::*;
 pub use unused_if_branch::*;
 pub use unused_while::*;
 
@@ -100,6 +108,7 @@ pub struct ContextBuilder<'a, 'resources, 'code> {
     original_code: &'code str,
     blocks: HashMap<PathBuf, &'a Block>,
     project_location: Option<PathBuf>,
+    source_map: bool,
 }
 
 impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
@@ -115,6 +124,7 @@ impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
             original_code,
             blocks: Default::default(),
             project_location: None,
+            source_map: false,
         }
     }
 
@@ -124,6 +134,13 @@ impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
         self
     }
 
+    /// Sets whether a source map is generated for the processed code, so that
+    /// rules keep track of where tokens originally come from.
+    pub fn with_source_map(mut self, enabled: bool) -> Self {
+        self.source_map = enabled;
+        self
+    }
+
     /// Builds the final context with all configured options.
     pub fn build(self) -> Context<'a, 'resources, 'code> {
         Context {
@@ -132,6 +149,7 @@ impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
             original_code: self.original_code,
             blocks: self.blocks,
             project_location: self.project_location,
+            source_map: self.source_map,
             dependencies: Default::default(),
         }
     }
@@ -153,6 +171,7 @@ pub struct Context<'a, 'resources, 'code> {
     original_code: &'code str,
     blocks: HashMap<PathBuf, &'a Block>,
     project_location: Option<PathBuf>,
+    source_map: bool,
     dependencies: std::cell::RefCell<Vec<PathBuf>>,
 }
 
@@ -192,6 +211,10 @@ impl Context<'_, '_, '_> {
         self.original_code
     }
 
+    fn has_source_map(&self) -> bool {
+        self.source_map
+    }
+
     fn project_location(&self) -> &Path {
         self.project_location.as_deref().unwrap_or_else(|| {
             let source = self.current_path();
@@ -294,6 +317,7 @@ pub fn get_default_rules() -> Vec<Box<dyn Rule>> {
 /// This includes both default and optional rules that can be used for code transformation.
 pub fn get_all_rule_names() -> Vec<&'static str> {
     vec![
+        ADD_TYPE_ASSERTIONS_RULE_NAME,
         APPEND_TEXT_COMMENT_RULE_NAME,
         COMPUTE_EXPRESSIONS_RULE_NAME,
         CONVERT_FUNCTION_TO_ASSIGNMENT_RULE_NAME,
@@ -305,6 +329,8 @@ pub fn get_all_rule_names() -> Vec<&'static str> {
         FILTER_AFTER_EARLY_RETURN_RULE_NAME,
         GROUP_LOCAL_ASSIGNMENT_RULE_NAME,
         INJECT_GLOBAL_VALUE_RULE_NAME,
+        INLINE_FUNCTIONS_RULE_NAME,
+        PROPAGATE_CONSTANTS_RULE_NAME,
         REMOVE_ASSERTIONS_RULE_NAME,
         REMOVE_COMMENTS_RULE_NAME,
         REMOVE_COMPOUND_ASSIGNMENT_RULE_NAME,
@@ -324,6 +350,7 @@ pub fn get_all_rule_names() -> Vec<&'static str> {
         RENAME_VARIABLES_RULE_NAME,
         REMOVE_IF_EXPRESSION_RULE_NAME,
         REMOVE_CONTINUE_RULE_NAME,
+        TREE_SHAKE_RULE_NAME,
     ]
 }
 
@@ -332,6 +359,7 @@ impl FromStr for Box<dyn Rule> {
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
         let rule: Box<dyn Rule> = match string {
+            ADD_TYPE_ASSERTIONS_RULE_NAME => Box::<AddTypeAssertions>::default(),
             APPEND_TEXT_COMMENT_RULE_NAME => Box::<AppendTextComment>::default(),
             COMPUTE_EXPRESSIONS_RULE_NAME => Box::<ComputeExpression>::default(),
             CONVERT_FUNCTION_TO_ASSIGNMENT_RULE_NAME => Box::<ConvertFunctionToAssign>::default(),
@@ -345,6 +373,8 @@ impl FromStr for Box<dyn Rule> {
             FILTER_AFTER_EARLY_RETURN_RULE_NAME => Box::<FilterAfterEarlyReturn>::default(),
             GROUP_LOCAL_ASSIGNMENT_RULE_NAME => Box::<GroupLocalAssignment>::default(),
             INJECT_GLOBAL_VALUE_RULE_NAME => Box::<InjectGlobalValue>::default(),
+            INLINE_FUNCTIONS_RULE_NAME => Box::<InlineFunctions>::default(),
+            PROPAGATE_CONSTANTS_RULE_NAME => Box::<PropagateConstants>::default(),
             REMOVE_ASSERTIONS_RULE_NAME => Box::<RemoveAssertions>::default(),
             REMOVE_COMMENTS_RULE_NAME => Box::<RemoveComments>::default(),
             REMOVE_COMPOUND_ASSIGNMENT_RULE_NAME => Box::<RemoveCompoundAssignment>::default(),
@@ -364,6 +394,7 @@ impl FromStr for Box<dyn Rule> {
             RENAME_VARIABLES_RULE_NAME => Box::<RenameVariables>::default(),
             REMOVE_IF_EXPRESSION_RULE_NAME => Box::<RemoveIfExpression>::default(),
             REMOVE_CONTINUE_RULE_NAME => Box::<RemoveContinue>::default(),
+            TREE_SHAKE_RULE_NAME => Box::<TreeShake>::default(),
             _ => return Err(format!("invalid rule name: {}", string)),
         };
 
@@ -0,0 +1,540 @@
+use std::collections::HashMap;
+
+use crate::nodes::*;
+use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+/// Keeps track of the local variables in scope and gives each declaration a unique
+/// identifier. Since the declarations are visited in the same order, the identifiers
+/// match between the analysis and the replacement passes.
+#[derive(Debug, Default)]
+struct LocalScopes {
+    scopes: Vec<Vec<(String, usize)>>,
+    next_id: usize,
+}
+
+impl LocalScopes {
+    fn push(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((name.to_owned(), id));
+        }
+        id
+    }
+
+    fn resolve(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(variable, _)| variable == name)
+            .map(|(_, id)| *id)
+    }
+
+    fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+}
+
+/// The length of the longest string constant that gets copied into every use of
+/// a local variable read more than once. Copying longer strings would make the code
+/// larger, so they are only propagated to a local variable read once.
+const MAX_COPIED_STRING_LENGTH: usize = 8;
+
+fn can_copy_constant(constant: &Expression) -> bool {
+    match constant {
+        Expression::String(string) => string.get_value().len() <= MAX_COPIED_STRING_LENGTH,
+        _ => true,
+    }
+}
+
+#[derive(Debug)]
+struct LocalDefinition {
+    depth: usize,
+    constant: Option<Expression>,
+    inline_value: Option<(Expression, Vec<(String, usize)>)>,
+    reassigned: bool,
+    reads: usize,
+    inlinable_reads: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum IdentifierUsage {
+    #[default]
+    Read,
+    Assign,
+    FunctionName,
+}
+
+/// Collects the names of the identifiers read in a node.
+#[derive(Debug, Default)]
+struct IdentifierCollector {
+    names: Vec<String>,
+}
+
+impl NodeProcessor for IdentifierCollector {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        self.names.push(identifier.get_name().to_owned());
+    }
+}
+
+/// Collects the identifiers of an expression that can be moved to a different
+/// location without changing its result. Returns false if the expression contains
+/// anything else than literals, identifiers or operators.
+fn collect_inlinable_identifiers(expression: &Expression, identifiers: &mut Vec<String>) -> bool {
+    match expression {
+        Expression::False(_)
+        | Expression::Nil(_)
+        | Expression::Number(_)
+        | Expression::String(_)
+        | Expression::True(_) => true,
+        Expression::Identifier(identifier) => {
+            identifiers.push(identifier.get_name().to_owned());
+            true
+        }
+        Expression::Binary(binary) => {
+            collect_inlinable_identifiers(binary.left(), identifiers)
+                && collect_inlinable_identifiers(binary.right(), identifiers)
+        }
+        Expression::Unary(unary) => {
+            collect_inlinable_identifiers(unary.get_expression(), identifiers)
+        }
+        Expression::Parenthese(parenthese) => {
+            collect_inlinable_identifiers(parenthese.inner_expression(), identifiers)
+        }
+        Expression::TypeCast(type_cast) => {
+            collect_inlinable_identifiers(type_cast.get_expression(), identifiers)
+        }
+        Expression::If(if_expression) => {
+            collect_inlinable_identifiers(if_expression.get_condition(), identifiers)
+                && collect_inlinable_identifiers(if_expression.get_result(), identifiers)
+                && if_expression.iter_branches().all(|branch| {
+                    collect_inlinable_identifiers(branch.get_condition(), identifiers)
+                        && collect_inlinable_identifiers(branch.get_result(), identifiers)
+                })
+                && collect_inlinable_identifiers(if_expression.get_else_result(), identifiers)
+        }
+        Expression::InterpolatedString(interpolated_string) => interpolated_string
+            .iter_segments()
+            .all(|segment| match segment {
+                InterpolationSegment::String(_) => true,
+                InterpolationSegment::Value(value) => {
+                    collect_inlinable_identifiers(value.get_expression(), identifiers)
+                }
+            }),
+        Expression::Call(_)
+        | Expression::Field(_)
+        | Expression::Function(_)
+        | Expression::Index(_)
+        | Expression::Table(_)
+        | Expression::VariableArguments(_) => false,
+    }
+}
+
+#[derive(Debug)]
+struct LocalAnalysis {
+    scopes: LocalScopes,
+    definitions: HashMap<usize, LocalDefinition>,
+    next_usage: IdentifierUsage,
+    inline_single_use: bool,
+    evaluator: Evaluator,
+}
+
+impl LocalAnalysis {
+    fn new(inline_single_use: bool) -> Self {
+        Self {
+            scopes: LocalScopes::default(),
+            definitions: HashMap::new(),
+            next_usage: IdentifierUsage::default(),
+            inline_single_use,
+            evaluator: Evaluator::default(),
+        }
+    }
+
+    fn define(&mut self, name: &str, value: Option<&Expression>) {
+        let constant = value
+            .filter(|value| !self.evaluator.has_side_effects(value))
+            .and_then(|value| self.evaluator.evaluate(value).to_expression());
+
+        let inline_value = if constant.is_none() && self.inline_single_use {
+            value.and_then(|value| self.get_inline_value(value))
+        } else {
+            None
+        };
+
+        let depth = self.scopes.depth();
+        let id = self.scopes.declare(name);
+
+        self.definitions.insert(
+            id,
+            LocalDefinition {
+                depth,
+                constant,
+                inline_value,
+                reassigned: false,
+                reads: 0,
+                inlinable_reads: 0,
+            },
+        );
+    }
+
+    fn define_unknown(&mut self, name: &str) {
+        let depth = self.scopes.depth();
+        let id = self.scopes.declare(name);
+        self.definitions.insert(
+            id,
+            LocalDefinition {
+                depth,
+                constant: None,
+                inline_value: None,
+                reassigned: false,
+                reads: 0,
+                inlinable_reads: 0,
+            },
+        );
+    }
+
+    fn get_inline_value(&self, value: &Expression) -> Option<(Expression, Vec<(String, usize)>)> {
+        if self.evaluator.has_side_effects(value) {
+            return None;
+        }
+
+        let mut identifiers = Vec::new();
+        if !collect_inlinable_identifiers(value, &mut identifiers) {
+            return None;
+        }
+
+        // every identifier must reference a local variable: global variables
+        // could be modified before the value is used
+        let dependencies = identifiers
+            .into_iter()
+            .map(|name| self.scopes.resolve(&name).map(|id| (name, id)))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((value.clone(), dependencies))
+    }
+
+    fn mark_non_inlinable(&mut self, names: &[String]) {
+        for name in names {
+            if let Some(definition) = self
+                .scopes
+                .resolve(name)
+                .and_then(|id| self.definitions.get_mut(&id))
+            {
+                definition.inline_value = None;
+            }
+        }
+    }
+
+    fn into_replacements(self) -> HashMap<usize, Expression> {
+        let definitions = &self.definitions;
+        let is_reassigned = |id: &usize| {
+            definitions
+                .get(id)
+                .map(|definition| definition.reassigned)
+                .unwrap_or(true)
+        };
+
+        definitions
+            .iter()
+            .filter(|(_, definition)| !definition.reassigned && definition.reads > 0)
+            .filter_map(|(id, definition)| {
+                if let Some(constant) = &definition.constant {
+                    (definition.reads == 1 || can_copy_constant(constant))
+                        .then(|| (*id, constant.clone()))
+                } else {
+                    definition
+                        .inline_value
+                        .as_ref()
+                        .filter(|(_, dependencies)| {
+                            definition.reads == 1
+                                && definition.inlinable_reads == 1
+                                && !dependencies.iter().any(|(_, id)| is_reassigned(id))
+                        })
+                        .map(|(value, _)| (*id, value.clone()))
+                }
+            })
+            .collect()
+    }
+}
+
+impl Scope for LocalAnalysis {
+    fn push(&mut self) {
+        self.scopes.push();
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.define_unknown(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.define_unknown("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, value: Option<&mut Expression>) {
+        self.define(identifier, value.as_deref());
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.define_unknown(function.get_name());
+    }
+}
+
+impl NodeProcessor for LocalAnalysis {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        self.next_usage = if name.get_field_names().is_empty() && !name.has_method() {
+            IdentifierUsage::Assign
+        } else {
+            IdentifierUsage::FunctionName
+        };
+    }
+
+    fn process_variable(&mut self, variable: &mut Variable) {
+        if let Variable::Identifier(_) = variable {
+            self.next_usage = IdentifierUsage::Assign;
+        }
+    }
+
+    fn process_while_statement(&mut self, statement: &mut WhileStatement) {
+        // the condition of a while loop is evaluated multiple times, so values can't
+        // be moved into it
+        let mut collector = IdentifierCollector::default();
+        DefaultVisitor::visit_expression(statement.mutate_condition(), &mut collector);
+        self.mark_non_inlinable(&collector.names);
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let usage = std::mem::take(&mut self.next_usage);
+        let name = identifier.get_name();
+
+        let id = match self.scopes.resolve(name) {
+            Some(id) => id,
+            None => return,
+        };
+
+        let depth = self.scopes.depth();
+        let scopes = &self.scopes;
+
+        if let Some(definition) = self.definitions.get_mut(&id) {
+            match usage {
+                IdentifierUsage::Assign => {
+                    definition.reassigned = true;
+                }
+                IdentifierUsage::FunctionName => {
+                    definition.reads += 1;
+                    definition.constant = None;
+                }
+                IdentifierUsage::Read => {
+                    definition.reads += 1;
+
+                    let can_inline = definition.depth == depth
+                        && definition
+                            .inline_value
+                            .as_ref()
+                            .map(|(_, dependencies)| {
+                                dependencies
+                                    .iter()
+                                    .all(|(name, id)| scopes.resolve(name) == Some(*id))
+                            })
+                            .unwrap_or(false);
+
+                    if can_inline {
+                        definition.inlinable_reads += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct ReplaceLocals {
+    scopes: LocalScopes,
+    replacements: HashMap<usize, Expression>,
+    mutated: bool,
+}
+
+impl ReplaceLocals {
+    fn new(replacements: HashMap<usize, Expression>) -> Self {
+        Self {
+            scopes: LocalScopes::default(),
+            replacements,
+            mutated: false,
+        }
+    }
+
+    fn get_replacement(&self, identifier: &Identifier) -> Option<Expression> {
+        self.scopes
+            .resolve(identifier.get_name())
+            .and_then(|id| self.replacements.get(&id))
+            .cloned()
+    }
+}
+
+impl Scope for ReplaceLocals {
+    fn push(&mut self) {
+        self.scopes.push();
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.scopes.declare(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.scopes.declare("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.scopes.declare(identifier);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.scopes.declare(function.get_name());
+    }
+}
+
+impl NodeProcessor for ReplaceLocals {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier(identifier) = expression {
+            if let Some(replacement) = self.get_replacement(identifier) {
+                *expression = replacement;
+                self.mutated = true;
+            }
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        if let Prefix::Identifier(identifier) = prefix {
+            if let Some(replacement) = self.get_replacement(identifier) {
+                *prefix = ParentheseExpression::new(replacement).into();
+                self.mutated = true;
+            }
+        }
+    }
+}
+
+pub const PROPAGATE_CONSTANTS_RULE_NAME: &str = "propagate_constants";
+
+/// A rule that replaces local variables that are never reassigned with their
+/// constant value, and inlines local variables used only once when their value
+/// does not have side effects.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PropagateConstants {
+    inline_single_use: bool,
+}
+
+impl Default for PropagateConstants {
+    fn default() -> Self {
+        Self {
+            inline_single_use: true,
+        }
+    }
+}
+
+impl FlawlessRule for PropagateConstants {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        loop {
+            let mut analysis = LocalAnalysis::new(self.inline_single_use);
+            ScopeVisitor::visit_block(block, &mut analysis);
+
+            let replacements = analysis.into_replacements();
+            if replacements.is_empty() {
+                break;
+            }
+
+            let mut processor = ReplaceLocals::new(replacements);
+            ScopeVisitor::visit_block(block, &mut processor);
+
+            if !processor.mutated {
+                break;
+            }
+        }
+    }
+}
+
+impl RuleConfiguration for PropagateConstants {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "inline_single_use" => {
+                    self.inline_single_use = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        PROPAGATE_CONSTANTS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if !self.inline_single_use {
+            properties.insert("inline_single_use".to_owned(), false.into());
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> PropagateConstants {
+        PropagateConstants::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_propagate_constants", rule);
+    }
+
+    #[test]
+    fn serialize_rule_without_single_use_inlining() {
+        let rule: Box<dyn Rule> = Box::new(PropagateConstants {
+            inline_single_use: false,
+        });
+
+        assert_json_snapshot!("propagate_constants_without_single_use_inlining", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'propagate_constants',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
@@ -9,81 +9,88 @@ use super::verify_no_rule_properties;
 #[derive(Debug)]
 struct Processor<'a> {
     code: &'a str,
+    record_origin: bool,
 }
 
 impl<'a> Processor<'a> {
-    fn new(code: &'a str) -> Self {
-        Self { code }
+    fn new(code: &'a str, record_origin: bool) -> Self {
+        Self {
+            code,
+            record_origin,
+        }
     }
 }
 
 impl NodeProcessor for Processor<'_> {
     fn process_block(&mut self, block: &mut Block) {
-        block.replace_referenced_tokens(self.code);
+        block.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_function_call(&mut self, call: &mut FunctionCall) {
-        call.replace_referenced_tokens(self.code);
-        call.mutate_arguments().replace_referenced_tokens(self.code);
+        call.replace_referenced_tokens(self.code, self.record_origin);
+        call.mutate_arguments()
+            .replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
-        assign.replace_referenced_tokens(self.code);
+        assign.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
-        assign.replace_referenced_tokens(self.code);
+        assign.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_do_statement(&mut self, statement: &mut DoStatement) {
-        statement.replace_referenced_tokens(self.code);
+        statement.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_function_statement(&mut self, function: &mut FunctionStatement) {
-        function.replace_referenced_tokens(self.code);
+        function.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_generic_for_statement(&mut self, generic_for: &mut GenericForStatement) {
-        generic_for.replace_referenced_tokens(self.code);
+        generic_for.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
-        if_statement.replace_referenced_tokens(self.code);
+        if_statement.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_last_statement(&mut self, statement: &mut LastStatement) {
         match statement {
             LastStatement::Break(token) | LastStatement::Continue(token) => {
                 if let Some(token) = token {
-                    token.replace_referenced_tokens(self.code);
+                    token.replace_referenced_tokens(self.code, self.record_origin);
                 }
             }
-            LastStatement::Return(statement) => statement.replace_referenced_tokens(self.code),
+            LastStatement::Return(statement) => {
+                statement.replace_referenced_tokens(self.code, self.record_origin)
+            }
         }
     }
 
     fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
-        assign.replace_referenced_tokens(self.code);
+        assign.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
-        function.replace_referenced_tokens(self.code);
+        function.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_numeric_for_statement(&mut self, numeric_for: &mut NumericForStatement) {
-        numeric_for.replace_referenced_tokens(self.code);
+        numeric_for.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_repeat_statement(&mut self, repeat: &mut RepeatStatement) {
-        repeat.replace_referenced_tokens(self.code);
+        repeat.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_while_statement(&mut self, statement: &mut WhileStatement) {
-        statement.replace_referenced_tokens(self.code);
+        statement.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_type_declaration(&mut self, type_declaration: &mut TypeDeclarationStatement) {
-        type_declaration.replace_referenced_tokens(self.code);
+        type_declaration.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_expression(&mut self, expression: &mut Expression) {
@@ -93,7 +100,7 @@ impl NodeProcessor for Processor<'_> {
             | Expression::True(token)
             | Expression::VariableArguments(token) => {
                 if let Some(token) = token {
-                    token.replace_referenced_tokens(self.code);
+                    token.replace_referenced_tokens(self.code, self.record_origin);
                 }
             }
             Expression::Binary(_)
@@ -114,58 +121,58 @@ impl NodeProcessor for Processor<'_> {
     }
 
     fn process_binary_expression(&mut self, binary: &mut BinaryExpression) {
-        binary.replace_referenced_tokens(self.code);
+        binary.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_field_expression(&mut self, field: &mut FieldExpression) {
-        field.replace_referenced_tokens(self.code);
+        field.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_function_expression(&mut self, function: &mut FunctionExpression) {
-        function.replace_referenced_tokens(self.code);
+        function.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_if_expression(&mut self, if_expression: &mut IfExpression) {
-        if_expression.replace_referenced_tokens(self.code);
+        if_expression.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_variable_expression(&mut self, identifier: &mut Identifier) {
-        identifier.replace_referenced_tokens(self.code);
+        identifier.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_index_expression(&mut self, index: &mut IndexExpression) {
-        index.replace_referenced_tokens(self.code);
+        index.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_number_expression(&mut self, number: &mut NumberExpression) {
-        number.replace_referenced_tokens(self.code);
+        number.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_parenthese_expression(&mut self, expression: &mut ParentheseExpression) {
-        expression.replace_referenced_tokens(self.code);
+        expression.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_string_expression(&mut self, string: &mut StringExpression) {
-        string.replace_referenced_tokens(self.code);
+        string.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_interpolated_string_expression(
         &mut self,
         string: &mut InterpolatedStringExpression,
     ) {
-        string.replace_referenced_tokens(self.code);
+        string.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_table_expression(&mut self, table: &mut TableExpression) {
-        table.replace_referenced_tokens(self.code);
+        table.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_unary_expression(&mut self, unary: &mut UnaryExpression) {
-        unary.replace_referenced_tokens(self.code);
+        unary.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_type_cast_expression(&mut self, type_cast: &mut TypeCastExpression) {
-        type_cast.replace_referenced_tokens(self.code);
+        type_cast.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_prefix_expression(&mut self, _: &mut Prefix) {}
@@ -174,7 +181,7 @@ impl NodeProcessor for Processor<'_> {
         match r#type {
             Type::True(token) | Type::False(token) | Type::Nil(token) => {
                 if let Some(token) = token {
-                    token.replace_referenced_tokens(self.code)
+                    token.replace_referenced_tokens(self.code, self.record_origin)
                 }
             }
             _ => {}
@@ -182,59 +189,59 @@ impl NodeProcessor for Processor<'_> {
     }
 
     fn process_type_name(&mut self, type_name: &mut TypeName) {
-        type_name.replace_referenced_tokens(self.code);
+        type_name.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_type_field(&mut self, type_field: &mut TypeField) {
-        type_field.replace_referenced_tokens(self.code);
+        type_field.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_string_type(&mut self, string_type: &mut StringType) {
-        string_type.replace_referenced_tokens(self.code);
+        string_type.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_array_type(&mut self, array: &mut ArrayType) {
-        array.replace_referenced_tokens(self.code);
+        array.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_table_type(&mut self, table: &mut TableType) {
-        table.replace_referenced_tokens(self.code);
+        table.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_expression_type(&mut self, expression_type: &mut ExpressionType) {
-        expression_type.replace_referenced_tokens(self.code);
+        expression_type.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_parenthese_type(&mut self, parenthese_type: &mut ParentheseType) {
-        parenthese_type.replace_referenced_tokens(self.code);
+        parenthese_type.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_function_type(&mut self, function_type: &mut FunctionType) {
-        function_type.replace_referenced_tokens(self.code);
+        function_type.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_optional_type(&mut self, optional: &mut OptionalType) {
-        optional.replace_referenced_tokens(self.code);
+        optional.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_intersection_type(&mut self, intersection: &mut IntersectionType) {
-        intersection.replace_referenced_tokens(self.code);
+        intersection.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_union_type(&mut self, union: &mut UnionType) {
-        union.replace_referenced_tokens(self.code);
+        union.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_type_pack(&mut self, type_pack: &mut TypePack) {
-        type_pack.replace_referenced_tokens(self.code);
+        type_pack.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_generic_type_pack(&mut self, generic_type_pack: &mut GenericTypePack) {
-        generic_type_pack.replace_referenced_tokens(self.code);
+        generic_type_pack.replace_referenced_tokens(self.code, self.record_origin);
     }
 
     fn process_variadic_type_pack(&mut self, variadic_type_pack: &mut VariadicTypePack) {
-        variadic_type_pack.replace_referenced_tokens(self.code);
+        variadic_type_pack.replace_referenced_tokens(self.code, self.record_origin);
     }
 }
 
@@ -245,7 +252,7 @@ pub(crate) struct ReplaceReferencedTokens {}
 
 impl FlawlessRule for ReplaceReferencedTokens {
     fn flawless_process(&self, block: &mut Block, context: &Context) {
-        let mut processor = Processor::new(context.original_code());
+        let mut processor = Processor::new(context.original_code(), context.has_source_map());
         DefaultVisitor::visit_block(block, &mut processor);
     }
 }
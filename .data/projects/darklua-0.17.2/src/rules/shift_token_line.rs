@@ -9,81 +9,88 @@ use super::verify_no_rule_properties;
 #[derive(Debug)]
 pub(crate) struct ShiftTokenLineProcessor {
     shift_amount: isize,
+    record_origin: bool,
 }
 
 impl ShiftTokenLineProcessor {
-    pub(crate) fn new(shift_amount: isize) -> Self {
-        Self { shift_amount }
+    pub(crate) fn new(shift_amount: isize, record_origin: bool) -> Self {
+        Self {
+            shift_amount,
+            record_origin,
+        }
     }
 }
 
 impl NodeProcessor for ShiftTokenLineProcessor {
     fn process_block(&mut self, block: &mut Block) {
-        block.shift_token_line(self.shift_amount);
+        block.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_function_call(&mut self, call: &mut FunctionCall) {
-        call.shift_token_line(self.shift_amount);
-        call.mutate_arguments().shift_token_line(self.shift_amount);
+        call.shift_token_line(self.shift_amount, self.record_origin);
+        call.mutate_arguments()
+            .shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
-        assign.shift_token_line(self.shift_amount);
+        assign.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
-        assign.shift_token_line(self.shift_amount);
+        assign.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_do_statement(&mut self, statement: &mut DoStatement) {
-        statement.shift_token_line(self.shift_amount);
+        statement.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_function_statement(&mut self, function: &mut FunctionStatement) {
-        function.shift_token_line(self.shift_amount);
+        function.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_generic_for_statement(&mut self, generic_for: &mut GenericForStatement) {
-        generic_for.shift_token_line(self.shift_amount);
+        generic_for.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
-        if_statement.shift_token_line(self.shift_amount);
+        if_statement.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_last_statement(&mut self, statement: &mut LastStatement) {
         match statement {
             LastStatement::Break(token) | LastStatement::Continue(token) => {
                 if let Some(token) = token {
-                    token.shift_token_line(self.shift_amount);
+                    token.shift_token_line(self.shift_amount, self.record_origin);
                 }
             }
-            LastStatement::Return(statement) => statement.shift_token_line(self.shift_amount),
+            LastStatement::Return(statement) => {
+                statement.shift_token_line(self.shift_amount, self.record_origin)
+            }
         }
     }
 
     fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
-        assign.shift_token_line(self.shift_amount);
+        assign.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
-        function.shift_token_line(self.shift_amount);
+        function.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_numeric_for_statement(&mut self, numeric_for: &mut NumericForStatement) {
-        numeric_for.shift_token_line(self.shift_amount);
+        numeric_for.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_repeat_statement(&mut self, repeat: &mut RepeatStatement) {
-        repeat.shift_token_line(self.shift_amount);
+        repeat.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_while_statement(&mut self, statement: &mut WhileStatement) {
-        statement.shift_token_line(self.shift_amount);
+        statement.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_type_declaration(&mut self, type_declaration: &mut TypeDeclarationStatement) {
-        type_declaration.shift_token_line(self.shift_amount);
+        type_declaration.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_expression(&mut self, expression: &mut Expression) {
@@ -93,7 +100,7 @@ impl NodeProcessor for ShiftTokenLineProcessor {
             | Expression::True(token)
             | Expression::VariableArguments(token) => {
                 if let Some(token) = token {
-                    token.shift_token_line(self.shift_amount)
+                    token.shift_token_line(self.shift_amount, self.record_origin)
                 }
             }
             Expression::Binary(_)
@@ -114,58 +121,58 @@ impl NodeProcessor for ShiftTokenLineProcessor {
     }
 
     fn process_binary_expression(&mut self, binary: &mut BinaryExpression) {
-        binary.shift_token_line(self.shift_amount);
+        binary.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_field_expression(&mut self, field: &mut FieldExpression) {
-        field.shift_token_line(self.shift_amount);
+        field.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_function_expression(&mut self, function: &mut FunctionExpression) {
-        function.shift_token_line(self.shift_amount);
+        function.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_if_expression(&mut self, if_expression: &mut IfExpression) {
-        if_expression.shift_token_line(self.shift_amount);
+        if_expression.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_variable_expression(&mut self, identifier: &mut Identifier) {
-        identifier.shift_token_line(self.shift_amount);
+        identifier.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_index_expression(&mut self, index: &mut IndexExpression) {
-        index.shift_token_line(self.shift_amount);
+        index.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_number_expression(&mut self, number: &mut NumberExpression) {
-        number.shift_token_line(self.shift_amount);
+        number.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_parenthese_expression(&mut self, expression: &mut ParentheseExpression) {
-        expression.shift_token_line(self.shift_amount);
+        expression.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_string_expression(&mut self, string: &mut StringExpression) {
-        string.shift_token_line(self.shift_amount);
+        string.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_interpolated_string_expression(
         &mut self,
         string: &mut InterpolatedStringExpression,
     ) {
-        string.shift_token_line(self.shift_amount);
+        string.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_table_expression(&mut self, table: &mut TableExpression) {
-        table.shift_token_line(self.shift_amount);
+        table.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_unary_expression(&mut self, unary: &mut UnaryExpression) {
-        unary.shift_token_line(self.shift_amount);
+        unary.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_type_cast_expression(&mut self, type_cast: &mut TypeCastExpression) {
-        type_cast.shift_token_line(self.shift_amount);
+        type_cast.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_prefix_expression(&mut self, _: &mut Prefix) {}
@@ -174,7 +181,7 @@ impl NodeProcessor for ShiftTokenLineProcessor {
         match r#type {
             Type::True(token) | Type::False(token) | Type::Nil(token) => {
                 if let Some(token) = token {
-                    token.shift_token_line(self.shift_amount);
+                    token.shift_token_line(self.shift_amount, self.record_origin);
                 }
             }
             _ => {}
@@ -182,59 +189,59 @@ impl NodeProcessor for ShiftTokenLineProcessor {
     }
 
     fn process_type_name(&mut self, type_name: &mut TypeName) {
-        type_name.shift_token_line(self.shift_amount);
+        type_name.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_type_field(&mut self, type_field: &mut TypeField) {
-        type_field.shift_token_line(self.shift_amount);
+        type_field.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_string_type(&mut self, string_type: &mut StringType) {
-        string_type.shift_token_line(self.shift_amount);
+        string_type.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_array_type(&mut self, array: &mut ArrayType) {
-        array.shift_token_line(self.shift_amount);
+        array.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_table_type(&mut self, table: &mut TableType) {
-        table.shift_token_line(self.shift_amount);
+        table.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_expression_type(&mut self, expression_type: &mut ExpressionType) {
-        expression_type.shift_token_line(self.shift_amount);
+        expression_type.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_parenthese_type(&mut self, parenthese_type: &mut ParentheseType) {
-        parenthese_type.shift_token_line(self.shift_amount);
+        parenthese_type.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_function_type(&mut self, function_type: &mut FunctionType) {
-        function_type.shift_token_line(self.shift_amount);
+        function_type.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_optional_type(&mut self, optional: &mut OptionalType) {
-        optional.shift_token_line(self.shift_amount);
+        optional.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_intersection_type(&mut self, intersection: &mut IntersectionType) {
-        intersection.shift_token_line(self.shift_amount);
+        intersection.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_union_type(&mut self, union: &mut UnionType) {
-        union.shift_token_line(self.shift_amount);
+        union.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_type_pack(&mut self, type_pack: &mut TypePack) {
-        type_pack.shift_token_line(self.shift_amount);
+        type_pack.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_generic_type_pack(&mut self, generic_type_pack: &mut GenericTypePack) {
-        generic_type_pack.shift_token_line(self.shift_amount);
+        generic_type_pack.shift_token_line(self.shift_amount, self.record_origin);
     }
 
     fn process_variadic_type_pack(&mut self, variadic_type_pack: &mut VariadicTypePack) {
-        variadic_type_pack.shift_token_line(self.shift_amount);
+        variadic_type_pack.shift_token_line(self.shift_amount, self.record_origin);
     }
 }
 
@@ -252,9 +259,10 @@ impl ShiftTokenLine {
 }
 
 impl FlawlessRule for ShiftTokenLine {
-    fn flawless_process(&self, block: &mut Block, _context: &Context) {
+    fn flawless_process(&self, block: &mut Block, context: &Context) {
         if self.shift_amount != 0 {
-            let mut processor = ShiftTokenLineProcessor::new(self.shift_amount);
+            let mut processor =
+                ShiftTokenLineProcessor::new(self.shift_amount, context.has_source_map());
             DefaultVisitor::visit_block(block, &mut processor);
         }
     }
@@ -0,0 +1,100 @@
+use crate::nodes::*;
+use crate::process::processors::FindUsage;
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor, ScopeVisitor};
+
+fn get_local_function_name(statement: &Statement) -> Option<String> {
+    match statement {
+        Statement::LocalFunction(function) => Some(function.get_name().to_owned()),
+        Statement::LocalAssign(assign) => {
+            if assign.variables_len() != 1 || assign.values_len() != 1 {
+                return None;
+            }
+            match assign.iter_values().next() {
+                Some(Expression::Function(_)) => assign
+                    .iter_variables()
+                    .next()
+                    .map(|variable| variable.get_name().to_owned()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Removes local functions that are never referenced after their declaration.
+/// Since the body of a local function is not visited when looking for usages,
+/// recursive functions that are not called from elsewhere are also removed.
+#[derive(Debug, Default)]
+struct RemoveUnusedLocalFunctionsProcessor {
+    mutated: bool,
+}
+
+impl RemoveUnusedLocalFunctionsProcessor {
+    fn is_used(
+        block: &mut Block,
+        index: usize,
+        name: &str,
+        extra: Option<&mut Expression>,
+    ) -> bool {
+        let mut find_usage = FindUsage::new(name);
+
+        block
+            .iter_mut_statements()
+            .skip(index + 1)
+            .any(|next_statement| {
+                ScopeVisitor::visit_statement(next_statement, &mut find_usage);
+                find_usage.has_found_usage()
+            })
+            || block
+                .mutate_last_statement()
+                .into_iter()
+                .any(|last_statement| {
+                    ScopeVisitor::visit_last_statement(last_statement, &mut find_usage);
+                    find_usage.has_found_usage()
+                })
+            || extra
+                .map(|expression| {
+                    ScopeVisitor::visit_expression(expression, &mut find_usage);
+                    find_usage.has_found_usage()
+                })
+                .unwrap_or(false)
+    }
+}
+
+impl NodeProcessor for RemoveUnusedLocalFunctionsProcessor {
+    fn process_scope(&mut self, block: &mut Block, mut extra: Option<&mut Expression>) {
+        let functions: Vec<_> = block
+            .iter_statements()
+            .enumerate()
+            .filter_map(|(index, statement)| {
+                get_local_function_name(statement).map(|name| (index, name))
+            })
+            .collect();
+
+        let unused_functions: Vec<_> = functions
+            .into_iter()
+            .filter(|(index, name)| !Self::is_used(block, *index, name, extra.as_deref_mut()))
+            .map(|(index, _)| index)
+            .collect();
+
+        if unused_functions.is_empty() {
+            return;
+        }
+
+        self.mutated = true;
+
+        let mut index = 0;
+        block.filter_statements(|_| {
+            let keep = !unused_functions.contains(&index);
+            index += 1;
+            keep
+        });
+    }
+}
+
+pub(super) fn remove_unused_local_functions(block: &mut Block) -> bool {
+    let mut processor = RemoveUnusedLocalFunctionsProcessor::default();
+    processor.process_scope(block, None);
+    DefaultVisitor::visit_block(block, &mut processor);
+    processor.mutated
+}
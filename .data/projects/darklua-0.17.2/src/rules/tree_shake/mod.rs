@@ -0,0 +1,123 @@
+mod local_functions;
+mod module_members;
+mod type_declarations;
+
+use local_functions::remove_unused_local_functions;
+use module_members::remove_unused_module_members;
+use type_declarations::remove_unused_type_declarations;
+
+use crate::nodes::Block;
+use crate::rules::bundle::DEFAULT_MODULE_IDENTIFIER;
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+    RulePropertyValue,
+};
+
+pub const TREE_SHAKE_RULE_NAME: &str = "tree_shake";
+
+/// A rule that removes code that cannot be reached from a bundle entry point:
+/// modules that are never required, module members that are never accessed,
+/// local functions that are never called and type declarations that are never
+/// referenced.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TreeShake {
+    modules_identifier: String,
+}
+
+impl Default for TreeShake {
+    fn default() -> Self {
+        Self {
+            modules_identifier: DEFAULT_MODULE_IDENTIFIER.to_owned(),
+        }
+    }
+}
+
+impl TreeShake {
+    pub fn with_modules_identifier(mut self, modules_identifier: impl Into<String>) -> Self {
+        self.modules_identifier = modules_identifier.into();
+        self
+    }
+}
+
+impl FlawlessRule for TreeShake {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        loop {
+            let mut mutated = remove_unused_module_members(block, &self.modules_identifier);
+            mutated |= remove_unused_local_functions(block);
+            mutated |= remove_unused_type_declarations(block);
+
+            if !mutated {
+                break;
+            }
+        }
+    }
+}
+
+impl RuleConfiguration for TreeShake {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "modules_identifier" => {
+                    self.modules_identifier = value.expect_string(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        TREE_SHAKE_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.modules_identifier != DEFAULT_MODULE_IDENTIFIER {
+            properties.insert(
+                "modules_identifier".to_owned(),
+                RulePropertyValue::String(self.modules_identifier.clone()),
+            );
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> TreeShake {
+        TreeShake::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_tree_shake", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_modules_identifier() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_modules_identifier("__MODULES"));
+
+        assert_json_snapshot!("tree_shake_with_modules_identifier", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'tree_shake',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
@@ -0,0 +1,694 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::nodes::*;
+use crate::process::processors::FindUsage;
+use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+use crate::utils::ScopedHashMap;
+
+/// Tracks how the value of a bundled module is used. Accesses are tracked only
+/// when the module value is directly indexed with a known field or when it is
+/// assigned to a local variable that is only indexed with known fields.
+#[derive(Debug, Default)]
+struct ModuleUsage {
+    calls: usize,
+    tracked_calls: usize,
+    reads: usize,
+    tracked_reads: usize,
+    fields: HashSet<String>,
+    methods: HashSet<String>,
+}
+
+impl ModuleUsage {
+    fn is_required(&self) -> bool {
+        self.calls != 0
+    }
+
+    fn is_fully_tracked(&self) -> bool {
+        self.calls == self.tracked_calls && self.reads == self.tracked_reads
+    }
+}
+
+fn get_module_call_name<'a>(call: &'a FunctionCall, modules_identifier: &str) -> Option<&'a str> {
+    if call.get_method().is_some() || !call.get_arguments().is_empty() {
+        return None;
+    }
+
+    match call.get_prefix() {
+        Prefix::Field(field) => match field.get_prefix() {
+            Prefix::Identifier(identifier) if identifier.get_name() == modules_identifier => {
+                Some(field.get_field().get_name().as_str())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn get_expression_module_name<'a>(
+    expression: &'a Expression,
+    modules_identifier: &str,
+) -> Option<&'a str> {
+    match expression {
+        Expression::Call(call) => get_module_call_name(call, modules_identifier),
+        _ => None,
+    }
+}
+
+fn get_string_index(expression: &Expression) -> Option<&str> {
+    match expression {
+        Expression::String(string) => string.get_string_value(),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+struct ModuleUsageCollector<'a> {
+    modules_identifier: &'a str,
+    /// a map from local variables to the name of the module they are bound to
+    bindings: ScopedHashMap<String, Option<String>>,
+    usages: HashMap<String, ModuleUsage>,
+}
+
+impl<'a> ModuleUsageCollector<'a> {
+    fn new(modules_identifier: &'a str) -> Self {
+        Self {
+            modules_identifier,
+            bindings: Default::default(),
+            usages: Default::default(),
+        }
+    }
+
+    fn get_binding(&self, identifier: &Identifier) -> Option<String> {
+        self.bindings
+            .get(identifier.get_name())
+            .and_then(Clone::clone)
+    }
+
+    fn get_usage(&mut self, module_name: &str) -> &mut ModuleUsage {
+        self.usages.entry(module_name.to_owned()).or_default()
+    }
+
+    fn track_access(&mut self, prefix: &Prefix, field: &str, is_method: bool) {
+        let (module_name, is_call) = match prefix {
+            Prefix::Call(call) => match get_module_call_name(call, self.modules_identifier) {
+                Some(module_name) => (module_name.to_owned(), true),
+                None => return,
+            },
+            Prefix::Identifier(identifier) => match self.get_binding(identifier) {
+                Some(module_name) => (module_name, false),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let usage = self.get_usage(&module_name);
+        if is_call {
+            usage.tracked_calls += 1;
+        } else {
+            usage.tracked_reads += 1;
+        }
+        usage.fields.insert(field.to_owned());
+        if is_method {
+            usage.methods.insert(field.to_owned());
+        }
+    }
+
+    fn declare(&mut self, identifier: &str, module_name: Option<String>) {
+        self.bindings.insert(identifier.to_owned(), module_name);
+    }
+}
+
+impl Scope for ModuleUsageCollector<'_> {
+    fn push(&mut self) {
+        self.bindings.push();
+    }
+
+    fn pop(&mut self) {
+        self.bindings.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare(identifier, None);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self", None);
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, value: Option<&mut Expression>) {
+        let module_name = value
+            .and_then(|value| get_expression_module_name(value, self.modules_identifier))
+            .map(ToOwned::to_owned);
+        self.declare(identifier, module_name);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.declare(function.get_name(), None);
+    }
+}
+
+impl NodeProcessor for ModuleUsageCollector<'_> {
+    fn process_local_assign_statement(&mut self, statement: &mut LocalAssignStatement) {
+        let bound_modules: Vec<_> = statement
+            .iter_variables()
+            .zip(statement.iter_values())
+            .filter_map(|(_, value)| get_expression_module_name(value, self.modules_identifier))
+            .map(ToOwned::to_owned)
+            .collect();
+
+        for module_name in bound_modules {
+            self.get_usage(&module_name).tracked_calls += 1;
+        }
+    }
+
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        if let Some(method) = call.get_method() {
+            let method = method.get_name().to_owned();
+            self.track_access(call.get_prefix(), &method, true);
+        }
+
+        if let Some(module_name) = get_module_call_name(call, self.modules_identifier) {
+            let module_name = module_name.to_owned();
+            self.get_usage(&module_name).calls += 1;
+        }
+    }
+
+    fn process_field_expression(&mut self, field: &mut FieldExpression) {
+        let field_name = field.get_field().get_name().to_owned();
+        self.track_access(field.get_prefix(), &field_name, false);
+    }
+
+    fn process_index_expression(&mut self, index: &mut IndexExpression) {
+        if let Some(key) = get_string_index(index.get_index()).map(ToOwned::to_owned) {
+            self.track_access(index.get_prefix(), &key, false);
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        if let Some(module_name) = self.get_binding(identifier) {
+            self.get_usage(&module_name).reads += 1;
+        }
+    }
+}
+
+fn get_module_definition<'a>(
+    statement: &'a mut Statement,
+    modules_identifier: &str,
+) -> Option<(String, &'a mut Block)> {
+    let block = match statement {
+        Statement::Do(do_statement) => do_statement.mutate_block(),
+        _ => return None,
+    };
+
+    if block.statements_len() != 2 || block.get_last_statement().is_some() {
+        return None;
+    }
+
+    let mut statements = block.iter_mut_statements();
+
+    match (statements.next(), statements.next()) {
+        (Some(Statement::LocalFunction(implementation)), Some(Statement::Function(accessor))) => {
+            let name = accessor.get_name();
+
+            if name.get_name().get_name() != modules_identifier || name.has_method() {
+                return None;
+            }
+
+            match name.get_field_names().as_slice() {
+                [module_name] => Some((
+                    module_name.get_name().to_owned(),
+                    implementation.mutate_block(),
+                )),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_modules_table_declaration(statement: &Statement, modules_identifier: &str) -> bool {
+    match statement {
+        Statement::LocalAssign(assign) => {
+            assign.variables_len() == 1
+                && assign
+                    .iter_variables()
+                    .all(|variable| variable.get_name() == modules_identifier)
+        }
+        _ => false,
+    }
+}
+
+fn remove_unused_modules_table(block: &mut Block, modules_identifier: &str) {
+    let declaration_index = match block
+        .iter_statements()
+        .position(|statement| is_modules_table_declaration(statement, modules_identifier))
+    {
+        Some(index) => index,
+        None => return,
+    };
+
+    let mut find_usage = FindUsage::new(modules_identifier);
+
+    let is_used = block
+        .iter_mut_statements()
+        .skip(declaration_index + 1)
+        .any(|statement| {
+            ScopeVisitor::visit_statement(statement, &mut find_usage);
+            find_usage.has_found_usage()
+        })
+        || block
+            .mutate_last_statement()
+            .into_iter()
+            .any(|last_statement| {
+                ScopeVisitor::visit_last_statement(last_statement, &mut find_usage);
+                find_usage.has_found_usage()
+            });
+
+    if !is_used {
+        block.remove_statement(declaration_index);
+    }
+}
+
+/// Removes the module definitions that are never required. When the table
+/// holding the modules is not used anymore, it is also removed.
+fn remove_unused_modules(
+    block: &mut Block,
+    modules_identifier: &str,
+    usages: &HashMap<String, ModuleUsage>,
+) -> bool {
+    let is_required = |module_name: &str| {
+        usages
+            .get(module_name)
+            .map(ModuleUsage::is_required)
+            .unwrap_or(false)
+    };
+
+    let mut mutated = false;
+
+    block.filter_mut_statements(|statement| {
+        if let Statement::Do(do_statement) = statement {
+            let modules_block = do_statement.mutate_block();
+            let original_length = modules_block.statements_len();
+
+            modules_block.filter_mut_statements(|statement| {
+                get_module_definition(statement, modules_identifier)
+                    .map(|(module_name, _)| is_required(&module_name))
+                    .unwrap_or(true)
+            });
+
+            if modules_block.statements_len() != original_length {
+                mutated = true;
+                return !modules_block.is_empty();
+            }
+        }
+        true
+    });
+
+    if mutated {
+        remove_unused_modules_table(block, modules_identifier);
+    }
+
+    mutated
+}
+
+fn get_table_entry_key(entry: &TableEntry) -> Option<&str> {
+    match entry {
+        TableEntry::Field(entry) => Some(entry.get_field().get_name().as_str()),
+        TableEntry::Index(entry) => get_string_index(entry.get_key()),
+        TableEntry::Value(_) => None,
+    }
+}
+
+fn get_table_entry_value(entry: &TableEntry) -> &Expression {
+    match entry {
+        TableEntry::Field(entry) => entry.get_value(),
+        TableEntry::Index(entry) => entry.get_value(),
+        TableEntry::Value(value) => value.as_ref(),
+    }
+}
+
+fn remove_unused_table_entries(
+    table: &mut TableExpression,
+    used_fields: &HashSet<String>,
+    evaluator: &Evaluator,
+) -> bool {
+    let keep: Vec<_> = table
+        .iter_entries()
+        .map(|entry| match get_table_entry_key(entry) {
+            Some(key) => {
+                used_fields.contains(key)
+                    || evaluator.has_side_effects(get_table_entry_value(entry))
+            }
+            None => true,
+        })
+        .collect();
+
+    if keep.iter().all(|keep_entry| *keep_entry) {
+        return false;
+    }
+
+    let mut keep_iter = keep.iter();
+    table
+        .mutate_entries()
+        .retain(|_| keep_iter.next().copied().unwrap_or(true));
+
+    if let Some(mut tokens) = table.get_tokens().cloned() {
+        let mut keep_iter = keep.iter();
+        tokens
+            .separators
+            .retain(|_| keep_iter.next().copied().unwrap_or(true));
+        table.set_tokens(tokens);
+    }
+
+    true
+}
+
+/// Collects the fields accessed on a local table from within the module that
+/// defines it. Inside methods defined with `function Table:method()`, `self` is
+/// considered to be the table.
+#[derive(Debug)]
+struct MemberUsageCollector<'a> {
+    table_name: &'a str,
+    in_method: bool,
+    reads: usize,
+    tracked_reads: usize,
+    fields: HashSet<String>,
+    methods: HashSet<String>,
+}
+
+impl<'a> MemberUsageCollector<'a> {
+    fn new(table_name: &'a str) -> Self {
+        Self {
+            table_name,
+            in_method: false,
+            reads: 0,
+            tracked_reads: 0,
+            fields: Default::default(),
+            methods: Default::default(),
+        }
+    }
+
+    fn is_table(&self, identifier: &Identifier) -> bool {
+        let name = identifier.get_name();
+        name == self.table_name || (self.in_method && name == "self")
+    }
+
+    fn track_access(&mut self, prefix: &Prefix, field: &str, is_method: bool) {
+        if let Prefix::Identifier(identifier) = prefix {
+            if self.is_table(identifier) {
+                self.tracked_reads += 1;
+                self.fields.insert(field.to_owned());
+                if is_method {
+                    self.methods.insert(field.to_owned());
+                }
+            }
+        }
+    }
+}
+
+impl NodeProcessor for MemberUsageCollector<'_> {
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        if let Some(method) = call.get_method() {
+            let method = method.get_name().to_owned();
+            self.track_access(call.get_prefix(), &method, true);
+        }
+    }
+
+    fn process_field_expression(&mut self, field: &mut FieldExpression) {
+        let field_name = field.get_field().get_name().to_owned();
+        self.track_access(field.get_prefix(), &field_name, false);
+    }
+
+    fn process_index_expression(&mut self, index: &mut IndexExpression) {
+        if let Some(key) = get_string_index(index.get_index()).map(ToOwned::to_owned) {
+            self.track_access(index.get_prefix(), &key, false);
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        if self.is_table(identifier) {
+            self.reads += 1;
+        }
+    }
+}
+
+enum MemberDefinition<'a> {
+    Function(&'a str),
+    Method(&'a str),
+    Assign(&'a str),
+}
+
+impl MemberDefinition<'_> {
+    fn name(&self) -> &str {
+        match self {
+            Self::Function(name) | Self::Method(name) | Self::Assign(name) => name,
+        }
+    }
+}
+
+fn get_member_definition<'a>(
+    statement: &'a Statement,
+    table_name: &str,
+) -> Option<MemberDefinition<'a>> {
+    match statement {
+        Statement::Function(function) => {
+            let name = function.get_name();
+            if name.get_name().get_name() != table_name {
+                return None;
+            }
+            match (name.get_field_names().as_slice(), name.get_method()) {
+                ([field], None) => Some(MemberDefinition::Function(field.get_name().as_str())),
+                ([], Some(method)) => Some(MemberDefinition::Method(method.get_name().as_str())),
+                _ => None,
+            }
+        }
+        Statement::Assign(assign) => {
+            if assign.variables_len() != 1 || assign.values_len() != 1 {
+                return None;
+            }
+            let (prefix, field) = match assign.get_variables().first()? {
+                Variable::Field(field) => {
+                    (field.get_prefix(), field.get_field().get_name().as_str())
+                }
+                Variable::Index(index) => {
+                    (index.get_prefix(), get_string_index(index.get_index())?)
+                }
+                Variable::Identifier(_) => return None,
+            };
+            match prefix {
+                Prefix::Identifier(identifier) if identifier.get_name() == table_name => {
+                    Some(MemberDefinition::Assign(field))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_table_declaration(statement: &Statement, table_name: &str) -> bool {
+    match statement {
+        Statement::LocalAssign(assign) => assign
+            .iter_variables()
+            .any(|variable| variable.get_name() == table_name),
+        Statement::LocalFunction(function) => function.get_name() == table_name,
+        _ => false,
+    }
+}
+
+/// Removes the members of a local table returned by a module. The table must be
+/// declared only once at the top level of the module and it must never be used
+/// other than by indexing it with known fields.
+fn remove_unused_table_members(
+    block: &mut Block,
+    table_name: &str,
+    module_usage: &ModuleUsage,
+    evaluator: &Evaluator,
+) -> bool {
+    let declarations: Vec<_> = block
+        .iter_statements()
+        .enumerate()
+        .filter(|(_, statement)| is_table_declaration(statement, table_name))
+        .map(|(index, _)| index)
+        .collect();
+
+    let declaration_index = match declarations.as_slice() {
+        [index] => *index,
+        _ => return false,
+    };
+
+    let mut collector = MemberUsageCollector::new(table_name);
+    let mut defined_methods = HashSet::new();
+
+    for (index, statement) in block.iter_mut_statements().enumerate() {
+        if index == declaration_index {
+            if let Statement::LocalAssign(assign) = statement {
+                if assign.variables_len() != 1 {
+                    return false;
+                }
+                for value in assign.iter_mut_values() {
+                    DefaultVisitor::visit_expression(value, &mut collector);
+                }
+            } else {
+                return false;
+            }
+            continue;
+        }
+
+        let definition = if index > declaration_index {
+            get_member_definition(statement, table_name).map(|definition| match definition {
+                MemberDefinition::Function(_) => false,
+                MemberDefinition::Method(name) => {
+                    defined_methods.insert(name.to_owned());
+                    true
+                }
+                MemberDefinition::Assign(_) => false,
+            })
+        } else {
+            None
+        };
+
+        match (definition, statement) {
+            (Some(is_method), Statement::Function(function)) => {
+                collector.in_method = is_method;
+                DefaultVisitor::visit_block(function.mutate_block(), &mut collector);
+                collector.in_method = false;
+            }
+            (Some(_), Statement::Assign(assign)) => {
+                for value in assign.iter_mut_values() {
+                    DefaultVisitor::visit_expression(value, &mut collector);
+                }
+            }
+            (_, statement) => {
+                DefaultVisitor::visit_statement(statement, &mut collector);
+            }
+        }
+    }
+
+    if collector.reads != collector.tracked_reads {
+        return false;
+    }
+
+    // calling a method passes the table as an argument, which can only be
+    // tracked when the method is defined using the `:` syntax
+    if !collector
+        .methods
+        .iter()
+        .chain(module_usage.methods.iter())
+        .all(|method| defined_methods.contains(method))
+    {
+        return false;
+    }
+
+    let used_fields: HashSet<_> = collector
+        .fields
+        .union(&module_usage.fields)
+        .cloned()
+        .collect();
+
+    let mut mutated = false;
+    let mut index = 0;
+
+    block.filter_mut_statements(|statement| {
+        let current_index = index;
+        index += 1;
+
+        if current_index == declaration_index {
+            if let Statement::LocalAssign(assign) = statement {
+                for value in assign.iter_mut_values() {
+                    if let Expression::Table(table) = value {
+                        mutated |= remove_unused_table_entries(table, &used_fields, evaluator);
+                    }
+                }
+            }
+            return true;
+        }
+
+        if current_index < declaration_index {
+            return true;
+        }
+
+        let remove = match get_member_definition(statement, table_name) {
+            Some(definition) if !used_fields.contains(definition.name()) => match &*statement {
+                Statement::Assign(assign) => !assign
+                    .iter_values()
+                    .any(|value| evaluator.has_side_effects(value)),
+                _ => true,
+            },
+            _ => false,
+        };
+
+        if remove {
+            mutated = true;
+        }
+        !remove
+    });
+
+    mutated
+}
+
+fn remove_unused_members(block: &mut Block, usage: &ModuleUsage, evaluator: &Evaluator) -> bool {
+    let table_name = match block.get_last_statement() {
+        Some(LastStatement::Return(return_statement)) if return_statement.len() == 1 => {
+            match return_statement.iter_expressions().next() {
+                Some(Expression::Identifier(identifier)) => Some(identifier.get_name().to_owned()),
+                Some(Expression::Table(_)) => None,
+                _ => return false,
+            }
+        }
+        _ => return false,
+    };
+
+    match table_name {
+        Some(table_name) => remove_unused_table_members(block, &table_name, usage, evaluator),
+        None => {
+            // methods defined in a table literal could access any field
+            // through their `self` parameter
+            if !usage.methods.is_empty() {
+                return false;
+            }
+            match block.mutate_last_statement() {
+                Some(LastStatement::Return(return_statement)) => {
+                    match return_statement.iter_mut_expressions().next() {
+                        Some(Expression::Table(table)) => {
+                            remove_unused_table_entries(table, &usage.fields, evaluator)
+                        }
+                        _ => false,
+                    }
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Removes bundled modules that are never required and the members of required
+/// modules that are never accessed.
+pub(super) fn remove_unused_module_members(block: &mut Block, modules_identifier: &str) -> bool {
+    let mut collector = ModuleUsageCollector::new(modules_identifier);
+    ScopeVisitor::visit_block(block, &mut collector);
+    let usages = collector.usages;
+
+    let mut mutated = remove_unused_modules(block, modules_identifier, &usages);
+
+    let evaluator = Evaluator::default();
+
+    for statement in block.iter_mut_statements() {
+        if let Statement::Do(do_statement) = statement {
+            for statement in do_statement.mutate_block().iter_mut_statements() {
+                if let Some((module_name, module_block)) =
+                    get_module_definition(statement, modules_identifier)
+                {
+                    if let Some(usage) = usages
+                        .get(&module_name)
+                        .filter(|usage| usage.is_fully_tracked())
+                    {
+                        mutated |= remove_unused_members(module_block, usage, &evaluator);
+                    }
+                }
+            }
+        }
+    }
+
+    mutated
+}
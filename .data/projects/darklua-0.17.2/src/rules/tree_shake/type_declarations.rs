@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::nodes::*;
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+
+/// Counts how many times each type name is referenced.
+#[derive(Debug, Default)]
+struct TypeReferenceCounter {
+    references: HashMap<String, usize>,
+}
+
+impl TypeReferenceCounter {
+    fn get_count(&self, name: &str) -> usize {
+        self.references.get(name).copied().unwrap_or(0)
+    }
+}
+
+impl NodeProcessor for TypeReferenceCounter {
+    fn process_type_name(&mut self, type_name: &mut TypeName) {
+        *self
+            .references
+            .entry(type_name.get_type_name().get_name().to_owned())
+            .or_default() += 1;
+    }
+}
+
+/// Removes type declarations that are not exported and never referenced outside
+/// of their own definition. Type names are matched without considering scopes,
+/// so a declaration is kept if any type with the same name is referenced.
+struct RemoveUnusedTypeDeclarations {
+    references: TypeReferenceCounter,
+    mutated: bool,
+}
+
+impl NodeProcessor for RemoveUnusedTypeDeclarations {
+    fn process_block(&mut self, block: &mut Block) {
+        let references = &self.references;
+        let mut mutated = false;
+
+        block.filter_mut_statements(|statement| {
+            let name = match statement {
+                Statement::TypeDeclaration(declaration) if !declaration.is_exported() => {
+                    declaration.get_name().get_name().to_owned()
+                }
+                _ => return true,
+            };
+
+            let mut own_references = TypeReferenceCounter::default();
+            DefaultVisitor::visit_statement(statement, &mut own_references);
+
+            let keep = references.get_count(&name) > own_references.get_count(&name);
+            if !keep {
+                mutated = true;
+            }
+            keep
+        });
+
+        self.mutated |= mutated;
+    }
+}
+
+pub(super) fn remove_unused_type_declarations(block: &mut Block) -> bool {
+    let mut references = TypeReferenceCounter::default();
+    DefaultVisitor::visit_block(block, &mut references);
+
+    let mut processor = RemoveUnusedTypeDeclarations {
+        references,
+        mutated: false,
+    };
+    DefaultVisitor::visit_block(block, &mut processor);
+    processor.mutated
+}
@@ -152,6 +152,11 @@ impl Context {
         self
     }
 
+    pub fn expect_failure(mut self) -> Self {
+        self.command.assert().code(1);
+        self
+    }
+
     pub fn replace_snapshot_content(
         mut self,
         matcher: impl Into<String>,
@@ -438,3 +443,51 @@ fn run_convert_command_errors_when_unrecognized_extension() {
         .replace_duration_labels()
         .snapshot_command("run_convert_command_errors_when_unrecognized_extension");
 }
+
+#[test]
+fn run_lint_command() {
+    Context::default()
+        .write_file("src/init.lua", "local a = 1\nvalue = true\n")
+        .arg("lint")
+        .arg("src")
+        .expect_success()
+        .snapshot_command("run_lint_command");
+}
+
+#[test]
+fn run_lint_command_fails_with_denied_lint() {
+    Context::default()
+        .write_file("src/init.lua", "value = true\n")
+        .arg("lint")
+        .arg("src")
+        .arg("--deny")
+        .arg("global_assignment")
+        .expect_failure()
+        .snapshot_command("run_lint_command_fails_with_denied_lint");
+}
+
+#[test]
+fn run_lint_command_with_config() {
+    Context::default()
+        .write_file("src/init.lua", "local a = 1\n")
+        .write_file(
+            ".darklua-lint.json",
+            "{ lints: { unused_variable: 'allow' } }",
+        )
+        .arg("lint")
+        .arg("src")
+        .expect_success()
+        .snapshot_command("run_lint_command_with_config");
+}
+
+#[test]
+fn run_lint_command_with_json_format() {
+    Context::default()
+        .write_file("src/init.lua", "local a = 1\n")
+        .arg("lint")
+        .arg("src")
+        .arg("--format")
+        .arg("json")
+        .expect_success()
+        .snapshot_command("run_lint_command_with_json_format");
+}
@@ -172,6 +172,568 @@ fn use_default_json5_config_in_place() {
     assert_eq!(resources.get("src/test.lua").unwrap(), "return 'Hello'");
 }
 
+#[test]
+fn generate_source_map_next_to_output() {
+    let resources = memory_resources!(
+        "src/test.lua" => "local value = true\nreturn value",
+        ".darklua.json" => "{ \"rules\": [], \"generator\": \"dense\", \"source_map\": true }",
+    );
+
+    process(&resources, Options::new("src").with_output("output"))
+        .unwrap()
+        .result()
+        .unwrap();
+
+    assert_eq!(
+        resources.get("output/test.lua").unwrap(),
+        "local value=true return value"
+    );
+    assert_eq!(
+        resources.get("output/test.lua.map").unwrap(),
+        "{\"version\":3,\"file\":\"test.lua\",\"sources\":[\"../src/test.lua\"],\"names\":[],\"mappings\":\"MAAM,MAAQ,YACP\"}"
+    );
+}
+
+#[test]
+fn generate_source_map_from_options() {
+    let resources = memory_resources!(
+        "src/test.lua" => "return true",
+        ".darklua.json" => "{ \"rules\": [], \"generator\": \"dense\" }",
+    );
+
+    process(
+        &resources,
+        Options::new("src").with_output("output").with_source_map(),
+    )
+    .unwrap()
+    .result()
+    .unwrap();
+
+    assert!(resources.get("output/test.lua.map").is_ok());
+}
+
+#[test]
+fn generate_source_map_for_bundled_modules() {
+    let resources = memory_resources!(
+        "src/main.lua" => "local value = require('./value')\nreturn value",
+        "src/value.lua" => "local  number =   1\n  return number",
+        ".darklua.json" => "{ \"rules\": [], \"generator\": \"dense\", \"source_map\": true, \"bundle\": { \"require_mode\": \"path\" } }",
+    );
+
+    process(
+        &resources,
+        Options::new("src/main.lua").with_output("out.lua"),
+    )
+    .unwrap()
+    .result()
+    .unwrap();
+
+    // the bundled module tokens map to their own lines and columns in `src/value.lua`,
+    // and the last line maps back to `value` and `return value` in `src/main.lua`
+    assert_eq!(
+        resources.get("out.lua.map").unwrap(),
+        "{\"version\":3,\"file\":\"out.lua\",\"sources\":[\"src/main.lua\",\"src/value.lua\"],\"names\":[],\"mappings\":\";MCAO,OAAW,SACT;;;ADDH,yCACC\"}"
+    );
+}
+
+mod process_sources {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use darklua_core::{process_sources, Configuration};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn sources(content: &[(&str, &str)]) -> HashMap<PathBuf, String> {
+        content
+            .iter()
+            .map(|(path, code)| (PathBuf::from(path), code.to_string()))
+            .collect()
+    }
+
+    fn bundle_configuration() -> Configuration {
+        serde_json::from_str(
+            "{ \"rules\": [], \"generator\": \"retain_lines\", \"bundle\": { \"require_mode\": \"path\" } }",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn apply_default_rules() {
+        let output = process_sources(
+            sources(&[("src/test.lua", ANY_CODE)]),
+            serde_json::from_str("{}").unwrap(),
+        );
+
+        assert!(!output.has_errors());
+        assert_eq!(output.code("src/test.lua"), Some(ANY_CODE_DEFAULT_PROCESS));
+    }
+
+    #[test]
+    fn only_process_lua_and_luau_files() {
+        let output = process_sources(
+            sources(&[
+                ("src/test.lua", ANY_CODE),
+                ("src/other.luau", ANY_CODE),
+                ("src/data.json", "{}"),
+            ]),
+            serde_json::from_str("{}").unwrap(),
+        );
+
+        let mut paths: Vec<_> = output.iter_code().map(|(path, _)| path).collect();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src/other.luau").as_path(),
+                PathBuf::from("src/test.lua").as_path()
+            ]
+        );
+    }
+
+    #[test]
+    fn returns_parser_errors() {
+        let output = process_sources(
+            sources(&[("src/test.lua", "local = 1")]),
+            Configuration::empty(),
+        );
+
+        assert_eq!(output.errors().len(), 1);
+        assert_eq!(output.code("src/test.lua"), None);
+    }
+
+    #[test]
+    fn bundle_returns_dependencies() {
+        let output = process_sources(
+            sources(&[
+                ("src/main.lua", "local value = require('./value')"),
+                ("src/value.lua", "return true"),
+            ]),
+            bundle_configuration(),
+        );
+
+        assert!(!output.has_errors());
+        assert_eq!(
+            output.dependencies("src/main.lua").collect::<Vec<_>>(),
+            vec![PathBuf::from("src/value.lua").as_path()]
+        );
+        assert_eq!(
+            output.iter_external_dependencies().collect::<Vec<_>>(),
+            vec![PathBuf::from("src/value.lua").as_path()]
+        );
+    }
+
+    #[test]
+    fn bundle_with_data_file() {
+        let output = process_sources(
+            sources(&[
+                ("src/main.lua", "local value = require('./value.json')"),
+                ("src/value.json", "true"),
+            ]),
+            bundle_configuration(),
+        );
+
+        assert!(!output.has_errors());
+        assert!(output.code("src/main.lua").is_some());
+        assert_eq!(output.code("src/value.json"), None);
+    }
+}
+
+mod incremental_session {
+    use std::path::Path;
+
+    use darklua_core::{Configuration, FileChange, IncrementalSession};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn paths<'a>(iterator: impl Iterator<Item = &'a Path>) -> Vec<&'a Path> {
+        let mut paths: Vec<_> = iterator.collect();
+        paths.sort();
+        paths
+    }
+
+    fn bundle_configuration() -> Configuration {
+        serde_json::from_str(
+            "{ \"rules\": [], \"generator\": \"retain_lines\", \"bundle\": { \"require_mode\": \"path\" } }",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn first_process_writes_all_outputs() {
+        let resources = memory_resources!(
+            "src/a.lua" => ANY_CODE,
+            "src/b.lua" => ANY_CODE,
+        );
+        let mut session =
+            IncrementalSession::new(resources.clone(), || Options::new("src").with_output("out"));
+
+        let update = session.process();
+
+        assert!(!update.has_errors());
+        assert_eq!(
+            paths(update.rewritten_outputs()),
+            vec![Path::new("out/a.lua"), Path::new("out/b.lua")]
+        );
+        assert_eq!(
+            resources.get("out/a.lua").unwrap(),
+            ANY_CODE_DEFAULT_PROCESS
+        );
+    }
+
+    #[test]
+    fn process_again_without_changes_is_empty() {
+        let resources = memory_resources!(
+            "src/a.lua" => ANY_CODE,
+        );
+        let mut session =
+            IncrementalSession::new(resources, || Options::new("src").with_output("out"));
+
+        session.process();
+        let update = session.process();
+
+        assert!(update.is_empty());
+    }
+
+    #[test]
+    fn modified_source_only_rewrites_its_output() {
+        let resources = memory_resources!(
+            "src/a.lua" => ANY_CODE,
+            "src/b.lua" => ANY_CODE,
+        );
+        let mut session =
+            IncrementalSession::new(resources.clone(), || Options::new("src").with_output("out"));
+
+        session.process();
+
+        resources.write("src/a.lua", "return false").unwrap();
+        let update = session.apply_changes([FileChange::Modified("src/a.lua".into())]);
+
+        assert!(!update.has_errors());
+        assert_eq!(
+            paths(update.rewritten_sources()),
+            vec![Path::new("src/a.lua")]
+        );
+        assert_eq!(
+            paths(update.rewritten_outputs()),
+            vec![Path::new("out/a.lua")]
+        );
+        assert_eq!(resources.get("out/a.lua").unwrap(), "return false");
+    }
+
+    #[test]
+    fn created_source_is_processed() {
+        let resources = memory_resources!(
+            "src/a.lua" => ANY_CODE,
+        );
+        let mut session =
+            IncrementalSession::new(resources.clone(), || Options::new("src").with_output("out"));
+
+        session.process();
+
+        resources.write("src/b.lua", ANY_CODE).unwrap();
+        let update = session.apply_changes([FileChange::Created("src/b.lua".into())]);
+
+        assert_eq!(
+            paths(update.rewritten_outputs()),
+            vec![Path::new("out/b.lua")]
+        );
+        assert_eq!(
+            resources.get("out/b.lua").unwrap(),
+            ANY_CODE_DEFAULT_PROCESS
+        );
+    }
+
+    #[test]
+    fn removed_source_removes_its_output() {
+        let resources = memory_resources!(
+            "src/a.lua" => ANY_CODE,
+            "src/b.lua" => ANY_CODE,
+        );
+        let mut session =
+            IncrementalSession::new(resources.clone(), || Options::new("src").with_output("out"));
+
+        session.process();
+
+        resources.remove("src/b.lua").unwrap();
+        let update = session.apply_changes([FileChange::Removed("src/b.lua".into())]);
+
+        assert_eq!(update.rewritten_outputs().count(), 0);
+        assert_eq!(
+            paths(update.removed_outputs()),
+            vec![Path::new("out/b.lua")]
+        );
+        assert!(!resources.exists("out/b.lua").unwrap());
+    }
+
+    #[test]
+    fn removed_source_without_output_reports_nothing() {
+        let resources = memory_resources!(
+            "src/a.lua" => ANY_CODE,
+            "src/b.lua" => ANY_CODE,
+        );
+        let mut session =
+            IncrementalSession::new(resources.clone(), || Options::new("src").with_output("out"));
+
+        session.process();
+
+        resources.remove("out/b.lua").unwrap();
+        resources.remove("src/b.lua").unwrap();
+        let update = session.apply_changes([FileChange::Removed("src/b.lua".into())]);
+
+        assert_eq!(update.removed_outputs().count(), 0);
+    }
+
+    #[test]
+    fn modified_dependency_rewrites_dependent_output() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local value = require('./value') return value",
+            "src/value.lua" => "return true",
+        );
+        let mut session = IncrementalSession::new(resources.clone(), || {
+            Options::new("src/main.lua")
+                .with_output("out/main.lua")
+                .with_configuration(bundle_configuration())
+        });
+
+        assert!(!session.process().has_errors());
+        assert_eq!(
+            paths(session.iter_external_dependencies()),
+            vec![Path::new("src/value.lua")]
+        );
+
+        resources.write("src/value.lua", "return false").unwrap();
+        let update = session.apply_changes([FileChange::Modified("src/value.lua".into())]);
+
+        assert!(!update.has_errors());
+        assert_eq!(
+            paths(update.rewritten_outputs()),
+            vec![Path::new("out/main.lua")]
+        );
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        let resources = memory_resources!(
+            "src/a.lua" => "return +",
+        );
+        let mut session =
+            IncrementalSession::new(resources, || Options::new("src").with_output("out"));
+
+        let update = session.process();
+
+        assert!(update.has_errors());
+        assert_eq!(update.rewritten_outputs().count(), 0);
+    }
+}
+
+mod build_cache {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn cache_entries(resources: &Resources) -> Vec<PathBuf> {
+        resources.walk("cache").collect()
+    }
+
+    fn replace_cached_code(resources: &Resources, code: &str) {
+        for entry_path in cache_entries(resources) {
+            let mut entry: serde_json::Value =
+                serde_json::from_str(&resources.get(&entry_path).unwrap()).unwrap();
+            entry["code"] = serde_json::Value::String(code.to_owned());
+            resources
+                .write(&entry_path, &serde_json::to_string(&entry).unwrap())
+                .unwrap();
+        }
+    }
+
+    fn options() -> Options {
+        Options::new("src")
+            .with_output("out")
+            .with_cache_directory("cache")
+    }
+
+    #[test]
+    fn writes_cache_entry() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+        );
+
+        process(&resources, options()).unwrap().result().unwrap();
+
+        assert_eq!(cache_entries(&resources).len(), 1);
+        assert_eq!(
+            resources.get("out/test.lua").unwrap(),
+            ANY_CODE_DEFAULT_PROCESS
+        );
+    }
+
+    #[test]
+    fn restores_unchanged_file_from_cache() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+        );
+
+        process(&resources, options()).unwrap().result().unwrap();
+        replace_cached_code(&resources, "return 'cached'");
+        process(&resources, options()).unwrap().result().unwrap();
+
+        assert_eq!(resources.get("out/test.lua").unwrap(), "return 'cached'");
+    }
+
+    #[test]
+    fn does_not_restore_modified_file() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+        );
+
+        process(&resources, options()).unwrap().result().unwrap();
+        replace_cached_code(&resources, "return 'cached'");
+        resources.write("src/test.lua", "return false").unwrap();
+        process(&resources, options()).unwrap().result().unwrap();
+
+        assert_eq!(resources.get("out/test.lua").unwrap(), "return false");
+    }
+
+    #[test]
+    fn does_not_restore_file_when_configuration_changes() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+            ".darklua.json" => "{ \"rules\": [] }",
+        );
+
+        process(&resources, options()).unwrap().result().unwrap();
+        replace_cached_code(&resources, "return 'cached'");
+        resources
+            .write(".darklua.json", "{ \"rules\": [\"remove_empty_do\"] }")
+            .unwrap();
+        process(&resources, options()).unwrap().result().unwrap();
+
+        assert_ne!(resources.get("out/test.lua").unwrap(), "return 'cached'");
+    }
+
+    #[test]
+    fn does_not_restore_file_when_dependency_changes() {
+        let resources = memory_resources!(
+            "src/main.lua" => "return require('./value.json')",
+            "src/value.json" => "true",
+            ".darklua.json" => "{ \"rules\": [], \"bundle\": { \"require_mode\": \"path\" } }",
+        );
+        let options = || {
+            Options::new("src/main.lua")
+                .with_output("out/main.lua")
+                .with_cache_directory("cache")
+        };
+
+        process(&resources, options()).unwrap().result().unwrap();
+        replace_cached_code(&resources, "return 'cached'");
+        process(&resources, options()).unwrap().result().unwrap();
+
+        assert_eq!(resources.get("out/main.lua").unwrap(), "return 'cached'");
+
+        resources.write("src/value.json", "false").unwrap();
+        process(&resources, options()).unwrap().result().unwrap();
+
+        let output = resources.get("out/main.lua").unwrap();
+        assert_ne!(output, "return 'cached'");
+        assert!(output.contains("false"));
+    }
+}
+
+mod convert_data_to_typed_module {
+    use darklua_core::convert_data_to_typed_module;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::utils::parse_input;
+
+    #[track_caller]
+    fn assert_module(value: serde_json::Value, expected: &str) {
+        let code = convert_data_to_typed_module(value, "Data").unwrap();
+
+        assert_eq!(
+            parse_input(&code),
+            parse_input(expected),
+            "generated: {}",
+            code
+        );
+    }
+
+    #[test]
+    fn string() {
+        assert_module(
+            json!("hello"),
+            "export type Data = string local data: Data = 'hello' return data",
+        );
+    }
+
+    #[test]
+    fn record() {
+        assert_module(
+            json!({ "count": 1, "enabled": true, "name": "a" }),
+            "export type Data = { count: number, enabled: boolean, name: string }
+            local data: Data = table.freeze({ count = 1, enabled = true, name = 'a' })
+            return data",
+        );
+    }
+
+    #[test]
+    fn record_with_key_that_is_not_an_identifier() {
+        assert_module(
+            json!({ "hello world": 1 }),
+            "export type Data = { [\"hello world\"]: number }
+            local data: Data = table.freeze({ ['hello world'] = 1 })
+            return data",
+        );
+    }
+
+    #[test]
+    fn array_of_records_with_optional_field() {
+        assert_module(
+            json!([{ "a": 1 }, { "a": 2, "b": "x" }]),
+            "export type Data = { { a: number, b: string? } }
+            local data: Data = table.freeze({ table.freeze({ a = 1 }), table.freeze({ a = 2, b = 'x' }) })
+            return data",
+        );
+    }
+
+    #[test]
+    fn array_of_mixed_values() {
+        assert_module(
+            json!([1, "a", null]),
+            "export type Data = { (number | string)? }
+            local data: Data = table.freeze({ 1, 'a', nil })
+            return data",
+        );
+    }
+
+    #[test]
+    fn nested_empty_table() {
+        assert_module(
+            json!({ "list": [] }),
+            "export type Data = { list: {} }
+            local data: Data = table.freeze({ list = table.freeze({}) })
+            return data",
+        );
+    }
+
+    #[test]
+    fn invalid_type_name() {
+        let error = convert_data_to_typed_module(json!(true), "not valid").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid type name `not valid` (it must be a valid identifier)"
+        );
+    }
+}
+
 mod errors {
     use std::path::{Path, PathBuf};
 
@@ -0,0 +1,155 @@
+use darklua_core::rules::{AddTypeAssertions, Rule};
+
+test_rule!(
+    add_type_assertions,
+    AddTypeAssertions::default(),
+    local_function_with_number_parameter(
+        "local function double(n: number) return n * 2 end"
+    ) => "local function double(n: number)
+        assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+        return n * 2
+    end",
+    function_statement_with_string_parameter(
+        "function Module.greet(name: string) print(name) end"
+    ) => "function Module.greet(name: string)
+        assert(typeof(name) == 'string', \"invalid type for parameter 'name' (expected string)\")
+        print(name)
+    end",
+    method_with_boolean_parameter(
+        "function Class:setEnabled(enabled: boolean) self.enabled = enabled end"
+    ) => "function Class:setEnabled(enabled: boolean)
+        assert(typeof(enabled) == 'boolean', \"invalid type for parameter 'enabled' (expected boolean)\")
+        self.enabled = enabled
+    end",
+    function_expression_with_parameter(
+        "return function(value: number) end"
+    ) => "return function(value: number)
+        assert(typeof(value) == 'number', \"invalid type for parameter 'value' (expected number)\")
+    end",
+    optional_parameter(
+        "local function f(value: string?) end"
+    ) => "local function f(value: string?)
+        assert(typeof(value) == 'string' or value == nil, \"invalid type for parameter 'value' (expected string or nil)\")
+    end",
+    union_parameter(
+        "local function f(value: string | number) end"
+    ) => "local function f(value: string | number)
+        assert(typeof(value) == 'string' or typeof(value) == 'number', \"invalid type for parameter 'value' (expected string or number)\")
+    end",
+    optional_union_parameter(
+        "local function f(value: (string | number)?) end"
+    ) => "local function f(value: (string | number)?)
+        assert(typeof(value) == 'string' or typeof(value) == 'number' or value == nil, \"invalid type for parameter 'value' (expected string or number or nil)\")
+    end",
+    singleton_types_parameter(
+        "local function f(value: 'a' | 'b' | true) end"
+    ) => "local function f(value: 'a' | 'b' | true)
+        assert(typeof(value) == 'string' or typeof(value) == 'boolean', \"invalid type for parameter 'value' (expected string or boolean)\")
+    end",
+    multiple_parameters_in_order(
+        "local function f(a: number, b, c: string) return a end"
+    ) => "local function f(a: number, b, c: string)
+        assert(typeof(a) == 'number', \"invalid type for parameter 'a' (expected number)\")
+        assert(typeof(c) == 'string', \"invalid type for parameter 'c' (expected string)\")
+        return a
+    end",
+    nested_function(
+        "local function f(a: number) return function(b: string) end end"
+    ) => "local function f(a: number)
+        assert(typeof(a) == 'number', \"invalid type for parameter 'a' (expected number)\")
+        return function(b: string)
+            assert(typeof(b) == 'string', \"invalid type for parameter 'b' (expected string)\")
+        end
+    end",
+);
+
+test_rule_without_effects!(
+    AddTypeAssertions::default(),
+    function_without_types("local function f(a, b) return a + b end"),
+    function_with_table_type("local function f(a: { number }) end"),
+    function_with_named_type("local function f(a: Instance) end"),
+    function_with_generic_type("local function f<T>(a: T) end"),
+    function_with_any_type("local function f(a: any) end"),
+    function_with_union_of_non_primitive_type("local function f(a: string | Instance) end"),
+    function_with_typed_variadic("local function f(...: number) end"),
+);
+
+test_rule!(
+    add_type_assertions_only_public_functions,
+    AddTypeAssertions::default().with_only_public_functions(true),
+    function_statement_with_field(
+        "local Module = {} function Module.double(n: number) return n * 2 end return Module"
+    ) => "local Module = {}
+    function Module.double(n: number)
+        assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+        return n * 2
+    end
+    return Module",
+    function_assigned_to_field(
+        "local Module = {} Module.double = function(n: number) return n * 2 end return Module"
+    ) => "local Module = {}
+    Module.double = function(n: number)
+        assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+        return n * 2
+    end
+    return Module",
+    local_function_returned_in_table(
+        "local function double(n: number) return n * 2 end return { double = double }"
+    ) => "local function double(n: number)
+        assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+        return n * 2
+    end
+    return { double = double }",
+    local_function_assigned_to_field(
+        "local Module = {} local function double(n: number) return n * 2 end Module.double = double return Module"
+    ) => "local Module = {}
+    local function double(n: number)
+        assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+        return n * 2
+    end
+    Module.double = double
+    return Module",
+    returned_function(
+        "local function double(n: number) return n * 2 end return double"
+    ) => "local function double(n: number)
+        assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+        return n * 2
+    end
+    return double",
+    function_in_returned_table(
+        "return { double = function(n: number) return n * 2 end }"
+    ) => "return {
+        double = function(n: number)
+            assert(typeof(n) == 'number', \"invalid type for parameter 'n' (expected number)\")
+            return n * 2
+        end
+    }",
+);
+
+test_rule_without_effects!(
+    AddTypeAssertions::default().with_only_public_functions(true),
+    private_local_function("local function double(n: number) return n * 2 end return double(2)"),
+    private_local_function_expression(
+        "local double = function(n: number) return n * 2 end return double(2)"
+    ),
+    callback_argument("table.sort(list, function(a: number, b: number) return a < b end)"),
+    nested_local_function_with_exported_name(
+        "local function run() local function double(n: number) return n * 2 end end return { double = run }"
+    ),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'add_type_assertions',
+        only_public_functions: true,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'add_type_assertions'").unwrap();
+}
@@ -0,0 +1,101 @@
+use darklua_core::rules::{InlineFunctions, Rule};
+
+test_rule!(
+    inline_functions,
+    InlineFunctions::default(),
+    inline_returned_expression(
+        "local function double(n) return n * 2 end print(double(4))"
+    ) => "print(4 * 2)",
+    inline_with_local_arguments(
+        "local function add(a, b) return a + b end local x, y = 1, 2 print(add(x, y))"
+    ) => "local x, y = 1, 2 print(x + y)",
+    inline_with_missing_argument(
+        "local function isNil(value) return value == nil end print(isNil())"
+    ) => "print(nil == nil)",
+    inline_without_extra_arguments(
+        "local function double(n) return n * 2 end print(double(4, 5))"
+    ) => "print(4 * 2)",
+    inline_call_used_as_prefix(
+        "local function getConfig() return CONFIG end print(getConfig().value)"
+    ) => "print(CONFIG.value)",
+    inline_call_statement(
+        "local function log(message) print('[log]', message) end log('hello')"
+    ) => "do local message = 'hello' print('[log]', message) end",
+    inline_call_statement_with_extra_arguments(
+        "local function log(message) print(message) end log('hello', compute())"
+    ) => "do local message = 'hello', compute() print(message) end",
+    inline_call_statement_with_returned_call(
+        "local function notify(value) return callback(value) end notify(1)"
+    ) => "do local value = 1 callback(value) end",
+    inline_pure_call_statement_keeps_arguments_side_effects(
+        "local function identity(value) return value end identity(compute())"
+    ) => "compute()",
+    inline_function_calling_inlined_function(
+        "local function double(n) return n * 2 end
+        local function quadruple(n) return double(n) * 2 end
+        print(quadruple(3))"
+    ) => "print(3 * 2 * 2)",
+    inline_only_calls_with_same_identifiers(
+        "local prefix = 'a'
+        local function getPrefix() return prefix end
+        print(getPrefix())
+        do
+            local prefix = 'b'
+            print(getPrefix())
+        end"
+    ) => "local prefix = 'a'
+        local function getPrefix() return prefix end
+        print(prefix)
+        do
+            local prefix = 'b'
+            print(getPrefix())
+        end",
+);
+
+test_rule_without_effects!(
+    InlineFunctions::default(),
+    function_used_as_value(
+        "local function double(n) return n * 2 end print(double(4)) return double"
+    ),
+    function_reassigned("local function double(n) return n * 2 end double = nil print(double(4))"),
+    expression_with_argument_side_effects(
+        "local function double(n) return n * 2 end print(double(compute()))"
+    ),
+    expression_with_reassigned_local_argument(
+        "local function double(n) return n * 2 end local x = 1 x = 2 print(double(x))"
+    ),
+    expression_with_global_argument("local function double(n) return n * 2 end print(double(x))"),
+    statements_in_expression(
+        "local function compute(n) local value = n * 2 return value end print(compute(1))"
+    ),
+    reassigned_parameter(
+        "local function log(message) message = message or '' print(message) end log('a')"
+    ),
+    recursive_function("local function count(n) if n > 0 then count(n - 1) end end count(3)"),
+    nested_return(
+        "local function check(value) if value then return end print(value) end check(true)"
+    ),
+    variadic_function("local function log(...) print(...) end log('a')"),
+    method_call("local function double(n) return n * 2 end print(double:call(2))"),
+    shadowed_global("local function log(message) print(message) end local print = warn log('a')"),
+    too_many_statements(
+        "local function log(message) print(1) print(2) print(3) print(message) end log('a')"
+    ),
+    function_returning_function(
+        "local function create(n) return function() return n end end print(create(1))"
+    ),
+);
+
+test_rule!(
+    inline_functions_with_max_statements,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'inline_functions',
+        max_statements: 4,
+    }"#,
+    )
+    .unwrap(),
+    inline_larger_function(
+        "local function log(message) print(1) print(2) print(3) print(message) end log('a')"
+    ) => "do local message = 'a' print(1) print(2) print(3) print(message) end",
+);
@@ -532,6 +532,7 @@ macro_rules! test_rule_snapshot {
     };
 }
 
+mod add_type_assertions;
 mod append_text_comment;
 mod compute_expression;
 mod convert_index_to_field;
@@ -542,7 +543,9 @@ mod filter_early_return;
 mod global_function_to_assign;
 mod group_local_assignment;
 mod inject_value;
+mod inline_functions;
 mod no_local_function;
+mod propagate_constants;
 mod remove_assertions;
 mod remove_call_parens;
 mod remove_comments;
@@ -561,3 +564,4 @@ mod remove_unused_if_branch;
 mod remove_unused_variable;
 mod remove_unused_while;
 mod rename_variables;
+mod tree_shake;
@@ -0,0 +1,83 @@
+use darklua_core::rules::{PropagateConstants, RemoveUnusedIfBranch, RemoveUnusedVariable, Rule};
+
+test_rule!(
+    propagate_constants,
+    PropagateConstants::default(),
+    propagate_false("local DEBUG = false if DEBUG then print('debug') end")
+        => "local DEBUG = false if false then print('debug') end",
+    propagate_true("local ENABLED = true return ENABLED") => "local ENABLED = true return true",
+    propagate_nil("local value = nil return value") => "local value = nil return nil",
+    propagate_number("local a = 1 return a") => "local a = 1 return 1",
+    propagate_string("local name = 'darklua' print(name)")
+        => "local name = 'darklua' print('darklua')",
+    propagate_computed_value("local a = 1 + 2 return a") => "local a = 1 + 2 return 3",
+    propagate_chained_constants("local a = 1 local b = a + 1 return b")
+        => "local a = 1 local b = 1 + 1 return 2",
+    propagate_into_nested_function("local a = true return function() return a end")
+        => "local a = true return function() return true end",
+    propagate_into_loop("local a = 2 for i = 1, 10 do print(a) end")
+        => "local a = 2 for i = 1, 10 do print(2) end",
+    propagate_string_used_twice("local name = 'lua' print(name, name)")
+        => "local name = 'lua' print('lua', 'lua')",
+    propagate_long_string_used_once("local name = 'a long string value' print(name)")
+        => "local name = 'a long string value' print('a long string value')",
+    propagate_number_used_twice("local a = 1 return a, a") => "local a = 1 return 1, 1",
+    propagate_string_used_with_method("local s = 'abc' return s:upper()")
+        => "local s = 'abc' return ('abc'):upper()",
+    propagate_outer_constant_after_shadowing("local a = 1 do local a = ... print(a) end return a")
+        => "local a = 1 do local a = ... print(a) end return 1",
+    inline_single_use_alias("local function f(value) local v = value return v end return f")
+        => "local function f(value) local v = value return value end return f",
+    inline_single_use_unary_expression("return function(a) local b = not a return b end")
+        => "return function(a) local b = not a return not a end",
+);
+
+test_rule_without_effects!(
+    PropagateConstants::default(),
+    reassigned_local("local a = 1 a = 2 return a"),
+    reassigned_local_in_function("local a = 1 local function f() a = 2 end f() return a"),
+    compound_assigned_local("local a = 1 a += 1 return a"),
+    local_redefined_with_function_statement("local a = 1 function a() end return a"),
+    table_local("local t = {} return t"),
+    function_call_local("local a = f() return a"),
+    single_use_with_global_dependency("local b = not a return b"),
+    long_string_used_twice("local name = 'a long string value' print(name, name)"),
+    value_used_twice("return function(a) local b = not a return b, b end"),
+    value_used_in_nested_function(
+        "return function(a) local b = not a return function() return b end end"
+    ),
+    value_used_in_loop("return function(a) local b = not a for i = 1, 2 do print(b) end end"),
+    value_used_in_while_condition("return function(a) local b = not a while b do end end"),
+    dependency_reassigned("return function(a) local b = not a a = 1 return b end"),
+    dependency_shadowed("return function(a) local b = not a local a = 1 return b end"),
+);
+
+test_rule!(
+    propagate_constants_without_single_use_inlining,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'propagate_constants',
+        inline_single_use: false,
+    }"#,
+    )
+    .unwrap(),
+    propagate_number("local a = 1 return a") => "local a = 1 return 1",
+    keep_single_use_unary_expression("return function(a) local b = not a return b end")
+        => "return function(a) local b = not a return b end",
+);
+
+test_rules!(
+    propagate_constants_and_remove_feature_flags,
+    [
+        Box::new(PropagateConstants::default()) as Box<dyn Rule>,
+        Box::new(RemoveUnusedIfBranch::default()),
+        Box::new(RemoveUnusedVariable::default()),
+    ],
+    remove_disabled_feature("local DEBUG = false if DEBUG then print('debug') end print('done')")
+        => "print('done')",
+    keep_enabled_feature("local DEBUG = true if DEBUG then print('debug') end")
+        => "do print('debug') end",
+    remove_disabled_feature_with_else(
+        "local USE_NEW_API = false if USE_NEW_API then new() else old() end"
+    ) => "do old() end",
+);
@@ -0,0 +1,316 @@
+use darklua_core::rules::{Rule, TreeShake};
+
+test_rule!(
+    tree_shake,
+    TreeShake::default(),
+    remove_unused_module(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl() print('load a') return 1 end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+            do
+                local function __modImpl() print('load b') return 2 end
+                function __DARKLUA_BUNDLE_MODULES.b() return __modImpl() end
+            end
+        end
+        print(__DARKLUA_BUNDLE_MODULES.a())"
+    ) => "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl() print('load a') return 1 end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        print(__DARKLUA_BUNDLE_MODULES.a())",
+    remove_all_unused_modules(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl() return 1 end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        print('hello')"
+    ) => "print('hello')",
+    remove_module_only_required_by_unused_module(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl() return 1 end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+            do
+                local function __modImpl() return __DARKLUA_BUNDLE_MODULES.a() end
+                function __DARKLUA_BUNDLE_MODULES.b() return __modImpl() end
+            end
+        end
+        print('hello')"
+    ) => "print('hello')",
+    remove_unused_function_member(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.used() return 1 end
+                    function M.unused() return 2 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M.used())"
+    ) => "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.used() return 1 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M.used())",
+    remove_unused_assigned_member(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = { version = '1.0.0', name = 'lib' }
+                    M.used = true
+                    M.unused = false
+                    M['also_unused'] = 0
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        print(__DARKLUA_BUNDLE_MODULES.a().used, __DARKLUA_BUNDLE_MODULES.a().name)"
+    ) => "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = { name = 'lib' }
+                    M.used = true
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        print(__DARKLUA_BUNDLE_MODULES.a().used, __DARKLUA_BUNDLE_MODULES.a().name)",
+    remove_unused_returned_table_entries_and_local_functions(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local function helper() return 0 end
+                    local function used() return 1 end
+                    local function unused() return helper() end
+                    return { used = used, unused = unused }
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local lib = __DARKLUA_BUNDLE_MODULES.a()
+        print(lib.used())"
+    ) => "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local function used() return 1 end
+                    return { used = used }
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local lib = __DARKLUA_BUNDLE_MODULES.a()
+        print(lib.used())",
+    keep_member_used_by_another_member(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.used() return M.helper() end
+                    function M.helper() return 1 end
+                    function M.unused() return M.other() end
+                    function M.other() return 2 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M.used())"
+    ) => "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.used() return M.helper() end
+                    function M.helper() return 1 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M.used())",
+    keep_member_accessed_through_self_in_method(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M:used() return self.helper() end
+                    function M.helper() return 1 end
+                    function M.unused() return 2 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M:used())"
+    ) => "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M:used() return self.helper() end
+                    function M.helper() return 1 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M:used())",
+    remove_unused_local_function("local function unused() end print('hello')") => "print('hello')",
+    remove_unused_local_function_assignment("local unused = function() end print('hello')")
+        => "print('hello')",
+    remove_unused_recursive_local_function("local function loop() loop() end print('hello')")
+        => "print('hello')",
+    remove_unused_local_function_in_nested_block(
+        "return function() local function unused() end return 1 end"
+    ) => "return function() return 1 end",
+    remove_unused_type_declaration("type Unused = string type Used = number local value: Used = 1 print(value)")
+        => "type Used = number local value: Used = 1 print(value)",
+    remove_unused_recursive_type_declaration("type List = { next: List? } print('hello')")
+        => "print('hello')",
+    remove_type_declaration_only_used_by_unused_type(
+        "type Item = string type Unused = { Item } print('hello')"
+    ) => "print('hello')",
+);
+
+test_rule_without_effects!(
+    TreeShake::default(),
+    keep_used_local_function("local function used() end used()"),
+    keep_local_function_used_in_returned_function(
+        "local function used() end return function() used() end"
+    ),
+    keep_exported_type("export type Value = string"),
+    keep_members_when_module_value_escapes(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.unused() return 2 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M)"
+    ),
+    keep_members_when_module_is_indexed_dynamically(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.unused() return 2 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M[key])"
+    ),
+    keep_members_when_table_escapes_inside_module(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    M.__index = M
+                    function M.unused() return 2 end
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M.__index)"
+    ),
+    keep_members_when_calling_method_not_defined_with_colon(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    function M.used(self) return self.unused end
+                    M.unused = 2
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M:used())"
+    ),
+    keep_member_with_side_effects(
+        "local __DARKLUA_BUNDLE_MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl()
+                    local M = {}
+                    M.value = compute()
+                    return M
+                end
+                function __DARKLUA_BUNDLE_MODULES.a() return __modImpl() end
+            end
+        end
+        local M = __DARKLUA_BUNDLE_MODULES.a()
+        print(M.other)"
+    ),
+);
+
+test_rule!(
+    tree_shake_with_custom_modules_identifier,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'tree_shake',
+        modules_identifier: '__MODULES',
+    }"#,
+    )
+    .unwrap(),
+    remove_unused_module(
+        "local __MODULES = { cache = {} }
+        do
+            do
+                local function __modImpl() return 1 end
+                function __MODULES.a() return __modImpl() end
+            end
+        end
+        print('hello')"
+    ) => "print('hello')",
+);
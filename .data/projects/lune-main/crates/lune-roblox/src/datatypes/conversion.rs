@@ -1,6 +1,6 @@
 use mlua::prelude::*;
 
-use rbx_dom_weak::types::{Variant as DomValue, VariantType as DomType};
+use rbx_dom_weak::types::{TerrainMaterials, Variant as DomValue, VariantType as DomType};
 
 use crate::{datatypes::extension::DomValueExt, instance::Instance};
 
@@ -54,6 +54,30 @@ impl DomValueToLua for LuaValue {
                 DomValue::ContentId(s) => Ok(LuaValue::String(
                     lua.create_string(AsRef::<str>::as_ref(s))?,
                 )),
+                DomValue::SharedString(s) => Ok(LuaValue::String(lua.create_string(s.data())?)),
+                DomValue::NetAssetRef(r) => Ok(LuaValue::String(lua.create_string(r.data())?)),
+
+                // NOTE: Enums are normally converted using property info, since the
+                // dom value does not know its enum type, so plain numbers are used here
+                DomValue::Enum(e) => Ok(LuaValue::Number(f64::from(e.to_u32()))),
+
+                DomValue::Tags(tags) => Ok(LuaValue::Table(lua.create_sequence_from(tags.iter())?)),
+                DomValue::Attributes(attributes) => {
+                    let tab = lua.create_table()?;
+                    for (key, value) in attributes {
+                        tab.set(key.as_str(), LuaValue::dom_value_to_lua(lua, value)?)?;
+                    }
+                    Ok(LuaValue::Table(tab))
+                }
+                DomValue::MaterialColors(colors) => {
+                    let tab = lua.create_table()?;
+                    for name in TERRAIN_MATERIAL_NAMES {
+                        let material = parse_terrain_material(name)?;
+                        let color = super::types::Color3::from(colors.get_color(material));
+                        tab.set(*name, color)?;
+                    }
+                    Ok(LuaValue::Table(tab))
+                }
 
                 // NOTE: Dom references may point to instances that
                 // no longer exist, so we handle that here instead of
@@ -107,6 +131,40 @@ impl LuaToDomValue for LuaValue {
                 (LuaValue::String(s), DomType::ContentId) => {
                     Ok(DomValue::ContentId(s.to_str()?.to_string().into()))
                 }
+                (LuaValue::String(s), DomType::SharedString) => Ok(DomValue::SharedString(
+                    dom::SharedString::new(s.as_bytes().to_vec()),
+                )),
+                (LuaValue::String(s), DomType::NetAssetRef) => Ok(DomValue::NetAssetRef(
+                    dom::NetAssetRef::new(s.as_bytes().to_vec()),
+                )),
+
+                (LuaValue::Integer(_) | LuaValue::Number(_), DomType::Enum) => {
+                    lua_number_to_enum(self).map(DomValue::Enum)
+                }
+
+                (LuaValue::Table(t), DomType::Tags) => {
+                    let tags = t
+                        .sequence_values::<String>()
+                        .collect::<LuaResult<Vec<_>>>()?;
+                    Ok(DomValue::Tags(tags.into()))
+                }
+                (LuaValue::Table(t), DomType::Attributes) => {
+                    let mut attributes = dom::Attributes::new();
+                    for pair in t.pairs::<String, LuaValue>() {
+                        let (key, value) = pair?;
+                        attributes.insert(key, value.lua_to_dom_value(lua, None)?);
+                    }
+                    Ok(DomValue::Attributes(attributes))
+                }
+                (LuaValue::Table(t), DomType::MaterialColors) => {
+                    let mut colors = dom::MaterialColors::default();
+                    for pair in t.pairs::<String, LuaAnyUserData>() {
+                        let (name, color) = pair?;
+                        let color = *color.borrow::<super::types::Color3>()?;
+                        colors.set_color(parse_terrain_material(&name)?, color.into());
+                    }
+                    Ok(DomValue::MaterialColors(colors))
+                }
 
                 // NOTE: Some values are either optional or default and we
                 // should handle that here before trying to convert as userdata
@@ -140,6 +198,63 @@ impl LuaToDomValue for LuaValue {
     }
 }
 
+/*
+    Names of all terrain materials that have a color in the
+    `MaterialColors` property, these are also `Enum.Material` names
+*/
+const TERRAIN_MATERIAL_NAMES: &[&str] = &[
+    "Grass",
+    "Slate",
+    "Concrete",
+    "Brick",
+    "Sand",
+    "WoodPlanks",
+    "Rock",
+    "Glacier",
+    "Snow",
+    "Sandstone",
+    "Mud",
+    "Basalt",
+    "Ground",
+    "CrackedLava",
+    "Asphalt",
+    "Cobblestone",
+    "Ice",
+    "LeafyGrass",
+    "Salt",
+    "Limestone",
+    "Pavement",
+];
+
+fn parse_terrain_material(name: &str) -> DomConversionResult<TerrainMaterials> {
+    name.parse::<TerrainMaterials>()
+        .map_err(|err| DomConversionError::External {
+            message: err.to_string(),
+        })
+}
+
+/**
+    Converts a Lua number into the value of an `Enum` property,
+    which must be a whole number that fits into a `u32`.
+*/
+fn lua_number_to_enum(value: &LuaValue) -> DomConversionResult<rbx_dom_weak::types::Enum> {
+    let converted = match value {
+        LuaValue::Integer(i) => u32::try_from(*i).ok(),
+        LuaValue::Number(n) if n.fract() == 0.0 => u32::try_from(*n as i64).ok(),
+        _ => None,
+    };
+    converted
+        .map(rbx_dom_weak::types::Enum::from_u32)
+        .ok_or_else(|| DomConversionError::ToDomValue {
+            to: "Enum",
+            from: value.type_name(),
+            detail: Some(format!(
+                "value must be a whole number between 0 and {}",
+                u32::MAX
+            )),
+        })
+}
+
 /*
 
     Trait implementations for converting between all of
@@ -211,6 +326,7 @@ impl DomValueToLua for LuaAnyUserData {
             DomValue::Rect(value)           => dom_to_userdata!(lua, value => Rect),
             DomValue::Region3(value)        => dom_to_userdata!(lua, value => Region3),
             DomValue::Region3int16(value)   => dom_to_userdata!(lua, value => Region3int16),
+            DomValue::SecurityCapabilities(value) => dom_to_userdata!(lua, value => SecurityCapabilities),
             DomValue::UDim(value)           => dom_to_userdata!(lua, value => UDim),
             DomValue::UDim2(value)          => dom_to_userdata!(lua, value => UDim2),
             DomValue::UniqueId(value)       => dom_to_userdata!(lua, value => UniqueId),
@@ -267,6 +383,7 @@ impl LuaToDomValue for LuaAnyUserData {
                 DomType::Ref            => userdata_to_dom!(self as Instance       => dom::Ref),
                 DomType::Region3        => userdata_to_dom!(self as Region3        => dom::Region3),
                 DomType::Region3int16   => userdata_to_dom!(self as Region3int16   => dom::Region3int16),
+                DomType::SecurityCapabilities => userdata_to_dom!(self as SecurityCapabilities => dom::SecurityCapabilities),
                 DomType::UDim           => userdata_to_dom!(self as UDim           => dom::UDim),
                 DomType::UDim2          => userdata_to_dom!(self as UDim2          => dom::UDim2),
                 DomType::UniqueId       => userdata_to_dom!(self as UniqueId       => dom::UniqueId),
@@ -326,6 +443,7 @@ impl LuaToDomValue for LuaAnyUserData {
                 value if value.is::<Rect>()           => userdata_to_dom!(value as Rect           => dom::Rect),
                 value if value.is::<Region3>()        => userdata_to_dom!(value as Region3        => dom::Region3),
                 value if value.is::<Region3int16>()   => userdata_to_dom!(value as Region3int16   => dom::Region3int16),
+                value if value.is::<SecurityCapabilities>() => userdata_to_dom!(value as SecurityCapabilities => dom::SecurityCapabilities),
                 value if value.is::<UDim>()           => userdata_to_dom!(value as UDim           => dom::UDim),
                 value if value.is::<UDim2>()          => userdata_to_dom!(value as UDim2          => dom::UDim2),
                 value if value.is::<UniqueId>()       => userdata_to_dom!(value as UniqueId       => dom::UniqueId),
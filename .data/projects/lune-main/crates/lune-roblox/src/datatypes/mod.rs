@@ -11,8 +11,7 @@ pub mod result;
 #[cfg(feature = "mlua")]
 pub mod types;
 
-#[cfg(feature = "mlua")]
-mod util;
+pub(crate) mod util;
 
 #[cfg(feature = "mlua")]
 use result::*;
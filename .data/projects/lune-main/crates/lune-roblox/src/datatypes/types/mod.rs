@@ -18,6 +18,7 @@ mod ray;
 mod rect;
 mod region3;
 mod region3int16;
+mod security_capabilities;
 mod udim;
 mod udim2;
 mod unique_id;
@@ -46,6 +47,7 @@ pub use ray::Ray;
 pub use rect::Rect;
 pub use region3::Region3;
 pub use region3int16::Region3int16;
+pub use security_capabilities::SecurityCapabilities;
 pub use udim::UDim;
 pub use udim2::UDim2;
 pub use unique_id::UniqueId;
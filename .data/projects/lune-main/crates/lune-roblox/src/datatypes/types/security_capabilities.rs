@@ -0,0 +1,109 @@
+use core::fmt;
+
+use mlua::prelude::*;
+use rbx_dom_weak::types::SecurityCapabilities as DomSecurityCapabilities;
+
+use lune_utils::TableBuilder;
+
+use crate::exports::LuaExportsTable;
+
+use super::{super::*, EnumItem};
+
+const ENUM_NAME: &str = "SecurityCapability";
+
+/**
+    An implementation of the [SecurityCapabilities](https://create.roblox.com/docs/reference/engine/datatypes/SecurityCapabilities) Roblox datatype.
+
+    This implements all documented properties, methods & constructors of the `SecurityCapabilities` class as of October 2025.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SecurityCapabilities {
+    pub(crate) bits: u64,
+}
+
+impl SecurityCapabilities {
+    fn bits_from_items(items: &[EnumItem]) -> LuaResult<u64> {
+        let mut bits = 0;
+        for (index, item) in items.iter().enumerate() {
+            if item.parent.desc.name != ENUM_NAME {
+                return Err(LuaError::RuntimeError(format!(
+                    "Expected argument #{} to be an Enum.{ENUM_NAME}, got Enum.{}",
+                    index + 1,
+                    item.parent.desc.name
+                )));
+            }
+            if item.value >= u64::BITS {
+                return Err(LuaError::RuntimeError(format!(
+                    "Enum.{ENUM_NAME}.{} is not a valid capability",
+                    item.name
+                )));
+            }
+            bits |= 1 << item.value;
+        }
+        Ok(bits)
+    }
+}
+
+impl LuaExportsTable for SecurityCapabilities {
+    const EXPORT_NAME: &'static str = "SecurityCapabilities";
+
+    fn create_exports_table(lua: Lua) -> LuaResult<LuaTable> {
+        let security_capabilities_new = |_: &Lua, items: LuaVariadic<EnumItem>| {
+            Ok(SecurityCapabilities {
+                bits: SecurityCapabilities::bits_from_items(&items)?,
+            })
+        };
+
+        TableBuilder::new(lua)?
+            .with_function("new", security_capabilities_new)?
+            .build_readonly()
+    }
+}
+
+impl LuaUserData for SecurityCapabilities {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("Contains", |_, this, items: LuaVariadic<EnumItem>| {
+            let bits = Self::bits_from_items(&items)?;
+            Ok(this.bits & bits == bits)
+        });
+        methods.add_method("Add", |_, this, items: LuaVariadic<EnumItem>| {
+            Ok(Self {
+                bits: this.bits | Self::bits_from_items(&items)?,
+            })
+        });
+        methods.add_method("Remove", |_, this, items: LuaVariadic<EnumItem>| {
+            Ok(Self {
+                bits: this.bits & !Self::bits_from_items(&items)?,
+            })
+        });
+        methods.add_meta_method(LuaMetaMethod::Eq, userdata_impl_eq);
+        methods.add_meta_method(LuaMetaMethod::ToString, userdata_impl_to_string);
+    }
+}
+
+impl fmt::Display for SecurityCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write = make_list_writer();
+        for value in 0..u64::BITS {
+            if self.bits & (1 << value) != 0 {
+                match EnumItem::from_enum_name_and_value(ENUM_NAME, value) {
+                    Some(item) => write(f, true, &item.name)?,
+                    None => write(f, true, &value.to_string())?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<DomSecurityCapabilities> for SecurityCapabilities {
+    fn from(v: DomSecurityCapabilities) -> Self {
+        Self { bits: v.bits() }
+    }
+}
+
+impl From<SecurityCapabilities> for DomSecurityCapabilities {
+    fn from(v: SecurityCapabilities) -> Self {
+        DomSecurityCapabilities::from_bits(v.bits)
+    }
+}
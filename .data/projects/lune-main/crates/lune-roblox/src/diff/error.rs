@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+#[cfg(feature = "mlua")]
+use mlua::prelude::*;
+
+#[derive(Debug, Clone, Error)]
+pub enum PatchError {
+    #[error("Failed to apply patch - no instance was found at path '{0}'")]
+    InstanceNotFound(String),
+    #[error("Failed to apply patch - the root instance can not be added or moved")]
+    RootNotMovable,
+    #[error("Failed to apply patch - the Name of the instance at path '{0}' must be a string")]
+    InvalidName(String),
+    #[error("Failed to apply patch - attribute '{0}' can not be a reference")]
+    InvalidAttribute(String),
+}
+
+#[cfg(feature = "mlua")]
+impl From<PatchError> for LuaError {
+    fn from(value: PatchError) -> Self {
+        Self::RuntimeError(value.to_string())
+    }
+}
@@ -0,0 +1,602 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use rbx_dom_weak::types::{Ref as DomRef, Variant as DomValue};
+use serde::{Deserialize, Serialize};
+
+use crate::instance::Instance;
+
+mod error;
+mod patch;
+
+pub use error::PatchError;
+pub use patch::apply_patch;
+
+pub type PatchResult<T> = Result<T, PatchError>;
+
+const PROPERTY_NAME_NAME: &str = "Name";
+const PROPERTY_NAME_ATTRIBUTES: &str = "Attributes";
+const PROPERTY_NAME_UNIQUE_ID: &str = "UniqueId";
+
+/**
+    A path to an instance, made of instance names, relative to the
+    root instance of a diff. The root instance itself has an empty path.
+*/
+pub type InstancePath = Vec<PathSegment>;
+
+/**
+    A single segment of an [`InstancePath`].
+
+    Siblings with the same name are told apart by their index, which is the
+    number of siblings with the same name that come before the instance.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathSegment {
+    pub name: String,
+    #[serde(default)]
+    pub index: usize,
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.index == 0 {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}[{}]", self.name, self.index)
+        }
+    }
+}
+
+fn display_path(path: &[PathSegment]) -> String {
+    path.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/**
+    A callback that returns the key used to match an instance
+    in a diff, or `None` if the instance should not be matched.
+*/
+pub type MatchKeyFn = Box<dyn Fn(&Instance) -> Option<String>>;
+
+/**
+    The strategy used to match instances of two trees in a diff.
+*/
+pub enum MatchBy {
+    /**
+        Matches instances that have the same path in both trees.
+
+        Siblings with the same name are matched in order.
+    */
+    Path,
+    /**
+        Matches instances that have the same `UniqueId` property.
+
+        Instances without a `UniqueId` are never matched.
+    */
+    UniqueId,
+    /**
+        Matches instances for which the callback returns the same key.
+
+        Instances for which the callback returns `None` are never matched.
+    */
+    Custom(MatchKeyFn),
+}
+
+/**
+    Options for computing a diff between two instance trees.
+*/
+pub struct DiffOptions {
+    match_by: MatchBy,
+    ignored_properties: Vec<String>,
+}
+
+impl DiffOptions {
+    /**
+        Creates new diff options, matching instances by path.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            match_by: MatchBy::Path,
+            ignored_properties: Vec::new(),
+        }
+    }
+
+    /**
+        Sets the strategy used to match instances.
+    */
+    #[must_use]
+    pub fn with_match_by(mut self, match_by: MatchBy) -> Self {
+        self.match_by = match_by;
+        self
+    }
+
+    /**
+        Ignores changes to the given property when comparing instances.
+    */
+    #[must_use]
+    pub fn with_ignored_property(mut self, name: impl Into<String>) -> Self {
+        self.ignored_properties.push(name.into());
+        self
+    }
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+    The value of a property in a diff.
+
+    References are stored as paths relative to the root of the diff,
+    so that they can be resolved when a diff is applied to another tree.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PropertyValue {
+    Value(DomValue),
+    Ref(Option<InstancePath>),
+}
+
+/**
+    A change of a single property or attribute.
+
+    A missing `old` value means that the property was added,
+    and a missing `new` value means that it was removed.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyChange {
+    #[serde(default)]
+    pub old: Option<PropertyValue>,
+    #[serde(default)]
+    pub new: Option<PropertyValue>,
+}
+
+/**
+    A snapshot of an instance and its descendants, used for added instances.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceSnapshot {
+    pub class_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyValue>,
+    #[serde(default)]
+    pub children: Vec<InstanceSnapshot>,
+}
+
+/**
+    An instance that was added, with its path in the new tree.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddedInstance {
+    pub path: InstancePath,
+    pub instance: InstanceSnapshot,
+}
+
+/**
+    An instance that was removed, with its path in the old tree.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemovedInstance {
+    pub path: InstancePath,
+}
+
+/**
+    An instance that was moved to a new parent, with
+    its path in the old tree and its path in the new tree.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MovedInstance {
+    pub from: InstancePath,
+    pub to: InstancePath,
+}
+
+/**
+    An instance that has changed properties or attributes, with its path in the old tree.
+
+    Changes to the name of an instance are stored as changes to the `Name` property.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangedInstance {
+    pub path: InstancePath,
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyChange>,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, PropertyChange>,
+}
+
+/**
+    A structural diff between two instance trees.
+
+    Only the topmost instance of an added or removed subtree is listed,
+    any descendants of an added instance are part of its snapshot.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceDiff {
+    #[serde(default)]
+    pub added: Vec<AddedInstance>,
+    #[serde(default)]
+    pub removed: Vec<RemovedInstance>,
+    #[serde(default)]
+    pub moved: Vec<MovedInstance>,
+    #[serde(default)]
+    pub changed: Vec<ChangedInstance>,
+}
+
+impl InstanceDiff {
+    /**
+        Checks if there are no differences between the two trees.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+struct DiffNode {
+    instance: Instance,
+    name: String,
+    path: InstancePath,
+    key: Option<String>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+struct DiffTree {
+    nodes: Vec<DiffNode>,
+    indices: HashMap<DomRef, usize>,
+}
+
+impl DiffTree {
+    fn new(root: Instance, match_by: &MatchBy) -> Self {
+        let mut nodes = vec![DiffNode {
+            instance: root,
+            name: root.get_name(),
+            path: Vec::new(),
+            key: None,
+            parent: None,
+            children: Vec::new(),
+        }];
+        let mut path_keys = vec![String::new()];
+
+        let mut index = 0;
+        while index < nodes.len() {
+            let mut occurrences = HashMap::<String, usize>::new();
+            for child in nodes[index].instance.get_children() {
+                let name = child.get_name();
+
+                let occurrence = occurrences.entry(name.clone()).or_default();
+                let path_key = format!("{}\0{name}\0{occurrence}", path_keys[index]);
+
+                let key = match match_by {
+                    MatchBy::Path => Some(path_key.clone()),
+                    MatchBy::UniqueId => match child.get_property(PROPERTY_NAME_UNIQUE_ID) {
+                        Some(DomValue::UniqueId(id)) => Some(id.to_string()),
+                        _ => None,
+                    },
+                    MatchBy::Custom(callback) => callback(&child),
+                };
+
+                let mut path = nodes[index].path.clone();
+                path.push(PathSegment {
+                    name: name.clone(),
+                    index: *occurrence,
+                });
+                *occurrence += 1;
+
+                let child_index = nodes.len();
+                nodes[index].children.push(child_index);
+                nodes.push(DiffNode {
+                    instance: child,
+                    name,
+                    path,
+                    key,
+                    parent: Some(index),
+                    children: Vec::new(),
+                });
+                path_keys.push(path_key);
+            }
+            index += 1;
+        }
+
+        let indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.instance.dom_ref, index))
+            .collect();
+
+        Self { nodes, indices }
+    }
+
+    fn property_value(&self, value: DomValue) -> PropertyValue {
+        match value {
+            DomValue::Ref(dom_ref) => PropertyValue::Ref(
+                self.indices
+                    .get(&dom_ref)
+                    .map(|index| self.nodes[*index].path.clone()),
+            ),
+            value => PropertyValue::Value(value),
+        }
+    }
+
+    fn properties(&self, index: usize, options: &DiffOptions) -> BTreeMap<String, DomValue> {
+        let instance = self.nodes[index].instance;
+        let mut properties = instance.get_properties();
+        properties.retain(|name, _| {
+            name != PROPERTY_NAME_ATTRIBUTES && !options.ignored_properties.contains(name)
+        });
+        if !options
+            .ignored_properties
+            .iter()
+            .any(|name| name == PROPERTY_NAME_NAME)
+        {
+            properties.insert(
+                PROPERTY_NAME_NAME.to_string(),
+                DomValue::String(instance.get_name()),
+            );
+        }
+        properties
+    }
+}
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    old: DiffTree,
+    new: DiffTree,
+    old_matches: Vec<Option<usize>>,
+    new_matches: Vec<Option<usize>>,
+}
+
+impl Differ<'_> {
+    fn match_nodes(&mut self) {
+        let mut new_by_key = HashMap::new();
+        for (index, node) in self.new.nodes.iter().enumerate().skip(1) {
+            if let Some(key) = &node.key {
+                new_by_key.entry(key.as_str()).or_insert(index);
+            }
+        }
+
+        // The roots are always matched, even if their keys differ
+        self.old_matches[0] = Some(0);
+        self.new_matches[0] = Some(0);
+
+        for (old_index, node) in self.old.nodes.iter().enumerate().skip(1) {
+            let Some(key) = &node.key else {
+                continue;
+            };
+            if let Some(&new_index) = new_by_key.get(key.as_str())
+                && self.new_matches[new_index].is_none()
+            {
+                self.old_matches[old_index] = Some(new_index);
+                self.new_matches[new_index] = Some(old_index);
+            }
+        }
+    }
+
+    /**
+        Updates the paths in the new tree to use the order that siblings will have once
+        the diff has been applied to the old tree, so that siblings with the same name
+        resolve to the correct instances while patching.
+
+        Patching keeps the order of instances that stay under the same parent, and then
+        appends instances in the order that they are inserted - moved instances first,
+        followed by added instances, and children of added instances come with them.
+    */
+    fn update_new_paths(&mut self) {
+        for parent in 0..self.new.nodes.len() {
+            let parent_matched = self.new_matches[parent].is_some();
+            let mut children = self.new.nodes[parent]
+                .children
+                .iter()
+                .map(|&child| {
+                    let order = match self.new_matches[child] {
+                        Some(old_child) if self.is_moved(old_child, child) => (1, old_child),
+                        Some(old_child) => (0, old_child),
+                        None if parent_matched => (2, child),
+                        None => (0, child),
+                    };
+                    (order, child)
+                })
+                .collect::<Vec<_>>();
+            children.sort_unstable();
+
+            let mut occurrences = HashMap::<&str, usize>::new();
+            let mut indices = Vec::with_capacity(children.len());
+            for (_, child) in children {
+                let occurrence = occurrences
+                    .entry(self.new.nodes[child].name.as_str())
+                    .or_default();
+                indices.push((child, *occurrence));
+                *occurrence += 1;
+            }
+
+            let parent_path = self.new.nodes[parent].path.clone();
+            for (child, index) in indices {
+                let node = &mut self.new.nodes[child];
+                node.path.clone_from(&parent_path);
+                node.path.push(PathSegment {
+                    name: node.name.clone(),
+                    index,
+                });
+            }
+        }
+    }
+
+    fn is_moved(&self, old_index: usize, new_index: usize) -> bool {
+        match (
+            self.old.nodes[old_index].parent,
+            self.new.nodes[new_index].parent,
+        ) {
+            (Some(old_parent), Some(new_parent)) => {
+                self.old_matches[old_parent] != Some(new_parent)
+            }
+            _ => false,
+        }
+    }
+
+    fn values_equal(&self, old: &DomValue, new: &DomValue) -> bool {
+        match (old, new) {
+            (DomValue::Ref(old_ref), DomValue::Ref(new_ref)) => {
+                match (self.old.indices.get(old_ref), self.new.indices.get(new_ref)) {
+                    (Some(old_index), Some(new_index)) => {
+                        self.old_matches[*old_index] == Some(*new_index)
+                    }
+                    (None, None) => old_ref == new_ref,
+                    _ => false,
+                }
+            }
+            (old, new) => old == new,
+        }
+    }
+
+    fn diff_values(
+        &self,
+        old: BTreeMap<String, DomValue>,
+        mut new: BTreeMap<String, DomValue>,
+    ) -> BTreeMap<String, PropertyChange> {
+        let mut changes = BTreeMap::new();
+
+        for (name, old_value) in old {
+            let new_value = new.remove(&name);
+            if new_value
+                .as_ref()
+                .is_some_and(|new_value| self.values_equal(&old_value, new_value))
+            {
+                continue;
+            }
+            changes.insert(
+                name,
+                PropertyChange {
+                    old: Some(self.old.property_value(old_value)),
+                    new: new_value.map(|value| self.new.property_value(value)),
+                },
+            );
+        }
+
+        for (name, new_value) in new {
+            changes.insert(
+                name,
+                PropertyChange {
+                    old: None,
+                    new: Some(self.new.property_value(new_value)),
+                },
+            );
+        }
+
+        changes
+    }
+
+    fn snapshot(&self, index: usize) -> InstanceSnapshot {
+        let node = &self.new.nodes[index];
+        let properties = node
+            .instance
+            .get_properties()
+            .into_iter()
+            .map(|(name, value)| (name, self.new.property_value(value)))
+            .collect();
+        let children = node
+            .children
+            .iter()
+            .filter(|child| self.new_matches[**child].is_none())
+            .map(|child| self.snapshot(*child))
+            .collect();
+        InstanceSnapshot {
+            class_name: node.instance.get_class_name().to_string(),
+            name: node.instance.get_name(),
+            properties,
+            children,
+        }
+    }
+
+    fn diff(mut self) -> InstanceDiff {
+        self.match_nodes();
+        self.update_new_paths();
+
+        let mut diff = InstanceDiff::default();
+
+        for (old_index, node) in self.old.nodes.iter().enumerate() {
+            let Some(new_index) = self.old_matches[old_index] else {
+                let parent = node.parent.expect("root is always matched");
+                if self.old_matches[parent].is_some() {
+                    diff.removed.push(RemovedInstance {
+                        path: node.path.clone(),
+                    });
+                }
+                continue;
+            };
+
+            let new_node = &self.new.nodes[new_index];
+            if self.is_moved(old_index, new_index) {
+                diff.moved.push(MovedInstance {
+                    from: node.path.clone(),
+                    to: new_node.path.clone(),
+                });
+            }
+
+            let properties = self.diff_values(
+                self.old.properties(old_index, self.options),
+                self.new.properties(new_index, self.options),
+            );
+            let attributes = self.diff_values(
+                node.instance.get_attributes().into_iter().collect(),
+                new_node.instance.get_attributes().into_iter().collect(),
+            );
+            if !properties.is_empty() || !attributes.is_empty() {
+                diff.changed.push(ChangedInstance {
+                    path: node.path.clone(),
+                    properties,
+                    attributes,
+                });
+            }
+        }
+
+        for (new_index, node) in self.new.nodes.iter().enumerate() {
+            if self.new_matches[new_index].is_some() {
+                continue;
+            }
+            let parent = node.parent.expect("root is always matched");
+            if self.new_matches[parent].is_some() {
+                diff.added.push(AddedInstance {
+                    path: node.path.clone(),
+                    instance: self.snapshot(new_index),
+                });
+            }
+        }
+
+        diff
+    }
+}
+
+/**
+    Computes a structural diff between two instance trees.
+
+    The given root instances are always matched to each other, and all paths
+    in the diff are relative to them. Instances in the trees are matched
+    using the strategy in the given options, any instance without a match
+    is considered added or removed, and any matched instance with a parent
+    that does not match the parent in the other tree is considered moved.
+*/
+#[must_use]
+pub fn diff_instances(old: Instance, new: Instance, options: &DiffOptions) -> InstanceDiff {
+    let old = DiffTree::new(old, &options.match_by);
+    let new = DiffTree::new(new, &options.match_by);
+    Differ {
+        options,
+        old_matches: vec![None; old.nodes.len()],
+        new_matches: vec![None; new.nodes.len()],
+        old,
+        new,
+    }
+    .diff()
+}
@@ -0,0 +1,164 @@
+use rbx_dom_weak::types::{Ref as DomRef, Variant as DomValue};
+
+use crate::instance::Instance;
+
+use super::{
+    InstanceDiff, InstancePath, InstanceSnapshot, PROPERTY_NAME_NAME, PatchError, PatchResult,
+    PathSegment, PropertyValue, display_path,
+};
+
+enum Insertion<'a> {
+    Added(&'a InstanceSnapshot),
+    Moved(Instance),
+}
+
+struct PendingRef {
+    instance: Instance,
+    name: String,
+    path: Option<InstancePath>,
+}
+
+fn resolve(root: Instance, path: &[PathSegment]) -> PatchResult<Instance> {
+    let mut current = root;
+    for segment in path {
+        current = current
+            .get_children()
+            .into_iter()
+            .filter(|child| child.get_name() == segment.name)
+            .nth(segment.index)
+            .ok_or_else(|| PatchError::InstanceNotFound(display_path(path)))?;
+    }
+    Ok(current)
+}
+
+fn create_instance(snapshot: &InstanceSnapshot, pending_refs: &mut Vec<PendingRef>) -> Instance {
+    let instance = Instance::new_orphaned(&snapshot.class_name);
+    instance.set_name(snapshot.name.clone());
+
+    for (name, value) in &snapshot.properties {
+        match value {
+            PropertyValue::Value(value) => instance.set_property(name, value.clone()),
+            PropertyValue::Ref(path) => pending_refs.push(PendingRef {
+                instance,
+                name: name.clone(),
+                path: path.clone(),
+            }),
+        }
+    }
+
+    for child in &snapshot.children {
+        create_instance(child, pending_refs).set_parent(Some(instance));
+    }
+
+    instance
+}
+
+/**
+    Applies a diff, created using [`diff_instances`](super::diff_instances), to an instance tree.
+
+    The given root instance should match the old tree of the diff. Paths of removed,
+    moved and changed instances are resolved before anything is modified, and
+    references are resolved once all instances have been added and moved.
+
+    # Errors
+
+    Errors if an instance referred to by the diff does not exist in the tree,
+    or if the diff contains invalid values for names or attributes.
+*/
+pub fn apply_patch(root: Instance, diff: &InstanceDiff) -> PatchResult<()> {
+    // Resolve everything using paths in the old tree first,
+    // since paths will change as soon as we start patching
+    let removed = diff
+        .removed
+        .iter()
+        .map(|removed| resolve(root, &removed.path))
+        .collect::<PatchResult<Vec<_>>>()?;
+    let moved = diff
+        .moved
+        .iter()
+        .map(|moved| Ok((resolve(root, &moved.from)?, &moved.to)))
+        .collect::<PatchResult<Vec<_>>>()?;
+    let changed = diff
+        .changed
+        .iter()
+        .map(|changed| Ok((resolve(root, &changed.path)?, changed)))
+        .collect::<PatchResult<Vec<_>>>()?;
+
+    // Moved instances may be descendants of removed ones,
+    // so they need to be detached before removing anything
+    for (instance, _) in &moved {
+        if *instance == root {
+            return Err(PatchError::RootNotMovable);
+        }
+        instance.set_parent(None);
+    }
+    for mut instance in removed {
+        instance.destroy();
+    }
+
+    let mut pending_refs = Vec::new();
+    for (instance, changed) in changed {
+        for (name, change) in &changed.properties {
+            if name == PROPERTY_NAME_NAME {
+                let Some(PropertyValue::Value(DomValue::String(new_name))) = &change.new else {
+                    return Err(PatchError::InvalidName(display_path(&changed.path)));
+                };
+                instance.set_name(new_name.clone());
+                continue;
+            }
+            match &change.new {
+                Some(PropertyValue::Value(value)) => instance.set_property(name, value.clone()),
+                Some(PropertyValue::Ref(path)) => pending_refs.push(PendingRef {
+                    instance,
+                    name: name.clone(),
+                    path: path.clone(),
+                }),
+                None => instance.remove_property(name),
+            }
+        }
+        for (name, change) in &changed.attributes {
+            match &change.new {
+                Some(PropertyValue::Value(value)) => instance.set_attribute(name, value.clone()),
+                Some(PropertyValue::Ref(_)) => {
+                    return Err(PatchError::InvalidAttribute(name.clone()));
+                }
+                None => instance.remove_attribute(name),
+            }
+        }
+    }
+
+    // Insert instances from the top down, so that the parent
+    // of an instance always exists by the time it is inserted
+    let mut insertions = moved
+        .into_iter()
+        .map(|(instance, path)| (path, Insertion::Moved(instance)))
+        .chain(
+            diff.added
+                .iter()
+                .map(|added| (&added.path, Insertion::Added(&added.instance))),
+        )
+        .collect::<Vec<_>>();
+    insertions.sort_by_key(|(path, _)| path.len());
+
+    for (path, insertion) in insertions {
+        let (_, parent_path) = path.split_last().ok_or(PatchError::RootNotMovable)?;
+        let parent = resolve(root, parent_path)?;
+        let instance = match insertion {
+            Insertion::Added(snapshot) => create_instance(snapshot, &mut pending_refs),
+            Insertion::Moved(instance) => instance,
+        };
+        instance.set_parent(Some(parent));
+    }
+
+    for pending in pending_refs {
+        let target = match &pending.path {
+            Some(path) => resolve(root, path)?.dom_ref,
+            None => DomRef::none(),
+        };
+        pending
+            .instance
+            .set_property(pending.name, DomValue::Ref(target));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,984 @@
+// Reference for the binary format:
+// https://dom.rojo.space/binary
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use super::{DocumentError, DocumentResult, filter::DocumentFilter, filter::FilterNode};
+
+pub(super) const FILE_MAGIC: &[u8] = b"<roblox!\x89\xff\r\n\x1a\n";
+pub(super) const FILE_HEADER_LEN: usize = FILE_MAGIC.len() + 2 + 4 + 4 + 8;
+pub(super) const CHUNK_HEADER_LEN: usize = 16;
+pub(super) const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+pub(super) const TYPE_STRING: u8 = 0x01;
+const TYPE_CFRAME: u8 = 0x10;
+const TYPE_SHARED_STRING: u8 = 0x1c;
+
+/**
+    Filters the instances of a binary document, without decoding any properties.
+
+    Chunks of classes that have no included instances are skipped entirely,
+    and the other chunks are rewritten to only contain the included instances.
+    The returned bytes are a valid, uncompressed, binary document.
+*/
+pub(crate) fn filter_binary_document(
+    bytes: &[u8],
+    filter: &DocumentFilter,
+) -> DocumentResult<Vec<u8>> {
+    filter_chunks(bytes, filter).map_err(DocumentError::ReadError)
+}
+
+fn filter_chunks(bytes: &[u8], filter: &DocumentFilter) -> Result<Vec<u8>, String> {
+    if bytes.len() < FILE_HEADER_LEN || !bytes.starts_with(FILE_MAGIC) {
+        return Err("invalid binary file header".to_string());
+    }
+
+    let chunks = read_chunks(&bytes[FILE_HEADER_LEN..])?;
+
+    // 1. Read the structure of the document: classes, parents, and names if needed
+    let mut classes = Vec::new();
+    let mut parents = Vec::new();
+    let mut names = HashMap::new();
+    let mut properties = HashMap::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        match &chunk.name {
+            b"INST" => classes.push(ClassChunk::read(&chunk.decompress()?)?),
+            b"PRNT" => parents.extend(read_parents(&chunk.decompress()?)?),
+            // NOTE: The property name is only known after decompressing a chunk,
+            // so any chunk decompressed here is kept around to be written below
+            b"PROP" if filter.uses_names() => {
+                let data = chunk.decompress()?;
+                let mut reader = ChunkReader::new(&data);
+                let class_id = reader.read_u32()?;
+                let property_name = reader.read_string()?;
+                if property_name == b"Name" && reader.read_u8()? == TYPE_STRING {
+                    names.insert(class_id, read_strings(&mut reader)?);
+                }
+                properties.insert(index, data);
+            }
+            _ => {}
+        }
+    }
+
+    // 2. Select the instances to keep
+    let kept = select_referents(&classes, &parents, &names, filter);
+
+    // 3. Write a new document with only the kept instances
+    let mut kept_classes = HashMap::new();
+    for class in &classes {
+        if class
+            .referents
+            .iter()
+            .any(|referent| kept.contains(referent))
+        {
+            kept_classes.insert(class.id, kept_classes.len() as u32);
+        }
+    }
+
+    let class_referents: HashMap<u32, &[i32]> = classes
+        .iter()
+        .map(|class| (class.id, class.referents.as_slice()))
+        .collect();
+
+    let mut class_chunks = classes.iter();
+    let mut output_chunks = Vec::new();
+    let mut used_shared_strings = HashSet::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let output = match &chunk.name {
+            b"INST" => {
+                let class = class_chunks
+                    .next()
+                    .expect("INST chunks are read in the same order");
+                match kept_classes.get(&class.id) {
+                    Some(new_id) => OutputChunk::Data(*b"INST", class.write(*new_id, &kept)),
+                    None => continue,
+                }
+            }
+            b"PROP" => {
+                let data = match properties.remove(&index) {
+                    Some(data) => data,
+                    None => chunk.decompress()?,
+                };
+                let mut reader = ChunkReader::new(&data);
+                let class_id = reader.read_u32()?;
+                let (Some(new_id), Some(referents)) =
+                    (kept_classes.get(&class_id), class_referents.get(&class_id))
+                else {
+                    continue;
+                };
+                let mask: Vec<bool> = referents.iter().map(|r| kept.contains(r)).collect();
+                let property = filter_property(&mut reader, *new_id, &mask, &kept)?;
+                used_shared_strings.extend(property.shared_strings);
+                OutputChunk::Data(*b"PROP", property.data)
+            }
+            b"PRNT" => {
+                let pairs = read_parents(&chunk.decompress()?)?
+                    .into_iter()
+                    .filter(|(child, _)| kept.contains(child))
+                    .collect::<Vec<_>>();
+                OutputChunk::Data(*b"PRNT", write_parents(&pairs))
+            }
+            b"SSTR" => OutputChunk::SharedStrings(chunk),
+            _ => OutputChunk::Raw(chunk),
+        };
+        output_chunks.push(output);
+    }
+
+    let mut output = Vec::with_capacity(bytes.len().min(1024 * 1024));
+    output.extend_from_slice(FILE_MAGIC);
+    output.extend_from_slice(&0u16.to_le_bytes());
+    output.extend_from_slice(&(kept_classes.len() as u32).to_le_bytes());
+    output.extend_from_slice(&(kept.len() as u32).to_le_bytes());
+    output.extend_from_slice(&[0; 8]);
+
+    for chunk in output_chunks {
+        match chunk {
+            OutputChunk::Raw(chunk) => output.extend_from_slice(chunk.raw),
+            OutputChunk::Data(name, data) => write_chunk(&mut output, name, &data),
+            OutputChunk::SharedStrings(chunk) => {
+                let data = filter_shared_strings(&chunk.decompress()?, &used_shared_strings)?;
+                write_chunk(&mut output, *b"SSTR", &data);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn select_referents(
+    classes: &[ClassChunk],
+    parents: &[(i32, i32)],
+    names: &HashMap<u32, Vec<Vec<u8>>>,
+    filter: &DocumentFilter,
+) -> HashSet<i32> {
+    let mut referents = Vec::new();
+    let mut nodes = Vec::new();
+    let mut indices = HashMap::new();
+
+    for class in classes {
+        let class_names = names.get(&class.id);
+        for (index, referent) in class.referents.iter().enumerate() {
+            let name = class_names.and_then(|names| names.get(index)).map_or_else(
+                || class.name.clone(),
+                |name| String::from_utf8_lossy(name).into_owned(),
+            );
+            indices.insert(*referent, nodes.len());
+            referents.push(*referent);
+            nodes.push(FilterNode {
+                class_name: class.name.clone(),
+                name,
+                children: Vec::new(),
+            });
+        }
+    }
+
+    let mut roots = Vec::new();
+    for (child, parent) in parents {
+        let Some(child_index) = indices.get(child).copied() else {
+            continue;
+        };
+        match indices.get(parent) {
+            Some(parent_index) => nodes[*parent_index].children.push(child_index),
+            None => roots.push(child_index),
+        }
+    }
+
+    filter
+        .select(&nodes, &roots)
+        .into_iter()
+        .zip(referents)
+        .filter_map(|(selected, referent)| selected.then_some(referent))
+        .collect()
+}
+
+/*
+    Chunks
+*/
+
+pub(super) struct Chunk<'a> {
+    pub name: [u8; 4],
+    pub compressed_len: usize,
+    pub len: usize,
+    pub payload: &'a [u8],
+    pub raw: &'a [u8],
+}
+
+impl Chunk<'_> {
+    pub fn decompress(&self) -> Result<Cow<'_, [u8]>, String> {
+        let name = String::from_utf8_lossy(&self.name);
+        if self.compressed_len == 0 {
+            return Ok(Cow::Borrowed(self.payload));
+        }
+
+        let data = if self.payload.starts_with(ZSTD_MAGIC) {
+            zstd::bulk::decompress(self.payload, self.len)
+                .map_err(|err| format!("failed to decompress {name} chunk - {err}"))?
+        } else {
+            lz4_flex::block::decompress(self.payload, self.len)
+                .map_err(|err| format!("failed to decompress {name} chunk - {err}"))?
+        };
+
+        // NOTE: Decompression only treats the length in the chunk header as
+        // an upper bound, but other readers expect it to match exactly
+        if data.len() != self.len {
+            return Err(format!(
+                "decompressed {name} chunk is {} bytes, but its header lists {} bytes",
+                data.len(),
+                self.len
+            ));
+        }
+        Ok(Cow::Owned(data))
+    }
+}
+
+enum OutputChunk<'a, 'b> {
+    Raw(&'b Chunk<'a>),
+    Data([u8; 4], Vec<u8>),
+    SharedStrings(&'b Chunk<'a>),
+}
+
+fn read_chunks(mut bytes: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+    let mut chunks = Vec::new();
+
+    while !bytes.is_empty() {
+        if bytes.len() < CHUNK_HEADER_LEN {
+            return Err("unexpected end of file in chunk header".to_string());
+        }
+
+        let mut name = [0; 4];
+        name.copy_from_slice(&bytes[0..4]);
+        let compressed_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let len = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+
+        let payload_len = if compressed_len == 0 {
+            len
+        } else {
+            compressed_len
+        };
+        let total_len = CHUNK_HEADER_LEN + payload_len;
+        if bytes.len() < total_len {
+            return Err(format!(
+                "unexpected end of file in {} chunk",
+                String::from_utf8_lossy(&name)
+            ));
+        }
+
+        chunks.push(Chunk {
+            name,
+            compressed_len,
+            len,
+            payload: &bytes[CHUNK_HEADER_LEN..total_len],
+            raw: &bytes[..total_len],
+        });
+
+        let is_end = &name == b"END\0";
+        bytes = &bytes[total_len..];
+        if is_end {
+            break;
+        }
+    }
+
+    Ok(chunks)
+}
+
+fn write_chunk(output: &mut Vec<u8>, name: [u8; 4], data: &[u8]) {
+    output.extend_from_slice(&name);
+    output.extend_from_slice(&0u32.to_le_bytes());
+    output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    output.extend_from_slice(&0u32.to_le_bytes());
+    output.extend_from_slice(data);
+}
+
+pub(super) struct ClassChunk {
+    pub id: u32,
+    pub name: String,
+    pub is_service: bool,
+    pub referents: Vec<i32>,
+}
+
+impl ClassChunk {
+    pub fn read(data: &[u8]) -> Result<Self, String> {
+        let mut reader = ChunkReader::new(data);
+        let id = reader.read_u32()?;
+        let name = String::from_utf8_lossy(reader.read_string()?).into_owned();
+        let is_service = reader.read_u8()? != 0;
+        let count = reader.read_u32()? as usize;
+        let referents = reader.read_referents(count)?;
+        Ok(Self {
+            id,
+            name,
+            is_service,
+            referents,
+        })
+    }
+
+    fn write(&self, new_id: u32, kept: &HashSet<i32>) -> Vec<u8> {
+        let referents: Vec<i32> = self
+            .referents
+            .iter()
+            .copied()
+            .filter(|referent| kept.contains(referent))
+            .collect();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&new_id.to_le_bytes());
+        write_string(&mut data, self.name.as_bytes());
+        data.push(u8::from(self.is_service));
+        data.extend_from_slice(&(referents.len() as u32).to_le_bytes());
+        write_referents(&mut data, &referents);
+        if self.is_service {
+            data.extend(std::iter::repeat_n(1, referents.len()));
+        }
+        data
+    }
+}
+
+pub(super) fn read_parents(data: &[u8]) -> Result<Vec<(i32, i32)>, String> {
+    let mut reader = ChunkReader::new(data);
+    let _version = reader.read_u8()?;
+    let count = reader.read_u32()? as usize;
+    let children = reader.read_referents(count)?;
+    let parents = reader.read_referents(count)?;
+    Ok(children.into_iter().zip(parents).collect())
+}
+
+fn write_parents(pairs: &[(i32, i32)]) -> Vec<u8> {
+    let children: Vec<i32> = pairs.iter().map(|(child, _)| *child).collect();
+    let parents: Vec<i32> = pairs.iter().map(|(_, parent)| *parent).collect();
+
+    let mut data = vec![0];
+    data.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+    write_referents(&mut data, &children);
+    write_referents(&mut data, &parents);
+    data
+}
+
+/**
+    Replaces the contents of shared strings that are not used by
+    any of the kept properties with empty strings, so that the
+    indices of the shared strings that are used do not change.
+*/
+fn filter_shared_strings(data: &[u8], used: &HashSet<u32>) -> Result<Vec<u8>, String> {
+    let mut reader = ChunkReader::new(data);
+    let version = reader.read_u32()?;
+    let count = reader.read_u32()?;
+
+    let mut output = Vec::new();
+    output.extend_from_slice(&version.to_le_bytes());
+    output.extend_from_slice(&count.to_le_bytes());
+    for index in 0..count {
+        let hash = reader.read_bytes(16)?;
+        let contents = reader.read_string()?;
+        output.extend_from_slice(hash);
+        if used.contains(&index) {
+            write_string(&mut output, contents);
+        } else {
+            write_string(&mut output, &[]);
+        }
+    }
+
+    Ok(output)
+}
+
+/*
+    Properties
+*/
+
+struct FilteredProperty {
+    data: Vec<u8>,
+    shared_strings: Vec<u32>,
+}
+
+/**
+    A list of values, or a part of each value, stored in a property chunk.
+*/
+pub(super) enum Column {
+    /// Values of a fixed width, stored in an interleaved array.
+    /// The values are stored one after the other once read.
+    Interleaved { width: usize, values: Vec<u8> },
+    /// Values stored one after the other, possibly with different widths.
+    Sequential(Vec<Vec<u8>>),
+    /// Referents to other instances, stored in a referent array.
+    Referents(Vec<i32>),
+    /// A single byte that does not belong to any value.
+    Marker(u8),
+    /// `Content` values, which reference strings and instances in separate arrays.
+    Content {
+        source_types: Vec<i32>,
+        uris: Vec<Vec<u8>>,
+        objects: Vec<i32>,
+    },
+    /// Values of a type that is not known, which can not be split into
+    /// separate values and are kept as is.
+    Unknown(Vec<u8>),
+}
+
+fn filter_property(
+    reader: &mut ChunkReader,
+    new_class_id: u32,
+    mask: &[bool],
+    kept: &HashSet<i32>,
+) -> Result<FilteredProperty, String> {
+    let name = reader.read_string()?.to_vec();
+    let ty = reader.read_u8()?;
+    let columns = read_columns(reader, ty, mask.len())?;
+
+    let mut shared_strings = Vec::new();
+    let mut data = Vec::new();
+    data.extend_from_slice(&new_class_id.to_le_bytes());
+    write_string(&mut data, &name);
+    data.push(ty);
+
+    for column in columns {
+        match column {
+            Column::Interleaved { width, values } => {
+                let values: Vec<u8> = values
+                    .chunks_exact(width)
+                    .zip(mask)
+                    .filter(|(_, keep)| **keep)
+                    .flat_map(|(value, _)| value.iter().copied())
+                    .collect();
+                if ty == TYPE_SHARED_STRING {
+                    shared_strings.extend(
+                        values
+                            .chunks_exact(4)
+                            .map(|value| u32::from_be_bytes(value.try_into().unwrap())),
+                    );
+                }
+                write_interleaved(&mut data, &values, width);
+            }
+            Column::Sequential(values) => {
+                for (value, _) in values.iter().zip(mask).filter(|(_, keep)| **keep) {
+                    data.extend_from_slice(value);
+                }
+            }
+            Column::Referents(referents) => {
+                let referents: Vec<i32> = referents
+                    .into_iter()
+                    .zip(mask)
+                    .filter(|(_, keep)| **keep)
+                    .map(|(referent, _)| remap_referent(referent, kept))
+                    .collect();
+                write_referents(&mut data, &referents);
+            }
+            Column::Marker(marker) => data.push(marker),
+            Column::Content {
+                source_types,
+                uris,
+                objects,
+            } => write_content(&mut data, &source_types, &uris, &objects, mask, kept),
+            // Deserializing skips unknown property types, so
+            // the values do not need to match the kept instances
+            Column::Unknown(values) => data.extend_from_slice(&values),
+        }
+    }
+
+    Ok(FilteredProperty {
+        data,
+        shared_strings,
+    })
+}
+
+fn remap_referent(referent: i32, kept: &HashSet<i32>) -> i32 {
+    if kept.contains(&referent) {
+        referent
+    } else {
+        -1
+    }
+}
+
+pub(super) fn read_columns(
+    reader: &mut ChunkReader,
+    ty: u8,
+    count: usize,
+) -> Result<Vec<Column>, String> {
+    let columns = match ty {
+        // String, ProtectedString
+        0x01 | 0x1d => {
+            let mut values = Vec::with_capacity(count);
+            for _ in 0..count {
+                let mut value = Vec::new();
+                write_string(&mut value, reader.read_string()?);
+                values.push(value);
+            }
+            vec![Column::Sequential(values)]
+        }
+        // Bool, Faces, Axes
+        0x02 | 0x09 | 0x0a => vec![reader.read_fixed(count, 1)?],
+        // Int32, Float32, BrickColor, Enum, SharedString
+        0x03 | 0x04 | 0x0b | 0x12 | 0x1c => vec![reader.read_interleaved(count, 4)?],
+        // Float64, NumberRange
+        0x05 | 0x17 => vec![reader.read_fixed(count, 8)?],
+        // UDim, Vector2
+        0x06 | 0x0d => reader.read_interleaved_columns(count, 4, 2)?,
+        // UDim2, Rect
+        0x07 | 0x18 => reader.read_interleaved_columns(count, 4, 4)?,
+        // Ray
+        0x08 => vec![reader.read_fixed(count, 24)?],
+        // Color3, Vector3
+        0x0c | 0x0e => reader.read_interleaved_columns(count, 4, 3)?,
+        // Vector2int16
+        0x0f => vec![reader.read_fixed(count, 4)?],
+        // CFrame
+        TYPE_CFRAME => reader.read_cframes(count)?,
+        // Ref
+        0x13 => vec![Column::Referents(reader.read_referents(count)?)],
+        // Vector3int16
+        0x14 => vec![reader.read_fixed(count, 6)?],
+        // NumberSequence, ColorSequence
+        0x15 | 0x16 => {
+            let keypoint_width = if ty == 0x15 { 12 } else { 20 };
+            let mut values = Vec::with_capacity(count);
+            for _ in 0..count {
+                let keypoints = reader.read_u32()? as usize;
+                let mut value = (keypoints as u32).to_le_bytes().to_vec();
+                value.extend_from_slice(reader.read_bytes(keypoints * keypoint_width)?);
+                values.push(value);
+            }
+            vec![Column::Sequential(values)]
+        }
+        // PhysicalProperties
+        0x19 => {
+            let mut values = Vec::with_capacity(count);
+            for _ in 0..count {
+                let flags = reader.read_u8()?;
+                let mut width = 0;
+                if flags & 0b01 != 0 {
+                    width += 20;
+                    if flags & 0b10 != 0 {
+                        width += 4;
+                    }
+                }
+                let mut value = vec![flags];
+                value.extend_from_slice(reader.read_bytes(width)?);
+                values.push(value);
+            }
+            vec![Column::Sequential(values)]
+        }
+        // Color3uint8
+        0x1a => reader.read_interleaved_columns(count, 1, 3)?,
+        // Int64, SecurityCapabilities
+        0x1b | 0x21 => vec![reader.read_interleaved(count, 8)?],
+        // OptionalCFrame
+        0x1e => {
+            let mut columns = vec![Column::Marker(reader.read_u8()?)];
+            columns.extend(reader.read_cframes(count)?);
+            columns.push(Column::Marker(reader.read_u8()?));
+            columns.push(reader.read_fixed(count, 1)?);
+            columns
+        }
+        // UniqueId
+        0x1f => vec![reader.read_interleaved(count, 16)?],
+        // Font
+        0x20 => {
+            let mut values = Vec::with_capacity(count);
+            for _ in 0..count {
+                let mut value = Vec::new();
+                write_string(&mut value, reader.read_string()?);
+                value.extend_from_slice(reader.read_bytes(3)?);
+                write_string(&mut value, reader.read_string()?);
+                values.push(value);
+            }
+            vec![Column::Sequential(values)]
+        }
+        // Content
+        0x22 => {
+            let source_types = reader.read_i32s(count)?;
+            let uris = read_strings_counted(reader)?;
+            let object_count = reader.read_u32()? as usize;
+            let objects = reader.read_referents(object_count)?;
+            let external_count = reader.read_u32()?;
+            if external_count != 0 {
+                return Err("external objects in Content properties are not supported".into());
+            }
+            vec![Column::Content {
+                source_types,
+                uris,
+                objects,
+            }]
+        }
+        _ => vec![Column::Unknown(
+            reader.read_bytes(reader.remaining())?.to_vec(),
+        )],
+    };
+
+    Ok(columns)
+}
+
+fn write_content(
+    data: &mut Vec<u8>,
+    source_types: &[i32],
+    uris: &[Vec<u8>],
+    objects: &[i32],
+    mask: &[bool],
+    kept: &HashSet<i32>,
+) {
+    const SOURCE_TYPE_URI: i32 = 1;
+    const SOURCE_TYPE_OBJECT: i32 = 2;
+
+    let mut kept_types = Vec::new();
+    let mut kept_uris = Vec::new();
+    let mut kept_objects = Vec::new();
+
+    let mut uris = uris.iter();
+    let mut objects = objects.iter();
+    for (source_type, keep) in source_types.iter().zip(mask) {
+        let uri = (*source_type == SOURCE_TYPE_URI)
+            .then(|| uris.next())
+            .flatten();
+        let object = (*source_type == SOURCE_TYPE_OBJECT)
+            .then(|| objects.next())
+            .flatten();
+        if *keep {
+            kept_types.push(*source_type);
+            kept_uris.extend(uri);
+            kept_objects.extend(object.map(|object| remap_referent(*object, kept)));
+        }
+    }
+
+    let transformed: Vec<u8> = kept_types
+        .iter()
+        .flat_map(|value| transform_i32(*value).to_be_bytes())
+        .collect();
+    write_interleaved(data, &transformed, 4);
+    data.extend_from_slice(&(kept_uris.len() as u32).to_le_bytes());
+    for uri in kept_uris {
+        write_string(data, uri);
+    }
+    data.extend_from_slice(&(kept_objects.len() as u32).to_le_bytes());
+    write_referents(data, &kept_objects);
+    data.extend_from_slice(&0u32.to_le_bytes());
+}
+
+pub(super) fn read_strings(reader: &mut ChunkReader) -> Result<Vec<Vec<u8>>, String> {
+    let mut values = Vec::new();
+    while !reader.is_empty() {
+        values.push(reader.read_string()?.to_vec());
+    }
+    Ok(values)
+}
+
+fn read_strings_counted(reader: &mut ChunkReader) -> Result<Vec<Vec<u8>>, String> {
+    let count = reader.read_u32()? as usize;
+    let mut values = Vec::with_capacity(count.min(reader.remaining()));
+    for _ in 0..count {
+        values.push(reader.read_string()?.to_vec());
+    }
+    Ok(values)
+}
+
+/*
+    Encoding
+*/
+
+pub(super) struct ChunkReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ChunkReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.remaining() {
+            return Err("unexpected end of chunk".to_string());
+        }
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_string(&mut self) -> Result<&'a [u8], String> {
+        let len = self.read_u32()? as usize;
+        self.read_bytes(len)
+    }
+
+    fn read_fixed(&mut self, count: usize, width: usize) -> Result<Column, String> {
+        let bytes = self.read_bytes(count * width)?;
+        Ok(Column::Sequential(
+            bytes.chunks_exact(width).map(<[u8]>::to_vec).collect(),
+        ))
+    }
+
+    fn read_interleaved(&mut self, count: usize, width: usize) -> Result<Column, String> {
+        let values = self.read_interleaved_bytes(count, width)?;
+        Ok(Column::Interleaved { width, values })
+    }
+
+    fn read_interleaved_bytes(&mut self, count: usize, width: usize) -> Result<Vec<u8>, String> {
+        let bytes = self.read_bytes(count * width)?;
+        let mut values = vec![0; count * width];
+        for (byte_index, bytes) in bytes.chunks_exact(count.max(1)).enumerate() {
+            for (value_index, byte) in bytes.iter().enumerate() {
+                values[value_index * width + byte_index] = *byte;
+            }
+        }
+        Ok(values)
+    }
+
+    fn read_interleaved_columns(
+        &mut self,
+        count: usize,
+        width: usize,
+        columns: usize,
+    ) -> Result<Vec<Column>, String> {
+        (0..columns)
+            .map(|_| self.read_interleaved(count, width))
+            .collect()
+    }
+
+    fn read_i32s(&mut self, count: usize) -> Result<Vec<i32>, String> {
+        let values = self.read_interleaved_bytes(count, 4)?;
+        Ok(values
+            .chunks_exact(4)
+            .map(|value| untransform_i32(u32::from_be_bytes(value.try_into().unwrap())))
+            .collect())
+    }
+
+    pub fn read_referents(&mut self, count: usize) -> Result<Vec<i32>, String> {
+        let mut referents = self.read_i32s(count)?;
+        let mut previous = 0i32;
+        for referent in &mut referents {
+            *referent = referent.wrapping_add(previous);
+            previous = *referent;
+        }
+        Ok(referents)
+    }
+
+    fn read_cframes(&mut self, count: usize) -> Result<Vec<Column>, String> {
+        let mut rotations = Vec::with_capacity(count);
+        for _ in 0..count {
+            let id = self.read_u8()?;
+            let mut rotation = vec![id];
+            if id == 0 {
+                rotation.extend_from_slice(self.read_bytes(36)?);
+            }
+            rotations.push(rotation);
+        }
+        let mut columns = vec![Column::Sequential(rotations)];
+        columns.extend(self.read_interleaved_columns(count, 4, 3)?);
+        Ok(columns)
+    }
+}
+
+fn write_string(data: &mut Vec<u8>, value: &[u8]) {
+    data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    data.extend_from_slice(value);
+}
+
+fn write_interleaved(data: &mut Vec<u8>, values: &[u8], width: usize) {
+    let count = values.len() / width;
+    for byte_index in 0..width {
+        for value_index in 0..count {
+            data.push(values[value_index * width + byte_index]);
+        }
+    }
+}
+
+fn write_referents(data: &mut Vec<u8>, referents: &[i32]) {
+    let mut previous = 0i32;
+    let transformed: Vec<u8> = referents
+        .iter()
+        .flat_map(|referent| {
+            let delta = referent.wrapping_sub(previous);
+            previous = *referent;
+            transform_i32(delta).to_be_bytes()
+        })
+        .collect();
+    write_interleaved(data, &transformed, 4);
+}
+
+fn transform_i32(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+fn untransform_i32(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use rbx_dom_weak::{
+        InstanceBuilder, WeakDom,
+        types::{CFrame, Matrix3, Ref, Variant, Vector3},
+        ustr,
+    };
+
+    use super::*;
+
+    fn create_place() -> Vec<u8> {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(
+                    InstanceBuilder::new("Workspace")
+                        .with_name("Workspace")
+                        .with_child(
+                            InstanceBuilder::new("Model").with_name("Map").with_child(
+                                InstanceBuilder::new("Part")
+                                    .with_name("Floor")
+                                    .with_property(
+                                        "CFrame",
+                                        CFrame::new(
+                                            Vector3::new(1.0, 2.0, 3.0),
+                                            Matrix3::identity(),
+                                        ),
+                                    ),
+                            ),
+                        )
+                        .with_child(InstanceBuilder::new("Part").with_name("Baseplate")),
+                )
+                .with_child(
+                    InstanceBuilder::new("ReplicatedStorage")
+                        .with_name("ReplicatedStorage")
+                        .with_child(
+                            InstanceBuilder::new("ObjectValue")
+                                .with_name("Pointer")
+                                .with_property("Value", Ref::none()),
+                        ),
+                ),
+        );
+
+        let mut bytes = Vec::new();
+        rbx_binary::to_writer(&mut bytes, &dom, dom.root().children()).unwrap();
+        bytes
+    }
+
+    fn read_names(bytes: &[u8]) -> Vec<String> {
+        let dom = rbx_binary::from_reader(bytes).unwrap();
+        let mut names = Vec::new();
+        let mut queue = dom.root().children().to_vec();
+        while let Some(dom_ref) = queue.pop() {
+            let inst = dom.get_by_ref(dom_ref).unwrap();
+            names.push(inst.name.clone());
+            queue.extend_from_slice(inst.children());
+        }
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn transform_roundtrip() {
+        for value in [0, 1, -1, 42, -42, i32::MAX, i32::MIN] {
+            assert_eq!(untransform_i32(transform_i32(value)), value);
+        }
+    }
+
+    #[test]
+    fn interleaved_roundtrip() {
+        let values = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let mut data = Vec::new();
+        write_interleaved(&mut data, &values, 4);
+        assert_eq!(data, vec![1, 5, 2, 6, 3, 7, 4, 8]);
+
+        assert_eq!(
+            ChunkReader::new(&data)
+                .read_interleaved_bytes(2, 4)
+                .unwrap(),
+            values
+        );
+    }
+
+    #[test]
+    fn referents_roundtrip() {
+        let referents = vec![0, 5, 3, -1, 100];
+        let mut data = Vec::new();
+        write_referents(&mut data, &referents);
+        assert_eq!(
+            ChunkReader::new(&data).read_referents(5).unwrap(),
+            referents
+        );
+    }
+
+    #[test]
+    fn unknown_property_types_are_kept() {
+        let mut data = Vec::new();
+        write_string(&mut data, b"Future");
+        data.push(0xff);
+        data.extend_from_slice(&[1, 2, 3]);
+
+        let property = filter_property(
+            &mut ChunkReader::new(&data),
+            7,
+            &[true, false],
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        let mut expected = 7u32.to_le_bytes().to_vec();
+        expected.extend_from_slice(&data);
+        assert_eq!(property.data, expected);
+    }
+
+    #[test]
+    fn empty_filter_keeps_services() {
+        let bytes = create_place();
+        let filtered = filter_binary_document(&bytes, &DocumentFilter::new()).unwrap();
+        assert_eq!(
+            read_names(&filtered),
+            vec!["ReplicatedStorage", "Workspace"]
+        );
+    }
+
+    #[test]
+    fn path_filter_keeps_subtree() {
+        let bytes = create_place();
+        let filter = DocumentFilter::new().with_path("Workspace.Map");
+        let filtered = filter_binary_document(&bytes, &filter).unwrap();
+        assert_eq!(
+            read_names(&filtered),
+            vec!["Floor", "Map", "ReplicatedStorage", "Workspace"]
+        );
+    }
+
+    #[test]
+    fn filtered_properties_are_preserved() {
+        let bytes = create_place();
+        let filter = DocumentFilter::new().with_path("Workspace.Map.Floor");
+        let filtered = filter_binary_document(&bytes, &filter).unwrap();
+
+        let dom = rbx_binary::from_reader(filtered.as_slice()).unwrap();
+        let workspace = dom.get_by_ref(dom.root().children()[0]).unwrap();
+        let map = dom.get_by_ref(workspace.children()[0]).unwrap();
+        let floor = dom.get_by_ref(map.children()[0]).unwrap();
+        assert_eq!(floor.name, "Floor");
+        assert_eq!(
+            floor.properties.get(&ustr("CFrame")),
+            Some(&Variant::CFrame(CFrame::new(
+                Vector3::new(1.0, 2.0, 3.0),
+                Matrix3::identity(),
+            )))
+        );
+    }
+
+    #[test]
+    fn class_name_filter_keeps_matching_instances() {
+        let bytes = create_place();
+        let filter = DocumentFilter::new().with_class_name("ObjectValue");
+        let filtered = filter_binary_document(&bytes, &filter).unwrap();
+        assert_eq!(
+            read_names(&filtered),
+            vec!["Pointer", "ReplicatedStorage", "Workspace"]
+        );
+    }
+}
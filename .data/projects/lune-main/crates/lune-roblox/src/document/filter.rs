@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use rbx_dom_weak::{WeakDom, types::Ref as DomRef};
+
+use crate::shared::instance::class_is_a;
+
+/**
+    A filter used to only load some instances of a document.
+
+    Top-level instances (services, for a place) are always loaded, so
+    that the document keeps its structure. Any other instance is only
+    loaded if it is included by the filter, or if it is an ancestor of
+    an included instance. Including an instance also includes all of
+    its descendants.
+
+    An empty filter only loads the top-level instances, which is
+    useful to get the list of services in a place file.
+
+    ---
+
+    ### Code Sample
+
+    ```rust ignore
+    let filter = DocumentFilter::new()
+        .with_path("ReplicatedStorage.Packages")
+        .with_class_name("ModuleScript");
+
+    let document = Document::from_bytes_filtered(bytes, DocumentKind::Place, &filter)?;
+    ```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentFilter {
+    paths: Vec<Vec<String>>,
+    class_names: Vec<String>,
+}
+
+impl DocumentFilter {
+    /**
+        Creates a new, empty filter.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Includes the instance at the given path, and all of its descendants.
+
+        The path is made of instance names separated by dots, starting
+        with the name of a top-level instance, such as `Workspace.Map`.
+    */
+    #[must_use]
+    pub fn with_path(mut self, path: impl AsRef<str>) -> Self {
+        self.paths
+            .push(path.as_ref().split('.').map(ToString::to_string).collect());
+        self
+    }
+
+    /**
+        Includes all instances that are of the given class, or
+        inherit from it, and all of their descendants.
+    */
+    #[must_use]
+    pub fn with_class_name(mut self, class_name: impl Into<String>) -> Self {
+        self.class_names.push(class_name.into());
+        self
+    }
+
+    /**
+        Checks if this filter only includes top-level instances.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.class_names.is_empty()
+    }
+
+    /**
+        Checks if this filter needs the names of instances to select them.
+    */
+    pub(crate) fn uses_names(&self) -> bool {
+        !self.paths.is_empty()
+    }
+
+    /**
+        Selects the nodes to load from a tree, returning
+        a flag for each node that is `true` if it is loaded.
+    */
+    pub(crate) fn select(&self, nodes: &[FilterNode], roots: &[usize]) -> Vec<bool> {
+        let mut selected = vec![false; nodes.len()];
+        let mut path = Vec::new();
+
+        for &root in roots {
+            selected[root] = true;
+            path.push(nodes[root].name.as_str());
+            self.visit(nodes, root, &mut path, &mut selected);
+            path.pop();
+        }
+
+        selected
+    }
+
+    fn visit<'a>(
+        &self,
+        nodes: &'a [FilterNode],
+        index: usize,
+        path: &mut Vec<&'a str>,
+        selected: &mut [bool],
+    ) -> bool {
+        let node = &nodes[index];
+
+        if self.matches(node, path) {
+            select_subtree(nodes, index, selected);
+            return true;
+        }
+
+        if !self.can_match_descendants(path) {
+            return false;
+        }
+
+        let mut has_selected_descendant = false;
+        for &child in &node.children {
+            path.push(nodes[child].name.as_str());
+            if self.visit(nodes, child, path, selected) {
+                has_selected_descendant = true;
+            }
+            path.pop();
+        }
+
+        if has_selected_descendant {
+            selected[index] = true;
+        }
+
+        has_selected_descendant
+    }
+
+    fn matches(&self, node: &FilterNode, path: &[&str]) -> bool {
+        self.paths.iter().any(|included| {
+            included.len() == path.len() && included.iter().zip(path).all(|(a, b)| a == b)
+        }) || self
+            .class_names
+            .iter()
+            .any(|class_name| class_is_a(&node.class_name, class_name).unwrap_or(false))
+    }
+
+    fn can_match_descendants(&self, path: &[&str]) -> bool {
+        !self.class_names.is_empty()
+            || self.paths.iter().any(|included| {
+                included.len() > path.len() && included.iter().zip(path).all(|(a, b)| a == b)
+            })
+    }
+
+    /**
+        Removes all instances that are not included by this filter from a dom.
+    */
+    pub(crate) fn apply_to_dom(&self, dom: &mut WeakDom) {
+        let mut refs = Vec::new();
+        let mut nodes = Vec::new();
+        let mut indices = HashMap::new();
+
+        let mut queue = dom.root().children().to_vec();
+        while let Some(dom_ref) = queue.pop() {
+            let Some(inst) = dom.get_by_ref(dom_ref) else {
+                continue;
+            };
+            indices.insert(dom_ref, refs.len());
+            refs.push(dom_ref);
+            nodes.push(FilterNode {
+                class_name: inst.class.to_string(),
+                name: inst.name.clone(),
+                children: Vec::new(),
+            });
+            queue.extend_from_slice(inst.children());
+        }
+
+        for (index, dom_ref) in refs.iter().enumerate() {
+            let children = dom
+                .get_by_ref(*dom_ref)
+                .map(|inst| {
+                    inst.children()
+                        .iter()
+                        .filter_map(|child| indices.get(child).copied())
+                        .collect()
+                })
+                .unwrap_or_default();
+            nodes[index].children = children;
+        }
+
+        let roots = dom
+            .root()
+            .children()
+            .iter()
+            .filter_map(|child| indices.get(child).copied())
+            .collect::<Vec<_>>();
+
+        let selected = self.select(&nodes, &roots);
+
+        let removed: Vec<DomRef> = nodes
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| selected[*index])
+            .flat_map(|(_, node)| node.children.iter())
+            .filter(|child| !selected[**child])
+            .map(|child| refs[*child])
+            .collect();
+
+        for dom_ref in removed {
+            dom.destroy(dom_ref);
+        }
+    }
+}
+
+/**
+    A node of an instance tree, used to select instances using a [`DocumentFilter`].
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct FilterNode {
+    pub class_name: String,
+    pub name: String,
+    pub children: Vec<usize>,
+}
+
+fn select_subtree(nodes: &[FilterNode], index: usize, selected: &mut [bool]) {
+    let mut stack = vec![index];
+    while let Some(index) = stack.pop() {
+        selected[index] = true;
+        stack.extend_from_slice(&nodes[index].children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rbx_dom_weak::InstanceBuilder;
+
+    use super::*;
+
+    fn create_dom() -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(
+                    InstanceBuilder::new("Workspace")
+                        .with_name("Workspace")
+                        .with_child(
+                            InstanceBuilder::new("Model").with_name("Map").with_child(
+                                InstanceBuilder::new("Part")
+                                    .with_name("Floor")
+                                    .with_child(InstanceBuilder::new("Decal").with_name("Logo")),
+                            ),
+                        )
+                        .with_child(InstanceBuilder::new("Part").with_name("Baseplate")),
+                )
+                .with_child(
+                    InstanceBuilder::new("ReplicatedStorage")
+                        .with_name("ReplicatedStorage")
+                        .with_child(
+                            InstanceBuilder::new("Folder")
+                                .with_name("Shared")
+                                .with_child(InstanceBuilder::new("ModuleScript").with_name("Util")),
+                        ),
+                ),
+        )
+    }
+
+    fn collect_names(dom: &WeakDom) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut queue = dom.root().children().to_vec();
+        while let Some(dom_ref) = queue.pop() {
+            let inst = dom.get_by_ref(dom_ref).unwrap();
+            names.push(inst.name.clone());
+            queue.extend_from_slice(inst.children());
+        }
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn empty_filter_keeps_top_level_instances() {
+        let mut dom = create_dom();
+        DocumentFilter::new().apply_to_dom(&mut dom);
+        assert_eq!(collect_names(&dom), vec!["ReplicatedStorage", "Workspace"]);
+    }
+
+    #[test]
+    fn path_keeps_subtree_and_ancestors() {
+        let mut dom = create_dom();
+        DocumentFilter::new()
+            .with_path("Workspace.Map")
+            .apply_to_dom(&mut dom);
+        assert_eq!(
+            collect_names(&dom),
+            vec!["Floor", "Logo", "Map", "ReplicatedStorage", "Workspace"]
+        );
+    }
+
+    #[test]
+    fn class_name_keeps_matching_instances() {
+        let mut dom = create_dom();
+        DocumentFilter::new()
+            .with_class_name("LuaSourceContainer")
+            .apply_to_dom(&mut dom);
+        assert_eq!(
+            collect_names(&dom),
+            vec!["ReplicatedStorage", "Shared", "Util", "Workspace"]
+        );
+    }
+
+    #[test]
+    fn class_name_includes_descendants() {
+        let mut dom = create_dom();
+        DocumentFilter::new()
+            .with_class_name("BasePart")
+            .apply_to_dom(&mut dom);
+        assert_eq!(
+            collect_names(&dom),
+            vec![
+                "Baseplate",
+                "Floor",
+                "Logo",
+                "Map",
+                "ReplicatedStorage",
+                "Workspace"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_path_keeps_top_level_instances() {
+        let mut dom = create_dom();
+        DocumentFilter::new()
+            .with_path("Workspace.Missing")
+            .apply_to_dom(&mut dom);
+        assert_eq!(collect_names(&dom), vec!["ReplicatedStorage", "Workspace"]);
+    }
+}
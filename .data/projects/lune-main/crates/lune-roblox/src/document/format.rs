@@ -3,6 +3,8 @@
 
 use std::path::Path;
 
+use serde::Serialize;
+
 /**
     A document format specifier.
 
@@ -13,7 +15,8 @@ use std::path::Path;
 
     Other variants are only to be used for logic internal to this crate.
 */
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DocumentFormat {
     Binary,
     Xml,
@@ -0,0 +1,578 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    panic,
+};
+
+use crate::{
+    document::{
+        DocumentKind,
+        binary_chunks::{
+            CHUNK_HEADER_LEN, Chunk, ChunkReader, ClassChunk, Column, FILE_HEADER_LEN, FILE_MAGIC,
+            TYPE_STRING, ZSTD_MAGIC, read_columns, read_parents, read_strings,
+        },
+    },
+    shared::instance::{class_exists, class_is_a_service},
+};
+
+use super::{ChunkCompression, ChunkReport, ClassReport, DocumentReport, PropertyReport};
+
+const END_CHUNK_CONTENTS: &[u8] = b"</roblox>";
+const NULL_REFERENT: i32 = -1;
+
+/**
+    Everything read from the chunks of a binary document that
+    is needed to check it for consistency once all chunks are read.
+*/
+#[derive(Default)]
+struct BinaryContents {
+    classes: Vec<ClassChunk>,
+    class_indices: HashMap<u32, usize>,
+    properties: HashMap<u32, Vec<PropertyReport>>,
+    referents: HashMap<i32, usize>,
+    names: HashMap<i32, String>,
+    parents: Vec<(i32, i32)>,
+    references: Vec<(String, Vec<i32>)>,
+}
+
+impl BinaryContents {
+    fn class_name(&self, class_id: u32) -> Option<&str> {
+        let index = self.class_indices.get(&class_id)?;
+        Some(self.classes[*index].name.as_str())
+    }
+
+    /**
+        Describes an instance using its name and class,
+        if known, to make issues easier to track down.
+    */
+    fn describe(&self, referent: i32) -> String {
+        let class_name = self
+            .referents
+            .get(&referent)
+            .map(|index| self.classes[*index].name.as_str());
+        match (self.names.get(&referent), class_name) {
+            (Some(name), Some(class_name)) => {
+                format!("'{name}' ({class_name}, referent {referent})")
+            }
+            (None, Some(class_name)) => format!("{class_name} (referent {referent})"),
+            _ => format!("referent {referent}"),
+        }
+    }
+}
+
+pub(super) fn inspect(bytes: &[u8], report: &mut DocumentReport) {
+    let Some((class_count, instance_count)) = inspect_header(bytes, report) else {
+        return;
+    };
+
+    let chunks = read_chunk_layout(bytes, report);
+    let mut contents = read_contents(&chunks, class_count, report);
+
+    if contents.classes.len() != class_count {
+        report.error(format!(
+            "File header lists {class_count} classes, but {} were found",
+            contents.classes.len()
+        ));
+    }
+    if contents.referents.len() != instance_count {
+        report.error(format!(
+            "File header lists {instance_count} instances, but {} were found",
+            contents.referents.len()
+        ));
+    }
+
+    check_parents(&contents, report);
+    check_references(&contents, report);
+
+    report.instance_count = contents.referents.len();
+    report.kind = detect_kind(&contents);
+    for class in &contents.classes {
+        let mut properties = contents.properties.remove(&class.id).unwrap_or_default();
+        properties.sort_by(|a, b| a.name.cmp(&b.name));
+        report.classes.push(ClassReport {
+            class_name: class.name.clone(),
+            instance_count: class.referents.len(),
+            is_service: class.is_service,
+            properties,
+        });
+    }
+
+    // The checks above only cover the structure of the document, so if it
+    // looks fine we also make sure that all of the values can be decoded,
+    // which may panic for some malformed documents instead of erroring
+    if report.valid {
+        match panic::catch_unwind(|| rbx_binary::from_reader(bytes)) {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => report.error(format!("Failed to decode document - {err}")),
+            Err(payload) => report.error(format!(
+                "Failed to decode document - {}",
+                panic_message(payload.as_ref())
+            )),
+        }
+    }
+}
+
+/**
+    Checks the file header, returning the number of classes and instances it lists.
+*/
+fn inspect_header(bytes: &[u8], report: &mut DocumentReport) -> Option<(usize, usize)> {
+    if bytes.len() < FILE_HEADER_LEN || !bytes.starts_with(FILE_MAGIC) {
+        report.error("Invalid file header");
+        return None;
+    }
+
+    let header = &bytes[FILE_MAGIC.len()..FILE_HEADER_LEN];
+    let version = u16::from_le_bytes([header[0], header[1]]);
+    let class_count = u32::from_le_bytes(header[2..6].try_into().unwrap()) as usize;
+    let instance_count = u32::from_le_bytes(header[6..10].try_into().unwrap()) as usize;
+    if version != 0 {
+        report.error(format!("Unsupported format version {version}"));
+    }
+    if header[10..].iter().any(|byte| *byte != 0) {
+        report.warning("Reserved bytes in the file header are not zero");
+    }
+
+    Some((class_count, instance_count))
+}
+
+/**
+    Reads the contents of all chunks, adding any issue found in a single chunk to the report.
+*/
+fn read_contents(
+    chunks: &[Chunk],
+    class_count: usize,
+    report: &mut DocumentReport,
+) -> BinaryContents {
+    let offsets = report
+        .chunks
+        .iter()
+        .map(|chunk| chunk.offset)
+        .collect::<Vec<_>>();
+
+    let mut contents = BinaryContents::default();
+    let mut seen_parents = false;
+    for (chunk, offset) in chunks.iter().zip(offsets) {
+        let name = chunk_name(chunk.name);
+        let data = match chunk.decompress() {
+            Ok(data) => data,
+            Err(err) => {
+                report.error(format!("Invalid {name} chunk at offset {offset} - {err}"));
+                continue;
+            }
+        };
+        let result = match &chunk.name {
+            b"META" => read_metadata(&data, report),
+            b"INST" => {
+                if seen_parents {
+                    report.warning(format!(
+                        "INST chunk at offset {offset} is after a PRNT chunk"
+                    ));
+                }
+                inspect_class(&data, &mut contents, class_count, report)
+            }
+            b"PROP" => inspect_property(&data, &mut contents, report),
+            b"PRNT" => {
+                if contents.classes.is_empty() {
+                    report.error(format!(
+                        "PRNT chunk at offset {offset} is before any INST chunk"
+                    ));
+                }
+                seen_parents = true;
+                read_parents(&data).map(|parents| contents.parents.extend(parents))
+            }
+            b"END\0" => {
+                if *data != *END_CHUNK_CONTENTS {
+                    report.warning(format!(
+                        "END chunk at offset {offset} has unexpected contents"
+                    ));
+                }
+                Ok(())
+            }
+            b"SSTR" | b"SIGN" => Ok(()),
+            _ => {
+                report.warning(format!("Unknown chunk '{name}' at offset {offset}"));
+                Ok(())
+            }
+        };
+        if let Err(err) = result {
+            report.error(format!("Invalid {name} chunk at offset {offset} - {err}"));
+        }
+    }
+
+    contents
+}
+
+/**
+    Reads the layout of all chunks in the document, adding them to the report.
+
+    Reading stops at the `END` chunk, or at the first chunk that is truncated.
+*/
+fn read_chunk_layout<'a>(bytes: &'a [u8], report: &mut DocumentReport) -> Vec<Chunk<'a>> {
+    let mut chunks = Vec::new();
+    let mut offset = FILE_HEADER_LEN;
+    let mut found_end = false;
+
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        if rest.len() < CHUNK_HEADER_LEN {
+            report.error(format!(
+                "Unexpected end of file in chunk header at offset {offset}"
+            ));
+            break;
+        }
+
+        let mut name = [0; 4];
+        name.copy_from_slice(&rest[0..4]);
+        let compressed_len = u32::from_le_bytes(rest[4..8].try_into().unwrap()) as usize;
+        let len = u32::from_le_bytes(rest[8..12].try_into().unwrap()) as usize;
+
+        let payload_len = if compressed_len == 0 {
+            len
+        } else {
+            compressed_len
+        };
+        let total_len = CHUNK_HEADER_LEN + payload_len;
+        if rest.len() < total_len {
+            report.error(format!(
+                "Unexpected end of file in {} chunk at offset {offset}",
+                chunk_name(name)
+            ));
+            break;
+        }
+
+        if rest[12..16].iter().any(|byte| *byte != 0) {
+            report.error(format!(
+                "Reserved bytes in the header of the {} chunk at offset {offset} are not zero",
+                chunk_name(name)
+            ));
+        }
+
+        let payload = &rest[CHUNK_HEADER_LEN..total_len];
+        report.chunks.push(ChunkReport {
+            name: chunk_name(name),
+            offset,
+            compression: if compressed_len == 0 {
+                ChunkCompression::None
+            } else if payload.starts_with(ZSTD_MAGIC) {
+                ChunkCompression::Zstd
+            } else {
+                ChunkCompression::Lz4
+            },
+            compressed_size: compressed_len,
+            size: len,
+        });
+        chunks.push(Chunk {
+            name,
+            compressed_len,
+            len,
+            payload,
+            raw: &rest[..total_len],
+        });
+
+        offset += total_len;
+        if &name == b"END\0" {
+            found_end = true;
+            break;
+        }
+    }
+
+    if !found_end {
+        report.error("Missing END chunk");
+    } else if offset < bytes.len() {
+        report.warning(format!(
+            "Found {} bytes of unexpected data after the END chunk",
+            bytes.len() - offset
+        ));
+    }
+
+    chunks
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "decoder panicked"
+    }
+}
+
+fn chunk_name(name: [u8; 4]) -> String {
+    String::from_utf8_lossy(&name)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+fn read_metadata(data: &[u8], report: &mut DocumentReport) -> Result<(), String> {
+    let mut reader = ChunkReader::new(data);
+    let count = reader.read_u32()?;
+    for _ in 0..count {
+        let key = String::from_utf8_lossy(reader.read_string()?).into_owned();
+        let value = String::from_utf8_lossy(reader.read_string()?).into_owned();
+        report.metadata.insert(key, value);
+    }
+    Ok(())
+}
+
+fn inspect_class(
+    data: &[u8],
+    contents: &mut BinaryContents,
+    class_count: usize,
+    report: &mut DocumentReport,
+) -> Result<(), String> {
+    let class = ClassChunk::read(data)?;
+
+    if contents.class_indices.contains_key(&class.id) {
+        report.error(format!(
+            "Class id {} is used by more than one class, including '{}'",
+            class.id, class.name
+        ));
+    } else if class.id as usize >= class_count {
+        report.error(format!(
+            "Class id {} of class '{}' is out of range, the file header lists {class_count} classes",
+            class.id, class.name
+        ));
+    }
+    if !class_exists(&class.name) {
+        report.warning(format!("Unknown class '{}'", class.name));
+    }
+
+    let index = contents.classes.len();
+    for referent in &class.referents {
+        if *referent < 0 {
+            report.error(format!(
+                "Instance of class '{}' has an invalid referent {referent}",
+                class.name
+            ));
+        } else if contents.referents.insert(*referent, index).is_some() {
+            report.error(format!(
+                "Referent {referent} is used by more than one instance"
+            ));
+        }
+    }
+
+    contents.class_indices.insert(class.id, index);
+    contents.classes.push(class);
+    Ok(())
+}
+
+fn inspect_property(
+    data: &[u8],
+    contents: &mut BinaryContents,
+    report: &mut DocumentReport,
+) -> Result<(), String> {
+    let mut reader = ChunkReader::new(data);
+    let class_id = reader.read_u32()?;
+    let property_name = String::from_utf8_lossy(reader.read_string()?).into_owned();
+    let ty = reader.read_u8()?;
+    let size = reader.remaining();
+
+    let Some(class_name) = contents.class_name(class_id).map(ToString::to_string) else {
+        report.error(format!(
+            "Property '{property_name}' refers to class id {class_id}, \
+            which is not defined by a preceding INST chunk"
+        ));
+        return Ok(());
+    };
+    let full_name = format!("{class_name}.{property_name}");
+
+    let properties = contents.properties.entry(class_id).or_default();
+    if properties
+        .iter()
+        .any(|property| property.name == property_name)
+    {
+        report.error(format!("Property '{full_name}' is defined more than once"));
+    }
+    properties.push(PropertyReport {
+        name: property_name.clone(),
+        type_name: type_name(ty),
+        size: Some(size),
+    });
+
+    let referents = contents.classes[contents.class_indices[&class_id]]
+        .referents
+        .clone();
+    match read_columns(&mut reader, ty, referents.len()) {
+        Ok(columns) if matches!(columns.as_slice(), [Column::Unknown(_)]) => {
+            report.warning(format!(
+                "Property '{full_name}' has an unknown type id 0x{ty:02x}"
+            ));
+            return Ok(());
+        }
+        Ok(columns) => {
+            if reader.remaining() > 0 {
+                report.warning(format!(
+                    "Property '{full_name}' has {} bytes of unexpected data after its values",
+                    reader.remaining()
+                ));
+            }
+            for column in columns {
+                match column {
+                    Column::Referents(values)
+                    | Column::Content {
+                        objects: values, ..
+                    } => {
+                        contents.references.push((full_name.clone(), values));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Err(err) => {
+            report.error(format!(
+                "Failed to read the values of property '{full_name}' - {err}"
+            ));
+            return Ok(());
+        }
+    }
+
+    if property_name == "Name" && ty == TYPE_STRING {
+        let mut reader = ChunkReader::new(data);
+        reader.read_u32()?;
+        reader.read_string()?;
+        reader.read_u8()?;
+        for (referent, name) in referents.into_iter().zip(read_strings(&mut reader)?) {
+            contents
+                .names
+                .insert(referent, String::from_utf8_lossy(&name).into_owned());
+        }
+    }
+
+    Ok(())
+}
+
+fn check_parents(contents: &BinaryContents, report: &mut DocumentReport) {
+    let mut parents = HashMap::new();
+    for (child, parent) in &contents.parents {
+        if !contents.referents.contains_key(child) {
+            report.error(format!(
+                "PRNT chunk refers to referent {child}, which is not defined by any INST chunk"
+            ));
+            continue;
+        }
+        if *parent != NULL_REFERENT && !contents.referents.contains_key(parent) {
+            report.error(format!(
+                "Instance {} has parent referent {parent}, which is not defined by any INST chunk",
+                contents.describe(*child)
+            ));
+        }
+        if parents.insert(*child, *parent).is_some() {
+            report.error(format!(
+                "Instance {} is listed more than once in PRNT chunks",
+                contents.describe(*child)
+            ));
+        }
+    }
+
+    let mut missing = contents
+        .referents
+        .keys()
+        .filter(|referent| !parents.contains_key(referent))
+        .collect::<Vec<_>>();
+    missing.sort_unstable();
+    for referent in missing {
+        report.warning(format!(
+            "Instance {} is not listed in any PRNT chunk",
+            contents.describe(*referent)
+        ));
+    }
+
+    // Any instance that is its own ancestor would make the tree impossible to build
+    let mut checked = HashSet::new();
+    let mut children = parents.keys().copied().collect::<Vec<_>>();
+    children.sort_unstable();
+    for child in children {
+        let mut visited = HashSet::new();
+        let mut current = child;
+        while let Some(parent) = parents.get(&current).copied() {
+            if checked.contains(&current) || parent == NULL_REFERENT {
+                break;
+            }
+            if !visited.insert(current) {
+                report.error(format!(
+                    "Instance {} is its own ancestor",
+                    contents.describe(current)
+                ));
+                break;
+            }
+            current = parent;
+        }
+        checked.extend(visited);
+    }
+}
+
+fn check_references(contents: &BinaryContents, report: &mut DocumentReport) {
+    for (full_name, referents) in &contents.references {
+        let mut missing = referents
+            .iter()
+            .filter(|referent| **referent != NULL_REFERENT)
+            .filter(|referent| !contents.referents.contains_key(referent))
+            .collect::<Vec<_>>();
+        missing.sort_unstable();
+        missing.dedup();
+        for referent in missing {
+            report.warning(format!(
+                "Property '{full_name}' refers to referent {referent}, \
+                which is not defined by any INST chunk"
+            ));
+        }
+    }
+}
+
+fn detect_kind(contents: &BinaryContents) -> Option<DocumentKind> {
+    let mut has_top_level_child = false;
+    for (child, parent) in &contents.parents {
+        let Some(index) = contents.referents.get(child) else {
+            continue;
+        };
+        if *parent == NULL_REFERENT {
+            let class = &contents.classes[*index];
+            if class.is_service || class_is_a_service(&class.name).unwrap_or(false) {
+                return Some(DocumentKind::Place);
+            }
+            has_top_level_child = true;
+        }
+    }
+    has_top_level_child.then_some(DocumentKind::Model)
+}
+
+fn type_name(ty: u8) -> String {
+    let name = match ty {
+        0x01 => "String",
+        0x02 => "Bool",
+        0x03 => "Int32",
+        0x04 => "Float32",
+        0x05 => "Float64",
+        0x06 => "UDim",
+        0x07 => "UDim2",
+        0x08 => "Ray",
+        0x09 => "Faces",
+        0x0a => "Axes",
+        0x0b => "BrickColor",
+        0x0c => "Color3",
+        0x0d => "Vector2",
+        0x0e => "Vector3",
+        0x0f => "Vector2int16",
+        0x10 => "CFrame",
+        0x12 => "Enum",
+        0x13 => "Ref",
+        0x14 => "Vector3int16",
+        0x15 => "NumberSequence",
+        0x16 => "ColorSequence",
+        0x17 => "NumberRange",
+        0x18 => "Rect",
+        0x19 => "PhysicalProperties",
+        0x1a => "Color3uint8",
+        0x1b => "Int64",
+        0x1c => "SharedString",
+        0x1d => "ProtectedString",
+        0x1e => "OptionalCFrame",
+        0x1f => "UniqueId",
+        0x20 => "Font",
+        0x21 => "SecurityCapabilities",
+        0x22 => "Content",
+        _ => return format!("Unknown(0x{ty:02x})"),
+    };
+    name.to_string()
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{DocumentError, DocumentFormat, DocumentKind, DocumentResult};
+
+mod binary;
+mod xml;
+
+/**
+    How severe an issue found when inspecting a document is.
+
+    Documents with errors are likely to fail to open in Roblox Studio,
+    while documents with only warnings should open, but may lose data.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+/**
+    A single issue found when inspecting a document.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocumentIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+/**
+    How the contents of a chunk in a binary document are compressed.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkCompression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+/**
+    A single chunk in a binary document.
+
+    The offset is relative to the start of the file, and the size is
+    the size of the contents of the chunk once it has been decompressed.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkReport {
+    pub name: String,
+    pub offset: usize,
+    pub compression: ChunkCompression,
+    pub compressed_size: usize,
+    pub size: usize,
+}
+
+/**
+    A single property of a class in a document.
+
+    The size is the number of bytes used by the values of the property
+    for all instances of the class, and is only known for binary documents.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyReport {
+    pub name: String,
+    pub type_name: String,
+    pub size: Option<usize>,
+}
+
+/**
+    A single class in a document, with the number of instances of the class.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassReport {
+    pub class_name: String,
+    pub instance_count: usize,
+    pub is_service: bool,
+    pub properties: Vec<PropertyReport>,
+}
+
+/**
+    A report of the structure of a document, and any issues found in it.
+
+    Chunks and metadata are only listed for binary documents, and classes are sorted by name.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentReport {
+    pub format: DocumentFormat,
+    pub kind: Option<DocumentKind>,
+    pub valid: bool,
+    pub instance_count: usize,
+    pub chunks: Vec<ChunkReport>,
+    pub metadata: BTreeMap<String, String>,
+    pub classes: Vec<ClassReport>,
+    pub issues: Vec<DocumentIssue>,
+}
+
+impl DocumentReport {
+    fn new(format: DocumentFormat) -> Self {
+        Self {
+            format,
+            kind: None,
+            valid: true,
+            instance_count: 0,
+            chunks: Vec::new(),
+            metadata: BTreeMap::new(),
+            classes: Vec::new(),
+            issues: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        self.valid = false;
+        self.issues.push(DocumentIssue {
+            severity: IssueSeverity::Error,
+            message: message.into(),
+        });
+    }
+
+    fn warning(&mut self, message: impl Into<String>) {
+        self.issues.push(DocumentIssue {
+            severity: IssueSeverity::Warning,
+            message: message.into(),
+        });
+    }
+
+    /**
+        Gets all issues of the given severity.
+    */
+    pub fn issues_with_severity(
+        &self,
+        severity: IssueSeverity,
+    ) -> impl Iterator<Item = &DocumentIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+}
+
+/**
+    Inspects the structure of a document, without loading it into a [`Document`](super::Document).
+
+    For binary documents, this reports the layout of the chunks in the file, the number of
+    instances of each class, and the size of each property, and checks that referents and
+    parents are consistent. For xml documents, classes and properties are checked against
+    the reflection database, since xml documents have no chunks to inspect.
+
+    Any issue found in the document is listed in the report instead of being returned as an error.
+
+    # Errors
+
+    Errors if the format of the document could not be detected.
+*/
+pub fn inspect_document(bytes: impl AsRef<[u8]>) -> DocumentResult<DocumentReport> {
+    let bytes = bytes.as_ref();
+    let format = DocumentFormat::from_bytes(bytes).ok_or(DocumentError::UnknownFormat)?;
+
+    let mut report = DocumentReport::new(format);
+    match format {
+        DocumentFormat::Binary => binary::inspect(bytes, &mut report),
+        DocumentFormat::Xml => xml::inspect(bytes, &mut report),
+    }
+    report
+        .classes
+        .sort_by(|a, b| a.class_name.cmp(&b.class_name));
+
+    Ok(report)
+}
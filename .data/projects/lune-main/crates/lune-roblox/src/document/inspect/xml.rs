@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use rbx_dom_weak::types::{Variant as DomValue, VariantType as DomType};
+use rbx_xml::{
+    DecodeOptions as XmlDecodeOptions, DecodePropertyBehavior as XmlDecodePropertyBehavior,
+};
+
+use crate::{
+    document::DocumentKind,
+    shared::instance::{class_exists, class_is_a_service, find_property_info},
+};
+
+use super::{ClassReport, DocumentReport, PropertyReport};
+
+const NULL_REFERENT: &str = "null";
+
+/**
+    Pairs of types that are used interchangeably for the same property,
+    where the type in the file may differ from the type in the reflection database.
+*/
+const COMPATIBLE_TYPES: [(DomType, DomType); 5] = [
+    (DomType::Float32, DomType::Float64),
+    (DomType::Int32, DomType::Int64),
+    (DomType::Color3, DomType::Color3uint8),
+    (DomType::String, DomType::BinaryString),
+    (DomType::Content, DomType::ContentId),
+];
+
+pub(super) fn inspect(bytes: &[u8], report: &mut DocumentReport) {
+    check_referents(&String::from_utf8_lossy(bytes), report);
+
+    let options = XmlDecodeOptions::new().property_behavior(XmlDecodePropertyBehavior::ReadUnknown);
+    let dom = match rbx_xml::from_reader(bytes, options) {
+        Ok(dom) => dom,
+        Err(err) => {
+            report.error(format!("Failed to decode document - {err}"));
+            return;
+        }
+    };
+    report.kind = DocumentKind::from_weak_dom(&dom);
+
+    let mut classes = BTreeMap::<String, (ClassReport, BTreeMap<String, String>)>::new();
+    let mut reported = HashSet::new();
+
+    let mut queue = VecDeque::from_iter(dom.root().children());
+    while let Some(inst) = queue.pop_front().and_then(|r| dom.get_by_ref(*r)) {
+        queue.extend(inst.children());
+        report.instance_count += 1;
+
+        let class_name = inst.class.as_str();
+        let (class, properties) = classes.entry(class_name.to_string()).or_insert_with(|| {
+            let class = ClassReport {
+                class_name: class_name.to_string(),
+                instance_count: 0,
+                is_service: class_is_a_service(class_name).unwrap_or(false),
+                properties: Vec::new(),
+            };
+            (class, BTreeMap::new())
+        });
+        class.instance_count += 1;
+
+        let is_known_class = class_exists(class_name);
+        if !is_known_class && reported.insert((class_name.to_string(), None)) {
+            report.warning(format!("Unknown class '{class_name}'"));
+        }
+
+        for (property_name, value) in &inst.properties {
+            let property_name = property_name.as_str();
+            properties
+                .entry(property_name.to_string())
+                .or_insert_with(|| format!("{:?}", value.ty()));
+
+            if !is_known_class || matches!(property_name, "Attributes" | "Tags") {
+                continue;
+            }
+            if let Some(issue) = check_property(class_name, property_name, value)
+                && reported.insert((class_name.to_string(), Some(property_name.to_string())))
+            {
+                report.warning(issue);
+            }
+        }
+    }
+
+    report.classes = classes
+        .into_values()
+        .map(|(mut class, properties)| {
+            class.properties = properties
+                .into_iter()
+                .map(|(name, type_name)| PropertyReport {
+                    name,
+                    type_name,
+                    size: None,
+                })
+                .collect();
+            class
+        })
+        .collect();
+}
+
+/**
+    Checks a property value against the reflection database,
+    returning a description of the issue if it does not match.
+*/
+fn check_property(class_name: &str, property_name: &str, value: &DomValue) -> Option<String> {
+    let Some(info) = find_property_info(class_name, property_name) else {
+        return Some(format!(
+            "Unknown property '{property_name}' for class '{class_name}'"
+        ));
+    };
+
+    let actual = value.ty();
+    let expected = if let Some(enum_name) = info.enum_name {
+        if matches!(actual, DomType::Enum | DomType::EnumItem) {
+            return None;
+        }
+        format!("Enum.{enum_name}")
+    } else if let Some(expected) = info.value_type {
+        let is_compatible = COMPATIBLE_TYPES
+            .iter()
+            .any(|(a, b)| (*a == actual && *b == expected) || (*a == expected && *b == actual));
+        if actual == expected || is_compatible {
+            return None;
+        }
+        format!("{expected:?}")
+    } else {
+        return None;
+    };
+
+    Some(format!(
+        "Property '{class_name}.{property_name}' has type {actual:?}, but {expected} was expected"
+    ))
+}
+
+/**
+    Checks that all referents in the document are unique, and that all
+    references to other instances refer to instances in the document.
+
+    This is done on the source of the document, since references to instances
+    that do not exist are silently ignored when the document is decoded.
+*/
+fn check_referents(source: &str, report: &mut DocumentReport) {
+    let mut referents = HashSet::new();
+    for referent in attribute_values(source, "referent") {
+        if !referents.insert(referent) {
+            report.error(format!(
+                "Referent '{referent}' is used by more than one instance"
+            ));
+        }
+    }
+
+    let mut reported = HashSet::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("<Ref name=\"") {
+        rest = &rest[start + "<Ref name=\"".len()..];
+        let Some((property_name, after_name)) = rest.split_once('"') else {
+            break;
+        };
+        let Some(value) = after_name
+            .split_once('>')
+            .and_then(|(_, contents)| contents.split_once("</Ref>"))
+            .map(|(value, _)| value.trim())
+        else {
+            break;
+        };
+        if value != NULL_REFERENT
+            && !value.is_empty()
+            && !referents.contains(value)
+            && reported.insert((property_name, value))
+        {
+            report.warning(format!(
+                "Property '{property_name}' refers to referent '{value}', \
+                which is not used by any instance"
+            ));
+        }
+    }
+}
+
+fn attribute_values<'a>(source: &'a str, attribute: &str) -> Vec<&'a str> {
+    let pattern = format!(" {attribute}=\"");
+    source
+        .match_indices(&pattern)
+        .filter_map(|(index, _)| {
+            let rest = &source[index + pattern.len()..];
+            rest.split_once('"').map(|(value, _)| value)
+        })
+        .collect()
+}
@@ -1,5 +1,7 @@
 use std::path::Path;
 
+use serde::Serialize;
+
 use rbx_dom_weak::WeakDom;
 
 use crate::shared::instance::class_is_a_service;
@@ -14,7 +16,8 @@ use crate::shared::instance::class_is_a_service;
 
     Other variants are only to be used for logic internal to this crate.
 */
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DocumentKind {
     Place,
     Model,
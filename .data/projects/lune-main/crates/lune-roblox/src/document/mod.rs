@@ -4,15 +4,24 @@ use rbx_xml::{
     EncodeOptions as XmlEncodeOptions, EncodePropertyBehavior as XmlEncodePropertyBehavior,
 };
 
+mod binary_chunks;
 mod error;
+mod filter;
 mod format;
+mod inspect;
 mod kind;
 mod postprocessing;
 
 pub use error::*;
+pub use filter::DocumentFilter;
 pub use format::*;
+pub use inspect::{
+    ChunkCompression, ChunkReport, ClassReport, DocumentIssue, DocumentReport, IssueSeverity,
+    PropertyReport, inspect_document,
+};
 pub use kind::*;
 
+use binary_chunks::filter_binary_document;
 use postprocessing::*;
 
 use crate::instance::Instance;
@@ -140,6 +149,41 @@ impl Document {
         Ok(Self { kind, format, dom })
     }
 
+    /**
+        Decodes and creates a new document from a byte buffer, only
+        loading the instances that are included by the given filter.
+
+        For binary documents, the chunks of the file are filtered before
+        being decoded, so any instance or property that is not included
+        is skipped without ever being loaded into memory. Xml documents
+        are decoded entirely and then filtered.
+
+        # Errors
+
+        Errors if the given bytes are not a valid roblox file.
+    */
+    pub fn from_bytes_filtered(
+        bytes: impl AsRef<[u8]>,
+        kind: DocumentKind,
+        filter: &DocumentFilter,
+    ) -> DocumentResult<Self> {
+        let bytes = bytes.as_ref();
+        let format = DocumentFormat::from_bytes(bytes).ok_or(DocumentError::UnknownFormat)?;
+        let dom = match format {
+            DocumentFormat::Binary => {
+                let filtered = filter_binary_document(bytes, filter)?;
+                let (_, dom) = Self::from_bytes_inner(filtered)?;
+                dom
+            }
+            DocumentFormat::Xml => {
+                let (_, mut dom) = Self::from_bytes_inner(bytes)?;
+                filter.apply_to_dom(&mut dom);
+                dom
+            }
+        };
+        Ok(Self { kind, format, dom })
+    }
+
     /**
         Encodes the document as a vector of bytes, to
         be written to a file or sent over the network.
@@ -186,6 +230,19 @@ impl Document {
         Ok(bytes)
     }
 
+    /**
+        Canonicalizes the document, so that encoding the same instances always
+        produces the same output, and removes any redundant data from it.
+
+        This will round 32-bit floating point values, remove properties that are equal
+        to their default values, and sort children by their names and classes.
+
+        Note that this changes the order of children, which may be meaningful.
+    */
+    pub fn canonicalize(&mut self) {
+        canonicalize_dom(&mut self.dom);
+    }
+
     /**
         Gets the kind this document was created with.
     */
@@ -260,6 +317,13 @@ impl Document {
         Ok(root_child_instances)
     }
 
+    /**
+        Consumes the document and returns its underlying weak dom.
+    */
+    pub(crate) fn into_weak_dom(self) -> WeakDom {
+        self.dom
+    }
+
     /**
         Creates a place document out of a `DataModel` instance.
 
@@ -1,10 +1,13 @@
 use rbx_dom_weak::{
     Instance as DomInstance, WeakDom,
-    types::{Ref as DomRef, VariantType as DomType},
+    types::{
+        CFrame as DomCFrame, Ref as DomRef, UDim as DomUDim, Variant as DomValue,
+        VariantType as DomType, Vector3 as DomVector3,
+    },
     ustr,
 };
 
-use crate::shared::instance::class_is_a;
+use crate::{datatypes::util::round_float_decimal, shared::instance::class_is_a};
 
 pub fn postprocess_dom_for_place(_dom: &mut WeakDom) {
     // Nothing here yet
@@ -25,6 +28,120 @@ pub fn postprocess_dom_for_model(dom: &mut WeakDom) {
     });
 }
 
+/**
+    Canonicalizes a dom so that serializing the same instances
+    always produces the same output, regardless of how they were
+    created or modified, and so that the output is as small as possible:
+
+    1. Float values are rounded, removing floating point noise
+    2. Properties that are equal to their default values are removed
+    3. Children are sorted by name, and then by class name
+*/
+pub fn canonicalize_dom(dom: &mut WeakDom) {
+    let root_ref = dom.root_ref();
+    recurse_instances(dom, root_ref, &|inst| {
+        let class_name = inst.class;
+        inst.properties.retain(|prop_name, prop_value| {
+            normalize_value(prop_value);
+            find_property_default(class_name.as_str(), prop_name.as_str())
+                .is_none_or(|default| !is_default_value(prop_value, default))
+        });
+    });
+
+    let mut parent_refs = vec![root_ref];
+    while let Some(parent_ref) = parent_refs.pop() {
+        let Some(parent) = dom.get_by_ref(parent_ref) else {
+            continue;
+        };
+
+        let mut child_refs = parent.children().to_vec();
+        child_refs.sort_by_cached_key(|child_ref| {
+            let child = dom.get_by_ref(*child_ref).expect("Invalid child ref");
+            (child.name.clone(), child.class)
+        });
+
+        // Transferring a child to its current parent moves it
+        // to the end, so doing it in order sorts the children
+        for child_ref in &child_refs {
+            dom.transfer_within(*child_ref, parent_ref);
+        }
+
+        parent_refs.extend(child_refs);
+    }
+}
+
+fn find_property_default(class_name: &str, prop_name: &str) -> Option<&'static DomValue> {
+    let db = rbx_reflection_database::get().unwrap();
+    let mut class_name = class_name;
+    while let Some(class) = db.classes.get(class_name) {
+        if let Some(default) = class.default_properties.get(prop_name) {
+            return Some(default);
+        }
+        class_name = class.superclass.as_deref()?;
+    }
+    None
+}
+
+fn is_default_value(value: &DomValue, default: &DomValue) -> bool {
+    let mut default = default.clone();
+    normalize_value(&mut default);
+    match (value, &default) {
+        // NOTE: Enums set from Lune are stored as enum items,
+        // while defaults from the reflection database are not
+        (DomValue::EnumItem(item), DomValue::Enum(default)) => item.value == default.to_u32(),
+        (value, default) => value == default,
+    }
+}
+
+fn normalize_float(value: f32) -> f32 {
+    // NOTE: Adding positive zero turns any negative zero into
+    // positive zero, which would otherwise serialize differently
+    round_float_decimal(value) + 0.0
+}
+
+fn normalize_vector3(value: &mut DomVector3) {
+    value.x = normalize_float(value.x);
+    value.y = normalize_float(value.y);
+    value.z = normalize_float(value.z);
+}
+
+fn normalize_cframe(value: &mut DomCFrame) {
+    normalize_vector3(&mut value.position);
+    normalize_vector3(&mut value.orientation.x);
+    normalize_vector3(&mut value.orientation.y);
+    normalize_vector3(&mut value.orientation.z);
+}
+
+fn normalize_udim(value: &mut DomUDim) {
+    value.scale = normalize_float(value.scale);
+}
+
+fn normalize_value(value: &mut DomValue) {
+    match value {
+        DomValue::Float32(f) => *f = normalize_float(*f),
+        // NOTE: Doubles are stored with full precision, such as the values
+        // of NumberValue instances, so only negative zero is normalized here
+        DomValue::Float64(f) => *f += 0.0,
+        DomValue::Vector2(v) => {
+            v.x = normalize_float(v.x);
+            v.y = normalize_float(v.y);
+        }
+        DomValue::Vector3(v) => normalize_vector3(v),
+        DomValue::CFrame(cf) | DomValue::OptionalCFrame(Some(cf)) => normalize_cframe(cf),
+        DomValue::Color3(c) => {
+            c.r = normalize_float(c.r);
+            c.g = normalize_float(c.g);
+            c.b = normalize_float(c.b);
+        }
+        DomValue::UDim(u) => normalize_udim(u),
+        DomValue::UDim2(u) => {
+            normalize_udim(&mut u.x);
+            normalize_udim(&mut u.y);
+        }
+        _ => {}
+    }
+}
+
 fn recurse_instances<F>(dom: &mut WeakDom, dom_ref: DomRef, f: &F)
 where
     F: Fn(&mut DomInstance) + 'static,
@@ -14,10 +14,15 @@ use crate::{
         types::EnumItem,
         userdata_impl_eq, userdata_impl_to_string,
     },
+    query::Selector,
     shared::instance::{class_is_a, find_property_info},
 };
 
-use super::{Instance, data_model, registry::InstanceRegistry};
+use super::{
+    Instance, data_model,
+    registry::InstanceRegistry,
+    signals::{InstanceSignal, SignalKind, fire_pending_signals},
+};
 
 #[allow(clippy::too_many_lines)]
 pub fn add_methods<M: LuaUserDataMethods<Instance>>(m: &mut M) {
@@ -32,13 +37,13 @@ pub fn add_methods<M: LuaUserDataMethods<Instance>>(m: &mut M) {
         ensure_not_destroyed(this)?;
         this.clone_instance().into_lua(lua)
     });
-    m.add_method_mut("Destroy", |_, this, ()| {
+    m.add_method_mut("Destroy", |lua, this, ()| {
         this.destroy();
-        Ok(())
+        fire_pending_signals(lua)
     });
-    m.add_method_mut("ClearAllChildren", |_, this, ()| {
+    m.add_method_mut("ClearAllChildren", |lua, this, ()| {
         this.clear_all_children();
-        Ok(())
+        fire_pending_signals(lua)
     });
     m.add_method("GetChildren", |lua, this, ()| {
         ensure_not_destroyed(this)?;
@@ -48,6 +53,11 @@ pub fn add_methods<M: LuaUserDataMethods<Instance>>(m: &mut M) {
         ensure_not_destroyed(this)?;
         this.get_descendants().into_lua(lua)
     });
+    m.add_method("QueryDescendants", |lua, this, selector: String| {
+        ensure_not_destroyed(this)?;
+        let selector = Selector::parse(selector)?;
+        this.query_descendants(&selector).into_lua(lua)
+    });
     m.add_method("GetFullName", |lua, this, ()| {
         ensure_not_destroyed(this)?;
         this.get_full_name().into_lua(lua)
@@ -156,13 +166,13 @@ pub fn add_methods<M: LuaUserDataMethods<Instance>>(m: &mut M) {
             ensure_valid_attribute_name(&attribute_name)?;
             if lua_value.is_nil() || lua_value.is_null() {
                 this.remove_attribute(attribute_name);
-                Ok(())
+                fire_pending_signals(lua)
             } else {
                 match lua_value.lua_to_dom_value(lua, None) {
                     Ok(dom_value) => {
                         ensure_valid_attribute_value(&dom_value)?;
                         this.set_attribute(attribute_name, dom_value);
-                        Ok(())
+                        fire_pending_signals(lua)
                     }
                     Err(e) => Err(e.into()),
                 }
@@ -177,16 +187,34 @@ pub fn add_methods<M: LuaUserDataMethods<Instance>>(m: &mut M) {
         ensure_not_destroyed(this)?;
         Ok(this.has_tag(tag))
     });
-    m.add_method("AddTag", |_, this, tag: String| {
+    m.add_method("AddTag", |lua, this, tag: String| {
         ensure_not_destroyed(this)?;
         this.add_tag(tag);
-        Ok(())
+        fire_pending_signals(lua)
     });
-    m.add_method("RemoveTag", |_, this, tag: String| {
+    m.add_method("RemoveTag", |lua, this, tag: String| {
         ensure_not_destroyed(this)?;
         this.remove_tag(tag);
-        Ok(())
+        fire_pending_signals(lua)
     });
+    m.add_method(
+        "GetPropertyChangedSignal",
+        |_, this, property_name: String| {
+            ensure_not_destroyed(this)?;
+            if matches!(property_name.as_str(), "Name" | "Parent")
+                || find_property_info(this.class_name, &property_name).is_some()
+            {
+                Ok(InstanceSignal::new(
+                    *this,
+                    SignalKind::PropertyChanged(property_name),
+                ))
+            } else {
+                Err(LuaError::RuntimeError(format!(
+                    "{property_name} is not a valid property name of {this}",
+                )))
+            }
+        },
+    );
 }
 
 fn ensure_not_destroyed(inst: &Instance) -> LuaResult<()> {
@@ -205,11 +233,12 @@ fn ensure_not_destroyed(inst: &Instance) -> LuaResult<()> {
     Getting a value does the following:
 
     1. Check if it is a special property like "ClassName", "Name" or "Parent"
-    2. Check if a property exists for the wanted name
-        2a. Get an existing instance property OR
-        2b. Get a property from a known default value
-    3. Get a current child of the instance
-    4. No valid property or instance found, throw error
+    2. Check if it is a signal like "Changed" or "ChildAdded"
+    3. Check if a property exists for the wanted name
+        3a. Get an existing instance property OR
+        3b. Get a property from a known default value
+    4. Get a current child of the instance
+    5. No valid property or instance found, throw error
 */
 fn instance_property_get(lua: &Lua, this: &Instance, prop_name: String) -> LuaResult<LuaValue> {
     match prop_name.as_str() {
@@ -226,6 +255,10 @@ fn instance_property_get(lua: &Lua, this: &Instance, prop_name: String) -> LuaRe
         return this.get_name().into_lua(lua);
     }
 
+    if let Some(kind) = SignalKind::from_property_name(&prop_name) {
+        return InstanceSignal::new(*this, kind).into_lua(lua);
+    }
+
     if let Some(info) = find_property_info(this.class_name, &prop_name) {
         if let Some(prop) = this.get_property(&prop_name) {
             if let DomValue::Enum(enum_value) = prop {
@@ -290,6 +323,7 @@ fn instance_property_get(lua: &Lua, this: &Instance, prop_name: String) -> LuaRe
     2. Check if a property exists for the wanted name
         2a. Set a strict enum from a given EnumItem OR
         2b. Set a normal property from a given value
+    3. Fire signals for any changes, such as Changed or ChildAdded
 */
 fn instance_property_set(
     lua: &Lua,
@@ -297,7 +331,16 @@ fn instance_property_set(
     (prop_name, prop_value): (String, LuaValue),
 ) -> LuaResult<()> {
     ensure_not_destroyed(this)?;
+    set_instance_property(lua, this, prop_name, prop_value)?;
+    fire_pending_signals(lua)
+}
 
+fn set_instance_property(
+    lua: &Lua,
+    this: &mut Instance,
+    prop_name: String,
+    prop_value: LuaValue,
+) -> LuaResult<()> {
     match prop_name.as_str() {
         "ClassName" => {
             return Err(LuaError::RuntimeError(
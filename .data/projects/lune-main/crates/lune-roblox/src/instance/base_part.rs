@@ -0,0 +1,25 @@
+use mlua::prelude::*;
+
+use crate::{datatypes::types::Vector3, shared::classes::add_class_restricted_method};
+
+use super::{Instance, spatial::get_part_bounds};
+
+pub const CLASS_NAME: &str = "BasePart";
+
+pub fn add_methods<M: LuaUserDataMethods<Instance>>(methods: &mut M) {
+    add_class_restricted_method(methods, CLASS_NAME, "GetCorners", base_part_get_corners);
+}
+
+/**
+    Gets the eight corners of the bounding box of the part, in world space.
+
+    Note that this does not have an equivalent in the Roblox engine API,
+    where corners would be computed from `CFrame` and `Size` manually.
+*/
+fn base_part_get_corners(_: &Lua, this: &Instance, (): ()) -> LuaResult<Vec<Vector3>> {
+    Ok(get_part_bounds(this)
+        .corners()
+        .into_iter()
+        .map(Vector3)
+        .collect())
+}
@@ -8,7 +8,7 @@ use crate::shared::{
     instance::class_is_a_service,
 };
 
-use super::Instance;
+use super::{Instance, signals::fire_pending_signals};
 
 pub const CLASS_NAME: &str = "DataModel";
 
@@ -39,7 +39,7 @@ fn data_model_get_workspace(_: &Lua, this: &Instance) -> LuaResult<Instance> {
     * [`GetService`](https://create.roblox.com/docs/reference/engine/classes/ServiceProvider#GetService)
       on the Roblox Developer Hub
 */
-fn data_model_get_service(_: &Lua, this: &Instance, service_name: String) -> LuaResult<Instance> {
+fn data_model_get_service(lua: &Lua, this: &Instance, service_name: String) -> LuaResult<Instance> {
     if matches!(class_is_a_service(&service_name), None | Some(false)) {
         Err(LuaError::RuntimeError(format!(
             "'{service_name}' is not a valid service name",
@@ -49,6 +49,7 @@ fn data_model_get_service(_: &Lua, this: &Instance, service_name: String) -> Lua
     } else {
         let service = Instance::new_orphaned(service_name);
         service.set_parent(Some(*this));
+        fire_pending_signals(lua)?;
         Ok(service)
     }
 }
@@ -0,0 +1,589 @@
+use std::{
+    cell::LazyCell,
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, Weak},
+};
+
+use rbx_dom_weak::{WeakDom, types::Ref as DomRef};
+
+use super::Instance;
+
+static QUEUES: Mutex<Vec<Weak<Mutex<QueuedEvents>>>> = Mutex::new(Vec::new());
+
+/**
+    A queue of instance events for a single Lua state.
+*/
+pub(crate) type EventQueue = Arc<Mutex<QueuedEvents>>;
+
+type InstanceListeners = HashMap<DomRef, HashSet<EventKind>>;
+
+/**
+    The events in a queue, together with the instances that
+    the queue wants events for, and the kinds of those events.
+*/
+#[derive(Debug, Default)]
+pub(crate) struct QueuedEvents {
+    events: Vec<InstanceEvent>,
+    listeners: InstanceListeners,
+}
+
+impl QueuedEvents {
+    fn wants(&self, event: &InstanceEvent) -> bool {
+        self.listeners
+            .get(&event.target())
+            .is_some_and(|kinds| event.kind().is_none_or(|kind| kinds.contains(&kind)))
+    }
+}
+
+/**
+    An event that happened to an instance in the internal dom.
+
+    Events are only recorded for instances that have signal connections
+    in at least one Lua state, and are then queued for each of those Lua
+    states until they get taken by their signal subsystem.
+*/
+#[derive(Debug, Clone)]
+pub(crate) enum InstanceEvent {
+    PropertyChanged {
+        instance: Instance,
+        property: String,
+    },
+    AttributeChanged {
+        instance: Instance,
+        attribute: String,
+    },
+    ChildAdded {
+        parent: Instance,
+        child: Instance,
+    },
+    ChildRemoved {
+        parent: Instance,
+        child: Instance,
+    },
+    DescendantAdded {
+        ancestor: Instance,
+        descendant: Instance,
+    },
+    DescendantRemoving {
+        ancestor: Instance,
+        descendant: Instance,
+    },
+    AncestryChanged {
+        instance: Instance,
+        child: Instance,
+        parent: Option<Instance>,
+    },
+    Destroyed {
+        instance: DomRef,
+    },
+}
+
+/**
+    The kind of an instance event, which signals listen for.
+
+    Destroyed events have no kind, since they are wanted
+    for any instance that has signal connections.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EventKind {
+    PropertyChanged,
+    AttributeChanged,
+    ChildAdded,
+    ChildRemoved,
+    DescendantAdded,
+    DescendantRemoving,
+    AncestryChanged,
+}
+
+impl InstanceEvent {
+    /**
+        Gets the referent of the instance that this event should fire signals for.
+    */
+    pub(crate) fn target(&self) -> DomRef {
+        match self {
+            Self::PropertyChanged { instance, .. }
+            | Self::AttributeChanged { instance, .. }
+            | Self::AncestryChanged { instance, .. } => instance.dom_ref,
+            Self::ChildAdded { parent, .. } | Self::ChildRemoved { parent, .. } => parent.dom_ref,
+            Self::DescendantAdded { ancestor, .. } | Self::DescendantRemoving { ancestor, .. } => {
+                ancestor.dom_ref
+            }
+            Self::Destroyed { instance } => *instance,
+        }
+    }
+
+    fn kind(&self) -> Option<EventKind> {
+        Some(match self {
+            Self::PropertyChanged { .. } => EventKind::PropertyChanged,
+            Self::AttributeChanged { .. } => EventKind::AttributeChanged,
+            Self::ChildAdded { .. } => EventKind::ChildAdded,
+            Self::ChildRemoved { .. } => EventKind::ChildRemoved,
+            Self::DescendantAdded { .. } => EventKind::DescendantAdded,
+            Self::DescendantRemoving { .. } => EventKind::DescendantRemoving,
+            Self::AncestryChanged { .. } => EventKind::AncestryChanged,
+            Self::Destroyed { .. } => return None,
+        })
+    }
+}
+
+/**
+    The instances that any Lua state wants events for, and the kinds of those events.
+
+    Used to only create the events that will actually fire signals, since
+    moving or destroying an instance may otherwise create an event for each
+    of its descendants, for each of its ancestors.
+*/
+#[derive(Debug, Default)]
+pub(crate) struct Listeners(InstanceListeners);
+
+impl Listeners {
+    fn has(&self, dom_ref: DomRef, kind: EventKind) -> bool {
+        self.0
+            .get(&dom_ref)
+            .is_some_and(|kinds| kinds.contains(&kind))
+    }
+
+    fn has_any(&self, dom_ref: DomRef) -> bool {
+        self.0.contains_key(&dom_ref)
+    }
+
+    fn has_kind(&self, kind: EventKind) -> bool {
+        self.0.values().any(|kinds| kinds.contains(&kind))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn live_queues() -> Vec<EventQueue> {
+    QUEUES
+        .lock()
+        .expect("Failed to lock instance event queues")
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+/**
+    Creates a new event queue, which instance events get pushed to
+    for as long as the queue is not dropped, for any instances that
+    the queue has listeners for.
+*/
+pub(crate) fn create_event_queue() -> EventQueue {
+    let queue = EventQueue::default();
+    let mut queues = QUEUES.lock().expect("Failed to lock instance event queues");
+    queues.retain(|queue| queue.strong_count() > 0);
+    queues.push(Arc::downgrade(&queue));
+    queue
+}
+
+/**
+    Sets the kinds of events that the given queue wants for an instance,
+    replacing any kinds that were previously set for that instance.
+*/
+pub(crate) fn set_listeners(
+    queue: &EventQueue,
+    dom_ref: DomRef,
+    kinds: impl IntoIterator<Item = EventKind>,
+) {
+    let kinds = kinds.into_iter().collect::<HashSet<_>>();
+    let mut queue = queue.lock().expect("Failed to lock instance events");
+    if kinds.is_empty() {
+        queue.listeners.remove(&dom_ref);
+    } else {
+        queue.listeners.insert(dom_ref, kinds);
+    }
+}
+
+/**
+    Gets the instances that any live event queue currently wants events for.
+*/
+pub(crate) fn current_listeners() -> Listeners {
+    let mut listeners = InstanceListeners::new();
+    for queue in live_queues() {
+        let queue = queue.lock().expect("Failed to lock instance events");
+        for (dom_ref, kinds) in &queue.listeners {
+            listeners
+                .entry(*dom_ref)
+                .or_default()
+                .extend(kinds.iter().copied());
+        }
+    }
+    Listeners(listeners)
+}
+
+/**
+    Checks if instance events should currently be recorded.
+*/
+pub(crate) fn events_enabled() -> bool {
+    live_queues().iter().any(|queue| {
+        !queue
+            .lock()
+            .expect("Failed to lock instance events")
+            .listeners
+            .is_empty()
+    })
+}
+
+/**
+    Pushes the given events to all event queues that are still alive,
+    skipping any events that a queue does not have listeners for.
+*/
+pub(crate) fn push_events(events: impl IntoIterator<Item = InstanceEvent>) {
+    let queues = live_queues();
+    if queues.is_empty() {
+        return;
+    }
+
+    let events = events.into_iter().collect::<Vec<_>>();
+    if events.is_empty() {
+        return;
+    }
+    for queue in queues {
+        let mut queue = queue.lock().expect("Failed to lock instance events");
+        let wanted = events
+            .iter()
+            .filter(|event| queue.wants(event))
+            .cloned()
+            .collect::<Vec<_>>();
+        queue.events.extend(wanted);
+    }
+}
+
+/**
+    Takes all events in the given queue, in the order they happened.
+*/
+pub(crate) fn take_events(queue: &EventQueue) -> Vec<InstanceEvent> {
+    std::mem::take(&mut queue.lock().expect("Failed to lock instance events").events)
+}
+
+fn instance_in_dom(dom: &WeakDom, dom_ref: DomRef) -> Option<Instance> {
+    if dom_ref == dom.root_ref() {
+        return None;
+    }
+    dom.get_by_ref(dom_ref).map(|inst| Instance {
+        dom_ref,
+        class_name: inst.class,
+    })
+}
+
+fn parent_in_dom(dom: &WeakDom, dom_ref: DomRef) -> DomRef {
+    dom.get_by_ref(dom_ref)
+        .map_or_else(DomRef::none, rbx_dom_weak::Instance::parent)
+}
+
+/**
+    Gets the given instance followed by all of its descendants.
+*/
+fn subtree_in_dom(dom: &WeakDom, dom_ref: DomRef) -> Vec<Instance> {
+    let mut subtree = Vec::new();
+    let mut stack = vec![dom_ref];
+    while let Some(current) = stack.pop() {
+        if let Some(inst) = dom.get_by_ref(current) {
+            subtree.extend(instance_in_dom(dom, current));
+            stack.extend(inst.children().iter().rev());
+        }
+    }
+    subtree
+}
+
+/**
+    Gets the given instance followed by all of its ancestors, excluding the dom root.
+*/
+fn ancestors_in_dom(dom: &WeakDom, dom_ref: DomRef) -> Vec<Instance> {
+    let mut ancestors = Vec::new();
+    let mut current = dom_ref;
+    while let Some(inst) = instance_in_dom(dom, current) {
+        ancestors.push(inst);
+        current = parent_in_dom(dom, current);
+    }
+    ancestors
+}
+
+/**
+    Creates the events for descendant signals of the given ancestors, for
+    every instance in the subtree, but only for ancestors with listeners.
+*/
+fn descendant_events(
+    ancestors: &[Instance],
+    subtree: &[Instance],
+    listeners: &Listeners,
+    kind: EventKind,
+    event: impl Fn(Instance, Instance) -> InstanceEvent,
+) -> Vec<InstanceEvent> {
+    let mut events = Vec::new();
+    for ancestor in ancestors {
+        if listeners.has(ancestor.dom_ref, kind) {
+            events.extend(
+                subtree
+                    .iter()
+                    .map(|descendant| event(*ancestor, *descendant)),
+            );
+        }
+    }
+    events
+}
+
+/**
+    Creates the events for an instance, and its descendants,
+    being removed from the current parent of the instance.
+
+    Must be called *before* the instance is removed, since it reads ancestors from the dom.
+*/
+pub(crate) fn removal_events(
+    dom: &WeakDom,
+    dom_ref: DomRef,
+    listeners: &Listeners,
+) -> Vec<InstanceEvent> {
+    let mut events = Vec::new();
+    let Some(child) = instance_in_dom(dom, dom_ref) else {
+        return events;
+    };
+    let ancestors = ancestors_in_dom(dom, parent_in_dom(dom, dom_ref));
+    if let Some(parent) = ancestors.first()
+        && listeners.has(parent.dom_ref, EventKind::ChildRemoved)
+    {
+        events.push(InstanceEvent::ChildRemoved {
+            parent: *parent,
+            child,
+        });
+    }
+    if ancestors
+        .iter()
+        .any(|ancestor| listeners.has(ancestor.dom_ref, EventKind::DescendantRemoving))
+    {
+        let subtree = subtree_in_dom(dom, dom_ref);
+        events.extend(descendant_events(
+            &ancestors,
+            &subtree,
+            listeners,
+            EventKind::DescendantRemoving,
+            |ancestor, descendant| InstanceEvent::DescendantRemoving {
+                ancestor,
+                descendant,
+            },
+        ));
+    }
+    events
+}
+
+/**
+    Creates the events for an instance, and its descendants,
+    having been inserted under the new parent of the instance.
+
+    Must be called *after* the instance has been inserted.
+*/
+pub(crate) fn insertion_events(
+    dom: &WeakDom,
+    dom_ref: DomRef,
+    listeners: &Listeners,
+) -> Vec<InstanceEvent> {
+    let mut events = Vec::new();
+    let Some(child) = instance_in_dom(dom, dom_ref) else {
+        return events;
+    };
+    let ancestors = ancestors_in_dom(dom, parent_in_dom(dom, dom_ref));
+    let parent = ancestors.first().copied();
+    let subtree = LazyCell::new(|| subtree_in_dom(dom, dom_ref));
+
+    if listeners.has(dom_ref, EventKind::PropertyChanged) {
+        events.push(InstanceEvent::PropertyChanged {
+            instance: child,
+            property: "Parent".to_string(),
+        });
+    }
+    if listeners.has_kind(EventKind::AncestryChanged) {
+        for instance in subtree.iter() {
+            if listeners.has(instance.dom_ref, EventKind::AncestryChanged) {
+                events.push(InstanceEvent::AncestryChanged {
+                    instance: *instance,
+                    child,
+                    parent,
+                });
+            }
+        }
+    }
+    if let Some(parent) = parent
+        && listeners.has(parent.dom_ref, EventKind::ChildAdded)
+    {
+        events.push(InstanceEvent::ChildAdded { parent, child });
+    }
+    if ancestors
+        .iter()
+        .any(|ancestor| listeners.has(ancestor.dom_ref, EventKind::DescendantAdded))
+    {
+        events.extend(descendant_events(
+            &ancestors,
+            &subtree,
+            listeners,
+            EventKind::DescendantAdded,
+            |ancestor, descendant| InstanceEvent::DescendantAdded {
+                ancestor,
+                descendant,
+            },
+        ));
+    }
+    events
+}
+
+/**
+    Creates the events for an instance, and its descendants, being destroyed.
+
+    Must be called *before* the instance is destroyed, since it reads the subtree from the dom.
+*/
+pub(crate) fn destroy_events(
+    dom: &WeakDom,
+    dom_ref: DomRef,
+    listeners: &Listeners,
+) -> Vec<InstanceEvent> {
+    if listeners.is_empty() {
+        return Vec::new();
+    }
+    let mut events = removal_events(dom, dom_ref, listeners);
+    events.extend(
+        subtree_in_dom(dom, dom_ref)
+            .into_iter()
+            .filter(|inst| listeners.has_any(inst.dom_ref))
+            .map(|inst| InstanceEvent::Destroyed {
+                instance: inst.dom_ref,
+            }),
+    );
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use rbx_dom_weak::InstanceBuilder;
+
+    use super::*;
+
+    fn listeners(entries: &[(DomRef, EventKind)]) -> Listeners {
+        let mut listeners = InstanceListeners::new();
+        for (dom_ref, kind) in entries {
+            listeners.entry(*dom_ref).or_default().insert(*kind);
+        }
+        Listeners(listeners)
+    }
+
+    /**
+        Creates a dom with a chain of three nested folders under the
+        root, where the innermost folder has a subtree of four parts.
+    */
+    fn create_dom() -> (WeakDom, [DomRef; 3]) {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let mut parent = dom.root_ref();
+        let mut chain = [DomRef::none(); 3];
+        for dom_ref in &mut chain {
+            *dom_ref = dom.insert(parent, InstanceBuilder::new("Folder"));
+            parent = *dom_ref;
+        }
+        let part = dom.insert(parent, InstanceBuilder::new("Part"));
+        for _ in 0..3 {
+            dom.insert(part, InstanceBuilder::new("Part"));
+        }
+        (dom, chain)
+    }
+
+    #[test]
+    fn events_are_only_queued_for_live_queues() {
+        let (dom, [outer, middle, _]) = create_dom();
+        let child_added = |dom_ref| {
+            let parent = instance_in_dom(&dom, dom_ref).unwrap();
+            InstanceEvent::ChildAdded {
+                parent,
+                child: parent,
+            }
+        };
+
+        let first = create_event_queue();
+        let second = create_event_queue();
+        set_listeners(&first, middle, [EventKind::ChildAdded]);
+        set_listeners(&second, middle, [EventKind::ChildAdded]);
+        assert!(events_enabled());
+
+        push_events([child_added(middle), child_added(middle)]);
+        assert_eq!(take_events(&first).len(), 2);
+        assert_eq!(take_events(&second).len(), 2);
+        assert!(take_events(&first).is_empty());
+
+        drop(second);
+        push_events([child_added(middle)]);
+        assert_eq!(take_events(&first).len(), 1);
+
+        // Events are only queued for instances with listeners
+        push_events([child_added(outer), child_added(middle)]);
+        let events = take_events(&first);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].target(), middle);
+
+        set_listeners(&first, middle, []);
+        push_events([child_added(middle)]);
+        assert!(take_events(&first).is_empty());
+        assert!(!events_enabled());
+
+        drop(first);
+        assert!(!events_enabled());
+    }
+
+    #[test]
+    fn no_events_are_created_without_listeners() {
+        let (dom, [_, _, inner]) = create_dom();
+        let listeners = Listeners::default();
+
+        assert!(removal_events(&dom, inner, &listeners).is_empty());
+        assert!(insertion_events(&dom, inner, &listeners).is_empty());
+        assert!(destroy_events(&dom, inner, &listeners).is_empty());
+    }
+
+    #[test]
+    fn descendant_events_are_only_created_for_listened_ancestors() {
+        let (dom, [outer, middle, inner]) = create_dom();
+        let listeners = listeners(&[
+            (outer, EventKind::DescendantRemoving),
+            (middle, EventKind::DescendantAdded),
+        ]);
+
+        // The inner folder and its four parts are removed, but only
+        // the outer folder listens for descendants being removed
+        let events = removal_events(&dom, inner, &listeners);
+        assert_eq!(events.len(), 5);
+        assert!(events.iter().all(|event| matches!(
+            event,
+            InstanceEvent::DescendantRemoving { ancestor, .. } if ancestor.dom_ref == outer
+        )));
+
+        let events = insertion_events(&dom, inner, &listeners);
+        assert_eq!(events.len(), 5);
+        assert!(events.iter().all(|event| event.target() == middle));
+    }
+
+    #[test]
+    fn child_and_ancestry_events_are_only_created_for_listened_instances() {
+        let (dom, [_, middle, inner]) = create_dom();
+        let part = dom.get_by_ref(inner).unwrap().children()[0];
+        let listeners = listeners(&[
+            (middle, EventKind::ChildRemoved),
+            (part, EventKind::AncestryChanged),
+        ]);
+
+        let events = removal_events(&dom, inner, &listeners);
+        assert!(matches!(
+            events.as_slice(),
+            [InstanceEvent::ChildRemoved { parent, .. }] if parent.dom_ref == middle
+        ));
+
+        let events = insertion_events(&dom, inner, &listeners);
+        assert!(matches!(
+            events.as_slice(),
+            [InstanceEvent::AncestryChanged { instance, .. }] if instance.dom_ref == part
+        ));
+
+        let events = destroy_events(&dom, inner, &listeners);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[1],
+            InstanceEvent::Destroyed { instance } if instance == part
+        ));
+    }
+}
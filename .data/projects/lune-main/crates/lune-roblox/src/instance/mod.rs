@@ -4,7 +4,10 @@ use std::{
     collections::{BTreeMap, VecDeque},
     fmt,
     hash::{Hash, Hasher},
-    sync::{LazyLock, Mutex},
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
 #[cfg(feature = "mlua")]
@@ -19,7 +22,7 @@ use rbx_dom_weak::{
 #[cfg(feature = "mlua")]
 use lune_utils::TableBuilder;
 
-use crate::shared::instance::class_is_a;
+use crate::{query::Selector, shared::instance::class_is_a};
 
 #[cfg(feature = "mlua")]
 use crate::{exports::LuaExportsTable, shared::instance::class_exists};
@@ -27,14 +30,33 @@ use crate::{exports::LuaExportsTable, shared::instance::class_exists};
 #[cfg(feature = "mlua")]
 pub(crate) mod base;
 #[cfg(feature = "mlua")]
+pub(crate) mod base_part;
+#[cfg(feature = "mlua")]
 pub(crate) mod data_model;
 #[cfg(feature = "mlua")]
+pub(crate) mod model;
+#[cfg(feature = "mlua")]
+pub(crate) mod pv_instance;
+#[cfg(feature = "mlua")]
+pub(crate) mod spatial;
+#[cfg(feature = "mlua")]
 pub(crate) mod terrain;
 #[cfg(feature = "mlua")]
 pub(crate) mod workspace;
+#[cfg(feature = "mlua")]
+pub(crate) mod world_root;
 
 #[cfg(feature = "mlua")]
 pub mod registry;
+#[cfg(feature = "mlua")]
+pub mod signals;
+
+pub(crate) mod events;
+
+use events::{
+    InstanceEvent, current_listeners, destroy_events, events_enabled, insertion_events,
+    push_events, removal_events,
+};
 
 const PROPERTY_NAME_ATTRIBUTES: &str = "Attributes";
 const PROPERTY_NAME_TAGS: &str = "Tags";
@@ -42,6 +64,22 @@ const PROPERTY_NAME_TAGS: &str = "Tags";
 static INTERNAL_DOM: LazyLock<Mutex<WeakDom>> =
     LazyLock::new(|| Mutex::new(WeakDom::new(DomInstanceBuilder::new("ROOT"))));
 
+/*
+    Incremented whenever instances in the internal dom are moved, destroyed
+    or have their properties changed, so that any data derived from the dom,
+    such as spatial indexes, knows when it needs to be rebuilt
+*/
+static INTERNAL_DOM_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn mark_dom_changed() {
+    INTERNAL_DOM_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(feature = "mlua")]
+pub(crate) fn dom_generation() -> u64 {
+    INTERNAL_DOM_GENERATION.load(Ordering::Relaxed)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Instance {
     pub(crate) dom_ref: DomRef,
@@ -210,11 +248,30 @@ impl Instance {
         } else {
             let mut dom = INTERNAL_DOM.lock().expect("Failed to lock document");
 
+            let events = destroy_events(&dom, self.dom_ref, &current_listeners());
             dom.destroy(self.dom_ref);
+
+            drop(dom);
+            mark_dom_changed();
+            push_events(events);
             true
         }
     }
 
+    fn push_property_changed(&self, property: impl Into<String>) {
+        push_events([InstanceEvent::PropertyChanged {
+            instance: *self,
+            property: property.into(),
+        }]);
+    }
+
+    fn push_attribute_changed(&self, attribute: impl AsRef<str>) {
+        push_events([InstanceEvent::AttributeChanged {
+            instance: *self,
+            attribute: attribute.as_ref().to_string(),
+        }]);
+    }
+
     fn is_destroyed(&self) -> bool {
         // NOTE: This property can not be cached since instance references
         // other than this one may have destroyed this one, and we don't
@@ -239,9 +296,16 @@ impl Instance {
             .expect("Failed to find instance in document");
 
         let child_refs = instance.children().to_vec();
+        let listeners = current_listeners();
+        let mut events = Vec::new();
         for child_ref in child_refs {
+            events.extend(destroy_events(&dom, child_ref, &listeners));
             dom.destroy(child_ref);
         }
+
+        drop(dom);
+        mark_dom_changed();
+        push_events(events);
     }
 
     /**
@@ -295,9 +359,15 @@ impl Instance {
     pub fn set_name(&self, name: impl Into<String>) {
         let mut dom = INTERNAL_DOM.lock().expect("Failed to lock document");
 
-        dom.get_by_ref_mut(self.dom_ref)
-            .expect("Failed to find instance in document")
-            .name = name.into();
+        let name = name.into();
+        let inst = dom
+            .get_by_ref_mut(self.dom_ref)
+            .expect("Failed to find instance in document");
+        if inst.name != name {
+            inst.name = name;
+            drop(dom);
+            self.push_property_changed("Name");
+        }
     }
 
     /**
@@ -335,8 +405,29 @@ impl Instance {
         let mut dom = INTERNAL_DOM.lock().expect("Failed to lock document");
 
         let parent_ref = parent.map_or_else(|| dom.root_ref(), |parent| parent.dom_ref);
+        mark_dom_changed();
+
+        let listeners = current_listeners();
+        if listeners.is_empty() {
+            dom.transfer_within(self.dom_ref, parent_ref);
+            return;
+        }
+
+        let old_parent_ref = dom
+            .get_by_ref(self.dom_ref)
+            .expect("Failed to find instance in document")
+            .parent();
+        if old_parent_ref == parent_ref {
+            dom.transfer_within(self.dom_ref, parent_ref);
+            return;
+        }
 
+        let mut events = removal_events(&dom, self.dom_ref, &listeners);
         dom.transfer_within(self.dom_ref, parent_ref);
+        events.extend(insertion_events(&dom, self.dom_ref, &listeners));
+
+        drop(dom);
+        push_events(events);
     }
 
     /**
@@ -360,13 +451,58 @@ impl Instance {
         property does not actually exist for the instance class.
     */
     pub fn set_property(&self, name: impl AsRef<str>, value: DomValue) {
-        INTERNAL_DOM
+        let name = name.as_ref();
+        let new_value = events_enabled().then(|| value.clone());
+        let old_value = INTERNAL_DOM
             .lock()
             .expect("Failed to lock document")
             .get_by_ref_mut(self.dom_ref)
             .expect("Failed to find instance in document")
             .properties
-            .insert(ustr(name.as_ref()), value);
+            .insert(ustr(name), value);
+        mark_dom_changed();
+        if new_value.is_some() && old_value != new_value {
+            self.push_property_changed(name);
+        }
+    }
+
+    /**
+        Removes a property from the instance, if it is set.
+
+        Note that this does not have an equivalent in the Roblox engine API,
+        and that the property will use its default value when read from lua.
+    */
+    pub fn remove_property(&self, name: impl AsRef<str>) {
+        let name = name.as_ref();
+        let old_value = INTERNAL_DOM
+            .lock()
+            .expect("Failed to lock document")
+            .get_by_ref_mut(self.dom_ref)
+            .expect("Failed to find instance in document")
+            .properties
+            .remove(&ustr(name));
+        mark_dom_changed();
+        if old_value.is_some() {
+            self.push_property_changed(name);
+        }
+    }
+
+    /**
+        Gets all properties that are currently set for the instance.
+
+        Note that this does not include the name of the instance,
+        and that any property that has not been set is not included.
+    */
+    pub fn get_properties(&self) -> BTreeMap<String, DomValue> {
+        INTERNAL_DOM
+            .lock()
+            .expect("Failed to lock document")
+            .get_by_ref(self.dom_ref)
+            .expect("Failed to find instance in document")
+            .properties
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
     }
 
     /**
@@ -429,10 +565,15 @@ impl Instance {
             DomValue::Int64(i) => DomValue::Float64(i as f64),
             value => value,
         };
-        if let Some(DomValue::Attributes(attributes)) =
+        let changed = if let Some(DomValue::Attributes(attributes)) =
             inst.properties.get_mut(&ustr(PROPERTY_NAME_ATTRIBUTES))
         {
-            attributes.insert(name.as_ref().to_string(), value);
+            if attributes.get(name.as_ref()) == Some(&value) {
+                false
+            } else {
+                attributes.insert(name.as_ref().to_string(), value);
+                true
+            }
         } else {
             let mut attributes = DomAttributes::new();
             attributes.insert(name.as_ref().to_string(), value);
@@ -440,6 +581,11 @@ impl Instance {
                 ustr(PROPERTY_NAME_ATTRIBUTES),
                 DomValue::Attributes(attributes),
             );
+            true
+        };
+        drop(dom);
+        if changed {
+            self.push_attribute_changed(name);
         }
     }
 
@@ -459,10 +605,14 @@ impl Instance {
         if let Some(DomValue::Attributes(attributes)) =
             inst.properties.get_mut(&ustr(PROPERTY_NAME_ATTRIBUTES))
         {
-            attributes.remove(name.as_ref());
+            let removed = attributes.remove(name.as_ref()).is_some();
             if attributes.is_empty() {
                 inst.properties.remove(&ustr(PROPERTY_NAME_ATTRIBUTES));
             }
+            drop(dom);
+            if removed {
+                self.push_attribute_changed(name);
+            }
         }
     }
 
@@ -486,6 +636,8 @@ impl Instance {
                 DomValue::Tags(vec![name.as_ref().to_string()].into()),
             );
         }
+        drop(dom);
+        self.push_property_changed(PROPERTY_NAME_TAGS);
     }
 
     /**
@@ -545,6 +697,8 @@ impl Instance {
             new_tags.retain(|tag| tag != name);
             inst.properties
                 .insert(ustr(PROPERTY_NAME_TAGS), DomValue::Tags(new_tags.into()));
+            drop(dom);
+            self.push_property_changed(PROPERTY_NAME_TAGS);
         }
     }
 
@@ -730,6 +884,37 @@ impl Instance {
 
         None
     }
+
+    /**
+        Finds all descendants of the instance that match the given
+        selector, in the same order as a breadth-first search.
+
+        See [`Selector`] for a description of the selector syntax.
+        Instances that have been destroyed have no descendants to match.
+    */
+    #[must_use]
+    pub fn query_descendants(&self, selector: &Selector) -> Vec<Instance> {
+        let dom = INTERNAL_DOM.lock().expect("Failed to lock document");
+        let Some(inst) = dom.get_by_ref(self.dom_ref) else {
+            return Vec::new();
+        };
+
+        let mut found = Vec::new();
+        let mut queue = VecDeque::from_iter(inst.children());
+
+        while let Some(queue_item) = queue
+            .pop_front()
+            .and_then(|queue_ref| dom.get_by_ref(*queue_ref))
+        {
+            if selector.matches(&dom, self.dom_ref, queue_item) {
+                found.push(queue_item.referent());
+            }
+            queue.extend(queue_item.children());
+        }
+
+        drop(dom); // Self::new needs mutex handle, drop it first
+        found.into_iter().map(Self::new).collect()
+    }
 }
 
 #[cfg(feature = "mlua")]
@@ -774,8 +959,12 @@ impl LuaUserData for Instance {
 
     fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
         base::add_methods(methods);
+        base_part::add_methods(methods);
         data_model::add_methods(methods);
+        model::add_methods(methods);
+        pv_instance::add_methods(methods);
         terrain::add_methods(methods);
+        world_root::add_methods(methods);
     }
 }
 
@@ -805,6 +994,8 @@ impl PartialEq for Instance {
     }
 }
 
+impl Eq for Instance {}
+
 impl From<Instance> for DomRef {
     fn from(value: Instance) -> Self {
         value.dom_ref
@@ -0,0 +1,65 @@
+use glam::Vec3;
+use mlua::prelude::*;
+
+use crate::{
+    datatypes::types::{CFrame, Vector3},
+    shared::classes::add_class_restricted_method,
+};
+
+use super::{
+    Instance,
+    pv_instance::get_pivot,
+    spatial::{OrientedBox, bounding_box, get_descendant_part_bounds},
+};
+
+pub const CLASS_NAME: &str = "Model";
+
+pub fn add_methods<M: LuaUserDataMethods<Instance>>(methods: &mut M) {
+    add_class_restricted_method(
+        methods,
+        CLASS_NAME,
+        "GetBoundingBox",
+        model_get_bounding_box,
+    );
+    add_class_restricted_method(
+        methods,
+        CLASS_NAME,
+        "GetExtentsSize",
+        model_get_extents_size,
+    );
+}
+
+/**
+    Gets the smallest box that contains all descendant parts of the
+    model, rotated to match the rotation of the pivot of the model.
+
+    A model without any descendant parts has an empty box at its pivot.
+*/
+fn get_model_bounds(model: &Instance) -> OrientedBox {
+    let pivot = get_pivot(model);
+    bounding_box(&get_descendant_part_bounds(model), pivot)
+        .unwrap_or_else(|| OrientedBox::new(pivot, Vec3::ZERO))
+}
+
+/**
+    Gets the orientation, position and size of a box that contains all descendant parts of the model.
+
+    ### See Also
+    * [`GetBoundingBox`](https://create.roblox.com/docs/reference/engine/classes/Model#GetBoundingBox)
+      on the Roblox Developer Hub
+*/
+fn model_get_bounding_box(_: &Lua, this: &Instance, (): ()) -> LuaResult<(CFrame, Vector3)> {
+    let bounds = get_model_bounds(this);
+    Ok((CFrame(bounds.cframe), Vector3(bounds.size)))
+}
+
+/**
+    Gets the size of a box that contains all descendant parts of the model.
+
+    ### See Also
+    * [`GetExtentsSize`](https://create.roblox.com/docs/reference/engine/classes/Model#GetExtentsSize)
+      on the Roblox Developer Hub
+*/
+fn model_get_extents_size(_: &Lua, this: &Instance, (): ()) -> LuaResult<Vector3> {
+    Ok(Vector3(get_model_bounds(this).size))
+}
@@ -0,0 +1,125 @@
+use glam::Mat4;
+use mlua::prelude::*;
+use rbx_dom_weak::types::Variant as DomValue;
+
+use crate::{
+    datatypes::types::CFrame,
+    shared::classes::{add_class_restricted_method, add_class_restricted_method_mut},
+};
+
+use super::{
+    Instance,
+    signals::fire_pending_signals,
+    spatial::{bounding_box, get_descendant_part_bounds},
+};
+
+pub const CLASS_NAME: &str = "PVInstance";
+
+pub fn add_methods<M: LuaUserDataMethods<Instance>>(methods: &mut M) {
+    add_class_restricted_method(methods, CLASS_NAME, "GetPivot", pv_instance_get_pivot);
+    add_class_restricted_method_mut(methods, CLASS_NAME, "PivotTo", pv_instance_pivot_to);
+}
+
+fn get_cframe_property(instance: &Instance, name: &str) -> Option<Mat4> {
+    match instance.get_property(name) {
+        Some(DomValue::CFrame(cframe) | DomValue::OptionalCFrame(Some(cframe))) => {
+            Some(CFrame::from(cframe).0)
+        }
+        _ => None,
+    }
+}
+
+fn set_cframe_property(instance: &Instance, name: &str, cframe: Mat4) {
+    instance.set_property(name, DomValue::CFrame(CFrame(cframe).into()));
+}
+
+fn is_movable_part(instance: &Instance) -> bool {
+    instance.is_a("BasePart") && !instance.is_a("Terrain")
+}
+
+fn get_primary_part(instance: &Instance) -> Option<Instance> {
+    match instance.get_property("PrimaryPart") {
+        Some(DomValue::Ref(part_ref)) => {
+            Instance::new_opt(part_ref).filter(|part| part.is_a("BasePart"))
+        }
+        _ => None,
+    }
+}
+
+/**
+    Gets the pivot of a part or model, in world space.
+
+    The pivot of a part is its `CFrame` offset by its `PivotOffset`, and the pivot of a model
+    is the pivot of its `PrimaryPart`, its `WorldPivot`, or the center of its bounding box.
+*/
+pub(super) fn get_pivot(instance: &Instance) -> Mat4 {
+    if instance.is_a("BasePart") {
+        let cframe = get_cframe_property(instance, "CFrame").unwrap_or(Mat4::IDENTITY);
+        let offset = get_cframe_property(instance, "PivotOffset").unwrap_or(Mat4::IDENTITY);
+        return cframe * offset;
+    }
+    if let Some(primary_part) = get_primary_part(instance) {
+        return get_pivot(&primary_part);
+    }
+    if let Some(pivot) = get_cframe_property(instance, "WorldPivotData") {
+        return pivot;
+    }
+    bounding_box(&get_descendant_part_bounds(instance), Mat4::IDENTITY)
+        .map_or(Mat4::IDENTITY, |bounds| bounds.cframe)
+}
+
+/**
+    Gets the pivot of the instance.
+
+    ### See Also
+    * [`GetPivot`](https://create.roblox.com/docs/reference/engine/classes/PVInstance#GetPivot)
+      on the Roblox Developer Hub
+*/
+fn pv_instance_get_pivot(_: &Lua, this: &Instance, (): ()) -> LuaResult<CFrame> {
+    Ok(CFrame(get_pivot(this)))
+}
+
+/**
+    Moves the instance, and all of its descendant parts, so that its pivot is at the given `CFrame`.
+
+    ### See Also
+    * [`PivotTo`](https://create.roblox.com/docs/reference/engine/classes/PVInstance#PivotTo)
+      on the Roblox Developer Hub
+*/
+fn pv_instance_pivot_to(
+    lua: &Lua,
+    this: &mut Instance,
+    target: LuaUserDataRef<CFrame>,
+) -> LuaResult<()> {
+    let target = target.0;
+
+    if this.is_a("BasePart") {
+        let offset = get_cframe_property(this, "PivotOffset").unwrap_or(Mat4::IDENTITY);
+        set_cframe_property(this, "CFrame", target * offset.inverse());
+        return fire_pending_signals(lua);
+    }
+
+    let delta = target * get_pivot(this).inverse();
+    for descendant in this.get_descendants() {
+        if is_movable_part(&descendant) {
+            let cframe = get_cframe_property(&descendant, "CFrame").unwrap_or(Mat4::IDENTITY);
+            set_cframe_property(&descendant, "CFrame", delta * cframe);
+        } else if let Some(DomValue::OptionalCFrame(Some(pivot))) =
+            descendant.get_property("WorldPivotData")
+        {
+            let pivot = delta * CFrame::from(pivot).0;
+            descendant.set_property(
+                "WorldPivotData",
+                DomValue::OptionalCFrame(Some(CFrame(pivot).into())),
+            );
+        }
+    }
+    if get_primary_part(this).is_none() {
+        this.set_property(
+            "WorldPivotData",
+            DomValue::OptionalCFrame(Some(CFrame(target).into())),
+        );
+    }
+
+    fire_pending_signals(lua)
+}
@@ -0,0 +1,383 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use mlua::{AppDataRef, prelude::*};
+use mlua_luau_scheduler::LuaSchedulerExt;
+
+use rbx_dom_weak::types::Ref as DomRef;
+
+use super::{
+    Instance,
+    events::{
+        EventKind, EventQueue, InstanceEvent, create_event_queue, set_listeners, take_events,
+    },
+};
+
+/**
+    The kind of a signal that can be connected to on an instance.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalKind {
+    Changed,
+    PropertyChanged(String),
+    AttributeChanged,
+    ChildAdded,
+    ChildRemoved,
+    DescendantAdded,
+    DescendantRemoving,
+    AncestryChanged,
+}
+
+impl SignalKind {
+    /**
+        Gets the kind of signal for a signal property name, such as `ChildAdded`.
+
+        Returns `None` if the name is not a known signal property.
+    */
+    #[must_use]
+    pub fn from_property_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "Changed" => Self::Changed,
+            "AttributeChanged" => Self::AttributeChanged,
+            "ChildAdded" => Self::ChildAdded,
+            "ChildRemoved" => Self::ChildRemoved,
+            "DescendantAdded" => Self::DescendantAdded,
+            "DescendantRemoving" => Self::DescendantRemoving,
+            "AncestryChanged" => Self::AncestryChanged,
+            _ => return None,
+        })
+    }
+
+    fn event_kind(&self) -> EventKind {
+        match self {
+            Self::Changed | Self::PropertyChanged(_) => EventKind::PropertyChanged,
+            Self::AttributeChanged => EventKind::AttributeChanged,
+            Self::ChildAdded => EventKind::ChildAdded,
+            Self::ChildRemoved => EventKind::ChildRemoved,
+            Self::DescendantAdded => EventKind::DescendantAdded,
+            Self::DescendantRemoving => EventKind::DescendantRemoving,
+            Self::AncestryChanged => EventKind::AncestryChanged,
+        }
+    }
+
+    fn args_for_event(&self, event: &InstanceEvent) -> Option<SignalArgs> {
+        Some(match (self, event) {
+            (Self::Changed, InstanceEvent::PropertyChanged { property, .. })
+            | (
+                Self::AttributeChanged,
+                InstanceEvent::AttributeChanged {
+                    attribute: property,
+                    ..
+                },
+            ) => SignalArgs::Name(property.clone()),
+            (Self::PropertyChanged(name), InstanceEvent::PropertyChanged { property, .. })
+                if name == property =>
+            {
+                SignalArgs::None
+            }
+            (Self::ChildAdded, InstanceEvent::ChildAdded { child, .. })
+            | (Self::ChildRemoved, InstanceEvent::ChildRemoved { child, .. })
+            | (
+                Self::DescendantAdded,
+                InstanceEvent::DescendantAdded {
+                    descendant: child, ..
+                },
+            )
+            | (
+                Self::DescendantRemoving,
+                InstanceEvent::DescendantRemoving {
+                    descendant: child, ..
+                },
+            ) => SignalArgs::Instance(*child),
+            (Self::AncestryChanged, InstanceEvent::AncestryChanged { child, parent, .. }) => {
+                SignalArgs::Ancestry(*child, *parent)
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for SignalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Changed => write!(f, "Changed"),
+            Self::PropertyChanged(name) => write!(f, "{name}Changed"),
+            Self::AttributeChanged => write!(f, "AttributeChanged"),
+            Self::ChildAdded => write!(f, "ChildAdded"),
+            Self::ChildRemoved => write!(f, "ChildRemoved"),
+            Self::DescendantAdded => write!(f, "DescendantAdded"),
+            Self::DescendantRemoving => write!(f, "DescendantRemoving"),
+            Self::AncestryChanged => write!(f, "AncestryChanged"),
+        }
+    }
+}
+
+enum SignalArgs {
+    None,
+    Name(String),
+    Instance(Instance),
+    Ancestry(Instance, Option<Instance>),
+}
+
+impl IntoLuaMulti for SignalArgs {
+    fn into_lua_multi(self, lua: &Lua) -> LuaResult<LuaMultiValue> {
+        match self {
+            Self::None => ().into_lua_multi(lua),
+            Self::Name(name) => name.into_lua_multi(lua),
+            Self::Instance(instance) => instance.into_lua_multi(lua),
+            Self::Ancestry(child, parent) => (child, parent).into_lua_multi(lua),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SignalConnection {
+    id: u64,
+    kind: SignalKind,
+    callback: LuaRegistryKey,
+    once: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SignalRegistry {
+    next_id: Arc<Mutex<u64>>,
+    connections: Arc<Mutex<HashMap<DomRef, Vec<SignalConnection>>>>,
+    events: EventQueue,
+}
+
+impl SignalRegistry {
+    /**
+        Lets the event queue know which kinds of events are wanted for
+        an instance, after its connections have been changed.
+    */
+    fn sync_listeners(
+        &self,
+        connections: &HashMap<DomRef, Vec<SignalConnection>>,
+        dom_ref: DomRef,
+    ) {
+        let kinds = connections
+            .get(&dom_ref)
+            .into_iter()
+            .flatten()
+            .map(|connection| connection.kind.event_kind());
+        set_listeners(&self.events, dom_ref, kinds);
+    }
+
+    // NOTE: Just like the instance registry, the signal registry
+    // is created lazily, since most scripts never connect to any
+    // signals, and instance events are not recorded until they do,
+    // and stop being recorded for a Lua state once it gets dropped
+    fn get_or_create(lua: &Lua) -> AppDataRef<'_, Self> {
+        if lua.app_data_ref::<Self>().is_none() {
+            lua.set_app_data(Self {
+                next_id: Arc::new(Mutex::new(0)),
+                connections: Arc::new(Mutex::new(HashMap::new())),
+                events: create_event_queue(),
+            });
+        }
+        lua.app_data_ref::<Self>()
+            .expect("Missing SignalRegistry in app data")
+    }
+
+    fn connect(
+        lua: &Lua,
+        instance: &Instance,
+        kind: SignalKind,
+        callback: LuaFunction,
+        once: bool,
+    ) -> LuaResult<SignalConnectionRef> {
+        let registry = Self::get_or_create(lua);
+
+        let id = {
+            let mut next_id = registry
+                .next_id
+                .lock()
+                .expect("Failed to lock signal registry");
+            *next_id += 1;
+            *next_id
+        };
+
+        let callback = lua.create_registry_value(callback)?;
+        let mut connections = registry
+            .connections
+            .lock()
+            .expect("Failed to lock signal registry");
+        connections
+            .entry(instance.dom_ref)
+            .or_default()
+            .push(SignalConnection {
+                id,
+                kind,
+                callback,
+                once,
+            });
+        registry.sync_listeners(&connections, instance.dom_ref);
+
+        Ok(SignalConnectionRef {
+            instance: instance.dom_ref,
+            id,
+        })
+    }
+
+    fn is_connected(lua: &Lua, connection: &SignalConnectionRef) -> bool {
+        lua.app_data_ref::<Self>().is_some_and(|registry| {
+            registry
+                .connections
+                .lock()
+                .expect("Failed to lock signal registry")
+                .get(&connection.instance)
+                .is_some_and(|list| list.iter().any(|c| c.id == connection.id))
+        })
+    }
+
+    fn disconnect(lua: &Lua, connection: &SignalConnectionRef) {
+        if let Some(registry) = lua.app_data_ref::<Self>() {
+            let mut connections = registry
+                .connections
+                .lock()
+                .expect("Failed to lock signal registry");
+            if let Some(list) = connections.get_mut(&connection.instance) {
+                list.retain(|c| c.id != connection.id);
+                if list.is_empty() {
+                    connections.remove(&connection.instance);
+                }
+                registry.sync_listeners(&connections, connection.instance);
+            }
+        }
+    }
+}
+
+/**
+    A signal of an instance, such as `ChildAdded`, that can be connected to.
+
+    Connected callbacks are not called immediately when the signal fires,
+    they are deferred to run on the scheduler, just like `task.defer`.
+*/
+#[derive(Debug, Clone)]
+pub struct InstanceSignal {
+    instance: Instance,
+    kind: SignalKind,
+}
+
+impl InstanceSignal {
+    /**
+        Creates a new signal of the given kind for an instance.
+    */
+    #[must_use]
+    pub fn new(instance: Instance, kind: SignalKind) -> Self {
+        Self { instance, kind }
+    }
+}
+
+impl LuaUserData for InstanceSignal {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("Signal {}", this.kind))
+        });
+        methods.add_method("Connect", |lua, this, callback: LuaFunction| {
+            SignalRegistry::connect(lua, &this.instance, this.kind.clone(), callback, false)
+        });
+        methods.add_method("Once", |lua, this, callback: LuaFunction| {
+            SignalRegistry::connect(lua, &this.instance, this.kind.clone(), callback, true)
+        });
+    }
+}
+
+/**
+    A connection to an [`InstanceSignal`], which can be disconnected.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct SignalConnectionRef {
+    instance: DomRef,
+    id: u64,
+}
+
+impl LuaUserData for SignalConnectionRef {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("Connected", |lua, this| {
+            Ok(SignalRegistry::is_connected(lua, this))
+        });
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, _, ()| {
+            Ok("Connection".to_string())
+        });
+        methods.add_method("Disconnect", |lua, this, ()| {
+            SignalRegistry::disconnect(lua, this);
+            Ok(())
+        });
+    }
+}
+
+/**
+    Fires signals for all instance events that happened since the last call,
+    deferring any connected callbacks to run on the current scheduler.
+
+    This is called by the instance methods that may cause events, and must
+    also be called after modifying instances using the Rust API directly,
+    for connected callbacks to run.
+
+    # Errors
+
+    Errors if a connected callback can not be deferred.
+
+    # Panics
+
+    Panics if there are connections and this is called outside of a running scheduler.
+*/
+pub fn fire_pending_signals(lua: &Lua) -> LuaResult<()> {
+    let Some(registry) = lua.app_data_ref::<SignalRegistry>() else {
+        return Ok(());
+    };
+
+    let events = take_events(&registry.events);
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let mut calls = Vec::new();
+    {
+        let mut connections = registry
+            .connections
+            .lock()
+            .expect("Failed to lock signal registry");
+        for event in &events {
+            let target = event.target();
+            if matches!(event, InstanceEvent::Destroyed { .. }) {
+                connections.remove(&target);
+                registry.sync_listeners(&connections, target);
+                continue;
+            }
+            let Some(list) = connections.get_mut(&target) else {
+                continue;
+            };
+            let count = list.len();
+            list.retain(|connection| {
+                let Some(args) = connection.kind.args_for_event(event) else {
+                    return true;
+                };
+                calls.push((
+                    lua.registry_value::<LuaFunction>(&connection.callback),
+                    args,
+                ));
+                !connection.once
+            });
+            if list.len() != count {
+                if list.is_empty() {
+                    connections.remove(&target);
+                }
+                registry.sync_listeners(&connections, target);
+            }
+        }
+    }
+    drop(registry);
+
+    for (callback, args) in calls {
+        lua.push_thread_back(callback?, args)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,674 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    ops::Range,
+    sync::{LazyLock, Mutex},
+};
+
+use glam::{Mat4, Vec3};
+use rbx_dom_weak::{
+    Instance as DomInstance, WeakDom,
+    types::{Ref as DomRef, Variant as DomValue},
+    ustr,
+};
+
+use crate::{
+    datatypes::types::{CFrame, Vector3},
+    shared::instance::{class_is_a, find_property_info},
+};
+
+use super::{INTERNAL_DOM, Instance, dom_generation};
+
+const LEAF_SIZE: usize = 4;
+const EPSILON: f32 = 1e-6;
+
+/*
+    Spatial indexes for each world root that has been queried, which are
+    lazily built on the first query and rebuilt after the dom has changed
+*/
+static SPATIAL_INDEXES: LazyLock<Mutex<HashMap<DomRef, SpatialIndex>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/**
+    A box in world space, positioned and rotated by a `CFrame`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct OrientedBox {
+    pub cframe: Mat4,
+    pub size: Vec3,
+}
+
+impl OrientedBox {
+    pub fn new(cframe: Mat4, size: Vec3) -> Self {
+        Self { cframe, size }
+    }
+
+    fn center(&self) -> Vec3 {
+        self.cframe.w_axis.truncate()
+    }
+
+    fn half_size(&self) -> Vec3 {
+        self.size.abs() * 0.5
+    }
+
+    fn axes(&self) -> [Vec3; 3] {
+        [
+            self.cframe.x_axis.truncate(),
+            self.cframe.y_axis.truncate(),
+            self.cframe.z_axis.truncate(),
+        ]
+    }
+
+    fn world_to_local(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.center();
+        let [x, y, z] = self.axes();
+        Vec3::new(offset.dot(x), offset.dot(y), offset.dot(z))
+    }
+
+    /**
+        Gets the corners of the box, in world space.
+    */
+    pub fn corners(&self) -> [Vec3; 8] {
+        let half = self.half_size();
+        std::array::from_fn(|index| {
+            let sign = Vec3::new(
+                if index & 1 == 0 { -1.0 } else { 1.0 },
+                if index & 2 == 0 { -1.0 } else { 1.0 },
+                if index & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            self.cframe.transform_point3(sign * half)
+        })
+    }
+
+    fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.corners()).expect("Boxes always have corners")
+    }
+
+    /**
+        Gets the distance from the box to the given point,
+        which is zero if the point is inside of the box.
+    */
+    pub fn distance_to_point(&self, point: Vec3) -> f32 {
+        let local = self.world_to_local(point);
+        (local.abs() - self.half_size()).max(Vec3::ZERO).length()
+    }
+
+    /**
+        Checks if this box overlaps another box, using the separating axis theorem.
+    */
+    pub fn intersects(&self, other: &Self) -> bool {
+        let axes_a = self.axes();
+        let axes_b = other.axes();
+        let half_a = self.half_size();
+        let half_b = other.half_size();
+        let offset = other.center() - self.center();
+
+        let projected_radius = |axes: &[Vec3; 3], half: Vec3, axis: Vec3| {
+            half.x * axes[0].dot(axis).abs()
+                + half.y * axes[1].dot(axis).abs()
+                + half.z * axes[2].dot(axis).abs()
+        };
+
+        let cross_axes = axes_a
+            .iter()
+            .flat_map(|a| axes_b.iter().map(move |b| a.cross(*b)));
+        axes_a
+            .into_iter()
+            .chain(axes_b)
+            .chain(cross_axes)
+            .filter(|axis| axis.length_squared() > EPSILON)
+            .all(|axis| {
+                let radius_a = projected_radius(&axes_a, half_a, axis);
+                let radius_b = projected_radius(&axes_b, half_b, axis);
+                offset.dot(axis).abs() <= radius_a + radius_b
+            })
+    }
+
+    /**
+        Casts a ray against the box, where the length of the direction is the length of the ray.
+
+        Returns the fraction of the ray at which it enters the box, and the normal
+        of the surface it entered through, or `None` if the ray does not hit the box.
+        Rays that start inside of the box do not hit it.
+    */
+    pub fn raycast(&self, origin: Vec3, direction: Vec3) -> Option<(f32, Vec3)> {
+        let axes = self.axes();
+        let half = self.half_size();
+        let local_origin = self.world_to_local(origin);
+        let local_direction = Vec3::new(
+            direction.dot(axes[0]),
+            direction.dot(axes[1]),
+            direction.dot(axes[2]),
+        );
+
+        let mut enter = f32::NEG_INFINITY;
+        let mut exit = f32::INFINITY;
+        let mut normal = Vec3::ZERO;
+        for axis in 0..3 {
+            let (o, d, h) = (local_origin[axis], local_direction[axis], half[axis]);
+            if d.abs() < EPSILON {
+                if o.abs() > h {
+                    return None;
+                }
+                continue;
+            }
+            let (mut near, mut far) = ((-h - o) / d, (h - o) / d);
+            let mut sign = -1.0;
+            if near > far {
+                std::mem::swap(&mut near, &mut far);
+                sign = 1.0;
+            }
+            if near > enter {
+                enter = near;
+                normal = axes[axis] * sign;
+            }
+            exit = exit.min(far);
+            if enter > exit {
+                return None;
+            }
+        }
+
+        (0.0..=1.0).contains(&enter).then_some((enter, normal))
+    }
+}
+
+/**
+    Computes the smallest box with the given rotation that contains all of the given boxes.
+
+    Returns `None` if there are no boxes.
+*/
+pub(crate) fn bounding_box(boxes: &[OrientedBox], rotation: Mat4) -> Option<OrientedBox> {
+    let rotation = Mat4::from_quat(rotation.to_scale_rotation_translation().1);
+    let inverse = rotation.inverse();
+    let local = Aabb::from_points(
+        boxes
+            .iter()
+            .flat_map(OrientedBox::corners)
+            .map(|corner| inverse.transform_point3(corner)),
+    )?;
+    let center = rotation.transform_point3((local.min + local.max) * 0.5);
+    let cframe = Mat4::from_cols(
+        rotation.x_axis,
+        rotation.y_axis,
+        rotation.z_axis,
+        center.extend(1.0),
+    );
+    Some(OrientedBox::new(cframe, local.max - local.min))
+}
+
+/**
+    An axis-aligned box in world space.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        points.into_iter().fold(None, |aabb, point| {
+            Some(match aabb {
+                None => Self {
+                    min: point,
+                    max: point,
+                },
+                Some(Self { min, max }) => Self {
+                    min: min.min(point),
+                    max: max.max(point),
+                },
+            })
+        })
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /**
+        Gets the fraction of the ray at which it enters this box, if it hits it.
+    */
+    fn ray_enter(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        let mut enter = 0.0_f32;
+        let mut exit = 1.0_f32;
+        for axis in 0..3 {
+            let (o, d) = (origin[axis], direction[axis]);
+            if d.abs() < EPSILON {
+                if o < self.min[axis] || o > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let near = (self.min[axis] - o) / d;
+            let far = (self.max[axis] - o) / d;
+            enter = enter.max(near.min(far));
+            exit = exit.min(near.max(far));
+            if enter > exit {
+                return None;
+            }
+        }
+        Some(enter)
+    }
+}
+
+/**
+    Filters parts by whether or not they are a descendant of any given instance.
+*/
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpatialFilter {
+    pub instances: HashSet<DomRef>,
+    pub include: bool,
+    pub max_parts: Option<usize>,
+}
+
+impl SpatialFilter {
+    fn allows(&self, dom: &WeakDom, part: DomRef) -> bool {
+        let mut current = dom.get_by_ref(part);
+        let mut matched = false;
+        while let Some(inst) = current {
+            if self.instances.contains(&inst.referent()) {
+                matched = true;
+                break;
+            }
+            current = dom.get_by_ref(inst.parent());
+        }
+        matched == self.include
+    }
+}
+
+/**
+    The closest part that was hit by a raycast.
+*/
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RaycastHit {
+    pub part: Instance,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    part: DomRef,
+    order: usize,
+    bounds: OrientedBox,
+    aabb: Aabb,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Leaf(Range<usize>),
+    Branch(usize, usize),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    aabb: Aabb,
+    kind: NodeKind,
+}
+
+/**
+    A bounding volume hierarchy over all of the parts in a world root.
+*/
+#[derive(Debug, Clone)]
+struct SpatialIndex {
+    generation: u64,
+    entries: Vec<Entry>,
+    nodes: Vec<Node>,
+}
+
+impl SpatialIndex {
+    fn build(dom: &WeakDom, root: DomRef, generation: u64) -> Self {
+        let mut entries = Vec::new();
+        let mut queue: VecDeque<DomRef> = VecDeque::new();
+        if let Some(root) = dom.get_by_ref(root) {
+            queue.extend(root.children().iter().copied());
+        }
+        while let Some(inst) = queue.pop_front().and_then(|r| dom.get_by_ref(r)) {
+            if is_spatial_part(inst) {
+                let bounds = part_bounds_in_dom(inst);
+                entries.push(Entry {
+                    part: inst.referent(),
+                    order: entries.len(),
+                    bounds,
+                    aabb: bounds.aabb(),
+                });
+            }
+            queue.extend(inst.children().iter().copied());
+        }
+
+        let mut nodes = Vec::new();
+        if !entries.is_empty() {
+            build_node(&mut entries, 0, &mut nodes);
+        }
+
+        Self {
+            generation,
+            entries,
+            nodes,
+        }
+    }
+
+    fn query(&self, aabb: &Aabb, mut visit: impl FnMut(&Entry)) {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+            match &node.kind {
+                NodeKind::Leaf(range) => {
+                    for entry in &self.entries[range.clone()] {
+                        if entry.aabb.intersects(aabb) {
+                            visit(entry);
+                        }
+                    }
+                }
+                NodeKind::Branch(left, right) => stack.extend([*left, *right]),
+            }
+        }
+    }
+
+    fn raycast(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        mut allows: impl FnMut(&Entry) -> bool,
+    ) -> Option<(&Entry, f32, Vec3)> {
+        let mut closest: Option<(&Entry, f32, Vec3)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let Some(enter) = node.aabb.ray_enter(origin, direction) else {
+                continue;
+            };
+            if closest.is_some_and(|(_, fraction, _)| enter > fraction) {
+                continue;
+            }
+            match &node.kind {
+                NodeKind::Leaf(range) => {
+                    for entry in &self.entries[range.clone()] {
+                        if let Some((fraction, normal)) = entry.bounds.raycast(origin, direction)
+                            && closest.is_none_or(|(_, closest, _)| fraction < closest)
+                            && allows(entry)
+                        {
+                            closest = Some((entry, fraction, normal));
+                        }
+                    }
+                }
+                NodeKind::Branch(left, right) => stack.extend([*left, *right]),
+            }
+        }
+        closest
+    }
+}
+
+fn build_node(entries: &mut [Entry], offset: usize, nodes: &mut Vec<Node>) -> usize {
+    let aabb = entries
+        .iter()
+        .map(|entry| entry.aabb)
+        .reduce(|a, b| a.union(&b))
+        .expect("Nodes are never empty");
+
+    let index = nodes.len();
+    if entries.len() <= LEAF_SIZE {
+        nodes.push(Node {
+            aabb,
+            kind: NodeKind::Leaf(offset..offset + entries.len()),
+        });
+        return index;
+    }
+
+    // Split the entries in half along the longest axis of their centers
+    let centers = Aabb::from_points(entries.iter().map(|entry| entry.aabb.center()))
+        .expect("Nodes are never empty");
+    let extent = centers.max - centers.min;
+    let axis = if extent.x >= extent.y && extent.x >= extent.z {
+        0
+    } else if extent.y >= extent.z {
+        1
+    } else {
+        2
+    };
+    let middle = entries.len() / 2;
+    entries.select_nth_unstable_by(middle, |a, b| {
+        a.aabb.center()[axis].total_cmp(&b.aabb.center()[axis])
+    });
+
+    nodes.push(Node {
+        aabb,
+        kind: NodeKind::Leaf(0..0),
+    });
+    let (left_entries, right_entries) = entries.split_at_mut(middle);
+    let left = build_node(left_entries, offset, nodes);
+    let right = build_node(right_entries, offset + middle, nodes);
+    nodes[index].kind = NodeKind::Branch(left, right);
+    index
+}
+
+fn is_spatial_part(inst: &DomInstance) -> bool {
+    class_is_a(inst.class, "BasePart").unwrap_or(false)
+        && !class_is_a(inst.class, "Terrain").unwrap_or(false)
+}
+
+fn part_bounds_in_dom(inst: &DomInstance) -> OrientedBox {
+    let cframe = match inst.properties.get(&ustr("CFrame")) {
+        Some(DomValue::CFrame(cframe)) => CFrame::from(*cframe).0,
+        _ => Mat4::IDENTITY,
+    };
+    let size = match inst.properties.get(&ustr("Size")) {
+        Some(DomValue::Vector3(size)) => Vector3::from(*size).0,
+        _ => match find_property_info(inst.class, "Size").and_then(|info| info.value_default) {
+            Some(DomValue::Vector3(size)) => Vector3::from(*size).0,
+            _ => Vec3::ONE,
+        },
+    };
+    OrientedBox::new(cframe, size)
+}
+
+/**
+    Runs the given function with the spatial index for the
+    given world root, building the index first if necessary.
+*/
+fn with_spatial_index<R>(root: &Instance, f: impl FnOnce(&WeakDom, &SpatialIndex) -> R) -> R {
+    let mut indexes = SPATIAL_INDEXES
+        .lock()
+        .expect("Failed to lock spatial indexes");
+    let dom = INTERNAL_DOM.lock().expect("Failed to lock document");
+
+    let generation = dom_generation();
+    indexes.retain(|_, index| index.generation == generation);
+    let index = indexes
+        .entry(root.dom_ref)
+        .or_insert_with(|| SpatialIndex::build(&dom, root.dom_ref, generation));
+
+    f(&dom, index)
+}
+
+/**
+    Gets the bounds of a single part.
+*/
+pub(crate) fn get_part_bounds(part: &Instance) -> OrientedBox {
+    let dom = INTERNAL_DOM.lock().expect("Failed to lock document");
+    part_bounds_in_dom(
+        dom.get_by_ref(part.dom_ref)
+            .expect("Failed to find instance in document"),
+    )
+}
+
+/**
+    Gets the bounds of all parts that are descendants of the given instance.
+*/
+pub(crate) fn get_descendant_part_bounds(root: &Instance) -> Vec<OrientedBox> {
+    let dom = INTERNAL_DOM.lock().expect("Failed to lock document");
+    let mut bounds = Vec::new();
+    let mut queue = VecDeque::from_iter(
+        dom.get_by_ref(root.dom_ref)
+            .expect("Failed to find instance in document")
+            .children(),
+    );
+    while let Some(inst) = queue.pop_front().and_then(|r| dom.get_by_ref(*r)) {
+        if is_spatial_part(inst) {
+            bounds.push(part_bounds_in_dom(inst));
+        }
+        queue.extend(inst.children());
+    }
+    bounds
+}
+
+/**
+    Finds all parts under the given world root that overlap the given box.
+*/
+pub(crate) fn get_parts_in_box(
+    root: &Instance,
+    bounds: &OrientedBox,
+    filter: &SpatialFilter,
+) -> Vec<Instance> {
+    let aabb = bounds.aabb();
+    let found = with_spatial_index(root, |dom, index| {
+        let mut found = Vec::new();
+        index.query(&aabb, |entry| {
+            if entry.bounds.intersects(bounds) && filter.allows(dom, entry.part) {
+                found.push((entry.order, entry.part));
+            }
+        });
+        found
+    });
+    into_instances(found, filter.max_parts)
+}
+
+/**
+    Finds all parts under the given world root that overlap the given sphere.
+*/
+pub(crate) fn get_parts_in_radius(
+    root: &Instance,
+    center: Vec3,
+    radius: f32,
+    filter: &SpatialFilter,
+) -> Vec<Instance> {
+    let aabb = Aabb {
+        min: center - Vec3::splat(radius),
+        max: center + Vec3::splat(radius),
+    };
+    let found = with_spatial_index(root, |dom, index| {
+        let mut found = Vec::new();
+        index.query(&aabb, |entry| {
+            if entry.bounds.distance_to_point(center) <= radius && filter.allows(dom, entry.part) {
+                found.push((entry.order, entry.part));
+            }
+        });
+        found
+    });
+    into_instances(found, filter.max_parts)
+}
+
+/**
+    Casts a ray against the bounding boxes of all parts under the given
+    world root, and returns the closest part that was hit, if any.
+*/
+pub(crate) fn raycast(
+    root: &Instance,
+    origin: Vec3,
+    direction: Vec3,
+    filter: &SpatialFilter,
+) -> Option<RaycastHit> {
+    let (part, fraction, normal) = with_spatial_index(root, |dom, index| {
+        index
+            .raycast(origin, direction, |entry| filter.allows(dom, entry.part))
+            .map(|(entry, fraction, normal)| (entry.part, fraction, normal))
+    })?;
+    Some(RaycastHit {
+        part: Instance::new(part),
+        position: origin + direction * fraction,
+        normal,
+        distance: direction.length() * fraction,
+    })
+}
+
+fn into_instances(mut found: Vec<(usize, DomRef)>, max_parts: Option<usize>) -> Vec<Instance> {
+    found.sort_unstable_by_key(|(order, _)| *order);
+    found
+        .into_iter()
+        .take(max_parts.unwrap_or(usize::MAX))
+        .map(|(_, part)| Instance::new(part))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Vec3};
+
+    use super::{OrientedBox, bounding_box};
+
+    fn block(position: Vec3, size: Vec3) -> OrientedBox {
+        OrientedBox::new(Mat4::from_translation(position), size)
+    }
+
+    #[test]
+    fn intersects_boxes() {
+        let a = block(Vec3::ZERO, Vec3::splat(2.0));
+        assert!(a.intersects(&block(Vec3::new(1.5, 0.0, 0.0), Vec3::splat(2.0))));
+        assert!(!a.intersects(&block(Vec3::new(3.0, 0.0, 0.0), Vec3::splat(2.0))));
+
+        // Rotated 45 degrees, the corner of this box reaches
+        // further than its size along the x axis would suggest
+        let rotated = OrientedBox::new(
+            Mat4::from_translation(Vec3::new(2.3, 0.0, 0.0))
+                * Mat4::from_rotation_y(std::f32::consts::FRAC_PI_4),
+            Vec3::splat(2.0),
+        );
+        assert!(a.intersects(&rotated));
+    }
+
+    #[test]
+    fn raycasts_box() {
+        let a = block(Vec3::new(0.0, 0.0, 10.0), Vec3::splat(2.0));
+        let (fraction, normal) = a
+            .raycast(Vec3::ZERO, Vec3::new(0.0, 0.0, 20.0))
+            .expect("Ray should hit the box");
+        assert!((fraction - 0.45).abs() < 1e-5);
+        assert_eq!(normal, Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(a.raycast(Vec3::ZERO, Vec3::new(0.0, 0.0, 5.0)).is_none());
+        assert!(a.raycast(Vec3::ZERO, Vec3::new(0.0, 20.0, 0.0)).is_none());
+        assert!(
+            a.raycast(Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, 20.0))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn distance_to_box() {
+        let a = block(Vec3::ZERO, Vec3::splat(2.0));
+        assert!(a.distance_to_point(Vec3::ZERO).abs() < 1e-5);
+        assert!((a.distance_to_point(Vec3::new(4.0, 0.0, 0.0)) - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bounds_of_boxes() {
+        let boxes = [
+            block(Vec3::new(-2.0, 0.0, 0.0), Vec3::splat(2.0)),
+            block(Vec3::new(4.0, 1.0, 0.0), Vec3::splat(2.0)),
+        ];
+        let bounds = bounding_box(&boxes, Mat4::IDENTITY).expect("Boxes are not empty");
+        assert_eq!(bounds.size, Vec3::new(8.0, 3.0, 2.0));
+        assert_eq!(bounds.cframe.w_axis.truncate(), Vec3::new(1.0, 0.5, 0.0));
+        assert!(bounding_box(&[], Mat4::IDENTITY).is_none());
+    }
+}
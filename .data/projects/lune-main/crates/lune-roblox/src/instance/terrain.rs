@@ -1,15 +1,27 @@
+use std::ops::Range;
+
+use glam::{Mat4, Vec3};
 use mlua::prelude::*;
 use rbx_dom_weak::types::{MaterialColors, TerrainMaterials, Variant};
 
 use crate::{
-    datatypes::types::{Color3, EnumItem},
+    datatypes::types::{CFrame, Color3, EnumItem, Region3, Vector3},
     shared::classes::{add_class_restricted_method, add_class_restricted_method_mut},
+    terrain::{TerrainGrid, TerrainMaterial, VOXEL_SIZE, Voxel, VoxelPosition},
 };
 
-use super::Instance;
+use super::{Instance, signals::fire_pending_signals};
 
 pub const CLASS_NAME: &str = "Terrain";
 
+const PROPERTY_NAME_SMOOTH_GRID: &str = "SmoothGrid";
+
+/**
+    The number of samples taken along each axis of a voxel to find how much of it
+    is covered by a block, for voxels that are only partially inside of the block.
+*/
+const FILL_SAMPLES: u8 = 8;
+
 pub fn add_methods<M: LuaUserDataMethods<Instance>>(methods: &mut M) {
     add_class_restricted_method(
         methods,
@@ -24,6 +36,13 @@ pub fn add_methods<M: LuaUserDataMethods<Instance>>(methods: &mut M) {
         "SetMaterialColor",
         terrain_set_material_color,
     );
+
+    add_class_restricted_method(methods, CLASS_NAME, "ReadVoxels", terrain_read_voxels);
+    add_class_restricted_method(methods, CLASS_NAME, "CountCells", terrain_count_cells);
+
+    add_class_restricted_method_mut(methods, CLASS_NAME, "WriteVoxels", terrain_write_voxels);
+    add_class_restricted_method_mut(methods, CLASS_NAME, "FillBlock", terrain_fill_block);
+    add_class_restricted_method_mut(methods, CLASS_NAME, "Clear", terrain_clear);
 }
 
 fn get_or_create_material_colors(instance: &Instance) -> MaterialColors {
@@ -91,3 +110,284 @@ fn terrain_set_material_color(
     this.set_property("MaterialColors", Variant::MaterialColors(material_colors));
     Ok(())
 }
+
+fn get_terrain_grid(instance: &Instance) -> LuaResult<TerrainGrid> {
+    if let Some(Variant::BinaryString(inner)) = instance.get_property(PROPERTY_NAME_SMOOTH_GRID) {
+        Ok(TerrainGrid::decode(inner)?)
+    } else {
+        Ok(TerrainGrid::new())
+    }
+}
+
+fn set_terrain_grid(lua: &Lua, instance: &Instance, grid: &TerrainGrid) -> LuaResult<()> {
+    instance.set_property(
+        PROPERTY_NAME_SMOOTH_GRID,
+        Variant::BinaryString(grid.encode().into()),
+    );
+    fire_pending_signals(lua)
+}
+
+fn terrain_material_from_enum(material: &EnumItem) -> LuaResult<TerrainMaterial> {
+    if &material.parent.desc.name != "Material" {
+        return Err(LuaError::RuntimeError(format!(
+            "Expected Enum.Material, got Enum.{}",
+            &material.parent.desc.name
+        )));
+    }
+
+    TerrainMaterial::from_name(&material.name).ok_or_else(|| {
+        LuaError::RuntimeError(format!(
+            "Enum.Material.{} is not a valid terrain material",
+            material.name
+        ))
+    })
+}
+
+fn region_to_voxel_ranges(region: &Region3, resolution: f32) -> LuaResult<[Range<i32>; 3]> {
+    if (resolution - VOXEL_SIZE).abs() > f32::EPSILON {
+        return Err(LuaError::RuntimeError(format!(
+            "Resolution must be {VOXEL_SIZE}, got {resolution}"
+        )));
+    }
+
+    let min = region.min / VOXEL_SIZE;
+    let max = region.max / VOXEL_SIZE;
+    if min != min.round() || max != max.round() {
+        return Err(LuaError::RuntimeError(
+            "Region has to be aligned to the grid (use Region3:ExpandToGrid)".to_string(),
+        ));
+    }
+
+    let min = min.round().as_ivec3();
+    let max = max.round().as_ivec3();
+    Ok([min.x..max.x, min.y..max.y, min.z..max.z])
+}
+
+fn voxel_ranges_size(ranges: &[Range<i32>; 3]) -> Vector3 {
+    let [x, y, z] = ranges.clone().map(|range| range.len() as f32);
+    Vector3(Vec3::new(x, y, z))
+}
+
+/**
+    Reads the materials and occupancies of all voxels in the given region.
+
+    Both returned tables are indexed as `[x][y][z]` and contain a `Size` field.
+
+    ### See Also
+    * [`ReadVoxels`](https://create.roblox.com/docs/reference/engine/classes/Terrain#ReadVoxels)
+      on the Roblox Developer Hub
+*/
+fn terrain_read_voxels(
+    lua: &Lua,
+    this: &Instance,
+    (region, resolution): (LuaUserDataRef<Region3>, f32),
+) -> LuaResult<(LuaTable, LuaTable)> {
+    let ranges = region_to_voxel_ranges(&region, resolution)?;
+    let grid = get_terrain_grid(this)?;
+
+    let material_items = TerrainMaterial::ALL
+        .into_iter()
+        .map(|material| {
+            EnumItem::from_enum_name_and_name("Material", material.name()).ok_or_else(|| {
+                LuaError::RuntimeError(format!("Missing Enum.Material.{}", material.name()))
+            })
+        })
+        .collect::<LuaResult<Vec<_>>>()?;
+
+    let [xs, ys, zs] = ranges.clone();
+    let materials = lua.create_table_with_capacity(xs.len(), 1)?;
+    let occupancies = lua.create_table_with_capacity(xs.len(), 1)?;
+    for x in xs {
+        let materials_x = lua.create_table_with_capacity(ys.len(), 0)?;
+        let occupancies_x = lua.create_table_with_capacity(ys.len(), 0)?;
+        for y in ys.clone() {
+            let materials_y = lua.create_table_with_capacity(zs.len(), 0)?;
+            let occupancies_y = lua.create_table_with_capacity(zs.len(), 0)?;
+            for z in zs.clone() {
+                let voxel = grid.get([x, y, z]);
+                materials_y.push(material_items[usize::from(voxel.material().id())].clone())?;
+                occupancies_y.push(f32::from(voxel.occupancy()) / f32::from(u8::MAX))?;
+            }
+            materials_x.push(materials_y)?;
+            occupancies_x.push(occupancies_y)?;
+        }
+        materials.push(materials_x)?;
+        occupancies.push(occupancies_x)?;
+    }
+
+    let size = voxel_ranges_size(&ranges);
+    materials.set("Size", size)?;
+    occupancies.set("Size", size)?;
+
+    Ok((materials, occupancies))
+}
+
+/**
+    Writes the materials and occupancies of all voxels in the given region.
+
+    Both given tables must be indexed as `[x][y][z]` and match the size of the region.
+
+    ### See Also
+    * [`WriteVoxels`](https://create.roblox.com/docs/reference/engine/classes/Terrain#WriteVoxels)
+      on the Roblox Developer Hub
+*/
+fn terrain_write_voxels(
+    lua: &Lua,
+    this: &mut Instance,
+    (region, resolution, materials, occupancies): (
+        LuaUserDataRef<Region3>,
+        f32,
+        LuaTable,
+        LuaTable,
+    ),
+) -> LuaResult<()> {
+    let [xs, ys, zs] = region_to_voxel_ranges(&region, resolution)?;
+    let mut grid = get_terrain_grid(this)?;
+
+    for (ix, x) in xs.enumerate() {
+        let materials_x: LuaTable = materials.get(ix + 1)?;
+        let occupancies_x: LuaTable = occupancies.get(ix + 1)?;
+        for (iy, y) in ys.clone().enumerate() {
+            let materials_y: LuaTable = materials_x.get(iy + 1)?;
+            let occupancies_y: LuaTable = occupancies_x.get(iy + 1)?;
+            for (iz, z) in zs.clone().enumerate() {
+                let material: EnumItem = materials_y.get(iz + 1)?;
+                let occupancy: f32 = occupancies_y.get(iz + 1)?;
+                let occupancy = (occupancy.clamp(0.0, 1.0) * f32::from(u8::MAX)).round() as u8;
+                let voxel = Voxel::new(terrain_material_from_enum(&material)?, occupancy);
+                grid.set([x, y, z], voxel);
+            }
+        }
+    }
+
+    set_terrain_grid(lua, this, &grid)
+}
+
+/**
+    Fills all voxels inside of the given block with the given material.
+
+    Voxels that are only partially inside of the block are given an occupancy
+    matching how much of them is covered by the block, and filling with air
+    removes that much occupancy from any existing voxels instead.
+
+    ### See Also
+    * [`FillBlock`](https://create.roblox.com/docs/reference/engine/classes/Terrain#FillBlock)
+      on the Roblox Developer Hub
+*/
+fn terrain_fill_block(
+    lua: &Lua,
+    this: &mut Instance,
+    (cframe, size, material): (LuaUserDataRef<CFrame>, LuaUserDataRef<Vector3>, EnumItem),
+) -> LuaResult<()> {
+    let material = terrain_material_from_enum(&material)?;
+    let mut grid = get_terrain_grid(this)?;
+
+    let half_size = size.0.abs() / 2.0;
+    let inverse = cframe.0.inverse();
+
+    // Find the axis-aligned bounds of the block, in voxels,
+    // and then fill voxels by how much the block covers them
+    let mut min = Vec3::INFINITY;
+    let mut max = Vec3::NEG_INFINITY;
+    for corner in box_corners(half_size) {
+        let corner = cframe.0.transform_point3(corner);
+        min = min.min(corner);
+        max = max.max(corner);
+    }
+    let min = (min / VOXEL_SIZE).floor().as_ivec3();
+    let max = (max / VOXEL_SIZE).ceil().as_ivec3();
+
+    for x in min.x..max.x {
+        for y in min.y..max.y {
+            for z in min.z..max.z {
+                let position: VoxelPosition = [x, y, z];
+                let voxel_min = Vec3::new(x as f32, y as f32, z as f32) * VOXEL_SIZE;
+                let coverage = block_coverage(&inverse, half_size, voxel_min);
+                let occupancy = (coverage * f32::from(u8::MAX)).round() as u8;
+                if occupancy == 0 {
+                    continue;
+                }
+
+                let existing = grid.get(position);
+                let voxel = if material == TerrainMaterial::Air {
+                    Voxel::new(
+                        existing.material(),
+                        existing.occupancy().saturating_sub(occupancy),
+                    )
+                } else {
+                    Voxel::new(material, existing.occupancy().max(occupancy))
+                };
+                grid.set(position, voxel);
+            }
+        }
+    }
+
+    set_terrain_grid(lua, this, &grid)
+}
+
+fn box_corners(half_size: Vec3) -> impl Iterator<Item = Vec3> {
+    [-1.0, 1.0]
+        .into_iter()
+        .flat_map(|x| [-1.0, 1.0].map(|y| (x, y)))
+        .flat_map(|(x, y)| [-1.0, 1.0].map(|z| Vec3::new(x, y, z)))
+        .map(move |corner| corner * half_size)
+}
+
+/**
+    Finds how much of the voxel starting at `voxel_min` is covered by
+    a block, given the inverse of its transform and half of its size.
+
+    Voxels that are completely inside or outside of the block are found
+    directly, any other voxel is sampled at evenly spaced points.
+*/
+fn block_coverage(inverse: &Mat4, half_size: Vec3, voxel_min: Vec3) -> f32 {
+    let half_voxel = Vec3::splat(VOXEL_SIZE / 2.0);
+    let is_inside = |point: Vec3| inverse.transform_point3(point).abs().cmple(half_size).all();
+
+    // The block can not reach the voxel if the voxel center is further away
+    // from any face of the block than the distance to a corner of the voxel
+    let center = inverse.transform_point3(voxel_min + half_voxel);
+    let reach = half_size + Vec3::splat(half_voxel.length());
+    if center.abs().cmpgt(reach).any() {
+        return 0.0;
+    }
+    if box_corners(half_voxel).all(|corner| is_inside(voxel_min + half_voxel + corner)) {
+        return 1.0;
+    }
+
+    let step = VOXEL_SIZE / f32::from(FILL_SAMPLES);
+    let mut inside = 0u32;
+    for x in 0..FILL_SAMPLES {
+        for y in 0..FILL_SAMPLES {
+            for z in 0..FILL_SAMPLES {
+                let offset = (Vec3::new(f32::from(x), f32::from(y), f32::from(z)) + 0.5) * step;
+                if is_inside(voxel_min + offset) {
+                    inside += 1;
+                }
+            }
+        }
+    }
+    inside as f32 / f32::from(FILL_SAMPLES).powi(3)
+}
+
+/**
+    Removes all voxels from the terrain.
+
+    ### See Also
+    * [`Clear`](https://create.roblox.com/docs/reference/engine/classes/Terrain#Clear)
+      on the Roblox Developer Hub
+*/
+fn terrain_clear(lua: &Lua, this: &mut Instance, (): ()) -> LuaResult<()> {
+    set_terrain_grid(lua, this, &TerrainGrid::new())
+}
+
+/**
+    Counts the number of voxels in the terrain that are not empty.
+
+    ### See Also
+    * [`CountCells`](https://create.roblox.com/docs/reference/engine/classes/Terrain#CountCells)
+      on the Roblox Developer Hub
+*/
+fn terrain_count_cells(_: &Lua, this: &Instance, (): ()) -> LuaResult<usize> {
+    Ok(get_terrain_grid(this)?.count_cells())
+}
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+
+use mlua::prelude::*;
+use rbx_dom_weak::types::Variant as DomValue;
+
+use lune_utils::TableBuilder;
+
+use crate::{
+    datatypes::types::{CFrame, EnumItem, Vector3},
+    shared::{classes::add_class_restricted_method, instance::find_property_info},
+};
+
+use super::{
+    Instance,
+    spatial::{OrientedBox, SpatialFilter, get_parts_in_box, get_parts_in_radius, raycast},
+};
+
+pub const CLASS_NAME: &str = "WorldRoot";
+
+pub fn add_methods<M: LuaUserDataMethods<Instance>>(methods: &mut M) {
+    add_class_restricted_method(
+        methods,
+        CLASS_NAME,
+        "GetPartBoundsInBox",
+        world_root_get_part_bounds_in_box,
+    );
+    add_class_restricted_method(
+        methods,
+        CLASS_NAME,
+        "GetPartBoundsInRadius",
+        world_root_get_part_bounds_in_radius,
+    );
+    add_class_restricted_method(methods, CLASS_NAME, "Raycast", world_root_raycast);
+}
+
+/**
+    Parses a table with the same fields as `OverlapParams` or `RaycastParams` into a filter.
+*/
+fn parse_spatial_filter(params: Option<LuaTable>) -> LuaResult<SpatialFilter> {
+    let Some(params) = params else {
+        return Ok(SpatialFilter::default());
+    };
+
+    let instances = params
+        .get::<Option<Vec<LuaUserDataRef<Instance>>>>("FilterDescendantsInstances")?
+        .unwrap_or_default()
+        .iter()
+        .map(|instance| instance.dom_ref)
+        .collect::<HashSet<_>>();
+
+    let include = match params.get::<Option<EnumItem>>("FilterType")? {
+        None => false,
+        Some(item) if item.parent.desc.name == "RaycastFilterType" => {
+            matches!(item.name.as_str(), "Include" | "Whitelist")
+        }
+        Some(item) => {
+            return Err(LuaError::RuntimeError(format!(
+                "Expected Enum.RaycastFilterType, got Enum.{}",
+                item.parent.desc.name
+            )));
+        }
+    };
+
+    let max_parts = params
+        .get::<Option<usize>>("MaxParts")?
+        .filter(|max_parts| *max_parts > 0);
+
+    Ok(SpatialFilter {
+        instances,
+        include,
+        max_parts,
+    })
+}
+
+/**
+    Gets all parts under this world root whose bounding boxes overlap the given box.
+
+    The optional parameters are a table with the same fields as `OverlapParams`.
+
+    ### See Also
+    * [`GetPartBoundsInBox`](https://create.roblox.com/docs/reference/engine/classes/WorldRoot#GetPartBoundsInBox)
+      on the Roblox Developer Hub
+*/
+fn world_root_get_part_bounds_in_box(
+    _: &Lua,
+    this: &Instance,
+    (cframe, size, params): (
+        LuaUserDataRef<CFrame>,
+        LuaUserDataRef<Vector3>,
+        Option<LuaTable>,
+    ),
+) -> LuaResult<Vec<Instance>> {
+    let filter = parse_spatial_filter(params)?;
+    let bounds = OrientedBox::new(cframe.0, size.0);
+    Ok(get_parts_in_box(this, &bounds, &filter))
+}
+
+/**
+    Gets all parts under this world root whose bounding boxes overlap the given sphere.
+
+    The optional parameters are a table with the same fields as `OverlapParams`.
+
+    ### See Also
+    * [`GetPartBoundsInRadius`](https://create.roblox.com/docs/reference/engine/classes/WorldRoot#GetPartBoundsInRadius)
+      on the Roblox Developer Hub
+*/
+fn world_root_get_part_bounds_in_radius(
+    _: &Lua,
+    this: &Instance,
+    (position, radius, params): (LuaUserDataRef<Vector3>, f32, Option<LuaTable>),
+) -> LuaResult<Vec<Instance>> {
+    let filter = parse_spatial_filter(params)?;
+    Ok(get_parts_in_radius(this, position.0, radius, &filter))
+}
+
+/**
+    Casts a ray against the bounding boxes of all parts under this world root, and
+    returns a table with the same fields as `RaycastResult` for the closest hit.
+
+    The optional parameters are a table with the same fields as `RaycastParams`.
+
+    ### See Also
+    * [`Raycast`](https://create.roblox.com/docs/reference/engine/classes/WorldRoot#Raycast)
+      on the Roblox Developer Hub
+*/
+fn world_root_raycast(
+    lua: &Lua,
+    this: &Instance,
+    (origin, direction, params): (
+        LuaUserDataRef<Vector3>,
+        LuaUserDataRef<Vector3>,
+        Option<LuaTable>,
+    ),
+) -> LuaResult<Option<LuaTable>> {
+    let filter = parse_spatial_filter(params)?;
+    let Some(hit) = raycast(this, origin.0, direction.0, &filter) else {
+        return Ok(None);
+    };
+
+    let material = match hit.part.get_property("Material") {
+        Some(DomValue::Enum(material)) => Some(material.to_u32()),
+        _ => find_property_info(hit.part.get_class_name(), "Material")
+            .and_then(|info| info.enum_default),
+    }
+    .and_then(|value| EnumItem::from_enum_name_and_value("Material", value));
+
+    let result = TableBuilder::new(lua.clone())?
+        .with_value("Instance", hit.part)?
+        .with_value("Position", Vector3(hit.position))?
+        .with_value("Normal", Vector3(hit.normal.normalize_or_zero()))?
+        .with_value("Distance", hit.distance)?
+        .with_value("Material", material)?
+        .build_readonly()?;
+    Ok(Some(result))
+}
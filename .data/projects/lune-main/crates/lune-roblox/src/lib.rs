@@ -7,9 +7,13 @@ use mlua::prelude::*;
 use lune_utils::TableBuilder;
 
 pub mod datatypes;
+pub mod diff;
 pub mod document;
 pub mod instance;
+pub mod query;
 pub mod reflection;
+pub mod rojo;
+pub mod terrain;
 
 #[cfg(feature = "mlua")]
 pub(crate) mod exports;
@@ -44,6 +48,7 @@ fn create_all_exports(lua: Lua) -> LuaResult<Vec<(&'static str, LuaValue)>> {
         export::<UniqueId>(lua.clone())?,
         export::<Region3>(lua.clone())?,
         export::<Region3int16>(lua.clone())?,
+        export::<SecurityCapabilities>(lua.clone())?,
         export::<Vector2>(lua.clone())?,
         export::<Vector2int16>(lua.clone())?,
         export::<Vector3>(lua.clone())?,
@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+#[cfg(feature = "mlua")]
+use mlua::prelude::*;
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid selector '{selector}' at position {position} - {message}")]
+pub struct QueryError {
+    pub(super) selector: String,
+    pub(super) position: usize,
+    pub(super) message: String,
+}
+
+impl QueryError {
+    /**
+        Gets the position in the selector where the error was found, in characters.
+    */
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /**
+        Gets a description of the error, without the selector or position.
+    */
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(feature = "mlua")]
+impl From<QueryError> for LuaError {
+    fn from(value: QueryError) -> Self {
+        Self::RuntimeError(value.to_string())
+    }
+}
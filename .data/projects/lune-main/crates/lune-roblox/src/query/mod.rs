@@ -0,0 +1,6 @@
+mod error;
+mod parser;
+mod selector;
+
+pub use error::QueryError;
+pub use selector::Selector;
@@ -0,0 +1,396 @@
+use std::str::FromStr;
+
+use super::{
+    QueryError,
+    selector::{
+        Combinator, ComplexSelector, CompoundSelector, Literal, Operator, Selector, SimpleSelector,
+        ValueSelector, ValueSource,
+    },
+};
+
+type ParseResult<T> = Result<T, QueryError>;
+
+impl Selector {
+    /**
+        Parses a selector from a string.
+
+        See [`Selector`] for a description of the selector syntax.
+
+        # Errors
+
+        Errors if the given string is not a valid selector.
+    */
+    pub fn parse(selector: impl AsRef<str>) -> ParseResult<Self> {
+        let selector = selector.as_ref();
+        let mut parser = Parser {
+            source: selector,
+            chars: selector.chars().collect(),
+            position: 0,
+        };
+        let alternatives = parser.parse_list()?;
+        Ok(Self { alternatives })
+    }
+}
+
+impl FromStr for Selector {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> ParseResult<Self> {
+        Self::parse(s)
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError {
+            selector: self.source.to_string(),
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    fn unexpected(&self) -> QueryError {
+        match self.peek() {
+            Some(c) => self.error(format!("unexpected character '{c}'")),
+            None => self.error("unexpected end of selector"),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.chars.get(self.position + 1).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+        self.position > start
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn parse_list(&mut self) -> ParseResult<Vec<ComplexSelector>> {
+        let mut alternatives = vec![self.parse_complex()?];
+        while self.eat(',') {
+            alternatives.push(self.parse_complex()?);
+        }
+        match self.peek() {
+            None => Ok(alternatives),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_complex(&mut self) -> ParseResult<ComplexSelector> {
+        self.skip_whitespace();
+        let combinator = self.parse_combinator().unwrap_or(Combinator::Descendant);
+        self.skip_whitespace();
+
+        let mut parts = vec![(combinator, self.parse_compound()?)];
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None | Some(',') => break,
+                Some('>') => {
+                    let combinator = self.parse_combinator();
+                    self.skip_whitespace();
+                    combinator.expect("Peeked combinator")
+                }
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.unexpected()),
+            };
+            parts.push((combinator, self.parse_compound()?));
+        }
+
+        Ok(ComplexSelector { parts })
+    }
+
+    fn parse_combinator(&mut self) -> Option<Combinator> {
+        if self.eat('>') {
+            Some(if self.eat('>') {
+                Combinator::Descendant
+            } else {
+                Combinator::Child
+            })
+        } else {
+            None
+        }
+    }
+
+    fn parse_compound(&mut self) -> ParseResult<CompoundSelector> {
+        let start = self.position;
+        let mut simple = Vec::new();
+
+        if !self.eat('*') && self.peek().is_some_and(is_identifier_char) {
+            simple.push(SimpleSelector::Class(self.parse_identifier()));
+        }
+
+        loop {
+            if self.eat('#') {
+                simple.push(SimpleSelector::Name(self.parse_name("name")?));
+            } else if self.eat('.') {
+                simple.push(SimpleSelector::Tag(self.parse_name("tag")?));
+            } else if self.eat('[') {
+                simple.push(SimpleSelector::Value(self.parse_value_selector()?));
+            } else {
+                break;
+            }
+        }
+
+        if self.position == start {
+            Err(match self.peek() {
+                None | Some(',') => self.error("expected a selector"),
+                Some(_) => self.unexpected(),
+            })
+        } else {
+            Ok(CompoundSelector { simple })
+        }
+    }
+
+    fn parse_identifier(&mut self) -> String {
+        self.take_while(is_identifier_char)
+    }
+
+    /**
+        Parses a name, tag, or attribute name, which may either
+        be an identifier or a quoted string for any other name.
+    */
+    fn parse_name(&mut self, what: &str) -> ParseResult<String> {
+        match self.peek() {
+            Some('"' | '\'') => self.parse_string(),
+            Some(c) if is_identifier_char(c) => Ok(self.parse_identifier()),
+            _ => Err(self.error(format!("expected a {what}"))),
+        }
+    }
+
+    fn parse_string(&mut self) -> ParseResult<String> {
+        let start = self.position;
+        let quote = self.peek().expect("Peeked quote");
+        self.position += 1;
+
+        let mut string = String::new();
+        loop {
+            match (self.peek(), self.peek_next()) {
+                (None, _) => {
+                    self.position = start;
+                    return Err(self.error("unterminated string"));
+                }
+                (Some(c), _) if c == quote => {
+                    self.position += 1;
+                    return Ok(string);
+                }
+                (Some('\\'), Some(escaped)) => {
+                    string.push(escaped);
+                    self.position += 2;
+                }
+                (Some(c), _) => {
+                    string.push(c);
+                    self.position += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_value_selector(&mut self) -> ParseResult<ValueSelector> {
+        self.skip_whitespace();
+        let source = if self.eat('$') {
+            ValueSource::Attribute
+        } else {
+            ValueSource::AttributeOrProperty
+        };
+        let name = self.parse_name("attribute or property name")?;
+        self.skip_whitespace();
+
+        let (operator, literal) = if self.peek() == Some(']') {
+            (Operator::Exists, None)
+        } else {
+            let operator = self
+                .parse_operator()
+                .ok_or_else(|| self.error("expected an operator or ']'"))?;
+            self.skip_whitespace();
+            let literal = self.parse_literal()?;
+            self.skip_whitespace();
+            (operator, Some(literal))
+        };
+
+        if !self.eat(']') {
+            return Err(self.error("expected ']'"));
+        }
+
+        Ok(ValueSelector {
+            source,
+            name,
+            operator,
+            literal,
+        })
+    }
+
+    fn parse_operator(&mut self) -> Option<Operator> {
+        let operator = match (self.peek()?, self.peek_next()) {
+            ('~' | '!', Some('=')) => Operator::NotEqual,
+            ('<', Some('=')) => Operator::LessOrEqual,
+            ('>', Some('=')) => Operator::GreaterOrEqual,
+            ('^', Some('=')) => Operator::StartsWith,
+            ('$', Some('=')) => Operator::EndsWith,
+            ('*', Some('=')) => Operator::Contains,
+            ('=', _) => Operator::Equal,
+            ('<', _) => Operator::Less,
+            ('>', _) => Operator::Greater,
+            _ => return None,
+        };
+        self.position += match operator {
+            Operator::Equal | Operator::Less | Operator::Greater => 1,
+            _ => 2,
+        };
+        Some(operator)
+    }
+
+    fn parse_literal(&mut self) -> ParseResult<Literal> {
+        match self.peek() {
+            Some('"' | '\'') => self.parse_string().map(Literal::String),
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let start = self.position;
+                let number = self
+                    .take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'));
+                number.parse().map(Literal::Number).map_err(|_| {
+                    self.position = start;
+                    self.error(format!("invalid number '{number}'"))
+                })
+            }
+            Some(c) if is_identifier_char(c) => Ok(match self.parse_identifier().as_str() {
+                "true" => Literal::Bool(true),
+                "false" => Literal::Bool(false),
+                identifier => Literal::String(identifier.to_string()),
+            }),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_single(selector: &str) -> Vec<(Combinator, CompoundSelector)> {
+        let mut parsed = Selector::parse(selector).unwrap();
+        assert_eq!(parsed.alternatives.len(), 1);
+        parsed.alternatives.remove(0).parts
+    }
+
+    #[test]
+    fn parses_compound_selectors() {
+        let parts = parse_single("Part#Door.Interactable[Locked]");
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].0, Combinator::Descendant);
+        assert_eq!(
+            parts[0].1.simple,
+            vec![
+                SimpleSelector::Class("Part".to_string()),
+                SimpleSelector::Name("Door".to_string()),
+                SimpleSelector::Tag("Interactable".to_string()),
+                SimpleSelector::Value(ValueSelector {
+                    source: ValueSource::AttributeOrProperty,
+                    name: "Locked".to_string(),
+                    operator: Operator::Exists,
+                    literal: None,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_combinators() {
+        let parts = parse_single("> Model Folder>Part >> *");
+        let combinators = parts.iter().map(|(c, _)| *c).collect::<Vec<_>>();
+        assert_eq!(
+            combinators,
+            vec![
+                Combinator::Child,
+                Combinator::Descendant,
+                Combinator::Child,
+                Combinator::Descendant,
+            ]
+        );
+        assert!(parts[3].1.simple.is_empty());
+    }
+
+    #[test]
+    fn parses_value_selectors() {
+        let parts = parse_single(r#"[$Health>=10][Name ~= "Spawn \"A\""][Material=Neon]"#);
+        assert_eq!(
+            parts[0].1.simple,
+            vec![
+                SimpleSelector::Value(ValueSelector {
+                    source: ValueSource::Attribute,
+                    name: "Health".to_string(),
+                    operator: Operator::GreaterOrEqual,
+                    literal: Some(Literal::Number(10.0)),
+                }),
+                SimpleSelector::Value(ValueSelector {
+                    source: ValueSource::AttributeOrProperty,
+                    name: "Name".to_string(),
+                    operator: Operator::NotEqual,
+                    literal: Some(Literal::String("Spawn \"A\"".to_string())),
+                }),
+                SimpleSelector::Value(ValueSelector {
+                    source: ValueSource::AttributeOrProperty,
+                    name: "Material".to_string(),
+                    operator: Operator::Equal,
+                    literal: Some(Literal::String("Neon".to_string())),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_selector_lists() {
+        let parsed = Selector::parse("Part, Model > #Root").unwrap();
+        assert_eq!(parsed.alternatives.len(), 2);
+        assert_eq!(parsed.alternatives[1].parts.len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_selectors() {
+        for (selector, position) in [
+            ("", 0),
+            ("Part,", 5),
+            ("Part > > Model", 7),
+            ("Part[Health >]", 13),
+            ("Part[Health", 11),
+            ("#'Unterminated", 1),
+            ("Part$", 4),
+        ] {
+            let err = Selector::parse(selector).unwrap_err();
+            assert_eq!(err.position(), position, "{selector}: {err}");
+        }
+    }
+}
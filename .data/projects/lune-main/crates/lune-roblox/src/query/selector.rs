@@ -0,0 +1,303 @@
+use std::cmp::Ordering;
+
+use rbx_dom_weak::{
+    Instance as DomInstance, WeakDom,
+    types::{Enum as DomEnum, Ref as DomRef, Variant as DomValue},
+    ustr,
+};
+
+use crate::shared::instance::{class_is_a, find_property_info};
+
+/**
+    How a compound selector relates to the one before it,
+    or to the root of the query for the first compound selector.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Combinator {
+    /// `A B` or `A >> B` - any descendant of the previous match.
+    Descendant,
+    /// `A > B` - a direct child of the previous match.
+    Child,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Operator {
+    Exists,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Literal {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ValueSource {
+    /// `[$Name]` - only attributes.
+    Attribute,
+    /// `[Name]` - attributes, falling back to properties if there is no such attribute.
+    AttributeOrProperty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ValueSelector {
+    pub source: ValueSource,
+    pub name: String,
+    pub operator: Operator,
+    pub literal: Option<Literal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum SimpleSelector {
+    /// `ClassName` - uses `IsA` semantics.
+    Class(String),
+    /// `#Name`
+    Name(String),
+    /// `.Tag`
+    Tag(String),
+    /// `[Attribute > 0]`, `[$Attribute]`, `[Property = value]`
+    Value(ValueSelector),
+}
+
+/**
+    A sequence of simple selectors that must all match the same instance, such as `Part.Tag#Name`.
+
+    An empty compound selector, written as `*`, matches any instance.
+*/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct CompoundSelector {
+    pub simple: Vec<SimpleSelector>,
+}
+
+/**
+    A chain of compound selectors joined by combinators, such as `Model > Part.Tag`.
+
+    Each compound selector has the combinator that relates it to the previous
+    compound selector, and the first one to the root of the query.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ComplexSelector {
+    pub parts: Vec<(Combinator, CompoundSelector)>,
+}
+
+/**
+    A parsed selector, which may be matched against instances in a [`WeakDom`].
+
+    Selectors are written similarly to CSS selectors:
+
+    - `Part` matches instances that are a `Part`, using `IsA` semantics
+    - `#Name` matches instances with the given name
+    - `.Tag` matches instances with the given tag
+    - `[Health > 0]` matches instances with an attribute, or a property if there is no
+      such attribute, that compares to the given value using one of `=`, `~=`, `!=`,
+      `<`, `<=`, `>`, `>=`, `^=` (starts with), `$=` (ends with) or `*=` (contains)
+    - `[$Health]` matches instances with the given attribute, ignoring properties
+    - `A B` or `A >> B` matches `B` if it is a descendant of `A`
+    - `A > B` matches `B` if it is a child of `A`
+    - `A, B` matches either `A` or `B`
+
+    A selector that starts with `>` only matches children of the root of the query.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub(super) alternatives: Vec<ComplexSelector>,
+}
+
+impl Selector {
+    /**
+        Checks if the given instance matches this selector.
+
+        The instance must be a descendant of the given scope, and any ancestors
+        matched by combinators must be descendants of the scope as well.
+    */
+    #[must_use]
+    pub fn matches(&self, dom: &WeakDom, scope: DomRef, inst: &DomInstance) -> bool {
+        self.alternatives.iter().any(|complex| {
+            let last = complex.parts.len() - 1;
+            matches_part(dom, scope, &complex.parts, last, inst)
+        })
+    }
+}
+
+fn matches_part(
+    dom: &WeakDom,
+    scope: DomRef,
+    parts: &[(Combinator, CompoundSelector)],
+    index: usize,
+    inst: &DomInstance,
+) -> bool {
+    let (combinator, compound) = &parts[index];
+    if inst.referent() == scope || !compound.matches(inst) {
+        return false;
+    }
+
+    let mut ancestor_ref = inst.parent();
+    while let Some(ancestor) = dom.get_by_ref(ancestor_ref) {
+        let is_match = if index == 0 {
+            ancestor_ref == scope
+        } else {
+            ancestor_ref != scope && matches_part(dom, scope, parts, index - 1, ancestor)
+        };
+        if is_match {
+            return true;
+        }
+        if ancestor_ref == scope || *combinator == Combinator::Child {
+            return false;
+        }
+        ancestor_ref = ancestor.parent();
+    }
+
+    false
+}
+
+impl CompoundSelector {
+    fn matches(&self, inst: &DomInstance) -> bool {
+        self.simple.iter().all(|simple| simple.matches(inst))
+    }
+}
+
+impl SimpleSelector {
+    fn matches(&self, inst: &DomInstance) -> bool {
+        match self {
+            Self::Class(class_name) => class_is_a(inst.class, class_name).unwrap_or(false),
+            Self::Name(name) => inst.name == *name,
+            Self::Tag(tag) => match inst.properties.get(&ustr("Tags")) {
+                Some(DomValue::Tags(tags)) => tags.iter().any(|t| t == tag.as_str()),
+                _ => false,
+            },
+            Self::Value(value) => value.matches(inst),
+        }
+    }
+}
+
+impl ValueSelector {
+    fn matches(&self, inst: &DomInstance) -> bool {
+        let (value, enum_name) = match self.source {
+            ValueSource::Attribute => (find_attribute(inst, &self.name), None),
+            ValueSource::AttributeOrProperty => match find_attribute(inst, &self.name) {
+                Some(value) => (Some(value), None),
+                None => find_property(inst, &self.name),
+            },
+        };
+        let Some(value) = value else {
+            return false;
+        };
+        let Some(literal) = &self.literal else {
+            return self.operator == Operator::Exists;
+        };
+
+        let enum_name = enum_name.as_deref();
+        let ordering = || compare(&value, enum_name, literal);
+        let text = || match literal {
+            Literal::String(pattern) => value_to_text(&value, enum_name).map(|t| (t, pattern)),
+            _ => None,
+        };
+
+        match self.operator {
+            Operator::Exists => true,
+            Operator::Equal => ordering() == Some(Ordering::Equal),
+            Operator::NotEqual => ordering() != Some(Ordering::Equal),
+            Operator::Less => ordering() == Some(Ordering::Less),
+            Operator::LessOrEqual => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+            Operator::Greater => ordering() == Some(Ordering::Greater),
+            Operator::GreaterOrEqual => {
+                matches!(ordering(), Some(Ordering::Greater | Ordering::Equal))
+            }
+            Operator::StartsWith => text().is_some_and(|(t, p)| t.starts_with(p.as_str())),
+            Operator::EndsWith => text().is_some_and(|(t, p)| t.ends_with(p.as_str())),
+            Operator::Contains => text().is_some_and(|(t, p)| t.contains(p.as_str())),
+        }
+    }
+}
+
+fn find_attribute(inst: &DomInstance, name: &str) -> Option<DomValue> {
+    match inst.properties.get(&ustr("Attributes")) {
+        Some(DomValue::Attributes(attributes)) => attributes.get(name).cloned(),
+        _ => None,
+    }
+}
+
+/**
+    Finds the value of a property, using its default value from
+    the reflection database if it has not been set for the instance.
+
+    Also returns the name of the enum for enum properties, so
+    that their values may be compared against enum item names.
+*/
+fn find_property(inst: &DomInstance, name: &str) -> (Option<DomValue>, Option<String>) {
+    match name {
+        "Name" => return (Some(DomValue::String(inst.name.clone())), None),
+        "ClassName" => return (Some(DomValue::String(inst.class.to_string())), None),
+        _ => {}
+    }
+
+    let info = find_property_info(inst.class, name);
+    let enum_name = info
+        .as_ref()
+        .and_then(|info| info.enum_name.as_ref())
+        .map(ToString::to_string);
+    let value = inst.properties.get(&ustr(name)).cloned().or_else(|| {
+        let info = info?;
+        match info.enum_default {
+            Some(enum_default) => Some(DomValue::Enum(DomEnum::from_u32(enum_default))),
+            None => info.value_default.cloned(),
+        }
+    });
+
+    (value, enum_name)
+}
+
+/**
+    Compares a value against a literal from a selector.
+
+    Returns `None` if the value and literal can not be compared, or
+    if they are not equal and have no meaningful ordering, such as
+    enum items with different names.
+*/
+fn compare(value: &DomValue, enum_name: Option<&str>, literal: &Literal) -> Option<Ordering> {
+    match (value, literal) {
+        (DomValue::Bool(b), Literal::Bool(l)) => Some(b.cmp(l)),
+        (DomValue::Float32(f), Literal::Number(n)) => f.partial_cmp(&(*n as f32)),
+        (DomValue::Float64(f), Literal::Number(n)) => f.partial_cmp(n),
+        (DomValue::Int32(i), Literal::Number(n)) => f64::from(*i).partial_cmp(n),
+        (DomValue::Int64(i), Literal::Number(n)) => (*i as f64).partial_cmp(n),
+        (DomValue::Enum(e), Literal::Number(n)) => f64::from(e.to_u32()).partial_cmp(n),
+        (DomValue::EnumItem(item), Literal::Number(n)) => f64::from(item.value).partial_cmp(n),
+        (DomValue::String(s), Literal::String(l)) => Some(s.as_str().cmp(l.as_str())),
+        (DomValue::Enum(_) | DomValue::EnumItem(_), Literal::String(l)) => {
+            let name = value_to_text(value, enum_name)?;
+            (name == *l).then_some(Ordering::Equal)
+        }
+        _ => None,
+    }
+}
+
+/**
+    Converts a value to text for string comparisons,
+    using the name of the item for enum values.
+*/
+fn value_to_text(value: &DomValue, enum_name: Option<&str>) -> Option<String> {
+    let (enum_name, enum_value) = match value {
+        DomValue::String(s) => return Some(s.clone()),
+        DomValue::Enum(e) => (enum_name?, e.to_u32()),
+        DomValue::EnumItem(item) => (item.ty.as_str(), item.value),
+        _ => return None,
+    };
+    let db = rbx_reflection_database::get().unwrap();
+    db.enums
+        .get(enum_name)?
+        .items
+        .iter()
+        .find(|(_, value)| **value == enum_value)
+        .map(|(name, _)| name.to_string())
+}
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+#[cfg(feature = "mlua")]
+use mlua::prelude::*;
+
+use crate::document::DocumentError;
+
+#[derive(Debug, Clone, Error)]
+pub enum RojoError {
+    #[error("Failed to find a Rojo project at '{0}'")]
+    ProjectNotFound(String),
+    #[error("Failed to access '{path}' - {message}")]
+    Io { path: String, message: String },
+    #[error("Failed to parse '{path}' - {message}")]
+    Parse { path: String, message: String },
+    #[error("Invalid value for property '{property}' in '{path}' - {message}")]
+    InvalidProperty {
+        path: String,
+        property: String,
+        message: String,
+    },
+    #[error("Instance name '{0}' can not be represented in a Rojo project")]
+    InvalidName(String),
+    #[error(transparent)]
+    Document(#[from] DocumentError),
+}
+
+#[cfg(feature = "mlua")]
+impl From<RojoError> for LuaError {
+    fn from(value: RojoError) -> Self {
+        Self::RuntimeError(value.to_string())
+    }
+}
@@ -0,0 +1,181 @@
+use std::{fs, path::Path};
+
+use serde_json::Value as JsonValue;
+
+mod error;
+mod read;
+mod value;
+mod write;
+
+pub use error::RojoError;
+pub use read::read_rojo_project;
+pub use write::write_rojo_project;
+
+pub type RojoResult<T> = Result<T, RojoError>;
+
+const PROJECT_FILE_NAME: &str = "default.project.json";
+const SOURCE_DIR_NAME: &str = "src";
+
+const INIT_NAME: &str = "init";
+const SCRIPT_EXTENSIONS: [&str; 2] = ["luau", "lua"];
+
+const SUFFIX_MODEL: &str = ".model.json";
+const SUFFIX_META: &str = ".meta.json";
+const SUFFIX_PROJECT: &str = ".project.json";
+
+/**
+    Attributes that Rojo uses to represent references between instances,
+    the referenced instance gets an id, which the referencing instance
+    stores in an attribute named after the property with the reference.
+*/
+const ATTRIBUTE_ID: &str = "Rojo_Id";
+const ATTRIBUTE_TARGET_PREFIX: &str = "Rojo_Target_";
+
+/**
+    Options for writing an instance tree as a Rojo project.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct RojoProjectOptions {
+    project_name: Option<String>,
+    canonicalize: bool,
+}
+
+impl RojoProjectOptions {
+    /**
+        Creates new options, using the name of the root instance as the project name.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Sets the name of the project, written to the project file.
+    */
+    #[must_use]
+    pub fn with_project_name(mut self, name: impl Into<String>) -> Self {
+        self.project_name = Some(name.into());
+        self
+    }
+
+    /**
+        Sets whether the instances should be canonicalized before they are written,
+        as described by [`Document::canonicalize`](crate::document::Document::canonicalize).
+    */
+    #[must_use]
+    pub fn with_canonicalize(mut self, canonicalize: bool) -> Self {
+        self.canonicalize = canonicalize;
+        self
+    }
+}
+
+/**
+    The kinds of scripts that Rojo represents as source files.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptKind {
+    Module,
+    Server,
+    Client,
+}
+
+impl ScriptKind {
+    const ALL: [Self; 3] = [Self::Module, Self::Server, Self::Client];
+
+    fn from_class_name(class_name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.class_name() == class_name)
+    }
+
+    /**
+        Parses a script file name such as `Name.server.luau` into its kind and instance name.
+    */
+    fn from_file_name(file_name: &str) -> Option<(Self, &str)> {
+        let stem = SCRIPT_EXTENSIONS.iter().find_map(|extension| {
+            file_name
+                .strip_suffix(extension)
+                .and_then(|rest| rest.strip_suffix('.'))
+        })?;
+        [Self::Server, Self::Client]
+            .into_iter()
+            .find_map(|kind| Some((kind, stem.strip_suffix(kind.suffix())?)))
+            .or(Some((Self::Module, stem)))
+    }
+
+    fn class_name(self) -> &'static str {
+        match self {
+            Self::Module => "ModuleScript",
+            Self::Server => "Script",
+            Self::Client => "LocalScript",
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Module => "",
+            Self::Server => ".server",
+            Self::Client => ".client",
+        }
+    }
+}
+
+/**
+    Checks if an instance name can be used as a file name in
+    a Rojo project, and read back as the same instance name.
+*/
+fn is_valid_file_name(name: &str) -> bool {
+    const RESERVED_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+    const RESERVED_SUFFIXES: [&str; 11] = [
+        ".server", ".client", ".model", ".meta", ".project", ".lua", ".luau", ".json", ".txt",
+        ".rbxm", ".rbxmx",
+    ];
+
+    let lower = name.to_ascii_lowercase();
+    !name.is_empty()
+        && name.trim() == name
+        && !name.starts_with('.')
+        && !name.ends_with('.')
+        && !name
+            .chars()
+            .any(|c| c.is_control() || RESERVED_CHARS.contains(&c))
+        && lower != INIT_NAME
+        && !lower.starts_with("init.")
+        && !RESERVED_SUFFIXES
+            .iter()
+            .any(|suffix| lower.ends_with(suffix))
+}
+
+fn read_file(path: &Path) -> RojoResult<Vec<u8>> {
+    fs::read(path).map_err(|err| RojoError::Io {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    })
+}
+
+fn read_json(path: &Path) -> RojoResult<JsonValue> {
+    serde_json::from_slice(&read_file(path)?).map_err(|err| RojoError::Parse {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    })
+}
+
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> RojoResult<()> {
+    fs::write(path, contents).map_err(|err| RojoError::Io {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    })
+}
+
+fn write_json(path: &Path, value: &JsonValue) -> RojoResult<()> {
+    let mut contents = serde_json::to_string_pretty(value).expect("Json values always serialize");
+    contents.push('\n');
+    write_file(path, contents)
+}
+
+fn create_dir(path: &Path) -> RojoResult<()> {
+    fs::create_dir_all(path).map_err(|err| RojoError::Io {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    })
+}
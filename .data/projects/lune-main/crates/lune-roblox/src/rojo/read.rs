@@ -0,0 +1,481 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use std::collections::HashMap;
+
+use rbx_dom_weak::{
+    InstanceBuilder as DomInstanceBuilder, WeakDom,
+    types::{Attributes as DomAttributes, Ref as DomRef, Variant as DomValue},
+    ustr,
+};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::{
+    document::{Document, DocumentKind},
+    instance::Instance,
+};
+
+use super::{
+    ATTRIBUTE_ID, ATTRIBUTE_TARGET_PREFIX, INIT_NAME, PROJECT_FILE_NAME, RojoError, RojoResult,
+    SCRIPT_EXTENSIONS, SUFFIX_META, SUFFIX_MODEL, SUFFIX_PROJECT, ScriptKind, read_file, read_json,
+    value::{attributes_from_json, property_from_json},
+};
+
+type JsonObject = JsonMap<String, JsonValue>;
+
+/**
+    Reads a Rojo project and builds an instance tree out of it.
+
+    The given path may either be a project file, or a directory
+    containing a `default.project.json` file. Implicit property
+    values are typed using the reflection database.
+
+    # Errors
+
+    - If the project file could not be found.
+    - If any file in the project could not be read or parsed.
+    - If any property in the project has an invalid value.
+*/
+pub fn read_rojo_project(path: impl AsRef<Path>) -> RojoResult<Instance> {
+    let path = path.as_ref();
+    let project_path = if path.is_dir() {
+        path.join(PROJECT_FILE_NAME)
+    } else {
+        path.to_path_buf()
+    };
+    if !project_path.is_file() {
+        return Err(RojoError::ProjectNotFound(path.display().to_string()));
+    }
+
+    let mut dom = WeakDom::new(DomInstanceBuilder::new("ROOT"));
+    let dom_root = dom.root_ref();
+    let root_ref = read_project_file(&mut dom, dom_root, &project_path, None)?;
+    resolve_references(&mut dom, root_ref);
+    Ok(Instance::from_external_dom(&mut dom, root_ref))
+}
+
+/**
+    Turns the `Rojo_Id` and `Rojo_Target_<Property>` attributes of
+    the given instance and its descendants back into references.
+
+    Targets with ids that are not in the instance tree are left as attributes.
+*/
+fn resolve_references(dom: &mut WeakDom, root_ref: DomRef) {
+    let attributes_name = ustr("Attributes");
+    let mut ids = HashMap::new();
+    let mut descendants = vec![root_ref];
+    let mut index = 0;
+    while let Some(&inst_ref) = descendants.get(index) {
+        index += 1;
+        let Some(inst) = dom.get_by_ref(inst_ref) else {
+            continue;
+        };
+        descendants.extend_from_slice(inst.children());
+        if let Some(DomValue::Attributes(attributes)) = inst.properties.get(&attributes_name)
+            && let Some(DomValue::String(id)) = attributes.get(ATTRIBUTE_ID)
+        {
+            ids.insert(id.clone(), inst_ref);
+        }
+    }
+
+    for inst_ref in descendants {
+        let Some(inst) = dom.get_by_ref_mut(inst_ref) else {
+            continue;
+        };
+        let Some(DomValue::Attributes(attributes)) = inst.properties.remove(&attributes_name)
+        else {
+            continue;
+        };
+
+        let mut targets = Vec::new();
+        let mut remaining = DomAttributes::new();
+        for (name, value) in attributes {
+            if name == ATTRIBUTE_ID {
+                continue;
+            }
+            if let Some(property) = name.strip_prefix(ATTRIBUTE_TARGET_PREFIX)
+                && let DomValue::String(id) = &value
+                && let Some(&target) = ids.get(id)
+            {
+                targets.push((property.to_string(), target));
+            } else {
+                remaining.insert(name, value);
+            }
+        }
+
+        if !remaining.is_empty() {
+            inst.properties
+                .insert(attributes_name, DomValue::Attributes(remaining));
+        }
+        for (property, target) in targets {
+            inst.properties
+                .insert(ustr(&property), DomValue::Ref(target));
+        }
+    }
+}
+
+fn read_project_file(
+    dom: &mut WeakDom,
+    parent: DomRef,
+    path: &Path,
+    name: Option<&str>,
+) -> RojoResult<DomRef> {
+    let project = read_json(path)?;
+    let tree = project
+        .get("tree")
+        .and_then(JsonValue::as_object)
+        .ok_or_else(|| parse_error(path, "missing project tree"))?;
+    let name = name
+        .or_else(|| project.get("name").and_then(JsonValue::as_str))
+        .ok_or_else(|| parse_error(path, "missing project name"))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    read_project_node(dom, parent, base_dir, name, tree, path)
+}
+
+/**
+    Reads a single node of a project tree, which may either
+    point to a path, or describe an instance and its children.
+*/
+fn read_project_node(
+    dom: &mut WeakDom,
+    parent: DomRef,
+    base_dir: &Path,
+    name: &str,
+    node: &JsonObject,
+    project_path: &Path,
+) -> RojoResult<DomRef> {
+    let class_name = node.get("$className").and_then(JsonValue::as_str);
+
+    let inst_ref = if let Some(path) = node.get("$path").and_then(JsonValue::as_str) {
+        let inst_ref = read_path(dom, parent, &base_dir.join(path), Some(name))?
+            .ok_or_else(|| parse_error(project_path, format!("invalid path '{path}'")))?;
+        if let Some(class_name) = class_name {
+            let inst = dom.get_by_ref_mut(inst_ref).expect("Invalid instance ref");
+            if inst.class == "Folder" {
+                inst.class = ustr(class_name);
+            }
+        }
+        inst_ref
+    } else {
+        // Services may omit their class name, since it is the same as their name
+        let class_name = class_name.unwrap_or(name);
+        let db = rbx_reflection_database::get().unwrap();
+        if !db.classes.contains_key(class_name) {
+            return Err(parse_error(
+                project_path,
+                format!("missing or unknown class name for '{name}'"),
+            ));
+        }
+        dom.insert(parent, DomInstanceBuilder::new(class_name).with_name(name))
+    };
+
+    apply_metadata(
+        dom,
+        inst_ref,
+        node,
+        "$properties",
+        "$attributes",
+        project_path,
+    )?;
+
+    for (child_name, child_node) in node {
+        if child_name.starts_with('$') {
+            continue;
+        }
+        let child_node = child_node
+            .as_object()
+            .ok_or_else(|| parse_error(project_path, format!("invalid node '{child_name}'")))?;
+        read_project_node(
+            dom,
+            inst_ref,
+            base_dir,
+            child_name,
+            child_node,
+            project_path,
+        )?;
+    }
+
+    Ok(inst_ref)
+}
+
+/**
+    Reads a file or directory into an instance, parented to the given instance.
+
+    Returns `None` if the path is a file that is not part of a project.
+*/
+fn read_path(
+    dom: &mut WeakDom,
+    parent: DomRef,
+    path: &Path,
+    name: Option<&str>,
+) -> RojoResult<Option<DomRef>> {
+    if path.is_dir() {
+        return read_directory(dom, parent, path, name).map(Some);
+    }
+
+    let file_name = file_name(path);
+    let stem_name = |suffix: &str| {
+        name.map_or_else(
+            || file_name[..file_name.len() - suffix.len()].to_string(),
+            ToString::to_string,
+        )
+    };
+
+    if file_name.ends_with(SUFFIX_META) {
+        return Ok(None);
+    }
+    if file_name.ends_with(SUFFIX_PROJECT) {
+        return read_project_file(dom, parent, path, name).map(Some);
+    }
+    if file_name.ends_with(SUFFIX_MODEL) {
+        let model = read_json(path)?;
+        let model = model
+            .as_object()
+            .ok_or_else(|| parse_error(path, "expected a model object"))?;
+        return read_model(dom, parent, &stem_name(SUFFIX_MODEL), model, path).map(Some);
+    }
+
+    let (class_name, inst_name, property_name) =
+        if let Some((kind, script_name)) = ScriptKind::from_file_name(&file_name) {
+            let name = name.map_or_else(|| script_name.to_string(), ToString::to_string);
+            (kind.class_name(), name, "Source")
+        } else if has_extension(path, "txt") {
+            ("StringValue", stem_name(".txt"), "Value")
+        } else if has_extension(path, "rbxm") || has_extension(path, "rbxmx") {
+            return read_model_file(dom, parent, path, name);
+        } else {
+            return Ok(None);
+        };
+
+    let contents = read_source(path)?;
+    let inst_ref = dom.insert(
+        parent,
+        DomInstanceBuilder::new(class_name)
+            .with_name(inst_name)
+            .with_property(property_name, DomValue::String(contents)),
+    );
+
+    let meta_path = path.with_file_name(format!(
+        "{}{SUFFIX_META}",
+        meta_stem(&file_name, class_name)
+    ));
+    if meta_path.is_file() {
+        let meta = read_meta(&meta_path)?;
+        apply_metadata(dom, inst_ref, &meta, "properties", "attributes", &meta_path)?;
+    }
+
+    Ok(Some(inst_ref))
+}
+
+/**
+    Reads a directory into an instance, which will be a script if the
+    directory contains an `init` script, and a `Folder` otherwise.
+*/
+fn read_directory(
+    dom: &mut WeakDom,
+    parent: DomRef,
+    path: &Path,
+    name: Option<&str>,
+) -> RojoResult<DomRef> {
+    let nested_project = path.join(PROJECT_FILE_NAME);
+    if nested_project.is_file() {
+        return read_project_file(dom, parent, &nested_project, name);
+    }
+
+    let name = name.map_or_else(|| file_name(path), ToString::to_string);
+    let meta_path = path.join(format!("{INIT_NAME}{SUFFIX_META}"));
+    let meta = if meta_path.is_file() {
+        read_meta(&meta_path)?
+    } else {
+        JsonObject::new()
+    };
+
+    let init_script = ScriptKind::ALL.into_iter().find_map(|kind| {
+        SCRIPT_EXTENSIONS.iter().find_map(|extension| {
+            let init_path = path.join(format!("{INIT_NAME}{}.{extension}", kind.suffix()));
+            init_path.is_file().then_some((kind, init_path))
+        })
+    });
+
+    let builder = if let Some((kind, init_path)) = init_script {
+        DomInstanceBuilder::new(kind.class_name())
+            .with_property("Source", DomValue::String(read_source(&init_path)?))
+    } else {
+        let class_name = meta
+            .get("className")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("Folder");
+        DomInstanceBuilder::new(class_name)
+    };
+    let inst_ref = dom.insert(parent, builder.with_name(name));
+    apply_metadata(dom, inst_ref, &meta, "properties", "attributes", &meta_path)?;
+
+    let mut entries = read_dir_sorted(path)?;
+    entries.retain(|entry| {
+        let entry_name = file_name(entry);
+        !entry_name.starts_with('.') && !entry_name.starts_with("init.")
+    });
+    for entry in entries {
+        read_path(dom, inst_ref, &entry, None)?;
+    }
+
+    Ok(inst_ref)
+}
+
+/**
+    Reads an instance, and its children, described by a `.model.json` file.
+*/
+fn read_model(
+    dom: &mut WeakDom,
+    parent: DomRef,
+    name: &str,
+    model: &JsonObject,
+    path: &Path,
+) -> RojoResult<DomRef> {
+    let class_name = model
+        .get("className")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| parse_error(path, format!("missing class name for '{name}'")))?;
+    let inst_ref = dom.insert(parent, DomInstanceBuilder::new(class_name).with_name(name));
+    apply_metadata(dom, inst_ref, model, "properties", "attributes", path)?;
+
+    if let Some(children) = model.get("children").and_then(JsonValue::as_array) {
+        for child in children {
+            let child = child
+                .as_object()
+                .ok_or_else(|| parse_error(path, "expected a child model object"))?;
+            let child_name = child
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| parse_error(path, "missing name for child model"))?;
+            read_model(dom, inst_ref, child_name, child, path)?;
+        }
+    }
+
+    Ok(inst_ref)
+}
+
+/**
+    Reads the instances in a `.rbxm` or `.rbxmx` file, renaming
+    the first instance if a name was given by the project tree.
+*/
+fn read_model_file(
+    dom: &mut WeakDom,
+    parent: DomRef,
+    path: &Path,
+    name: Option<&str>,
+) -> RojoResult<Option<DomRef>> {
+    let document = Document::from_bytes(read_file(path)?, DocumentKind::Model)?;
+    let mut model_dom = document.into_weak_dom();
+
+    let child_refs = model_dom.root().children().to_vec();
+    let mut first_ref = None;
+    for child_ref in child_refs {
+        model_dom.transfer(child_ref, dom, parent);
+        first_ref.get_or_insert(child_ref);
+    }
+
+    if let (Some(first_ref), Some(name)) = (first_ref, name) {
+        let inst = dom.get_by_ref_mut(first_ref).expect("Invalid instance ref");
+        inst.name = name.to_string();
+    }
+
+    Ok(first_ref)
+}
+
+/**
+    Applies the properties and attributes found under the given keys of a
+    project node, model, or meta file, to the instance with the given ref.
+*/
+fn apply_metadata(
+    dom: &mut WeakDom,
+    inst_ref: DomRef,
+    object: &JsonObject,
+    properties_key: &str,
+    attributes_key: &str,
+    path: &Path,
+) -> RojoResult<()> {
+    let inst = dom.get_by_ref_mut(inst_ref).expect("Invalid instance ref");
+    let class_name = inst.class.to_string();
+
+    if let Some(properties) = object.get(properties_key).and_then(JsonValue::as_object) {
+        for (property_name, value) in properties {
+            let value = property_from_json(&class_name, property_name, value)
+                .map_err(|message| invalid_property(path, property_name, message))?;
+            inst.properties.insert(ustr(property_name), value);
+        }
+    }
+
+    if let Some(attributes) = object.get(attributes_key).and_then(JsonValue::as_object) {
+        let attributes = attributes_from_json(attributes)
+            .map_err(|message| invalid_property(path, "Attributes", message))?;
+        inst.properties
+            .insert(ustr("Attributes"), DomValue::Attributes(attributes));
+    }
+
+    Ok(())
+}
+
+fn read_meta(path: &Path) -> RojoResult<JsonObject> {
+    match read_json(path)? {
+        JsonValue::Object(meta) => Ok(meta),
+        _ => Err(parse_error(path, "expected a meta object")),
+    }
+}
+
+fn read_source(path: &Path) -> RojoResult<String> {
+    String::from_utf8(read_file(path)?).map_err(|err| parse_error(path, err.to_string()))
+}
+
+fn read_dir_sorted(path: &Path) -> RojoResult<Vec<PathBuf>> {
+    let io_error = |err: std::io::Error| RojoError::Io {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    };
+    let mut entries = fs::read_dir(path)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_error)?;
+    entries.sort();
+    Ok(entries)
+}
+
+/**
+    Gets the stem that a sibling `.meta.json` file uses for the given file,
+    which is the file name without any script or text file suffixes.
+*/
+fn meta_stem<'a>(file_name: &'a str, class_name: &str) -> &'a str {
+    if class_name == "StringValue" {
+        return file_name.strip_suffix(".txt").unwrap_or(file_name);
+    }
+    ScriptKind::from_file_name(file_name).map_or(file_name, |(_, stem)| stem)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn parse_error(path: &Path, message: impl Into<String>) -> RojoError {
+    RojoError::Parse {
+        path: path.display().to_string(),
+        message: message.into(),
+    }
+}
+
+fn invalid_property(path: &Path, property: &str, message: String) -> RojoError {
+    RojoError::InvalidProperty {
+        path: path.display().to_string(),
+        property: property.to_string(),
+        message,
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
@@ -0,0 +1,107 @@
+use rbx_dom_weak::types::{Attributes as DomAttributes, Enum as DomEnum, Variant as DomValue};
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+use crate::{datatypes::extension::DomValueExt, shared::instance::find_property_info};
+
+/**
+    Converts a property value into its explicit Rojo json representation,
+    such as `{ "Vector3": [1, 2, 3] }`.
+
+    Returns `None` for values that Rojo can not represent as properties, such as
+    references, which are instead written as attributes of the instances.
+*/
+pub(super) fn property_to_json(value: &DomValue) -> Option<JsonValue> {
+    match value {
+        DomValue::Ref(_) => None,
+        DomValue::EnumItem(item) => Some(json!({ "Enum": item.value })),
+        value => serde_json::to_value(value).ok(),
+    }
+}
+
+/**
+    Converts a Rojo json value into a property value for the given class and property.
+
+    Explicit values such as `{ "Vector3": [1, 2, 3] }` are used as-is, implicit
+    values such as `[1, 2, 3]` are typed using the reflection database.
+*/
+pub(super) fn property_from_json(
+    class_name: &str,
+    property_name: &str,
+    value: &JsonValue,
+) -> Result<DomValue, String> {
+    if let Ok(explicit) = serde_json::from_value::<DomValue>(value.clone()) {
+        return Ok(explicit);
+    }
+
+    match property_name {
+        "Tags" => {
+            return serde_json::from_value::<Vec<String>>(value.clone())
+                .map(|tags| DomValue::Tags(tags.into()))
+                .map_err(|err| err.to_string());
+        }
+        "Attributes" => {
+            return match value {
+                JsonValue::Object(map) => attributes_from_json(map).map(DomValue::Attributes),
+                _ => Err("expected a table of attributes".to_string()),
+            };
+        }
+        _ => {}
+    }
+
+    let info = find_property_info(class_name, property_name)
+        .ok_or_else(|| format!("unknown property for class '{class_name}'"))?;
+
+    if let Some(enum_name) = info.enum_name {
+        return enum_from_json(&enum_name, value).map(DomValue::Enum);
+    }
+
+    let value_type = info
+        .value_type
+        .and_then(|ty| ty.variant_name())
+        .ok_or_else(|| "property type is not supported".to_string())?;
+    let mut explicit = JsonMap::new();
+    explicit.insert(value_type.to_string(), value.clone());
+    serde_json::from_value(JsonValue::Object(explicit)).map_err(|err| err.to_string())
+}
+
+/**
+    Converts a Rojo json table of attributes into attributes.
+
+    Booleans, numbers, and strings may be given implicitly,
+    any other attribute value must be given explicitly.
+*/
+pub(super) fn attributes_from_json(
+    map: &JsonMap<String, JsonValue>,
+) -> Result<DomAttributes, String> {
+    let mut attributes = DomAttributes::new();
+    for (name, value) in map {
+        let value = match value {
+            JsonValue::Bool(b) => DomValue::Bool(*b),
+            JsonValue::Number(n) => DomValue::Float64(n.as_f64().unwrap_or_default()),
+            JsonValue::String(s) => DomValue::String(s.clone()),
+            value => serde_json::from_value::<DomValue>(value.clone())
+                .map_err(|err| format!("invalid value for attribute '{name}' - {err}"))?,
+        };
+        attributes.insert(name.clone(), value);
+    }
+    Ok(attributes)
+}
+
+fn enum_from_json(enum_name: &str, value: &JsonValue) -> Result<DomEnum, String> {
+    match value {
+        JsonValue::Number(n) => n
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(DomEnum::from_u32)
+            .ok_or_else(|| format!("invalid value for Enum.{enum_name}")),
+        JsonValue::String(s) => {
+            let db = rbx_reflection_database::get().unwrap();
+            db.enums
+                .get(enum_name)
+                .and_then(|descriptor| descriptor.items.get(s.as_str()))
+                .map(|value| DomEnum::from_u32(*value))
+                .ok_or_else(|| format!("'{s}' is not a valid item of Enum.{enum_name}"))
+        }
+        _ => Err(format!("expected a name or number for Enum.{enum_name}")),
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use rbx_dom_weak::{
+    Instance as DomInstance, WeakDom,
+    types::{Attributes as DomAttributes, Ref as DomRef, Variant as DomValue},
+    ustr,
+};
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+use crate::{document::Document, instance::Instance};
+
+use super::{
+    ATTRIBUTE_ID, ATTRIBUTE_TARGET_PREFIX, INIT_NAME, PROJECT_FILE_NAME, RojoError,
+    RojoProjectOptions, RojoResult, SOURCE_DIR_NAME, SUFFIX_META, SUFFIX_MODEL, ScriptKind,
+    create_dir, is_valid_file_name, value::property_to_json, write_file, write_json,
+};
+
+/**
+    The ids of all instances that are referenced by other instances.
+*/
+type ReferenceIds = HashMap<DomRef, String>;
+
+/**
+    Properties that are unique to each instance, and not meaningful to store in a project.
+*/
+const IGNORED_PROPERTIES: [&str; 3] = ["UniqueId", "HistoryId", "ScriptGuid"];
+
+const PROPERTY_NAME_SOURCE: &str = "Source";
+const PROPERTY_NAME_ATTRIBUTES: &str = "Attributes";
+
+/**
+    How a single instance is represented on the filesystem.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// A `.luau` file, or a directory with an `init.luau` file if it has children.
+    Script(ScriptKind),
+    /// A directory, with an `init.meta.json` file for its class and properties.
+    Directory,
+    /// A single `.model.json` file, containing all of its descendants.
+    Model,
+}
+
+/**
+    Writes the given instance and all of its descendants to
+    the given directory as a Rojo project, including a
+    `default.project.json` file describing the project.
+
+    If the given instance is a `DataModel`, each of its children
+    is written to its own path and mapped in the project tree.
+
+    References between instances are written as `Rojo_Id` and
+    `Rojo_Target_<Property>` attributes, which Rojo turns back into references.
+
+    # Errors
+
+    - If any file or directory could not be written.
+    - If the instance is a `DataModel` and the names of its
+      children are not unique or can not be used as file names.
+*/
+pub fn write_rojo_project(
+    root: Instance,
+    output_dir: impl AsRef<Path>,
+    options: &RojoProjectOptions,
+) -> RojoResult<()> {
+    let output_dir = output_dir.as_ref();
+    let project_name = options
+        .project_name
+        .clone()
+        .unwrap_or_else(|| root.get_name());
+    let is_data_model = root.get_class_name() == "DataModel";
+
+    let mut document = if is_data_model {
+        Document::from_data_model_instance(root)?
+    } else {
+        Document::from_instance_array(vec![root])?
+    };
+    if options.canonicalize {
+        document.canonicalize();
+    }
+    let dom = document.into_weak_dom();
+    let ids = reference_ids(&dom);
+
+    create_dir(output_dir)?;
+
+    let children = dom
+        .root()
+        .children()
+        .iter()
+        .filter_map(|&child_ref| dom.get_by_ref(child_ref));
+    let mut tree = JsonMap::new();
+    if is_data_model {
+        let source_dir = output_dir.join(SOURCE_DIR_NAME);
+        create_dir(&source_dir)?;
+
+        tree.insert("$className".to_string(), json!("DataModel"));
+        for child in children {
+            if !is_valid_file_name(&child.name) || tree.contains_key(&child.name) {
+                return Err(RojoError::InvalidName(child.name.clone()));
+            }
+            let path = write_instance(&dom, &ids, child, &source_dir, &child.name)?;
+            tree.insert(
+                child.name.clone(),
+                json!({ "$path": project_relative_path(output_dir, &path) }),
+            );
+        }
+    } else {
+        // The document only contains the given instance, which is the root of the project
+        for child in children {
+            let path = write_instance(&dom, &ids, child, output_dir, SOURCE_DIR_NAME)?;
+            tree.insert(
+                "$path".to_string(),
+                json!(project_relative_path(output_dir, &path)),
+            );
+        }
+    }
+
+    write_json(
+        &output_dir.join(PROJECT_FILE_NAME),
+        &json!({ "name": project_name, "tree": tree }),
+    )
+}
+
+/**
+    Gives an id to every instance that is referenced by a property of another instance.
+
+    Ids are made of the names of the instance and its ancestors, so that writing the
+    same instances always gives the same ids, with a number added if it is not unique.
+*/
+fn reference_ids(dom: &WeakDom) -> ReferenceIds {
+    let mut ids = ReferenceIds::new();
+    let mut used = HashSet::new();
+    for inst in dom.descendants() {
+        let mut targets = inst
+            .properties
+            .iter()
+            .filter_map(|(name, value)| match value {
+                DomValue::Ref(target) => Some((name.as_str(), *target)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        targets.sort_unstable_by_key(|(name, _)| *name);
+
+        for (_, target) in targets {
+            if target == dom.root_ref() || ids.contains_key(&target) {
+                continue;
+            }
+            let Some(target_inst) = dom.get_by_ref(target) else {
+                continue;
+            };
+
+            let mut names = vec![target_inst.name.as_str()];
+            let mut parent = target_inst.parent();
+            while let Some(inst) = dom.get_by_ref(parent).filter(|_| parent != dom.root_ref()) {
+                names.push(inst.name.as_str());
+                parent = inst.parent();
+            }
+            names.reverse();
+
+            let path = names.join("/");
+            let mut id = path.clone();
+            let mut count = 1;
+            while !used.insert(id.clone()) {
+                count += 1;
+                id = format!("{path}#{count}");
+            }
+            ids.insert(target, id);
+        }
+    }
+    ids
+}
+
+/**
+    Writes a single instance, and its descendants, into the given directory.
+
+    Returns the path of the written file or directory.
+*/
+fn write_instance(
+    dom: &WeakDom,
+    ids: &ReferenceIds,
+    inst: &DomInstance,
+    parent_dir: &Path,
+    stem: &str,
+) -> RojoResult<PathBuf> {
+    match choose_layout(dom, inst) {
+        Layout::Script(kind) => {
+            let file_name = |stem: &str| format!("{stem}{}.luau", kind.suffix());
+            let properties = properties_to_json(ids, inst, false);
+            if inst.children().is_empty() {
+                write_file(&parent_dir.join(file_name(stem)), script_source(inst))?;
+                if !properties.is_empty() {
+                    write_json(
+                        &parent_dir.join(format!("{stem}{SUFFIX_META}")),
+                        &json!({ "properties": properties }),
+                    )?;
+                }
+                Ok(parent_dir.join(file_name(stem)))
+            } else {
+                let dir = parent_dir.join(stem);
+                create_dir(&dir)?;
+                write_file(&dir.join(file_name(INIT_NAME)), script_source(inst))?;
+                if !properties.is_empty() {
+                    write_json(
+                        &dir.join(format!("{INIT_NAME}{SUFFIX_META}")),
+                        &json!({ "properties": properties }),
+                    )?;
+                }
+                write_children(dom, ids, inst, &dir)?;
+                Ok(dir)
+            }
+        }
+        Layout::Directory => {
+            let dir = parent_dir.join(stem);
+            create_dir(&dir)?;
+            let properties = properties_to_json(ids, inst, true);
+            if inst.class != "Folder" || !properties.is_empty() {
+                let mut meta = JsonMap::new();
+                if inst.class != "Folder" {
+                    meta.insert("className".to_string(), json!(inst.class.as_str()));
+                }
+                if !properties.is_empty() {
+                    meta.insert("properties".to_string(), JsonValue::Object(properties));
+                }
+                write_json(
+                    &dir.join(format!("{INIT_NAME}{SUFFIX_META}")),
+                    &JsonValue::Object(meta),
+                )?;
+            }
+            write_children(dom, ids, inst, &dir)?;
+            Ok(dir)
+        }
+        Layout::Model => {
+            let path = parent_dir.join(format!("{stem}{SUFFIX_MODEL}"));
+            write_json(&path, &model_to_json(dom, ids, inst, false))?;
+            Ok(path)
+        }
+    }
+}
+
+fn write_children(
+    dom: &WeakDom,
+    ids: &ReferenceIds,
+    inst: &DomInstance,
+    dir: &Path,
+) -> RojoResult<()> {
+    for &child_ref in inst.children() {
+        let child = dom.get_by_ref(child_ref).expect("Invalid child ref");
+        write_instance(dom, ids, child, dir, &child.name)?;
+    }
+    Ok(())
+}
+
+/**
+    Chooses the layout for an instance.
+
+    Scripts are always written as source files, and other instances are written
+    as directories only if they need to be, to contain scripts. If the children
+    of an instance can not be written as separate files, because their names are
+    not unique or not valid file names, the instance falls back to a model file.
+*/
+fn choose_layout(dom: &WeakDom, inst: &DomInstance) -> Layout {
+    if !has_valid_child_names(dom, inst) {
+        return Layout::Model;
+    }
+    if let Some(kind) = ScriptKind::from_class_name(inst.class.as_str()) {
+        return Layout::Script(kind);
+    }
+    let has_children = !inst.children().is_empty();
+    if has_children && (inst.class == "Folder" || has_script_descendant(dom, inst)) {
+        Layout::Directory
+    } else {
+        Layout::Model
+    }
+}
+
+fn has_valid_child_names(dom: &WeakDom, inst: &DomInstance) -> bool {
+    let mut seen = HashSet::new();
+    inst.children().iter().all(|&child_ref| {
+        let child = dom.get_by_ref(child_ref).expect("Invalid child ref");
+        is_valid_file_name(&child.name) && seen.insert(child.name.to_ascii_lowercase())
+    })
+}
+
+fn has_script_descendant(dom: &WeakDom, inst: &DomInstance) -> bool {
+    inst.children().iter().any(|&child_ref| {
+        let child = dom.get_by_ref(child_ref).expect("Invalid child ref");
+        ScriptKind::from_class_name(child.class.as_str()).is_some()
+            || has_script_descendant(dom, child)
+    })
+}
+
+fn script_source(inst: &DomInstance) -> &str {
+    match inst.properties.get(&ustr(PROPERTY_NAME_SOURCE)) {
+        Some(DomValue::String(source)) => source.as_str(),
+        _ => "",
+    }
+}
+
+/**
+    Converts the properties of an instance into a json table, sorted by name.
+
+    The source of scripts is only included if `include_source` is set,
+    since it is otherwise written to its own file. References are added
+    to the attributes of the instance, since Rojo can not store them as
+    property values.
+*/
+fn properties_to_json(
+    ids: &ReferenceIds,
+    inst: &DomInstance,
+    include_source: bool,
+) -> JsonMap<String, JsonValue> {
+    let mut attributes = match inst.properties.get(&ustr(PROPERTY_NAME_ATTRIBUTES)) {
+        Some(DomValue::Attributes(attributes)) => attributes.clone(),
+        _ => DomAttributes::new(),
+    };
+    if let Some(id) = ids.get(&inst.referent()) {
+        attributes.insert(ATTRIBUTE_ID.to_string(), DomValue::String(id.clone()));
+    }
+    for (name, value) in &inst.properties {
+        if let DomValue::Ref(target) = value
+            && let Some(id) = ids.get(target)
+        {
+            attributes.insert(
+                format!("{ATTRIBUTE_TARGET_PREFIX}{name}"),
+                DomValue::String(id.clone()),
+            );
+        }
+    }
+
+    let mut properties = inst
+        .properties
+        .iter()
+        .filter(|(name, _)| !IGNORED_PROPERTIES.contains(&name.as_str()))
+        .filter(|(name, _)| include_source || name.as_str() != PROPERTY_NAME_SOURCE)
+        .filter(|(name, _)| name.as_str() != PROPERTY_NAME_ATTRIBUTES)
+        .filter_map(|(name, value)| Some((name.to_string(), property_to_json(value)?)))
+        .collect::<Vec<_>>();
+    if !attributes.is_empty()
+        && let Some(value) = property_to_json(&DomValue::Attributes(attributes))
+    {
+        properties.push((PROPERTY_NAME_ATTRIBUTES.to_string(), value));
+    }
+    properties.sort_by(|a, b| a.0.cmp(&b.0));
+    properties.into_iter().collect()
+}
+
+fn model_to_json(
+    dom: &WeakDom,
+    ids: &ReferenceIds,
+    inst: &DomInstance,
+    include_name: bool,
+) -> JsonValue {
+    let mut model = JsonMap::new();
+    if include_name {
+        model.insert("name".to_string(), json!(inst.name));
+    }
+    model.insert("className".to_string(), json!(inst.class.as_str()));
+
+    let properties = properties_to_json(ids, inst, true);
+    if !properties.is_empty() {
+        model.insert("properties".to_string(), JsonValue::Object(properties));
+    }
+
+    let children = inst
+        .children()
+        .iter()
+        .map(|&child_ref| {
+            let child = dom.get_by_ref(child_ref).expect("Invalid child ref");
+            model_to_json(dom, ids, child, true)
+        })
+        .collect::<Vec<_>>();
+    if !children.is_empty() {
+        model.insert("children".to_string(), JsonValue::Array(children));
+    }
+
+    JsonValue::Object(model)
+}
+
+fn project_relative_path(output_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(output_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
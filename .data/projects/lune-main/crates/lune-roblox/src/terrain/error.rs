@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+#[cfg(feature = "mlua")]
+use mlua::prelude::*;
+
+#[derive(Debug, Clone, Error)]
+pub enum SmoothGridError {
+    #[error("Failed to decode terrain - unexpected end of data")]
+    UnexpectedEnd,
+    #[error("Failed to decode terrain - unsupported version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Failed to decode terrain - unsupported chunk size {0}")]
+    UnsupportedChunkSize(u8),
+    #[error("Failed to decode terrain - invalid chunk position")]
+    InvalidChunkPosition,
+    #[error("Failed to decode terrain - unknown material id {0}")]
+    UnknownMaterial(u8),
+    #[error("Failed to decode terrain - chunk contains too many voxels")]
+    TooManyVoxels,
+}
+
+#[cfg(feature = "mlua")]
+impl From<SmoothGridError> for LuaError {
+    fn from(value: SmoothGridError) -> Self {
+        Self::RuntimeError(value.to_string())
+    }
+}
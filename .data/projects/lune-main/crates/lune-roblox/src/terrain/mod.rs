@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+
+mod error;
+mod smooth_grid;
+
+pub use error::SmoothGridError;
+
+pub type SmoothGridResult<T> = Result<T, SmoothGridError>;
+
+/**
+    The size of a single terrain voxel, in studs.
+*/
+pub const VOXEL_SIZE: f32 = 4.0;
+
+const CHUNK_SIZE_EXPONENT: u8 = 5;
+const CHUNK_SIZE: i32 = 1 << CHUNK_SIZE_EXPONENT;
+const CHUNK_VOLUME: usize = 1 << (CHUNK_SIZE_EXPONENT * 3);
+
+/**
+    The position of a voxel, in voxels, where each voxel
+    covers `VOXEL_SIZE` studs along each axis.
+*/
+pub type VoxelPosition = [i32; 3];
+
+type ChunkPosition = [i32; 3];
+type Chunk = Box<[Voxel]>;
+
+/**
+    A material that terrain voxels can be made of.
+
+    The discriminant of each material is the id used for it in the `SmoothGrid`
+    format, and the name of each material is also an `Enum.Material` name.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum TerrainMaterial {
+    Air = 0,
+    Water,
+    Grass,
+    Slate,
+    Concrete,
+    Brick,
+    Sand,
+    WoodPlanks,
+    Rock,
+    Glacier,
+    Snow,
+    Sandstone,
+    Mud,
+    Basalt,
+    Ground,
+    CrackedLava,
+    Asphalt,
+    Cobblestone,
+    Ice,
+    LeafyGrass,
+    Salt,
+    Limestone,
+    Pavement,
+}
+
+impl TerrainMaterial {
+    /**
+        All known terrain materials, ordered by id.
+    */
+    pub const ALL: [Self; 23] = [
+        Self::Air,
+        Self::Water,
+        Self::Grass,
+        Self::Slate,
+        Self::Concrete,
+        Self::Brick,
+        Self::Sand,
+        Self::WoodPlanks,
+        Self::Rock,
+        Self::Glacier,
+        Self::Snow,
+        Self::Sandstone,
+        Self::Mud,
+        Self::Basalt,
+        Self::Ground,
+        Self::CrackedLava,
+        Self::Asphalt,
+        Self::Cobblestone,
+        Self::Ice,
+        Self::LeafyGrass,
+        Self::Salt,
+        Self::Limestone,
+        Self::Pavement,
+    ];
+
+    /**
+        Gets the terrain material with the given `SmoothGrid` id, if one exists.
+    */
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(id)).copied()
+    }
+
+    /**
+        Gets the terrain material with the given `Enum.Material` name, if one exists.
+    */
+    #[must_use]
+    pub fn from_name(name: impl AsRef<str>) -> Option<Self> {
+        let name = name.as_ref();
+        Self::ALL
+            .into_iter()
+            .find(|material| material.name() == name)
+    }
+
+    /**
+        Gets the `SmoothGrid` id of this material.
+    */
+    #[must_use]
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /**
+        Gets the `Enum.Material` name of this material.
+    */
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Air => "Air",
+            Self::Water => "Water",
+            Self::Grass => "Grass",
+            Self::Slate => "Slate",
+            Self::Concrete => "Concrete",
+            Self::Brick => "Brick",
+            Self::Sand => "Sand",
+            Self::WoodPlanks => "WoodPlanks",
+            Self::Rock => "Rock",
+            Self::Glacier => "Glacier",
+            Self::Snow => "Snow",
+            Self::Sandstone => "Sandstone",
+            Self::Mud => "Mud",
+            Self::Basalt => "Basalt",
+            Self::Ground => "Ground",
+            Self::CrackedLava => "CrackedLava",
+            Self::Asphalt => "Asphalt",
+            Self::Cobblestone => "Cobblestone",
+            Self::Ice => "Ice",
+            Self::LeafyGrass => "LeafyGrass",
+            Self::Salt => "Salt",
+            Self::Limestone => "Limestone",
+            Self::Pavement => "Pavement",
+        }
+    }
+}
+
+/**
+    A single terrain voxel.
+
+    The occupancy of a voxel ranges from `0` (empty) to `255` (completely filled),
+    and a voxel with zero occupancy is always considered to be made of air.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Voxel {
+    material: TerrainMaterial,
+    occupancy: u8,
+}
+
+impl Voxel {
+    pub const AIR: Self = Self {
+        material: TerrainMaterial::Air,
+        occupancy: 0,
+    };
+
+    /**
+        Creates a new voxel of the given material and occupancy.
+
+        Air voxels and voxels with zero occupancy are both normalized to [`Voxel::AIR`].
+    */
+    #[must_use]
+    pub fn new(material: TerrainMaterial, occupancy: u8) -> Self {
+        if material == TerrainMaterial::Air || occupancy == 0 {
+            Self::AIR
+        } else {
+            Self {
+                material,
+                occupancy,
+            }
+        }
+    }
+
+    /**
+        Creates a new voxel that is completely filled with the given material.
+    */
+    #[must_use]
+    pub fn filled(material: TerrainMaterial) -> Self {
+        Self::new(material, u8::MAX)
+    }
+
+    /**
+        Gets the material of this voxel.
+    */
+    #[must_use]
+    pub fn material(self) -> TerrainMaterial {
+        self.material
+    }
+
+    /**
+        Gets the occupancy of this voxel, from `0` to `255`.
+    */
+    #[must_use]
+    pub fn occupancy(self) -> u8 {
+        self.occupancy
+    }
+
+    /**
+        Checks if this voxel is empty, meaning it is made of air.
+    */
+    #[must_use]
+    pub fn is_air(self) -> bool {
+        self.material == TerrainMaterial::Air
+    }
+}
+
+impl Default for Voxel {
+    fn default() -> Self {
+        Self::AIR
+    }
+}
+
+/**
+    A sparse grid of terrain voxels, stored in chunks of 32x32x32 voxels.
+
+    This is the decoded form of the `SmoothGrid` property of `Terrain`,
+    and can be converted to and from it using [`TerrainGrid::decode`]
+    and [`TerrainGrid::encode`]. Any voxel that has not been written
+    is considered to be air.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct TerrainGrid {
+    chunks: BTreeMap<ChunkPosition, Chunk>,
+}
+
+impl TerrainGrid {
+    /**
+        Creates a new, empty, terrain grid.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Gets the voxel at the given position.
+    */
+    #[must_use]
+    pub fn get(&self, position: VoxelPosition) -> Voxel {
+        let (chunk, index) = split_position(position);
+        self.chunks
+            .get(&chunk)
+            .map_or(Voxel::AIR, |voxels| voxels[index])
+    }
+
+    /**
+        Sets the voxel at the given position.
+    */
+    pub fn set(&mut self, position: VoxelPosition, voxel: Voxel) {
+        let (chunk, index) = split_position(position);
+        if let Some(voxels) = self.chunks.get_mut(&chunk) {
+            voxels[index] = voxel;
+        } else if !voxel.is_air() {
+            let mut voxels = empty_chunk();
+            voxels[index] = voxel;
+            self.chunks.insert(chunk, voxels);
+        }
+    }
+
+    /**
+        Removes all voxels from the grid.
+    */
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /**
+        Counts the number of voxels in the grid that are not air.
+    */
+    #[must_use]
+    pub fn count_cells(&self) -> usize {
+        self.chunks
+            .values()
+            .map(|voxels| voxels.iter().filter(|voxel| !voxel.is_air()).count())
+            .sum()
+    }
+}
+
+fn empty_chunk() -> Chunk {
+    vec![Voxel::AIR; CHUNK_VOLUME].into_boxed_slice()
+}
+
+fn split_position(position: VoxelPosition) -> (ChunkPosition, usize) {
+    let chunk = position.map(|value| value.div_euclid(CHUNK_SIZE));
+    let [x, y, z] = position.map(|value| {
+        usize::try_from(value.rem_euclid(CHUNK_SIZE)).expect("Euclidean remainder is positive")
+    });
+    let size = 1 << CHUNK_SIZE_EXPONENT;
+    (chunk, (x * size + y) * size + z)
+}
@@ -0,0 +1,293 @@
+/*
+    The SmoothGrid format, as stored in the `SmoothGrid` property of `Terrain`:
+
+    - A header with the format version (`1`) and the chunk size exponent (`5`)
+    - Any number of chunks, each one containing:
+        - A prefix byte, where each pair of bits starting from the lowest describes
+          the width of the x, y and z position deltas that follow (1, 2 or 4 bytes)
+        - The signed, little endian, position deltas from the previous chunk, in chunks
+        - Runs of voxels that cover the entire chunk, in x, y, z order, each one containing:
+            - A flags byte, where the lowest 6 bits are the material id, the next bit
+              marks that an occupancy byte follows and the highest bit marks that a
+              count byte follows
+            - The occupancy of the voxels, if not the default for the material
+            - The number of voxels in the run minus one, if more than one
+*/
+
+use super::{
+    CHUNK_SIZE_EXPONENT, CHUNK_VOLUME, ChunkPosition, SmoothGridError, SmoothGridResult,
+    TerrainGrid, TerrainMaterial, Voxel, empty_chunk,
+};
+
+const VERSION: u8 = 1;
+
+const FLAG_MATERIAL_MASK: u8 = 0b0011_1111;
+const FLAG_HAS_OCCUPANCY: u8 = 0b0100_0000;
+const FLAG_HAS_COUNT: u8 = 0b1000_0000;
+
+const MAX_RUN_LENGTH: usize = 256;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_array<const N: usize>(&mut self) -> SmoothGridResult<[u8; N]> {
+        let (head, rest) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or(SmoothGridError::UnexpectedEnd)?;
+        self.bytes = rest;
+        Ok(*head)
+    }
+
+    fn read_u8(&mut self) -> SmoothGridResult<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+}
+
+fn default_occupancy(material: TerrainMaterial) -> u8 {
+    if material == TerrainMaterial::Air {
+        0
+    } else {
+        u8::MAX
+    }
+}
+
+impl TerrainGrid {
+    /**
+        Decodes a terrain grid from the contents of a `SmoothGrid` property.
+
+        An empty `SmoothGrid` decodes to an empty terrain grid.
+
+        # Errors
+
+        Errors if the given bytes are not a valid `SmoothGrid`.
+    */
+    pub fn decode(bytes: impl AsRef<[u8]>) -> SmoothGridResult<Self> {
+        let mut grid = Self::new();
+        let mut reader = Reader {
+            bytes: bytes.as_ref(),
+        };
+        if reader.is_empty() {
+            return Ok(grid);
+        }
+
+        let version = reader.read_u8()?;
+        if version != VERSION {
+            return Err(SmoothGridError::UnsupportedVersion(version));
+        }
+        let exponent = reader.read_u8()?;
+        if exponent != CHUNK_SIZE_EXPONENT {
+            return Err(SmoothGridError::UnsupportedChunkSize(exponent));
+        }
+
+        let mut position: ChunkPosition = [0; 3];
+        while !reader.is_empty() {
+            let prefix = reader.read_u8()?;
+            for (axis, value) in position.iter_mut().enumerate() {
+                let delta = match (prefix >> (axis * 2)) & 0b11 {
+                    0 => i32::from(i8::from_le_bytes(reader.read_array()?)),
+                    1 => i32::from(i16::from_le_bytes(reader.read_array()?)),
+                    2 => i32::from_le_bytes(reader.read_array()?),
+                    _ => return Err(SmoothGridError::InvalidChunkPosition),
+                };
+                *value = value.wrapping_add(delta);
+            }
+
+            let mut voxels = empty_chunk();
+            let mut index = 0;
+            while index < CHUNK_VOLUME {
+                let flags = reader.read_u8()?;
+                let id = flags & FLAG_MATERIAL_MASK;
+                let material =
+                    TerrainMaterial::from_id(id).ok_or(SmoothGridError::UnknownMaterial(id))?;
+                let occupancy = if flags & FLAG_HAS_OCCUPANCY == 0 {
+                    default_occupancy(material)
+                } else {
+                    reader.read_u8()?
+                };
+                let count = if flags & FLAG_HAS_COUNT == 0 {
+                    1
+                } else {
+                    usize::from(reader.read_u8()?) + 1
+                };
+                let run = voxels
+                    .get_mut(index..index + count)
+                    .ok_or(SmoothGridError::TooManyVoxels)?;
+                run.fill(Voxel::new(material, occupancy));
+                index += count;
+            }
+
+            grid.chunks.insert(position, voxels);
+        }
+
+        Ok(grid)
+    }
+
+    /**
+        Encodes this terrain grid into the contents of a `SmoothGrid` property.
+
+        Chunks that only contain air are not included in the encoded data.
+    */
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![VERSION, CHUNK_SIZE_EXPONENT];
+
+        let mut previous: ChunkPosition = [0; 3];
+        for (position, voxels) in &self.chunks {
+            if voxels.iter().all(|voxel| voxel.is_air()) {
+                continue;
+            }
+
+            let prefix_index = bytes.len();
+            let mut prefix = 0;
+            bytes.push(prefix);
+            for (axis, (current, previous)) in position.iter().zip(previous).enumerate() {
+                let delta = current.wrapping_sub(previous);
+                if let Ok(delta) = i8::try_from(delta) {
+                    bytes.extend(delta.to_le_bytes());
+                } else if let Ok(delta) = i16::try_from(delta) {
+                    prefix |= 1 << (axis * 2);
+                    bytes.extend(delta.to_le_bytes());
+                } else {
+                    prefix |= 2 << (axis * 2);
+                    bytes.extend(delta.to_le_bytes());
+                }
+            }
+            bytes[prefix_index] = prefix;
+            previous = *position;
+
+            for run in voxels.chunk_by(|a, b| a == b) {
+                let voxel = run[0];
+                for part in run.chunks(MAX_RUN_LENGTH) {
+                    let mut flags = voxel.material().id();
+                    if voxel.occupancy() != default_occupancy(voxel.material()) {
+                        flags |= FLAG_HAS_OCCUPANCY;
+                    }
+                    if part.len() > 1 {
+                        flags |= FLAG_HAS_COUNT;
+                    }
+                    bytes.push(flags);
+                    if flags & FLAG_HAS_OCCUPANCY != 0 {
+                        bytes.push(voxel.occupancy());
+                    }
+                    if flags & FLAG_HAS_COUNT != 0 {
+                        // NOTE: Runs are split into parts of at most
+                        // MAX_RUN_LENGTH voxels, so this never truncates
+                        bytes.push((part.len() - 1) as u8);
+                    }
+                }
+            }
+        }
+
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NOTE: This is the `SmoothGrid` that Roblox Studio saves for
+    // places without any terrain, such as the default baseplate
+    const SAVED_EMPTY_GRID: &[u8] = &[0x01, 0x05];
+
+    fn chunk_bytes(prefix: u8, deltas: [i8; 3], runs: &[(u8, Option<u8>, usize)]) -> Vec<u8> {
+        let mut bytes = vec![prefix];
+        bytes.extend(deltas.map(|delta| delta.to_le_bytes()[0]));
+        for &(material, occupancy, count) in runs {
+            let mut remaining = count;
+            while remaining > 0 {
+                let part = remaining.min(MAX_RUN_LENGTH);
+                let mut flags = material;
+                if occupancy.is_some() {
+                    flags |= FLAG_HAS_OCCUPANCY;
+                }
+                if part > 1 {
+                    flags |= FLAG_HAS_COUNT;
+                }
+                bytes.push(flags);
+                bytes.extend(occupancy);
+                if part > 1 {
+                    bytes.push(u8::try_from(part - 1).unwrap());
+                }
+                remaining -= part;
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn decodes_saved_empty_grid() {
+        let grid = TerrainGrid::decode(SAVED_EMPTY_GRID).unwrap();
+        assert_eq!(grid.count_cells(), 0);
+        assert_eq!(grid.encode(), SAVED_EMPTY_GRID);
+    }
+
+    #[test]
+    fn decodes_chunks_at_relative_positions() {
+        let grass = TerrainMaterial::Grass.id();
+        let water = TerrainMaterial::Water.id();
+
+        let mut bytes = SAVED_EMPTY_GRID.to_vec();
+        bytes.extend(chunk_bytes(
+            0,
+            [1, -1, 0],
+            &[
+                (grass, Some(128), 1),
+                (grass, None, 2),
+                (0, None, CHUNK_VOLUME - 3),
+            ],
+        ));
+        bytes.extend(chunk_bytes(0, [0, 0, 2], &[(water, None, CHUNK_VOLUME)]));
+
+        let grid = TerrainGrid::decode(&bytes).unwrap();
+        assert_eq!(grid.count_cells(), 3 + CHUNK_VOLUME);
+        assert_eq!(
+            grid.get([32, -32, 0]),
+            Voxel::new(TerrainMaterial::Grass, 128)
+        );
+        assert_eq!(
+            grid.get([32, -32, 1]),
+            Voxel::filled(TerrainMaterial::Grass)
+        );
+        assert_eq!(
+            grid.get([32, -32, 2]),
+            Voxel::filled(TerrainMaterial::Grass)
+        );
+        assert_eq!(grid.get([32, -32, 3]), Voxel::AIR);
+        assert_eq!(
+            grid.get([63, -1, 64]),
+            Voxel::filled(TerrainMaterial::Water)
+        );
+        assert_eq!(grid.get([63, -1, 96]), Voxel::AIR);
+    }
+
+    #[test]
+    fn rejects_invalid_grids() {
+        let mut truncated = SAVED_EMPTY_GRID.to_vec();
+        truncated.extend(chunk_bytes(0, [0, 0, 0], &[(0, None, CHUNK_VOLUME - 1)]));
+        assert!(matches!(
+            TerrainGrid::decode(&truncated),
+            Err(SmoothGridError::UnexpectedEnd)
+        ));
+
+        let mut overflowing = SAVED_EMPTY_GRID.to_vec();
+        overflowing.extend(chunk_bytes(0, [0, 0, 0], &[(0, None, CHUNK_VOLUME - 1)]));
+        overflowing.extend([FLAG_HAS_COUNT, 1]);
+        assert!(matches!(
+            TerrainGrid::decode(&overflowing),
+            Err(SmoothGridError::TooManyVoxels)
+        ));
+
+        assert!(matches!(
+            TerrainGrid::decode([2, 5]),
+            Err(SmoothGridError::UnsupportedVersion(2))
+        ));
+    }
+}
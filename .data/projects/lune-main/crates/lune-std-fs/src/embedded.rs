@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use mlua::prelude::*;
+
+use lune_utils::path::{EmbeddedAssets, EmbeddedFiles};
+
+/**
+    A path given to one of the `fs` functions.
+
+    Paths starting with `@embedded/` refer to assets embedded into a standalone
+    binary. When not running as a standalone binary, there are no embedded assets,
+    and such paths instead fall back to files relative to the current directory.
+*/
+#[derive(Debug, Clone)]
+pub enum FsPath {
+    Disk(PathBuf),
+    Embedded {
+        assets: EmbeddedAssets,
+        path: PathBuf,
+        display: String,
+    },
+}
+
+impl FsPath {
+    pub fn new(lua: &Lua, path: String) -> Self {
+        let Some(relative) = EmbeddedAssets::strip_prefix(&path) else {
+            return Self::Disk(PathBuf::from(path));
+        };
+        match lua.app_data_ref::<EmbeddedAssets>() {
+            Some(assets) => Self::Embedded {
+                assets: assets.clone(),
+                path: EmbeddedFiles::embedded_path(relative),
+                display: path,
+            },
+            None => Self::Disk(PathBuf::from(relative)),
+        }
+    }
+
+    /**
+        Converts this path into a path on disk, for functions that write, move
+        or copy files, erroring if the path refers to an embedded asset.
+    */
+    pub fn into_disk(self) -> LuaResult<PathBuf> {
+        match self {
+            Self::Disk(path) => Ok(path),
+            Self::Embedded { display, .. } => Err(LuaError::RuntimeError(format!(
+                "Embedded files can only be read, not modified or copied: '{display}'"
+            ))),
+        }
+    }
+}
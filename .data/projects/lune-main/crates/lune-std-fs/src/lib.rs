@@ -1,7 +1,6 @@
 #![allow(clippy::cargo_common_metadata)]
 
 use std::io::ErrorKind as IoErrorKind;
-use std::path::PathBuf;
 
 use async_fs as fs;
 use bstr::{BString, ByteSlice};
@@ -11,11 +10,13 @@ use mlua::prelude::*;
 use lune_utils::TableBuilder;
 
 mod copy;
+mod embedded;
 mod metadata;
 mod options;
 
 use self::copy::copy;
-use self::metadata::FsMetadata;
+use self::embedded::FsPath;
+use self::metadata::{FsMetadata, FsMetadataKind};
 use self::options::FsWriteOptions;
 
 const TYPEDEFS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/types.d.luau"));
@@ -52,12 +53,41 @@ pub fn module(lua: Lua) -> LuaResult<LuaTable> {
 }
 
 async fn fs_read_file(lua: Lua, path: String) -> LuaResult<LuaString> {
-    let bytes = fs::read(&path).await.into_lua_err()?;
+    let bytes = match FsPath::new(&lua, path) {
+        FsPath::Disk(path) => fs::read(&path).await.into_lua_err()?,
+        FsPath::Embedded {
+            assets,
+            path,
+            display,
+        } => match assets.files().get(&path) {
+            Some(bytes) => bytes.to_vec(),
+            None => {
+                return Err(LuaError::RuntimeError(format!(
+                    "No embedded file exists at the path '{display}'"
+                )));
+            }
+        },
+    };
 
     lua.create_string(bytes)
 }
 
-async fn fs_read_dir(_: Lua, path: String) -> LuaResult<Vec<String>> {
+async fn fs_read_dir(lua: Lua, path: String) -> LuaResult<Vec<String>> {
+    let path = match FsPath::new(&lua, path) {
+        FsPath::Disk(path) => path,
+        FsPath::Embedded {
+            assets,
+            path,
+            display,
+        } => {
+            return assets.files().read_dir(&path).ok_or_else(|| {
+                LuaError::RuntimeError(format!(
+                    "No embedded directory exists at the path '{display}'"
+                ))
+            });
+        }
+    };
+
     let mut dir_strings = Vec::new();
     let mut dir = fs::read_dir(&path).await.into_lua_err()?;
     while let Some(dir_entry) = dir.try_next().await.into_lua_err()? {
@@ -73,23 +103,40 @@ async fn fs_read_dir(_: Lua, path: String) -> LuaResult<Vec<String>> {
     Ok(dir_strings)
 }
 
-async fn fs_write_file(_: Lua, (path, contents): (String, BString)) -> LuaResult<()> {
+async fn fs_write_file(lua: Lua, (path, contents): (String, BString)) -> LuaResult<()> {
+    let path = FsPath::new(&lua, path).into_disk()?;
     fs::write(&path, contents.as_bytes()).await.into_lua_err()
 }
 
-async fn fs_write_dir(_: Lua, path: String) -> LuaResult<()> {
+async fn fs_write_dir(lua: Lua, path: String) -> LuaResult<()> {
+    let path = FsPath::new(&lua, path).into_disk()?;
     fs::create_dir_all(&path).await.into_lua_err()
 }
 
-async fn fs_remove_file(_: Lua, path: String) -> LuaResult<()> {
+async fn fs_remove_file(lua: Lua, path: String) -> LuaResult<()> {
+    let path = FsPath::new(&lua, path).into_disk()?;
     fs::remove_file(&path).await.into_lua_err()
 }
 
-async fn fs_remove_dir(_: Lua, path: String) -> LuaResult<()> {
+async fn fs_remove_dir(lua: Lua, path: String) -> LuaResult<()> {
+    let path = FsPath::new(&lua, path).into_disk()?;
     fs::remove_dir_all(&path).await.into_lua_err()
 }
 
-async fn fs_metadata(_: Lua, path: String) -> LuaResult<FsMetadata> {
+async fn fs_metadata(lua: Lua, path: String) -> LuaResult<FsMetadata> {
+    let path = match FsPath::new(&lua, path) {
+        FsPath::Disk(path) => path,
+        FsPath::Embedded { assets, path, .. } => {
+            return Ok(if assets.files().is_file(&path) {
+                FsMetadata::embedded(FsMetadataKind::File)
+            } else if assets.files().is_dir(&path) {
+                FsMetadata::embedded(FsMetadataKind::Dir)
+            } else {
+                FsMetadata::not_found()
+            });
+        }
+    };
+
     match fs::metadata(path).await {
         Err(e) if e.kind() == IoErrorKind::NotFound => Ok(FsMetadata::not_found()),
         Ok(meta) => Ok(FsMetadata::from(meta)),
@@ -97,7 +144,12 @@ async fn fs_metadata(_: Lua, path: String) -> LuaResult<FsMetadata> {
     }
 }
 
-async fn fs_is_file(_: Lua, path: String) -> LuaResult<bool> {
+async fn fs_is_file(lua: Lua, path: String) -> LuaResult<bool> {
+    let path = match FsPath::new(&lua, path) {
+        FsPath::Disk(path) => path,
+        FsPath::Embedded { assets, path, .. } => return Ok(assets.files().is_file(&path)),
+    };
+
     match fs::metadata(path).await {
         Err(e) if e.kind() == IoErrorKind::NotFound => Ok(false),
         Ok(meta) => Ok(meta.is_file()),
@@ -105,7 +157,12 @@ async fn fs_is_file(_: Lua, path: String) -> LuaResult<bool> {
     }
 }
 
-async fn fs_is_dir(_: Lua, path: String) -> LuaResult<bool> {
+async fn fs_is_dir(lua: Lua, path: String) -> LuaResult<bool> {
+    let path = match FsPath::new(&lua, path) {
+        FsPath::Disk(path) => path,
+        FsPath::Embedded { assets, path, .. } => return Ok(assets.files().is_dir(&path)),
+    };
+
     match fs::metadata(path).await {
         Err(e) if e.kind() == IoErrorKind::NotFound => Ok(false),
         Ok(meta) => Ok(meta.is_dir()),
@@ -113,15 +170,15 @@ async fn fs_is_dir(_: Lua, path: String) -> LuaResult<bool> {
     }
 }
 
-async fn fs_move(_: Lua, (from, to, options): (String, String, FsWriteOptions)) -> LuaResult<()> {
-    let path_from = PathBuf::from(from);
+async fn fs_move(lua: Lua, (from, to, options): (String, String, FsWriteOptions)) -> LuaResult<()> {
+    let path_from = FsPath::new(&lua, from).into_disk()?;
     if !path_from.exists() {
         return Err(LuaError::RuntimeError(format!(
             "No file or directory exists at the path '{}'",
             path_from.display()
         )));
     }
-    let path_to = PathBuf::from(to);
+    let path_to = FsPath::new(&lua, to).into_disk()?;
     if !options.overwrite && path_to.exists() {
         return Err(LuaError::RuntimeError(format!(
             "A file or directory already exists at the path '{}'",
@@ -132,6 +189,8 @@ async fn fs_move(_: Lua, (from, to, options): (String, String, FsWriteOptions))
     Ok(())
 }
 
-async fn fs_copy(_: Lua, (from, to, options): (String, String, FsWriteOptions)) -> LuaResult<()> {
+async fn fs_copy(lua: Lua, (from, to, options): (String, String, FsWriteOptions)) -> LuaResult<()> {
+    let from = FsPath::new(&lua, from).into_disk()?;
+    let to = FsPath::new(&lua, to).into_disk()?;
     copy(from, to, options).await
 }
@@ -114,6 +114,17 @@ impl FsMetadata {
             permissions: None,
         }
     }
+
+    pub fn embedded(kind: FsMetadataKind) -> Self {
+        Self {
+            kind,
+            exists: true,
+            created_at: None,
+            modified_at: None,
+            accessed_at: None,
+            permissions: Some(FsPermissions { read_only: true }),
+        }
+    }
 }
 
 impl IntoLua for FsMetadata {
@@ -1,14 +1,19 @@
 #![allow(clippy::cargo_common_metadata)]
 
-use std::sync::OnceLock;
+use std::{collections::HashMap, sync::OnceLock};
 
 use mlua::prelude::*;
 use mlua_luau_scheduler::LuaSpawnExt;
 
 use lune_roblox::{
-    document::{Document, DocumentError, DocumentFormat, DocumentKind},
-    instance::{Instance, registry::InstanceRegistry},
+    diff::{DiffOptions, InstanceDiff, MatchBy, apply_patch, diff_instances},
+    document::{
+        Document, DocumentError, DocumentFilter, DocumentFormat, DocumentKind, inspect_document,
+    },
+    instance::{Instance, registry::InstanceRegistry, signals::fire_pending_signals},
+    query::Selector,
     reflection::Database as ReflectionDatabase,
+    rojo::{RojoError, RojoProjectOptions, read_rojo_project, write_rojo_project},
 };
 
 static REFLECTION_DATABASE: OnceLock<ReflectionDatabase> = OnceLock::new();
@@ -47,6 +52,12 @@ pub fn module(lua: Lua) -> LuaResult<LuaTable> {
         .with_async_function("deserializeModel", deserialize_model)?
         .with_async_function("serializePlace", serialize_place)?
         .with_async_function("serializeModel", serialize_model)?
+        .with_async_function("toRojoProject", to_rojo_project)?
+        .with_async_function("fromRojoProject", from_rojo_project)?
+        .with_async_function("inspectDocument", inspect_document_contents)?
+        .with_function("query", query)?
+        .with_function("diff", diff)?
+        .with_function("applyPatch", apply_diff_patch)?
         .with_function("getAuthCookie", get_auth_cookie)?
         .with_function("getReflectionDatabase", get_reflection_database)?
         .with_function("implementProperty", implement_property)?
@@ -58,16 +69,47 @@ pub fn module(lua: Lua) -> LuaResult<LuaTable> {
         .build_readonly()
 }
 
-async fn deserialize_place(lua: Lua, contents: LuaString) -> LuaResult<LuaValue> {
+async fn deserialize_place(
+    lua: Lua,
+    (contents, options): (LuaString, Option<LuaTable>),
+) -> LuaResult<LuaValue> {
+    let filter = parse_document_filter(options)?;
     let bytes = contents.as_bytes().to_vec();
     let fut = lua.spawn_blocking(move || {
-        let doc = Document::from_bytes(bytes, DocumentKind::Place)?;
+        let doc = match filter {
+            Some(filter) => Document::from_bytes_filtered(bytes, DocumentKind::Place, &filter)?,
+            None => Document::from_bytes(bytes, DocumentKind::Place)?,
+        };
         let data_model = doc.into_data_model_instance()?;
         Ok::<_, DocumentError>(data_model)
     });
     fut.await.into_lua_err()?.into_lua(&lua)
 }
 
+fn parse_document_filter(options: Option<LuaTable>) -> LuaResult<Option<DocumentFilter>> {
+    let Some(include) = options
+        .map(|options| options.get::<Option<LuaTable>>("include"))
+        .transpose()?
+        .flatten()
+    else {
+        return Ok(None);
+    };
+
+    let paths = include.get::<Option<Vec<String>>>("paths")?;
+    let class_names = include.get::<Option<Vec<String>>>("classNames")?;
+
+    let filter = paths
+        .into_iter()
+        .flatten()
+        .fold(DocumentFilter::new(), DocumentFilter::with_path);
+    let filter = class_names
+        .into_iter()
+        .flatten()
+        .fold(filter, DocumentFilter::with_class_name);
+
+    Ok(Some(filter))
+}
+
 async fn deserialize_model(lua: Lua, contents: LuaString) -> LuaResult<LuaValue> {
     let bytes = contents.as_bytes().to_vec();
     let fut = lua.spawn_blocking(move || {
@@ -80,11 +122,15 @@ async fn deserialize_model(lua: Lua, contents: LuaString) -> LuaResult<LuaValue>
 
 async fn serialize_place(
     lua: Lua,
-    (data_model, as_xml): (LuaUserDataRef<Instance>, Option<bool>),
+    (data_model, as_xml, options): (LuaUserDataRef<Instance>, Option<bool>, Option<LuaTable>),
 ) -> LuaResult<LuaString> {
     let data_model = *data_model;
+    let canonicalize = parse_canonicalize_option(options)?;
     let fut = lua.spawn_blocking(move || {
-        let doc = Document::from_data_model_instance(data_model)?;
+        let mut doc = Document::from_data_model_instance(data_model)?;
+        if canonicalize {
+            doc.canonicalize();
+        }
         let bytes = doc.to_bytes_with_format(match as_xml {
             Some(true) => DocumentFormat::Xml,
             _ => DocumentFormat::Binary,
@@ -97,11 +143,19 @@ async fn serialize_place(
 
 async fn serialize_model(
     lua: Lua,
-    (instances, as_xml): (Vec<LuaUserDataRef<Instance>>, Option<bool>),
+    (instances, as_xml, options): (
+        Vec<LuaUserDataRef<Instance>>,
+        Option<bool>,
+        Option<LuaTable>,
+    ),
 ) -> LuaResult<LuaString> {
     let instances = instances.iter().map(|i| **i).collect();
+    let canonicalize = parse_canonicalize_option(options)?;
     let fut = lua.spawn_blocking(move || {
-        let doc = Document::from_instance_array(instances)?;
+        let mut doc = Document::from_instance_array(instances)?;
+        if canonicalize {
+            doc.canonicalize();
+        }
         let bytes = doc.to_bytes_with_format(match as_xml {
             Some(true) => DocumentFormat::Xml,
             _ => DocumentFormat::Binary,
@@ -112,6 +166,124 @@ async fn serialize_model(
     lua.create_string(bytes)
 }
 
+fn parse_canonicalize_option(options: Option<LuaTable>) -> LuaResult<bool> {
+    Ok(match options {
+        Some(options) => options
+            .get::<Option<bool>>("canonicalize")?
+            .unwrap_or_default(),
+        None => false,
+    })
+}
+
+async fn to_rojo_project(
+    lua: Lua,
+    (root, output_dir, options): (LuaUserDataRef<Instance>, String, Option<LuaTable>),
+) -> LuaResult<()> {
+    let root = *root;
+    let project_name = options
+        .as_ref()
+        .map(|options| options.get::<Option<String>>("projectName"))
+        .transpose()?
+        .flatten();
+    let options = RojoProjectOptions::new().with_canonicalize(parse_canonicalize_option(options)?);
+    let options = match project_name {
+        Some(name) => options.with_project_name(name),
+        None => options,
+    };
+    let fut = lua.spawn_blocking(move || write_rojo_project(root, output_dir, &options));
+    fut.await.into_lua_err()
+}
+
+async fn from_rojo_project(lua: Lua, path: String) -> LuaResult<LuaValue> {
+    let fut = lua.spawn_blocking(move || {
+        let root = read_rojo_project(path)?;
+        Ok::<_, RojoError>(root)
+    });
+    fut.await.into_lua_err()?.into_lua(&lua)
+}
+
+async fn inspect_document_contents(lua: Lua, contents: LuaString) -> LuaResult<LuaValue> {
+    let bytes = contents.as_bytes().to_vec();
+    let fut = lua.spawn_blocking(move || inspect_document(bytes));
+    let report = fut.await.into_lua_err()?;
+    lua.to_value_with(
+        &report,
+        LuaSerializeOptions::new().serialize_none_to_null(false),
+    )
+}
+
+fn query(lua: &Lua, (root, selector): (LuaUserDataRef<Instance>, String)) -> LuaResult<LuaValue> {
+    let selector = Selector::parse(selector)?;
+    root.query_descendants(&selector).into_lua(lua)
+}
+
+fn diff(
+    lua: &Lua,
+    (old, new, options): (
+        LuaUserDataRef<Instance>,
+        LuaUserDataRef<Instance>,
+        Option<LuaTable>,
+    ),
+) -> LuaResult<LuaValue> {
+    let options = parse_diff_options(*old, *new, options)?;
+    let diff = diff_instances(*old, *new, &options);
+    lua.to_value_with(
+        &diff,
+        LuaSerializeOptions::new().serialize_none_to_null(false),
+    )
+}
+
+fn parse_diff_options(
+    old: Instance,
+    new: Instance,
+    options: Option<LuaTable>,
+) -> LuaResult<DiffOptions> {
+    let Some(options) = options else {
+        return Ok(DiffOptions::new());
+    };
+
+    let match_by = match options.get::<LuaValue>("matchBy")? {
+        LuaValue::Nil => MatchBy::Path,
+        LuaValue::String(s) if s == "path" => MatchBy::Path,
+        LuaValue::String(s) if s == "uniqueId" => MatchBy::UniqueId,
+        LuaValue::Function(callback) => {
+            // The callback may error or yield, so we call it for all
+            // instances up front instead of while computing the diff
+            let mut keys = HashMap::new();
+            for root in [old, new] {
+                for instance in std::iter::once(root).chain(root.get_descendants()) {
+                    if let Some(key) = callback.call::<Option<String>>(instance)? {
+                        keys.insert(instance, key);
+                    }
+                }
+            }
+            MatchBy::Custom(Box::new(move |instance| keys.get(instance).cloned()))
+        }
+        value => {
+            return Err(LuaError::runtime(format!(
+                "Invalid matchBy option - expected 'path', 'uniqueId' or a function, got {}",
+                value.type_name()
+            )));
+        }
+    };
+
+    let ignored_properties = options.get::<Option<Vec<String>>>("ignoreProperties")?;
+
+    Ok(ignored_properties.into_iter().flatten().fold(
+        DiffOptions::new().with_match_by(match_by),
+        DiffOptions::with_ignored_property,
+    ))
+}
+
+fn apply_diff_patch(
+    lua: &Lua,
+    (root, diff): (LuaUserDataRef<Instance>, LuaValue),
+) -> LuaResult<()> {
+    let diff: InstanceDiff = lua.from_value(diff)?;
+    apply_patch(*root, &diff)?;
+    fire_pending_signals(lua)
+}
+
 fn get_auth_cookie(_: &Lua, raw: Option<bool>) -> LuaResult<Option<String>> {
     if matches!(raw, Some(true)) {
         Ok(rbx_cookie::get_value())
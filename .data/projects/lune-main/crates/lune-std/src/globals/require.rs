@@ -1,8 +1,13 @@
+use lune_utils::path::EmbeddedFiles;
 use mlua::prelude::*;
 
 use crate::require::RequireResolver;
 
 pub fn create(lua: Lua) -> LuaResult<LuaValue> {
-    lua.create_require_function(RequireResolver::new())
+    let embedded = lua
+        .app_data_ref::<EmbeddedFiles>()
+        .map(|files| files.clone())
+        .unwrap_or_default();
+    lua.create_require_function(RequireResolver::new(embedded))
         .map(LuaValue::Function)
 }
@@ -8,7 +8,10 @@ use std::{
 use async_channel::{Receiver, Sender};
 use async_fs::read as read_file;
 
-use lune_utils::path::constants::FILE_CHUNK_PREFIX;
+use lune_utils::{
+    coverage::CoverageTracker,
+    path::{EmbeddedFiles, constants::FILE_CHUNK_PREFIX},
+};
 use mlua::prelude::*;
 use mlua_luau_scheduler::LuaSchedulerExt;
 
@@ -57,12 +60,14 @@ impl RequireLoaderState {
 #[derive(Debug, Clone)]
 pub(crate) struct RequireLoader {
     state: RequireLoaderState,
+    embedded: EmbeddedFiles,
 }
 
 impl RequireLoader {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(embedded: EmbeddedFiles) -> Self {
         Self {
             state: RequireLoaderState::new(),
+            embedded,
         }
     }
 
@@ -76,12 +81,14 @@ impl RequireLoader {
         let absolute_path = absolute_path.to_path_buf();
 
         let state = self.state.clone();
+        let embedded = self.embedded.clone();
 
         lua.create_async_function(move |lua, (): ()| {
             let relative_path = relative_path.clone();
             let absolute_path = absolute_path.clone();
 
             let state = state.clone();
+            let embedded = embedded.clone();
 
             async move {
                 if let Some(rx) = state.get_pending_at_path(&absolute_path) {
@@ -93,9 +100,19 @@ impl RequireLoader {
                     let tx = state.create_pending_at_path(&absolute_path);
 
                     let chunk_name = format!("{FILE_CHUNK_PREFIX}{}", relative_path.display());
-                    let chunk_bytes = read_file(&absolute_path).await?;
-
-                    let chunk = lua.load(chunk_bytes).set_name(chunk_name);
+                    let chunk_bytes = match embedded.get(&absolute_path) {
+                        Some(bytes) => bytes.to_vec(),
+                        None => read_file(&absolute_path).await?,
+                    };
+
+                    let chunk = lua.load(chunk_bytes).set_name(chunk_name).into_function()?;
+
+                    // Embedded modules are precompiled, and never have any coverage
+                    if !EmbeddedFiles::is_embedded_path(&absolute_path)
+                        && let Some(tracker) = lua.app_data_ref::<CoverageTracker>()
+                    {
+                        tracker.track(&absolute_path, chunk.clone());
+                    }
 
                     let thread_id = lua.push_thread_back(chunk, ())?;
                     lua.track_thread(thread_id);
@@ -5,8 +5,8 @@ use std::{
 };
 
 use lune_utils::path::{
-    LuauModulePath, clean_path_and_make_absolute,
-    constants::{FILE_CHUNK_PREFIX, FILE_NAME_CONFIG},
+    EmbeddedFiles, LuauModulePath, clean_path, clean_path_and_make_absolute,
+    constants::{EMBEDDED_ROOT, FILE_CHUNK_PREFIX, FILE_NAME_CONFIG},
     relative_path_normalize, relative_path_parent,
 };
 use mlua::prelude::*;
@@ -28,17 +28,23 @@ pub(crate) struct RequireResolver {
     /// Path to the current filesystem entry that
     /// directly represents the current module path.
     resolved: Option<LuauModulePath>,
+    /// Files embedded into a standalone binary, if any.
+    ///
+    /// Modules with embedded paths are resolved using these
+    /// files instead of the real filesystem, see [`EmbeddedFiles`].
+    embedded: EmbeddedFiles,
     /// Loader and accompanying state.
     loader: RequireLoader,
 }
 
 impl RequireResolver {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(embedded: EmbeddedFiles) -> Self {
         Self {
             relative: PathBuf::new(),
             absolute: PathBuf::new(),
             resolved: None,
-            loader: RequireLoader::new(),
+            loader: RequireLoader::new(embedded.clone()),
+            embedded,
         }
     }
 
@@ -61,7 +67,11 @@ impl RequireResolver {
         }
 
         // Make sure to resolve path **before** updating any paths state
-        let resolved = LuauModulePath::resolve(&absolute)?;
+        let resolved = if EmbeddedFiles::is_embedded_path(&absolute) {
+            LuauModulePath::resolve_embedded(&absolute, &self.embedded)?
+        } else {
+            LuauModulePath::resolve(&absolute)?
+        };
 
         self.absolute = absolute;
         self.relative = relative;
@@ -84,8 +94,7 @@ impl LuaRequire for RequireResolver {
         self.navigate_reset();
 
         if let Some(path) = chunk_name.strip_prefix(FILE_CHUNK_PREFIX) {
-            let rel = relative_path_normalize(Path::new(path));
-            let abs = clean_path_and_make_absolute(&rel);
+            let (rel, abs) = navigation_paths(Path::new(path));
 
             self.navigate_to(rel, abs)
         } else {
@@ -96,8 +105,7 @@ impl LuaRequire for RequireResolver {
     }
 
     fn jump_to_alias(&mut self, path: &str) -> Result<(), LuaNavigateError> {
-        let rel = relative_path_normalize(Path::new(path));
-        let abs = clean_path_and_make_absolute(&rel);
+        let (rel, abs) = navigation_paths(Path::new(path));
 
         self.navigate_to(rel, abs)
     }
@@ -106,7 +114,8 @@ impl LuaRequire for RequireResolver {
         let mut rel = self.relative.clone();
         let mut abs = self.absolute.clone();
 
-        if abs.pop() {
+        // The root of any embedded files has no parent, same as a filesystem root
+        if abs.as_os_str() != EMBEDDED_ROOT && abs.pop() {
             relative_path_parent(&mut rel);
             self.navigate_to(rel, abs)
         } else {
@@ -145,11 +154,19 @@ impl LuaRequire for RequireResolver {
     }
 
     fn has_config(&self) -> bool {
-        self.absolute.is_dir() && self.absolute.join(FILE_NAME_CONFIG).is_file()
+        if EmbeddedFiles::is_embedded_path(&self.absolute) {
+            self.embedded.is_file(self.absolute.join(FILE_NAME_CONFIG))
+        } else {
+            self.absolute.is_dir() && self.absolute.join(FILE_NAME_CONFIG).is_file()
+        }
     }
 
     fn config(&self) -> IoResult<Vec<u8>> {
-        read_file(self.absolute.join(FILE_NAME_CONFIG))
+        let path = self.absolute.join(FILE_NAME_CONFIG);
+        match self.embedded.get(&path) {
+            Some(contents) => Ok(contents.to_vec()),
+            None => read_file(path),
+        }
     }
 
     fn loader(&self, lua: &Lua) -> LuaResult<LuaFunction> {
@@ -159,3 +176,20 @@ impl LuaRequire for RequireResolver {
         self.loader.load(lua, self.relative.as_path(), resolved)
     }
 }
+
+/**
+    Converts a path to a pair of relative and absolute paths for navigation.
+
+    Embedded paths are already absolute within their own virtual
+    filesystem, and must not be resolved against the current directory.
+*/
+fn navigation_paths(path: &Path) -> (PathBuf, PathBuf) {
+    if EmbeddedFiles::is_embedded_path(path) {
+        let path = clean_path(path);
+        (path.clone(), path)
+    } else {
+        let rel = relative_path_normalize(path);
+        let abs = clean_path_and_make_absolute(&rel);
+        (rel, abs)
+    }
+}
@@ -0,0 +1,214 @@
+/*!
+    Utilities for collecting line coverage from Luau chunks.
+*/
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use mlua::prelude::*;
+
+use crate::path::{clean_path_and_make_absolute, get_current_dir};
+
+/**
+    Line coverage for a set of source files, mapping each executable
+    line in each file to the number of times that it was hit.
+
+    Files are identified by their paths, which are relative to the
+    current directory when possible, using `/` as the separator.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    files: BTreeMap<String, BTreeMap<u32, u64>>,
+}
+
+impl CoverageReport {
+    /**
+        Creates a new, empty, coverage report.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Records hits for an executable line in the given file,
+        adding to any hits that were previously recorded.
+
+        Lines with zero hits are recorded as executable lines that were never hit.
+    */
+    pub fn record(&mut self, path: impl Into<String>, line: u32, hits: u64) {
+        let count = self
+            .files
+            .entry(path.into())
+            .or_default()
+            .entry(line)
+            .or_default();
+        *count = count.saturating_add(hits);
+    }
+
+    /**
+        Merges another coverage report into this one, adding together the hits for any
+        lines in both reports, such as when the same file was loaded by multiple runtimes.
+    */
+    pub fn merge(&mut self, other: &Self) {
+        for (path, lines) in &other.files {
+            for (line, hits) in lines {
+                self.record(path.clone(), *line, *hits);
+            }
+        }
+    }
+
+    /**
+        Retains only the files with paths matching the given predicate.
+    */
+    pub fn retain(&mut self, mut predicate: impl FnMut(&str) -> bool) {
+        self.files.retain(|path, _| predicate(path));
+    }
+
+    /**
+        Returns an iterator over all files, with the hits for
+        each executable line in the file, sorted by path.
+    */
+    pub fn files(&self) -> impl Iterator<Item = (&str, &BTreeMap<u32, u64>)> {
+        self.files
+            .iter()
+            .map(|(path, lines)| (path.as_str(), lines))
+    }
+
+    /**
+        Returns the total number of executable lines, in all files.
+    */
+    #[must_use]
+    pub fn lines_found(&self) -> usize {
+        self.files.values().map(BTreeMap::len).sum()
+    }
+
+    /**
+        Returns the total number of executable lines that were hit at least once, in all files.
+    */
+    #[must_use]
+    pub fn lines_hit(&self) -> usize {
+        self.files
+            .values()
+            .map(|lines| lines.values().filter(|hits| **hits > 0).count())
+            .sum()
+    }
+
+    /**
+        Returns `true` if there are no files.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/**
+    Tracks chunks loaded from files, so that line coverage can be collected from them.
+
+    Chunks must be compiled with a coverage level of at least `1`, otherwise no coverage
+    will be collected for them. Runtimes with coverage enabled store a tracker as app data,
+    which is then used to track all chunks loaded from files, including required modules.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct CoverageTracker {
+    chunks: Rc<RefCell<Vec<(PathBuf, LuaFunction)>>>,
+}
+
+impl CoverageTracker {
+    /**
+        Creates a new coverage tracker, without any tracked chunks.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Tracks the function for a chunk that was loaded from the file at the given path.
+    */
+    pub fn track(&self, path: impl AsRef<Path>, function: LuaFunction) {
+        let path = clean_path_and_make_absolute(path);
+        self.chunks.borrow_mut().push((path, function));
+    }
+
+    /**
+        Collects the current line coverage for all tracked chunks.
+    */
+    #[must_use]
+    pub fn report(&self) -> CoverageReport {
+        let cwd = get_current_dir();
+        let mut report = CoverageReport::new();
+
+        for (path, function) in self.chunks.borrow().iter() {
+            let path = path.strip_prefix(&cwd).unwrap_or(path);
+            let path = path.to_string_lossy().replace('\\', "/");
+
+            // Coverage is given for each function in the chunk, and the same line may appear
+            // in more than one function, such as for one-line closures, so use the most hits
+            let mut lines = BTreeMap::<u32, u64>::new();
+            function.coverage(|info| {
+                for (line, hits) in info.hits.iter().enumerate() {
+                    // Lines without any executable code have negative hits
+                    if let (Ok(line), Ok(hits)) = (u32::try_from(line), u64::try_from(*hits)) {
+                        let count = lines.entry(line).or_default();
+                        *count = (*count).max(hits);
+                    }
+                }
+            });
+
+            for (line, hits) in lines {
+                report.record(path.clone(), line, hits);
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_line_coverage() {
+        let lua = Lua::new();
+        lua.set_compiler(mlua::Compiler::new().set_coverage_level(1));
+
+        let function = lua
+            .load("local x = 0\nfor i = 1, 3 do\n\tx += i\nend\nif x > 100 then\n\tx = 0\nend")
+            .into_function()
+            .unwrap();
+        function.call::<()>(()).unwrap();
+
+        let tracker = CoverageTracker::new();
+        tracker.track(get_current_dir().join("chunk.luau"), function);
+
+        let report = tracker.report();
+        let (path, lines) = report.files().next().unwrap();
+        assert_eq!(path, "chunk.luau");
+        assert_eq!(lines.get(&1), Some(&1));
+        assert_eq!(lines.get(&3), Some(&3));
+        assert_eq!(lines.get(&6), Some(&0));
+        assert!(report.lines_hit() < report.lines_found());
+    }
+
+    #[test]
+    fn merges_reports() {
+        let mut a = CoverageReport::new();
+        a.record("a.luau", 1, 1);
+        a.record("a.luau", 2, 0);
+
+        let mut b = CoverageReport::new();
+        b.record("a.luau", 2, 2);
+        b.record("b.luau", 1, 0);
+
+        a.merge(&b);
+        assert_eq!(a.lines_found(), 3);
+        assert_eq!(a.lines_hit(), 2);
+    }
+}
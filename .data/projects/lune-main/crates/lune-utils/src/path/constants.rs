@@ -6,3 +6,5 @@ pub const FILE_CHUNK_PREFIX: char = '@';
 pub const FILE_NAME_INIT: &str = "init";
 pub const FILE_NAME_CONFIG: &str = ".luaurc";
 pub const FILE_EXTENSIONS: [&str; 2] = ["luau", "lua"];
+pub const EMBEDDED_ROOT: &str = "$";
+pub const EMBEDDED_ASSETS_PREFIX: &str = "@embedded/";
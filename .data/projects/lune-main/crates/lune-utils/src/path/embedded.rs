@@ -0,0 +1,246 @@
+/*!
+    Utilities for working with files embedded into standalone binaries.
+*/
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use super::constants::{EMBEDDED_ASSETS_PREFIX, EMBEDDED_ROOT};
+use super::std::clean_path;
+
+/**
+    A set of files embedded into a standalone binary, forming a read-only virtual filesystem.
+
+    Files are stored using paths relative to the root of the virtual filesystem, with `/` as
+    the separator, such as `src/main.luau`. When looking up files, paths must instead start
+    with the [`EMBEDDED_ROOT`] component, such as `$/src/main.luau`, which makes it possible
+    to tell embedded paths apart from paths on the real filesystem.
+
+    Cloning is cheap, since the files are shared between all clones.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedFiles {
+    files: Arc<BTreeMap<String, Vec<u8>>>,
+}
+
+impl EmbeddedFiles {
+    /**
+        Creates a new, empty, set of embedded files.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Checks if the given path is an embedded path, meaning
+        that it starts with the [`EMBEDDED_ROOT`] component.
+
+        Does not check if any file exists at the given path.
+    */
+    #[must_use]
+    pub fn is_embedded_path(path: impl AsRef<Path>) -> bool {
+        path.as_ref().components().next() == Some(Component::Normal(OsStr::new(EMBEDDED_ROOT)))
+    }
+
+    /**
+        Converts a path relative to the root of the virtual filesystem,
+        using `/` as the separator, to an embedded path.
+    */
+    #[must_use]
+    pub fn embedded_path(relative: impl AsRef<str>) -> PathBuf {
+        relative
+            .as_ref()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .fold(PathBuf::from(EMBEDDED_ROOT), |path, segment| {
+                path.join(segment)
+            })
+    }
+
+    /**
+        Adds a file, using a path relative to the root of the
+        virtual filesystem, with `/` as the separator.
+
+        Replaces any existing file at the same path.
+    */
+    pub fn insert(&mut self, relative: impl AsRef<str>, contents: impl Into<Vec<u8>>) {
+        let key = relative
+            .as_ref()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+        Arc::make_mut(&mut self.files).insert(key, contents.into());
+    }
+
+    /**
+        Gets the contents of the file at the given embedded path, if any.
+    */
+    #[must_use]
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&[u8]> {
+        let key = embedded_key(path.as_ref())?;
+        self.files.get(&key).map(Vec::as_slice)
+    }
+
+    /**
+        Checks if a file exists at the given embedded path.
+    */
+    #[must_use]
+    pub fn is_file(&self, path: impl AsRef<Path>) -> bool {
+        self.get(path).is_some()
+    }
+
+    /**
+        Checks if a directory exists at the given embedded path,
+        meaning that there is at least one file contained in it.
+    */
+    #[must_use]
+    pub fn is_dir(&self, path: impl AsRef<Path>) -> bool {
+        let Some(mut prefix) = embedded_key(path.as_ref()) else {
+            return false;
+        };
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        self.files
+            .range(prefix.clone()..)
+            .next()
+            .is_some_and(|(key, _)| key.starts_with(&prefix))
+    }
+
+    /**
+        Reads the names of all files and directories directly
+        contained in the directory at the given embedded path.
+
+        Returns `None` if there is no such directory.
+    */
+    #[must_use]
+    pub fn read_dir(&self, path: impl AsRef<Path>) -> Option<Vec<String>> {
+        let path = path.as_ref();
+        if !self.is_dir(path) {
+            return None;
+        }
+
+        let mut prefix = embedded_key(path)?;
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+
+        let mut names = self
+            .files
+            .range(prefix.clone()..)
+            .map_while(|(key, _)| key.strip_prefix(&prefix))
+            .map(|rest| rest.split('/').next().unwrap_or(rest).to_string())
+            .collect::<Vec<_>>();
+        names.dedup();
+        Some(names)
+    }
+
+    /**
+        Returns an iterator over all files, with paths relative
+        to the root of the virtual filesystem, sorted by path.
+    */
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.files
+            .iter()
+            .map(|(key, contents)| (key.as_str(), contents.as_slice()))
+    }
+
+    /**
+        Returns the number of files.
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /**
+        Returns `true` if there are no files.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for EmbeddedFiles
+where
+    K: AsRef<str>,
+    V: Into<Vec<u8>>,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut files = Self::new();
+        for (relative, contents) in iter {
+            files.insert(relative, contents);
+        }
+        files
+    }
+}
+
+/**
+    Asset files embedded into a standalone binary, such as data files and templates.
+
+    Assets are read using paths starting with [`EMBEDDED_ASSETS_PREFIX`], such as
+    `@embedded/data/config.json`, and are separate from any embedded modules.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedAssets {
+    files: EmbeddedFiles,
+}
+
+impl EmbeddedAssets {
+    /**
+        Creates a new set of embedded assets, from files with paths relative to the
+        directory that the assets were included from, with `/` as the separator.
+    */
+    #[must_use]
+    pub fn new(files: EmbeddedFiles) -> Self {
+        Self { files }
+    }
+
+    /**
+        Strips the [`EMBEDDED_ASSETS_PREFIX`] from the given path,
+        returning `None` if the path is not an asset path.
+
+        The rest of the path is relative to the directory that the assets were
+        included from, and may be converted to an embedded path for use with
+        [`EmbeddedAssets::files`] using [`EmbeddedFiles::embedded_path`].
+    */
+    #[must_use]
+    pub fn strip_prefix(path: &str) -> Option<&str> {
+        path.strip_prefix(EMBEDDED_ASSETS_PREFIX)
+    }
+
+    /**
+        Returns the embedded asset files.
+    */
+    #[must_use]
+    pub fn files(&self) -> &EmbeddedFiles {
+        &self.files
+    }
+}
+
+/**
+    Converts an embedded path to the key used for storing
+    files, or `None` if the path is not an embedded path.
+*/
+fn embedded_key(path: &Path) -> Option<String> {
+    let path = clean_path(path);
+    if !EmbeddedFiles::is_embedded_path(&path) {
+        return None;
+    }
+
+    let mut segments = Vec::new();
+    for component in path.components().skip(1) {
+        let Component::Normal(name) = component else {
+            return None;
+        };
+        segments.push(name.to_str()?);
+    }
+    Some(segments.join("/"))
+}
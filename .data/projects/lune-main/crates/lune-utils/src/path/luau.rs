@@ -11,6 +11,7 @@ use std::{
 use mlua::prelude::*;
 
 use super::constants::{FILE_EXTENSIONS, FILE_NAME_INIT};
+use super::embedded::EmbeddedFiles;
 use super::std::append_extension;
 
 /**
@@ -29,8 +30,25 @@ pub enum LuauFilePath {
 
 impl LuauFilePath {
     fn resolve(module: impl AsRef<Path>) -> Result<Self, LuaNavigateError> {
-        let module = module.as_ref();
+        Self::resolve_with(module.as_ref(), Path::is_file, Path::is_dir)
+    }
+
+    fn resolve_embedded(
+        module: impl AsRef<Path>,
+        files: &EmbeddedFiles,
+    ) -> Result<Self, LuaNavigateError> {
+        Self::resolve_with(
+            module.as_ref(),
+            |path| files.is_file(path),
+            |path| files.is_dir(path),
+        )
+    }
 
+    fn resolve_with(
+        module: &Path,
+        is_file: impl Fn(&Path) -> bool,
+        is_dir: impl Fn(&Path) -> bool,
+    ) -> Result<Self, LuaNavigateError> {
         // Modules named "init" are ambiguous and not allowed
         if module
             .file_name()
@@ -44,17 +62,17 @@ impl LuauFilePath {
         // Try files first
         for ext in FILE_EXTENSIONS {
             let candidate = append_extension(module, ext);
-            if candidate.is_file() && found.replace(candidate).is_some() {
+            if is_file(&candidate) && found.replace(candidate).is_some() {
                 return Err(LuaNavigateError::Ambiguous);
             }
         }
 
         // Try directories with init files in them
-        if module.is_dir() {
+        if is_dir(module) {
             let init = Path::new(FILE_NAME_INIT);
             for ext in FILE_EXTENSIONS {
                 let candidate = module.join(append_extension(init, ext));
-                if candidate.is_file() && found.replace(candidate).is_some() {
+                if is_file(&candidate) && found.replace(candidate).is_some() {
                     return Err(LuaNavigateError::Ambiguous);
                 }
             }
@@ -190,6 +208,27 @@ impl LuauModulePath {
         Ok(Self { source, target })
     }
 
+    /**
+        Resolves an existing file or directory path for the given *module* path,
+        using the given embedded files instead of the real filesystem.
+
+        The module path must be an embedded path, see [`EmbeddedFiles`] for more information.
+        Files are searched for in the same way as in [`LuauModulePath::resolve`].
+
+        # Errors
+
+        - If the given module path is ambiguous.
+        - If the given module path does not resolve to a valid embedded file or directory.
+    */
+    pub fn resolve_embedded(
+        module: impl Into<PathBuf>,
+        files: &EmbeddedFiles,
+    ) -> Result<Self, LuaNavigateError> {
+        let source = module.into();
+        let target = LuauFilePath::resolve_embedded(&source, files)?;
+        Ok(Self { source, target })
+    }
+
     /**
         Returns the source Luau module path.
     */
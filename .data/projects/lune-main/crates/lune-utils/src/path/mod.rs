@@ -1,3 +1,4 @@
+mod embedded;
 mod luau;
 mod std;
 
@@ -8,4 +9,5 @@ pub use self::std::{
     relative_path_normalize, relative_path_parent,
 };
 
+pub use self::embedded::{EmbeddedAssets, EmbeddedFiles};
 pub use self::luau::{LuauFilePath, LuauModulePath};
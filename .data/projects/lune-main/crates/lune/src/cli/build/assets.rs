@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use lune_utils::path::{EmbeddedFiles, clean_path_and_make_absolute, get_current_dir};
+
+/**
+    Collects all asset files matching the given glob patterns, so that
+    they can be embedded into a standalone binary. Any directories
+    matching one of the patterns are included recursively.
+
+    Patterns and the resulting asset paths are relative to the current directory,
+    which means that assets can be read using the same `@embedded/` paths both
+    before and after building, since such paths fall back to the current directory.
+*/
+pub fn collect_assets(patterns: &[String]) -> Result<EmbeddedFiles> {
+    let cwd = get_current_dir();
+    let mut assets = EmbeddedFiles::new();
+
+    for pattern in patterns {
+        let entries = glob::glob(pattern)
+            .with_context(|| format!("invalid include pattern \"{pattern}\""))?;
+
+        let mut matched = false;
+        for entry in entries {
+            let path = entry?;
+            for file in collect_files(&path)? {
+                let key = asset_key(&cwd, &file)?;
+                let contents = fs::read(&file)
+                    .with_context(|| format!("failed to read asset \"{}\"", file.display()))?;
+                assets.insert(key, contents);
+                matched = true;
+            }
+        }
+
+        if !matched {
+            bail!("include pattern \"{pattern}\" did not match any files");
+        }
+    }
+
+    Ok(assets)
+}
+
+/**
+    Collects the given file, or all files in the given directory, recursively.
+*/
+fn collect_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        files.extend(collect_files(&entry?.path())?);
+    }
+    Ok(files)
+}
+
+/**
+    Creates the path that an asset is stored at, relative to the current directory.
+*/
+fn asset_key(cwd: &Path, path: &Path) -> Result<String> {
+    let absolute = clean_path_and_make_absolute(path);
+    let Ok(relative) = absolute.strip_prefix(cwd) else {
+        bail!(
+            "asset \"{}\" is outside of the current directory",
+            path.display()
+        );
+    };
+
+    let mut segments = Vec::new();
+    for component in relative.components() {
+        let Component::Normal(name) = component else {
+            bail!("asset \"{}\" has an invalid path", path.display());
+        };
+        let Some(name) = name.to_str() else {
+            bail!(
+                "asset \"{}\" has a path that is not valid UTF-8",
+                path.display()
+            );
+        };
+        segments.push(name);
+    }
+    Ok(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_keys() {
+        let cwd = get_current_dir();
+        assert_eq!(
+            asset_key(&cwd, Path::new("data/config.json")).unwrap(),
+            "data/config.json"
+        );
+        assert_eq!(
+            asset_key(&cwd, &cwd.join("templates").join("frame.luau")).unwrap(),
+            "templates/frame.luau"
+        );
+        assert!(asset_key(&cwd, Path::new("../outside.json")).is_err());
+    }
+}
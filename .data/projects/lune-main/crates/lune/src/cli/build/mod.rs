@@ -1,17 +1,19 @@
 use std::{path::PathBuf, process::ExitCode};
 
 use anyhow::{Context, Result, bail};
-use async_fs as fs;
+use blocking::unblock;
 use clap::Parser;
 use console::style;
 
-use crate::standalone::metadata::Metadata;
+use crate::standalone::{metadata::Metadata, tracer::Trace};
 
+mod assets;
 mod base_exe;
 mod files;
 mod result;
 mod target;
 
+use self::assets::collect_assets;
 use self::base_exe::get_or_download_base_executable;
 use self::files::{remove_source_file_ext, write_executable_file_to};
 use self::target::BuildTarget;
@@ -31,6 +33,11 @@ pub struct BuildCommand {
     /// defaults to the os and arch of the current system
     #[clap(short, long)]
     pub target: Option<BuildTarget>,
+
+    /// Glob patterns for asset files to embed, relative to the current
+    /// directory - embedded assets can be read using `@embedded/` paths
+    #[clap(short, long)]
+    pub include: Vec<String>,
 }
 
 impl BuildCommand {
@@ -54,22 +61,43 @@ impl BuildCommand {
             );
         }
 
-        // Try to read the given input file
-        // FUTURE: We should try and resolve a full require file graph using the input
-        // path here instead, see the notes in the `standalone` module for more details
-        let source_code = fs::read(&self.input)
+        // Trace all modules required by the input file, so that they can all be embedded
+        let input = self.input.clone();
+        let trace = unblock(move || Trace::from_entrypoint(input))
+            .await
+            .context("failed to trace requires for input file")?;
+        for warning in &trace.warnings {
+            println!("{} {warning}", style("Warning:").yellow());
+        }
+
+        // Collect any asset files that should be embedded alongside the modules
+        let patterns = self.include.clone();
+        let assets = unblock(move || collect_assets(&patterns))
             .await
-            .context("failed to read input file")?;
+            .context("failed to collect asset files")?;
 
         // Derive the base executable path based on the arguments provided
         let base_exe_path = get_or_download_base_executable(target).await?;
 
         // Read the contents of the lune interpreter as our starting point
         println!(
-            "Compiling standalone binary from {}",
-            style(self.input.display()).green()
+            "Compiling standalone binary from {} ({} {})",
+            style(self.input.display()).green(),
+            trace.modules.len(),
+            if trace.modules.len() == 1 {
+                "module"
+            } else {
+                "modules"
+            }
         );
-        let patched_bin = Metadata::create_env_patched_bin(base_exe_path, source_code)
+        if !assets.is_empty() {
+            println!(
+                "Embedding {} {}",
+                assets.len(),
+                if assets.len() == 1 { "asset" } else { "assets" }
+            );
+        }
+        let patched_bin = Metadata::create_env_patched_bin(base_exe_path, trace, assets)
             .await
             .context("failed to create patched binary")?;
 
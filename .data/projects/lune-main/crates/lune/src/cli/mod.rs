@@ -1,4 +1,4 @@
-use std::{env::args_os, process::ExitCode};
+use std::{env::args_os, path::PathBuf, process::ExitCode};
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
@@ -6,21 +6,31 @@ use clap::{Parser, Subcommand};
 pub(crate) mod build;
 pub(crate) mod list;
 pub(crate) mod repl;
+#[cfg(feature = "std-roblox")]
+pub(crate) mod roblox;
 pub(crate) mod run;
 pub(crate) mod setup;
+pub(crate) mod test;
 pub(crate) mod utils;
 
 pub use self::{
-    build::BuildCommand, list::ListCommand, repl::ReplCommand, run::RunCommand, setup::SetupCommand,
+    build::BuildCommand, list::ListCommand, repl::ReplCommand, run::RunCommand,
+    setup::SetupCommand, test::TestCommand,
 };
 
+#[cfg(feature = "std-roblox")]
+pub use self::roblox::RobloxCommand;
+
 #[derive(Debug, Clone, Subcommand)]
 pub enum CliSubcommand {
     Run(RunCommand),
     List(ListCommand),
     Setup(SetupCommand),
     Build(BuildCommand),
+    Test(TestCommand),
     Repl(ReplCommand),
+    #[cfg(feature = "std-roblox")]
+    Roblox(RobloxCommand),
 }
 
 impl Default for CliSubcommand {
@@ -45,15 +55,27 @@ impl Cli {
             .nth(1)
             .is_some_and(|arg| arg.eq_ignore_ascii_case("run"))
         {
-            let Some(script_path) = args_os()
-                .nth(2)
-                .and_then(|arg| arg.to_str().map(String::from))
-            else {
-                return Self::parse(); // Will fail and return the help message
+            // Flags for the run command itself must come before the script path,
+            // since all arguments after the script path are passed to the script
+            let mut run_args = args_os().skip(2);
+            let mut coverage = false;
+            let mut profile = None;
+            let script_path = loop {
+                match run_args
+                    .next()
+                    .and_then(|arg| arg.to_str().map(String::from))
+                {
+                    Some(arg) if arg == "--coverage" => coverage = true,
+                    Some(arg) if arg == "--profile" => match run_args.next() {
+                        Some(path) => profile = Some(PathBuf::from(path)),
+                        None => return Self::parse(),
+                    },
+                    Some(arg) => break arg,
+                    None => return Self::parse(), // Will fail and return the help message
+                }
             };
 
-            let script_args = args_os()
-                .skip(3)
+            let script_args = run_args
                 .filter_map(|arg| arg.to_str().map(String::from))
                 .collect::<Vec<_>>();
 
@@ -61,6 +83,8 @@ impl Cli {
                 subcommand: Some(CliSubcommand::Run(RunCommand {
                     script_path,
                     script_args,
+                    coverage,
+                    profile,
                 })),
             }
         } else {
@@ -74,7 +98,10 @@ impl Cli {
             CliSubcommand::List(cmd) => cmd.run().await,
             CliSubcommand::Setup(cmd) => cmd.run().await,
             CliSubcommand::Build(cmd) => cmd.run().await,
+            CliSubcommand::Test(cmd) => cmd.run().await,
             CliSubcommand::Repl(cmd) => cmd.run().await,
+            #[cfg(feature = "std-roblox")]
+            CliSubcommand::Roblox(cmd) => cmd.run().await,
         }
     }
 }
@@ -0,0 +1,165 @@
+use std::{fmt::Write as _, path::PathBuf, process::ExitCode};
+
+use anyhow::{Context, Result};
+use async_fs as fs;
+use blocking::unblock;
+use clap::{Parser, Subcommand};
+use console::style;
+
+use lune_roblox::document::{
+    ChunkCompression, DocumentFormat, DocumentKind, DocumentReport, IssueSeverity, inspect_document,
+};
+
+/// Work with Roblox place and model files
+#[derive(Debug, Clone, Parser)]
+pub struct RobloxCommand {
+    #[clap(subcommand)]
+    subcommand: RobloxSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum RobloxSubcommand {
+    Validate(ValidateCommand),
+}
+
+impl RobloxCommand {
+    pub async fn run(self) -> Result<ExitCode> {
+        match self.subcommand {
+            RobloxSubcommand::Validate(cmd) => cmd.run().await,
+        }
+    }
+}
+
+/// Inspect the structure of a place or model file, and check it for issues
+#[derive(Debug, Clone, Parser)]
+struct ValidateCommand {
+    /// The path to the place or model file
+    file: PathBuf,
+
+    /// Also list the properties of each class, and their sizes
+    #[clap(short, long)]
+    verbose: bool,
+
+    /// Print the full report as json instead
+    #[clap(long)]
+    json: bool,
+}
+
+impl ValidateCommand {
+    async fn run(self) -> Result<ExitCode> {
+        let contents = fs::read(&self.file)
+            .await
+            .context("failed to read input file")?;
+
+        let report = unblock(move || inspect_document(contents))
+            .await
+            .context("failed to inspect input file")?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{}", self.format_report(&report)?);
+        }
+
+        Ok(if report.valid {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    }
+
+    fn format_report(&self, report: &DocumentReport) -> Result<String> {
+        let mut buffer = String::new();
+
+        let kind = report.kind.map_or("unknown", kind_name);
+        writeln!(
+            &mut buffer,
+            "{} ({} {kind}, {} instances)",
+            style(self.file.display()).green(),
+            format_name(report.format),
+            report.instance_count,
+        )?;
+
+        if !report.chunks.is_empty() {
+            writeln!(&mut buffer, "\nChunks:")?;
+            for chunk in &report.chunks {
+                writeln!(
+                    &mut buffer,
+                    "  {:<4}  offset {:<10}  {:<4}  {} bytes",
+                    chunk.name,
+                    chunk.offset,
+                    compression_name(chunk.compression),
+                    chunk.size,
+                )?;
+            }
+        }
+
+        if !report.metadata.is_empty() {
+            writeln!(&mut buffer, "\nMetadata:")?;
+            for (key, value) in &report.metadata {
+                writeln!(&mut buffer, "  {key} = {value}")?;
+            }
+        }
+
+        if !report.classes.is_empty() {
+            writeln!(&mut buffer, "\nClasses:")?;
+            for class in &report.classes {
+                writeln!(
+                    &mut buffer,
+                    "  {:<32}  {}",
+                    class.class_name, class.instance_count
+                )?;
+                if !self.verbose {
+                    continue;
+                }
+                for property in &class.properties {
+                    let size = match property.size {
+                        Some(size) => format!("{size} bytes"),
+                        None => String::new(),
+                    };
+                    writeln!(
+                        &mut buffer,
+                        "    {:<30}  {:<16}  {size}",
+                        property.name, property.type_name
+                    )?;
+                }
+            }
+        }
+
+        writeln!(&mut buffer)?;
+        if report.issues.is_empty() {
+            writeln!(&mut buffer, "{}", style("No issues found").green())?;
+        }
+        for issue in &report.issues {
+            let severity = match issue.severity {
+                IssueSeverity::Error => style("error").red(),
+                IssueSeverity::Warning => style("warning").yellow(),
+            };
+            writeln!(&mut buffer, "{severity}: {}", issue.message)?;
+        }
+
+        Ok(buffer)
+    }
+}
+
+fn kind_name(kind: DocumentKind) -> &'static str {
+    match kind {
+        DocumentKind::Place => "place",
+        DocumentKind::Model => "model",
+    }
+}
+
+fn format_name(format: DocumentFormat) -> &'static str {
+    match format {
+        DocumentFormat::Binary => "binary",
+        DocumentFormat::Xml => "xml",
+    }
+}
+
+fn compression_name(compression: ChunkCompression) -> &'static str {
+    match compression {
+        ChunkCompression::None => "none",
+        ChunkCompression::Lz4 => "lz4",
+        ChunkCompression::Zstd => "zstd",
+    }
+}
@@ -1,4 +1,4 @@
-use std::{env, io::stdin, process::ExitCode};
+use std::{env, io::stdin, path::PathBuf, process::ExitCode};
 
 use anyhow::{Context, Result};
 use blocking::Unblock;
@@ -7,7 +7,10 @@ use futures_lite::prelude::*;
 
 use lune::Runtime;
 
-use super::utils::files::discover_script_path_including_lune_dirs;
+use super::utils::{
+    coverage::write_coverage_report, files::discover_script_path_including_lune_dirs,
+    profile::write_profile,
+};
 
 /// Run a script
 #[derive(Debug, Clone, Parser)]
@@ -16,6 +19,13 @@ pub struct RunCommand {
     pub(super) script_path: String,
     /// Arguments to pass to the script, stored in process.args
     pub(super) script_args: Vec<String>,
+    /// Collect line coverage, and write it to the `coverage` directory when done
+    #[clap(long)]
+    pub(super) coverage: bool,
+    /// Sample the call stack while running, and write it to the given file, as
+    /// speedscope JSON if it ends with `.json`, and otherwise as collapsed stacks
+    #[clap(long)]
+    pub(super) profile: Option<PathBuf>,
 }
 
 impl RunCommand {
@@ -28,7 +38,9 @@ impl RunCommand {
         // Create a new lune runtime with all globals & run the script
         let mut rt = Runtime::new()?
             .with_args(self.script_args)
-            .with_jit(!jit_disabled);
+            .with_jit(!jit_disabled)
+            .with_coverage(self.coverage)
+            .with_profiling(self.profile.is_some());
 
         // Figure out if we should run stdin or run a file,
         // reading from stdin is marked by passing a single "-"
@@ -45,12 +57,23 @@ impl RunCommand {
             rt.run_file(file_path).await
         };
 
-        Ok(match result {
+        let code = match result {
             Err(err) => {
                 eprintln!("{err}");
                 ExitCode::FAILURE
             }
             Ok(values) => ExitCode::from(values.status()),
-        })
+        };
+
+        if let Some(report) = rt.coverage() {
+            write_coverage_report(&report).await?;
+        }
+        if let Some(path) = &self.profile
+            && let Some(profile) = rt.profile()
+        {
+            write_profile(path, &profile).await?;
+        }
+
+        Ok(code)
     }
 }
@@ -0,0 +1,95 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+use lune_utils::path::{clean_path, get_current_dir};
+
+const TEST_FILE_SUFFIXES: &[&str] = &[".spec.luau", ".test.luau", ".spec.lua", ".test.lua"];
+
+/**
+    Checks if the given path is a test file, meaning that it
+    ends with a suffix such as `.spec.luau` or `.test.luau`.
+*/
+pub fn is_test_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            TEST_FILE_SUFFIXES
+                .iter()
+                .any(|suffix| name.len() > suffix.len() && name.ends_with(suffix))
+        })
+}
+
+/**
+    Discovers all test files in the given files and directories, sorted by path.
+
+    Directories are searched recursively, skipping any hidden directories, while
+    files given directly are always included, even if they are not named as test files.
+
+    Paths inside of the current directory are returned as relative paths.
+*/
+pub fn discover_test_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_file() {
+            files.push(path.clone());
+        } else if path.is_dir() {
+            find_test_files(path, &mut files)
+                .with_context(|| format!("failed to search for tests in {}", path.display()))?;
+        } else {
+            bail!("no file or directory exists at {}", path.display());
+        }
+    }
+
+    let cwd = get_current_dir();
+    let mut files = files
+        .into_iter()
+        .map(|file| {
+            let file = clean_path(file);
+            match file.strip_prefix(&cwd) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => file,
+            }
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+fn find_test_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let is_hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if !is_hidden {
+                find_test_files(&path, files)?;
+            }
+        } else if is_test_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_names() {
+        assert!(is_test_file(Path::new("tests/parser.spec.luau")));
+        assert!(is_test_file(Path::new("tests/parser.test.luau")));
+        assert!(is_test_file(Path::new("parser.spec.lua")));
+        assert!(!is_test_file(Path::new("tests/parser.luau")));
+        assert!(!is_test_file(Path::new("tests/spec.luau")));
+        assert!(!is_test_file(Path::new(".spec.luau")));
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::{Arc, Mutex};
+
+use mlua::prelude::*;
+
+use super::report::{TestCase, TestStatus};
+
+const HARNESS_SOURCE: &str = include_str!("./harness.luau");
+
+/**
+    Creates the globals provided to test files, such as `describe`, `it` and `expect`.
+
+    Tests with names not containing any of the given filters are not run, and
+    the results of all other tests are pushed to the given list as they finish.
+*/
+pub fn create_test_globals(
+    lua: &Lua,
+    filters: &[String],
+    results: Arc<Mutex<Vec<TestCase>>>,
+) -> LuaResult<LuaTable> {
+    let report = lua.create_function(move |_, result: LuaTable| {
+        let status = match result.get::<String>("status")?.as_str() {
+            "passed" => TestStatus::Passed,
+            "failed" => TestStatus::Failed,
+            _ => TestStatus::Skipped,
+        };
+        let case = TestCase {
+            name: result.get("name")?,
+            status,
+            duration: result.get("duration")?,
+            error: result.get("error")?,
+        };
+        results
+            .lock()
+            .expect("results lock was poisoned")
+            .push(case);
+        Ok(())
+    })?;
+
+    let config = lua.create_table()?;
+    config.set("filters", filters.to_vec())?;
+    config.set("report", report)?;
+
+    lua.load(HARNESS_SOURCE)
+        .set_name("=lune test")
+        .call::<LuaTable>(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use lune::Runtime;
+
+    use super::*;
+
+    fn run_tests(source: &str, filters: &[String]) -> Vec<TestCase> {
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let inner = Arc::clone(&results);
+
+        async_io::block_on(async {
+            let mut rt = Runtime::new()
+                .unwrap()
+                .with_globals(|lua| create_test_globals(lua, filters, inner))
+                .unwrap();
+            let values = rt.run_custom("test", source).await.unwrap();
+            assert!(values.success(), "test file errored outside of a test");
+        });
+
+        results.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn reports_results() {
+        let results = run_tests(
+            r#"
+            local calls = 0
+            describe("math", function()
+                beforeEach(function()
+                    calls += 1
+                end)
+                it("adds", function()
+                    expect(1 + 1).toBe(2)
+                    expect({ a = { 1, 2 } }).toEqual({ a = { 1, 2 } })
+                    expect("hello").toContain("ell")
+                    expect(function() error("oops") end).toThrow("oops")
+                    expect(nil).never.toBeTruthy()
+                end)
+                it("fails", function()
+                    expect(1).toBe(2)
+                end)
+                it.skip("skips", function()
+                    error("should not run")
+                end)
+            end)
+            assert(calls == 2, "hooks should run once per test")
+            "#,
+            &[],
+        );
+
+        let summary = results
+            .iter()
+            .map(|case| (case.name.as_str(), case.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("math > adds", TestStatus::Passed),
+                ("math > fails", TestStatus::Failed),
+                ("math > skips", TestStatus::Skipped),
+            ]
+        );
+        assert!(
+            results[1]
+                .error
+                .as_deref()
+                .is_some_and(|error| error.contains("Expected 1 to be 2"))
+        );
+    }
+
+    #[test]
+    fn filters_tests() {
+        let results = run_tests(
+            r#"
+            describe("parser", function()
+                it("parses numbers", function() end)
+                it("parses strings", function() end)
+            end)
+            it("formats", function() end)
+            "#,
+            &["strings".to_string(), "format".to_string()],
+        );
+
+        let names = results
+            .iter()
+            .map(|case| case.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["parser > parses strings", "formats"]);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    cell::RefCell,
+    env,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use async_fs as fs;
+use blocking::unblock;
+use clap::Parser;
+use mlua::prelude::*;
+use mlua_luau_scheduler::Scheduler;
+
+use lune::Runtime;
+use lune_utils::{coverage::CoverageReport, path::get_current_dir};
+
+use super::utils::coverage::write_coverage_report;
+
+mod discover;
+mod harness;
+mod report;
+
+use self::discover::{discover_test_files, is_test_file};
+use self::harness::create_test_globals;
+use self::report::{TestFile, TestReport, print_file, print_summary};
+
+/// Run tests in `*.spec.luau` and `*.test.luau` files
+#[derive(Debug, Clone, Parser)]
+pub struct TestCommand {
+    /// Test files or directories to search for test files
+    /// in - defaults to the current directory
+    pub paths: Vec<PathBuf>,
+
+    /// Only run tests with names containing the given filter,
+    /// including the names of any `describe` blocks
+    #[clap(short, long)]
+    pub filter: Vec<String>,
+
+    /// The number of test files to run concurrently -
+    /// defaults to the number of available CPU cores
+    #[clap(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Print all tests, and not only failed tests
+    #[clap(short, long)]
+    pub verbose: bool,
+
+    /// Write test results as `JUnit` XML to the given file
+    #[clap(long)]
+    pub junit: Option<PathBuf>,
+
+    /// Write test results as JSON to the given file
+    #[clap(long)]
+    pub json: Option<PathBuf>,
+
+    /// Collect line coverage for all modules required by test
+    /// files, and write it to the `coverage` directory when done
+    #[clap(long)]
+    pub coverage: bool,
+}
+
+impl TestCommand {
+    pub async fn run(self) -> Result<ExitCode> {
+        let paths = if self.paths.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            self.paths.clone()
+        };
+        let files = unblock(move || discover_test_files(&paths)).await?;
+        if files.is_empty() {
+            println!("No test files found.");
+            return Ok(ExitCode::FAILURE);
+        }
+
+        // Each test file runs in its own runtime, driven by one of the workers
+        let jobs = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(files.len());
+        let options = RunOptions {
+            filters: self.filter.clone(),
+            verbose: self.verbose,
+            coverage: self.coverage,
+        };
+
+        let start = Instant::now();
+        let (files, coverage) = run_test_files(files, options, jobs).await?;
+        let report = TestReport {
+            files,
+            duration: start.elapsed().as_secs_f64(),
+        };
+
+        print_summary(&report);
+
+        if let Some(path) = &self.junit {
+            fs::write(path, report.to_junit_xml()?)
+                .await
+                .with_context(|| format!("failed to write JUnit XML to {}", path.display()))?;
+        }
+        if let Some(path) = &self.json {
+            fs::write(path, report.to_json()?)
+                .await
+                .with_context(|| format!("failed to write JSON to {}", path.display()))?;
+        }
+
+        if let Some(mut coverage) = coverage {
+            // Test files themselves are not interesting for coverage
+            coverage.retain(|path| !is_test_file(Path::new(path)));
+            write_coverage_report(&coverage).await?;
+        }
+
+        Ok(if report.success() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    }
+}
+
+/**
+    Options shared by all test files in a single test run.
+*/
+#[derive(Debug, Clone)]
+struct RunOptions {
+    filters: Vec<String>,
+    verbose: bool,
+    coverage: bool,
+}
+
+/**
+    Runs the given test files using a number of worker threads on a scheduler,
+    printing the results of each file as it finishes, and returning all results
+    in order, together with the combined coverage for all files, if enabled.
+
+    Each test file still gets its own runtime, the scheduler
+    only drives the runtimes of all files forward concurrently.
+*/
+async fn run_test_files(
+    files: Vec<PathBuf>,
+    options: RunOptions,
+    jobs: usize,
+) -> LuaResult<(Vec<TestFile>, Option<CoverageReport>)> {
+    let queue = Rc::new(RefCell::new(files.into_iter().enumerate()));
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let coverage = Rc::new(RefCell::new(CoverageReport::new()));
+    let options = Rc::new(options);
+
+    let lua = Lua::new();
+    let sched = Scheduler::new(lua.clone());
+    let worker = lua.create_async_function({
+        let (queue, results, coverage, options) = (
+            Rc::clone(&queue),
+            Rc::clone(&results),
+            Rc::clone(&coverage),
+            Rc::clone(&options),
+        );
+        move |_, ()| {
+            let (queue, results, coverage, options) = (
+                Rc::clone(&queue),
+                Rc::clone(&results),
+                Rc::clone(&coverage),
+                Rc::clone(&options),
+            );
+            async move {
+                loop {
+                    let next = queue.borrow_mut().next();
+                    let Some((index, path)) = next else {
+                        break;
+                    };
+                    let (file, file_coverage) = run_test_file(path, &options).await;
+                    print_file(&file, options.verbose);
+                    if let Some(file_coverage) = file_coverage {
+                        coverage.borrow_mut().merge(&file_coverage);
+                    }
+                    results.borrow_mut().push((index, file));
+                }
+                Ok(())
+            }
+        }
+    })?;
+    for _ in 0..jobs {
+        sched.push_thread_back(worker.clone(), ())?;
+    }
+    sched.run().await;
+
+    let mut results = results.take();
+    results.sort_by_key(|(index, _)| *index);
+    let files = results.into_iter().map(|(_, file)| file).collect();
+    Ok((files, options.coverage.then(|| coverage.take())))
+}
+
+/**
+    Runs a single test file in a new, isolated, runtime.
+*/
+async fn run_test_file(path: PathBuf, options: &RunOptions) -> (TestFile, Option<CoverageReport>) {
+    // Check if the user has explicitly disabled JIT (on by default)
+    let jit_disabled = env::var("LUNE_LUAU_JIT")
+        .ok()
+        .is_some_and(|s| matches!(s.as_str(), "0" | "false" | "off"));
+
+    let start = Instant::now();
+    let tests = Arc::new(Mutex::new(Vec::new()));
+    let inner = Arc::clone(&tests);
+
+    let mut coverage = None;
+    let result = async {
+        let mut rt = Runtime::new()?
+            .with_jit(!jit_disabled)
+            .with_coverage(options.coverage)
+            .with_globals(|lua| create_test_globals(lua, &options.filters, inner))?;
+        let result = rt.run_file(get_current_dir().join(&path)).await;
+        coverage = rt.coverage();
+        result
+    }
+    .await;
+
+    let error = match result {
+        Err(err) => Some(err.to_string()),
+        Ok(values) if values.errored => {
+            Some("An error was thrown outside of a test, see the output above".to_string())
+        }
+        Ok(values) if !values.success() => {
+            Some(format!("Test file exited with status {}", values.status()))
+        }
+        Ok(_) => None,
+    };
+
+    let tests = tests.lock().expect("tests lock was poisoned").clone();
+    let file = TestFile {
+        path: path.display().to_string().replace('\\', "/"),
+        duration: start.elapsed().as_secs_f64(),
+        error,
+        tests,
+    };
+    (file, coverage)
+}
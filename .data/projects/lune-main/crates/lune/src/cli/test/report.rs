@@ -0,0 +1,414 @@
+use std::fmt::{self, Write as _};
+
+use console::style;
+use serde::Serialize;
+
+/**
+    The status of a single test.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/**
+    The result of a single test, as reported by the test harness.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct TestCase {
+    /// The full name of the test, including the names of any `describe` blocks.
+    pub name: String,
+    pub status: TestStatus,
+    /// The duration of the test, in seconds.
+    pub duration: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/**
+    The results of all tests in a single test file.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct TestFile {
+    pub path: String,
+    /// The duration of the entire file, in seconds.
+    pub duration: f64,
+    /// An error that happened outside of any test, such as a syntax error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub tests: Vec<TestCase>,
+}
+
+impl TestFile {
+    pub fn count(&self, status: TestStatus) -> usize {
+        self.tests
+            .iter()
+            .filter(|test| test.status == status)
+            .count()
+    }
+
+    pub fn success(&self) -> bool {
+        self.error.is_none() && self.count(TestStatus::Failed) == 0
+    }
+}
+
+/**
+    The results of all test files in a single test run.
+*/
+#[derive(Debug, Clone)]
+pub struct TestReport {
+    pub files: Vec<TestFile>,
+    /// The duration of the entire test run, in seconds.
+    pub duration: f64,
+}
+
+impl TestReport {
+    pub fn count(&self, status: TestStatus) -> usize {
+        self.files.iter().map(|file| file.count(status)).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.files.iter().map(|file| file.tests.len()).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|file| file.error.is_some())
+            .count()
+    }
+
+    pub fn success(&self) -> bool {
+        self.files.iter().all(TestFile::success)
+    }
+
+    /**
+        Serializes the report to JSON, including a summary of all test results.
+    */
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        #[derive(Serialize)]
+        struct JsonReport<'a> {
+            success: bool,
+            duration: f64,
+            passed: usize,
+            failed: usize,
+            skipped: usize,
+            total: usize,
+            files: &'a [TestFile],
+        }
+
+        serde_json::to_string_pretty(&JsonReport {
+            success: self.success(),
+            duration: self.duration,
+            passed: self.count(TestStatus::Passed),
+            failed: self.count(TestStatus::Failed),
+            skipped: self.count(TestStatus::Skipped),
+            total: self.total(),
+            files: &self.files,
+        })
+    }
+
+    /**
+        Serializes the report to `JUnit` XML, with one test suite per test file.
+
+        Errors outside of any test are reported as an extra test case
+        in the test suite, named after the file, using an `error` element.
+    */
+    pub fn to_junit_xml(&self) -> Result<String, fmt::Error> {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            xml,
+            r#"<testsuites name="lune" tests="{}" failures="{}" errors="{}" skipped="{}" time="{:.3}">"#,
+            self.total() + self.errors(),
+            self.count(TestStatus::Failed),
+            self.errors(),
+            self.count(TestStatus::Skipped),
+            self.duration
+        )?;
+
+        for file in &self.files {
+            let path = escape_xml(&file.path);
+            writeln!(
+                xml,
+                r#"  <testsuite name="{path}" tests="{}" failures="{}" errors="{}" skipped="{}" time="{:.3}">"#,
+                file.tests.len() + usize::from(file.error.is_some()),
+                file.count(TestStatus::Failed),
+                usize::from(file.error.is_some()),
+                file.count(TestStatus::Skipped),
+                file.duration
+            )?;
+
+            for test in &file.tests {
+                write!(
+                    xml,
+                    r#"    <testcase name="{}" classname="{path}" time="{:.3}""#,
+                    escape_xml(&test.name),
+                    test.duration
+                )?;
+                match test.status {
+                    TestStatus::Passed => xml.push_str(" />\n"),
+                    TestStatus::Skipped => xml.push_str(">\n      <skipped />\n    </testcase>\n"),
+                    TestStatus::Failed => {
+                        let error = test.error.as_deref().unwrap_or_default();
+                        writeln!(
+                            xml,
+                            ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                            escape_xml(error.lines().next().unwrap_or_default()),
+                            escape_xml(error)
+                        )?;
+                    }
+                }
+            }
+
+            if let Some(error) = &file.error {
+                writeln!(
+                    xml,
+                    "    <testcase name=\"{path}\" classname=\"{path}\" time=\"0.000\">\
+                    \n      <error message=\"{}\">{}</error>\n    </testcase>",
+                    escape_xml(error.lines().next().unwrap_or_default()),
+                    escape_xml(error)
+                )?;
+            }
+
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+        Ok(xml)
+    }
+}
+
+/**
+    Prints the results of a single test file.
+
+    Failed tests are always printed, while passed and skipped tests are only printed if verbose.
+*/
+pub fn print_file(file: &TestFile, verbose: bool) {
+    // Results are written to a buffer first, so that output
+    // from test files running in parallel is not interleaved
+    let mut buffer = String::new();
+    write_file(&mut buffer, file, verbose).expect("writing to a string never fails");
+    print!("{buffer}");
+}
+
+fn write_file(buffer: &mut String, file: &TestFile, verbose: bool) -> fmt::Result {
+    let label = if file.success() {
+        style(" PASS ").black().on_green()
+    } else {
+        style(" FAIL ").black().on_red()
+    };
+    writeln!(
+        buffer,
+        "{label} {} {}",
+        file.path,
+        style(format!(
+            "({} {}, {})",
+            file.tests.len(),
+            if file.tests.len() == 1 {
+                "test"
+            } else {
+                "tests"
+            },
+            format_duration(file.duration)
+        ))
+        .dim()
+    )?;
+
+    for test in &file.tests {
+        match test.status {
+            TestStatus::Failed => {
+                writeln!(buffer, "  {} {}", style("✕").red(), test.name)?;
+                for line in test.error.as_deref().unwrap_or_default().lines() {
+                    writeln!(buffer, "      {}", style(line).red())?;
+                }
+            }
+            TestStatus::Passed if verbose => {
+                writeln!(buffer, "  {} {}", style("✓").green(), test.name)?;
+            }
+            TestStatus::Skipped if verbose => {
+                writeln!(
+                    buffer,
+                    "  {} {} {}",
+                    style("○").yellow(),
+                    test.name,
+                    style("(skipped)").dim()
+                )?;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(error) = &file.error {
+        for line in error.lines() {
+            writeln!(buffer, "  {}", style(line).red())?;
+        }
+    }
+
+    Ok(())
+}
+
+/**
+    Prints a summary of all test results.
+*/
+pub fn print_summary(report: &TestReport) {
+    let failed = report.count(TestStatus::Failed);
+    let passed = report.count(TestStatus::Passed);
+    let skipped = report.count(TestStatus::Skipped);
+
+    let mut tests = Vec::new();
+    if failed > 0 {
+        tests.push(style(format!("{failed} failed")).red().bold().to_string());
+    }
+    if passed > 0 {
+        tests.push(style(format!("{passed} passed")).green().bold().to_string());
+    }
+    if skipped > 0 {
+        tests.push(
+            style(format!("{skipped} skipped"))
+                .yellow()
+                .bold()
+                .to_string(),
+        );
+    }
+    tests.push(format!("{} total", report.total()));
+
+    let failed_files = report.files.iter().filter(|file| !file.success()).count();
+    let mut files = Vec::new();
+    if failed_files > 0 {
+        files.push(
+            style(format!("{failed_files} failed"))
+                .red()
+                .bold()
+                .to_string(),
+        );
+    }
+    if failed_files < report.files.len() {
+        let passed_files = report.files.len() - failed_files;
+        files.push(
+            style(format!("{passed_files} passed"))
+                .green()
+                .bold()
+                .to_string(),
+        );
+    }
+    files.push(format!("{} total", report.files.len()));
+
+    println!();
+    println!("{} {}", style("Tests:").bold(), tests.join(", "));
+    println!("{} {}", style("Files:").bold(), files.join(", "));
+    println!(
+        "{}  {}",
+        style("Time:").bold(),
+        format_duration(report.duration)
+    );
+}
+
+fn format_duration(seconds: f64) -> String {
+    if seconds < 1.0 {
+        format!("{}ms", (seconds * 1000.0).round() as u64)
+    } else {
+        format!("{seconds:.2}s")
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Other control characters are not allowed in XML 1.0
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> TestReport {
+        TestReport {
+            files: vec![
+                TestFile {
+                    path: "tests/math.spec.luau".to_string(),
+                    duration: 0.5,
+                    error: None,
+                    tests: vec![
+                        TestCase {
+                            name: "math > adds".to_string(),
+                            status: TestStatus::Passed,
+                            duration: 0.1,
+                            error: None,
+                        },
+                        TestCase {
+                            name: "math > compares <numbers>".to_string(),
+                            status: TestStatus::Failed,
+                            duration: 0.2,
+                            error: Some("Expected 1 to be 2\nstack".to_string()),
+                        },
+                        TestCase {
+                            name: "math > divides".to_string(),
+                            status: TestStatus::Skipped,
+                            duration: 0.0,
+                            error: None,
+                        },
+                    ],
+                },
+                TestFile {
+                    path: "tests/broken.spec.luau".to_string(),
+                    duration: 0.0,
+                    error: Some("syntax error".to_string()),
+                    tests: Vec::new(),
+                },
+            ],
+            duration: 1.0,
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let report = report();
+        assert_eq!(report.count(TestStatus::Passed), 1);
+        assert_eq!(report.count(TestStatus::Failed), 1);
+        assert_eq!(report.count(TestStatus::Skipped), 1);
+        assert_eq!(report.total(), 3);
+        assert_eq!(report.errors(), 1);
+        assert!(!report.success());
+    }
+
+    #[test]
+    fn junit_xml() {
+        let xml = report().to_junit_xml().unwrap();
+        assert!(xml.contains(
+            r#"<testsuites name="lune" tests="4" failures="1" errors="1" skipped="1" time="1.000">"#
+        ));
+        assert!(xml.contains(
+            r#"<testcase name="math &gt; adds" classname="tests/math.spec.luau" time="0.100" />"#
+        ));
+        assert!(xml.contains(r#"<failure message="Expected 1 to be 2">Expected 1 to be 2"#));
+        assert!(xml.contains("math &gt; compares &lt;numbers&gt;"));
+        assert!(xml.contains("<skipped />"));
+        assert!(xml.contains(r#"<error message="syntax error">syntax error</error>"#));
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn json() {
+        let json = report().to_json().unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(value["success"], false);
+        assert_eq!(value["total"], 3);
+        assert_eq!(value["files"][0]["tests"][1]["status"], "failed");
+        assert_eq!(value["files"][1]["error"], "syntax error");
+    }
+}
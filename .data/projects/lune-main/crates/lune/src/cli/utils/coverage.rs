@@ -0,0 +1,221 @@
+use std::{
+    fmt::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use async_fs as fs;
+use console::style;
+
+use lune_utils::coverage::CoverageReport;
+
+/**
+    The directory that coverage reports are written to, relative to the current directory.
+*/
+pub const COVERAGE_DIR: &str = "coverage";
+
+/**
+    Writes the given coverage report to the coverage directory, as both an
+    `lcov.info` file and an `index.html` summary, and prints the total coverage.
+*/
+pub async fn write_coverage_report(report: &CoverageReport) -> Result<()> {
+    let dir = PathBuf::from(COVERAGE_DIR);
+    fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("failed to create coverage directory {}", dir.display()))?;
+
+    let lcov_path = dir.join("lcov.info");
+    fs::write(&lcov_path, to_lcov(report)?)
+        .await
+        .with_context(|| format!("failed to write {}", lcov_path.display()))?;
+
+    let mut sources = Vec::new();
+    for (path, _) in report.files() {
+        sources.push(fs::read_to_string(path).await.ok());
+    }
+
+    let html_path = dir.join("index.html");
+    fs::write(&html_path, to_html(report, &sources)?)
+        .await
+        .with_context(|| format!("failed to write {}", html_path.display()))?;
+
+    println!(
+        "{} {} of lines covered ({}/{}), report written to {}",
+        style("Coverage:").bold(),
+        style(format_percentage(report.lines_hit(), report.lines_found())).bold(),
+        report.lines_hit(),
+        report.lines_found(),
+        style(Path::new(COVERAGE_DIR).display()).blue()
+    );
+
+    Ok(())
+}
+
+/**
+    Creates an lcov tracefile, containing line coverage for all files in the report.
+*/
+fn to_lcov(report: &CoverageReport) -> Result<String, fmt::Error> {
+    let mut lcov = String::new();
+    for (path, lines) in report.files() {
+        writeln!(lcov, "TN:")?;
+        writeln!(lcov, "SF:{path}")?;
+        for (line, hits) in lines {
+            writeln!(lcov, "DA:{line},{hits}")?;
+        }
+        writeln!(lcov, "LF:{}", lines.len())?;
+        writeln!(
+            lcov,
+            "LH:{}",
+            lines.values().filter(|hits| **hits > 0).count()
+        )?;
+        writeln!(lcov, "end_of_record")?;
+    }
+    Ok(lcov)
+}
+
+/**
+    Creates a standalone HTML page, with a summary of the coverage for each file in the report,
+    followed by the source of each file with covered and uncovered lines highlighted.
+
+    Sources are given in the same order as the files in the report, and may be missing.
+*/
+fn to_html(report: &CoverageReport, sources: &[Option<String>]) -> Result<String, fmt::Error> {
+    let mut html = String::from(HTML_HEADER);
+
+    writeln!(
+        html,
+        "<h1>Coverage report</h1>\n<p>{} of lines covered ({}/{})</p>",
+        format_percentage(report.lines_hit(), report.lines_found()),
+        report.lines_hit(),
+        report.lines_found()
+    )?;
+
+    html.push_str("<table>\n<tr><th>File</th><th>Lines</th><th>Hit</th><th>Coverage</th></tr>\n");
+    for (index, (path, lines)) in report.files().enumerate() {
+        let hit = lines.values().filter(|hits| **hits > 0).count();
+        writeln!(
+            html,
+            "<tr><td><a href=\"#file-{index}\">{}</a></td><td>{}</td><td>{hit}</td>\
+            <td class=\"{}\">{}</td></tr>",
+            escape_html(path),
+            lines.len(),
+            coverage_class(hit, lines.len()),
+            format_percentage(hit, lines.len())
+        )?;
+    }
+    html.push_str("</table>\n");
+
+    for (index, ((path, lines), source)) in report.files().zip(sources).enumerate() {
+        writeln!(html, "<h2 id=\"file-{index}\">{}</h2>", escape_html(path))?;
+        let Some(source) = source else {
+            html.push_str("<p>Source not available</p>\n");
+            continue;
+        };
+
+        html.push_str("<pre>");
+        for (line, text) in (1..).zip(source.lines()) {
+            let (class, hits) = match lines.get(&line) {
+                Some(0) => ("miss", "0".to_string()),
+                Some(hits) => ("hit", hits.to_string()),
+                None => ("none", String::new()),
+            };
+            writeln!(
+                html,
+                "<span class=\"{class}\">{line:>5} {hits:>6} | {}</span>",
+                escape_html(text)
+            )?;
+        }
+        html.push_str("</pre>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+const HTML_HEADER: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Coverage report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.25em 1em; border-bottom: 1px solid #ddd; text-align: left; }
+pre { background: #fafafa; padding: 0.5em; overflow-x: auto; }
+pre span { display: block; }
+.high { color: #1a7f37; }
+.medium { color: #9a6700; }
+.low { color: #cf222e; }
+.hit { background: #dafbe1; }
+.miss { background: #ffebe9; }
+</style>
+</head>
+<body>
+"#;
+
+fn coverage_class(hit: usize, found: usize) -> &'static str {
+    let ratio = if found == 0 {
+        1.0
+    } else {
+        hit as f64 / found as f64
+    };
+    if ratio >= 0.8 {
+        "high"
+    } else if ratio >= 0.5 {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+fn format_percentage(hit: usize, found: usize) -> String {
+    if found == 0 {
+        return "100.0%".to_string();
+    }
+    format!("{:.1}%", hit as f64 / found as f64 * 100.0)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CoverageReport {
+        let mut report = CoverageReport::new();
+        report.record("src/main.luau", 1, 1);
+        report.record("src/main.luau", 2, 0);
+        report.record("src/util.luau", 3, 4);
+        report
+    }
+
+    #[test]
+    fn lcov() {
+        assert_eq!(
+            to_lcov(&report()).unwrap(),
+            "TN:\nSF:src/main.luau\nDA:1,1\nDA:2,0\nLF:2\nLH:1\nend_of_record\n\
+            TN:\nSF:src/util.luau\nDA:3,4\nLF:1\nLH:1\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn html() {
+        let sources = [Some("local a = 1\nlocal b = a < 2".to_string()), None];
+        let html = to_html(&report(), &sources).unwrap();
+        assert!(html.contains("66.7% of lines covered (2/3)"));
+        assert!(html.contains("<span class=\"miss\">    2      0 | local b = a &lt; 2</span>"));
+        assert!(html.contains("Source not available"));
+    }
+}
@@ -1,2 +1,4 @@
+pub mod coverage;
 pub mod files;
 pub mod listing;
+pub mod profile;
@@ -0,0 +1,181 @@
+use std::{
+    fmt::{self, Write as _},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use async_fs as fs;
+use console::style;
+use serde::Serialize;
+
+use lune::{Profile, ProfileFrame};
+
+/**
+    Writes the given profile to a file, as speedscope JSON if the file has a `.json`
+    extension, and otherwise as collapsed stacks, and prints the total sampled time.
+*/
+pub async fn write_profile(path: &Path, profile: &Profile) -> Result<()> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let contents = if is_json {
+        to_speedscope(profile, &path.display().to_string())?
+    } else {
+        to_collapsed(profile)?
+    };
+
+    fs::write(path, contents)
+        .await
+        .with_context(|| format!("failed to write profile to {}", path.display()))?;
+
+    println!(
+        "{} {} samples over {:.2}s, written to {}",
+        style("Profile:").bold(),
+        profile.samples(),
+        profile.total_weight() as f64 / 1_000_000.0,
+        style(path.display()).blue()
+    );
+
+    Ok(())
+}
+
+/**
+    Creates a collapsed stack file, as used by `flamegraph.pl` and `inferno`, with one
+    line per unique stack, containing the frames separated by `;` and then its weight.
+*/
+fn to_collapsed(profile: &Profile) -> Result<String, fmt::Error> {
+    let mut collapsed = String::new();
+    for (stack, weight) in profile.stacks() {
+        for (index, frame) in stack.iter().enumerate() {
+            if index > 0 {
+                collapsed.push(';');
+            }
+            collapsed.push_str(&format_frame(&profile.frames()[*frame]).replace(';', ","));
+        }
+        writeln!(collapsed, " {weight}")?;
+    }
+    Ok(collapsed)
+}
+
+fn format_frame(frame: &ProfileFrame) -> String {
+    match frame.line {
+        Some(line) => format!("{} ({}:{line})", frame.name, frame.file),
+        None => format!("{} ({})", frame.name, frame.file),
+    }
+}
+
+/**
+    Creates a JSON file in the speedscope file format, containing
+    a single sampled profile with one sample per unique stack.
+
+    See <https://github.com/jlfwong/speedscope/wiki/Importing-from-custom-sources>.
+*/
+fn to_speedscope(profile: &Profile, name: &str) -> serde_json::Result<String> {
+    #[derive(Serialize)]
+    struct SpeedscopeFile<'a> {
+        #[serde(rename = "$schema")]
+        schema: &'static str,
+        name: &'a str,
+        exporter: &'static str,
+        shared: SpeedscopeShared<'a>,
+        profiles: [SpeedscopeProfile<'a>; 1],
+    }
+
+    #[derive(Serialize)]
+    struct SpeedscopeShared<'a> {
+        frames: Vec<SpeedscopeFrame<'a>>,
+    }
+
+    #[derive(Serialize)]
+    struct SpeedscopeFrame<'a> {
+        name: &'a str,
+        file: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct SpeedscopeProfile<'a> {
+        #[serde(rename = "type")]
+        kind: &'static str,
+        name: &'a str,
+        unit: &'static str,
+        start_value: u64,
+        end_value: u64,
+        samples: Vec<&'a [usize]>,
+        weights: Vec<u64>,
+    }
+
+    let (samples, weights): (Vec<_>, Vec<_>) = profile.stacks().unzip();
+    serde_json::to_string(&SpeedscopeFile {
+        schema: "https://www.speedscope.app/file-format-schema.json",
+        name,
+        exporter: concat!("lune@", env!("CARGO_PKG_VERSION")),
+        shared: SpeedscopeShared {
+            frames: profile
+                .frames()
+                .iter()
+                .map(|frame| SpeedscopeFrame {
+                    name: &frame.name,
+                    file: &frame.file,
+                    line: frame.line,
+                })
+                .collect(),
+        },
+        profiles: [SpeedscopeProfile {
+            kind: "sampled",
+            name,
+            unit: "microseconds",
+            start_value: 0,
+            end_value: profile.total_weight(),
+            samples,
+            weights,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> Profile {
+        let main = ProfileFrame {
+            name: "main chunk".to_string(),
+            file: "main.luau".to_string(),
+            line: Some(0),
+        };
+        let read = ProfileFrame {
+            name: "readFile".to_string(),
+            file: "[C]".to_string(),
+            line: None,
+        };
+        let mut profile = Profile::new();
+        profile.record([main.clone()], 100);
+        profile.record([main.clone(), read.clone()], 250);
+        profile.record([main, read], 50);
+        profile
+    }
+
+    #[test]
+    fn collapsed() {
+        assert_eq!(
+            to_collapsed(&profile()).unwrap(),
+            "main chunk (main.luau:0) 100\nmain chunk (main.luau:0);readFile ([C]) 300\n"
+        );
+    }
+
+    #[test]
+    fn speedscope() {
+        let json = to_speedscope(&profile(), "profile.json").unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(value["shared"]["frames"][1]["name"], "readFile");
+        assert!(value["shared"]["frames"][1].get("line").is_none());
+
+        let sampled = &value["profiles"][0];
+        assert_eq!(sampled["type"], "sampled");
+        assert_eq!(sampled["endValue"], 400);
+        assert_eq!(sampled["samples"], serde_json::json!([[0], [0, 1]]));
+        assert_eq!(sampled["weights"], serde_json::json!([100, 300]));
+    }
+}
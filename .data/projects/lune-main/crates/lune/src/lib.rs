@@ -5,4 +5,6 @@ mod rt;
 #[cfg(test)]
 mod tests;
 
-pub use crate::rt::{Runtime, RuntimeError, RuntimeResult, RuntimeReturnValues};
+pub use crate::rt::{
+    Profile, ProfileFrame, Runtime, RuntimeError, RuntimeResult, RuntimeReturnValues,
+};
@@ -1,5 +1,7 @@
+mod profiler;
 mod result;
 mod runtime;
 
+pub use self::profiler::{Profile, ProfileFrame};
 pub use self::result::{RuntimeError, RuntimeResult};
 pub use self::runtime::{Runtime, RuntimeReturnValues};
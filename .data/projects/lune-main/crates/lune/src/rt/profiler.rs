@@ -0,0 +1,395 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    ffi::c_void,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use mlua::{Debug as LuaDebug, VmState, prelude::*};
+
+/**
+    The interval at which the Luau call stack is sampled while profiling.
+*/
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/**
+    The chunk name that `mlua` uses for the Luau wrapper around async functions.
+
+    Frames in this chunk are skipped, so that time spent waiting on an async
+    function such as `fs.readFile` is attributed to the Lua function calling it.
+*/
+const ASYNC_POLL_CHUNK: &str = "__mlua_async_poll";
+
+/**
+    A single function in a sampled call stack.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProfileFrame {
+    /// The name of the function, or `main chunk` / `anonymous` if it has no name.
+    pub name: String,
+    /// The short source of the function, such as the file it was defined in, or `[C]`.
+    pub file: String,
+    /// The line that the function was defined on, if it is a Luau function.
+    pub line: Option<usize>,
+}
+
+impl ProfileFrame {
+    fn from_debug(debug: &LuaDebug) -> Self {
+        let source = debug.source();
+        let name = match debug.names().name {
+            Some(name) => name.into_owned(),
+            None if source.what == "main" => "main chunk".to_string(),
+            None => "anonymous".to_string(),
+        };
+        let file = source
+            .short_src
+            .map_or_else(|| "?".to_string(), Cow::into_owned);
+        Self {
+            name,
+            file,
+            line: source.line_defined,
+        }
+    }
+}
+
+/**
+    Sampled call stacks, aggregated across all threads run by a runtime.
+
+    Each unique stack is stored once, together with its total weight,
+    which is the time spent in it, in microseconds.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    frames: Vec<ProfileFrame>,
+    frame_indices: HashMap<ProfileFrame, usize>,
+    stacks: BTreeMap<Vec<usize>, u64>,
+    samples: u64,
+}
+
+impl Profile {
+    /**
+        Creates a new, empty, profile.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Records a sample for the given stack, ordered from the outermost
+        function to the innermost, adding the weight to the stack.
+    */
+    pub fn record(&mut self, stack: impl IntoIterator<Item = ProfileFrame>, weight: u64) {
+        let stack = stack
+            .into_iter()
+            .map(|frame| {
+                if let Some(index) = self.frame_indices.get(&frame) {
+                    *index
+                } else {
+                    let index = self.frames.len();
+                    self.frames.push(frame.clone());
+                    self.frame_indices.insert(frame, index);
+                    index
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let total = self.stacks.entry(stack).or_default();
+        *total = total.saturating_add(weight);
+        self.samples += 1;
+    }
+
+    /**
+        Returns all unique frames in the profile, which stacks refer to by index.
+    */
+    #[must_use]
+    pub fn frames(&self) -> &[ProfileFrame] {
+        &self.frames
+    }
+
+    /**
+        Returns an iterator over all unique stacks, as indices into [`Profile::frames`]
+        ordered from the outermost function to the innermost, with their total weight.
+    */
+    pub fn stacks(&self) -> impl Iterator<Item = (&[usize], u64)> {
+        self.stacks
+            .iter()
+            .map(|(stack, weight)| (stack.as_slice(), *weight))
+    }
+
+    /**
+        Returns the total number of samples that were recorded.
+    */
+    #[must_use]
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /**
+        Returns the total weight of all stacks, in microseconds.
+    */
+    #[must_use]
+    pub fn total_weight(&self) -> u64 {
+        self.stacks.values().sum()
+    }
+
+    /**
+        Returns `true` if no samples were recorded.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+}
+
+/**
+    A sampling profiler, installed as the interrupt callback of a Luau VM.
+
+    A background thread requests a sample at a fixed interval, which is then taken by the
+    interrupt callback the next time that Luau code runs. Each sample is weighted by the
+    time since the previous sample.
+
+    Time spent waiting on async functions is instead attributed to the stack of the thread
+    that was waiting, once it resumes, since any other thread may run in the meantime.
+*/
+#[derive(Debug)]
+pub(crate) struct Profiler {
+    profile: Rc<RefCell<Profile>>,
+    stopped: Arc<AtomicBool>,
+}
+
+/**
+    The last interrupt in the async poll chunk for each thread, with the
+    line it happened on and when, used to find out when threads yield.
+*/
+type AsyncPolls = HashMap<*const c_void, (usize, Instant)>;
+
+impl Profiler {
+    /**
+        Starts profiling the given Luau VM, replacing any existing
+        interrupt and thread collection callbacks.
+    */
+    pub(crate) fn start(lua: &Lua) -> Self {
+        let profile = Rc::new(RefCell::new(Profile::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let pending = Arc::new(AtomicBool::new(false));
+
+        let sampler_stopped = Arc::clone(&stopped);
+        let sampler_pending = Arc::clone(&pending);
+        thread::spawn(move || {
+            while !sampler_stopped.load(Ordering::Relaxed) {
+                thread::sleep(SAMPLE_INTERVAL);
+                sampler_pending.store(true, Ordering::Relaxed);
+            }
+        });
+
+        let polls = Rc::new(RefCell::new(AsyncPolls::new()));
+        let collected = Rc::clone(&polls);
+        lua.set_thread_collection_callback(move |thread| {
+            // NOTE: This may run during the interrupt callback, in which case
+            // the entry is left as-is, since panicking here would abort
+            if let Ok(mut polls) = collected.try_borrow_mut() {
+                polls.remove(&thread.0.cast_const());
+            }
+        });
+
+        let inner = Rc::clone(&profile);
+        let last_sample = Cell::new(Instant::now());
+        lua.set_interrupt(move |lua| {
+            /*
+                Interrupts happen right before each call in the async poll chunk, and
+                both `yield` and the following `poll` are called on the same line, so
+                two interrupts in a row on the same line and thread mean that the thread
+                yielded in between. The stack can not change while a thread is yielded,
+                so the wait is attributed to the stack of the thread as it resumes.
+            */
+            let poll_line = lua
+                .inspect_stack(0, |debug| {
+                    let source = debug.source();
+                    (source.short_src.as_deref() == Some(ASYNC_POLL_CHUNK))
+                        .then(|| debug.current_line())
+                        .flatten()
+                })
+                .flatten();
+            if let Some(line) = poll_line {
+                let now = Instant::now();
+                let thread = lua.current_thread().to_pointer();
+                let previous = polls.borrow_mut().insert(thread, (line, now));
+                if let Some((previous_line, yielded)) = previous
+                    && previous_line == line
+                {
+                    polls.borrow_mut().remove(&thread);
+                    let elapsed = now.duration_since(yielded);
+                    let weight = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+                    inner.borrow_mut().record(capture_stack(lua), weight);
+                    // The wait is now accounted for, and should not be included in the next sample
+                    last_sample.set(now);
+                    pending.store(false, Ordering::Relaxed);
+                }
+            }
+
+            if pending.swap(false, Ordering::Relaxed) {
+                let now = Instant::now();
+                let elapsed = now.duration_since(last_sample.replace(now));
+                let weight = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+                inner.borrow_mut().record(capture_stack(lua), weight);
+            }
+            Ok(VmState::Continue)
+        });
+
+        Self { profile, stopped }
+    }
+
+    /**
+        Returns a snapshot of all samples recorded so far.
+    */
+    pub(crate) fn profile(&self) -> Profile {
+        self.profile.borrow().clone()
+    }
+}
+
+impl Drop for Profiler {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/**
+    Captures the call stack of the currently running Luau thread,
+    ordered from the outermost function to the innermost.
+*/
+fn capture_stack(lua: &Lua) -> Vec<ProfileFrame> {
+    let mut frames = Vec::new();
+    let mut level = 0;
+    while let Some(frame) = lua.inspect_stack(level, ProfileFrame::from_debug) {
+        if frame.file != ASYNC_POLL_CHUNK {
+            frames.push(frame);
+        }
+        level += 1;
+    }
+    frames.reverse();
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(name: &str) -> ProfileFrame {
+        ProfileFrame {
+            name: name.to_string(),
+            file: "main.luau".to_string(),
+            line: Some(1),
+        }
+    }
+
+    #[test]
+    fn aggregates_stacks() {
+        let mut profile = Profile::new();
+        profile.record([frame("main chunk"), frame("a")], 10);
+        profile.record([frame("main chunk"), frame("b")], 5);
+        profile.record([frame("main chunk"), frame("a")], 20);
+
+        assert_eq!(profile.frames().len(), 3);
+        assert_eq!(profile.samples(), 3);
+        assert_eq!(profile.total_weight(), 35);
+        assert_eq!(
+            profile.stacks().collect::<Vec<_>>(),
+            vec![(&[0, 1][..], 30), (&[0, 2][..], 5)]
+        );
+    }
+
+    #[test]
+    fn samples_luau_functions() {
+        let lua = Lua::new();
+        let profiler = Profiler::start(&lua);
+
+        lua.load(
+            "local function spin()\n\
+            \tlocal start = os.clock()\n\
+            \twhile os.clock() - start < 0.05 do end\n\
+            end\n\
+            spin()",
+        )
+        .set_name("=spin.luau")
+        .exec()
+        .unwrap();
+
+        let profile = profiler.profile();
+        assert!(!profile.is_empty());
+        assert!(profile.frames().iter().any(|frame| frame.name == "spin"
+            && frame.file == "spin.luau"
+            && frame.line == Some(1)));
+    }
+
+    #[test]
+    fn attributes_async_waits_to_waiting_stack() {
+        let lua = Lua::new();
+        let profiler = Profiler::start(&lua);
+
+        let sleep = lua
+            .create_async_function(|_, ms: u64| async move {
+                async_io::Timer::after(Duration::from_millis(ms)).await;
+                Ok(())
+            })
+            .unwrap();
+        lua.globals().set("sleep", sleep).unwrap();
+
+        // Both threads wait for just as long, but the other thread then
+        // also does a short wait, which should not be charged any more
+        let waiting = lua
+            .load("local function wait()\n\tsleep(100)\nend\nwait()")
+            .set_name("=wait.luau")
+            .into_function()
+            .unwrap();
+        let other = lua
+            .load(
+                "local function short()\n\tsleep(0)\nend\n\
+                local function other()\n\tsleep(100)\n\tshort()\nend\nother()",
+            )
+            .set_name("=other.luau")
+            .into_function()
+            .unwrap();
+        let waiting = lua.create_thread(waiting).unwrap();
+        let other = lua.create_thread(other).unwrap();
+        async_io::block_on(futures_lite::future::zip(
+            waiting.into_async::<()>(()).unwrap(),
+            other.into_async::<()>(()).unwrap(),
+        ))
+        .0
+        .unwrap();
+
+        let profile = profiler.profile();
+        let weight_of = |name: &str| {
+            profile
+                .stacks()
+                .filter(|(stack, _)| {
+                    stack
+                        .last()
+                        .is_some_and(|&index| profile.frames()[index].name == name)
+                })
+                .map(|(_, weight)| weight)
+                .sum::<u64>()
+        };
+        let (wait, other, short) = (weight_of("wait"), weight_of("other"), weight_of("short"));
+        assert!(
+            wait > short * 10,
+            "wait was not attributed to the waiting stack"
+        );
+        assert!(
+            other > short * 10,
+            "wait of the other thread was not attributed to its own stack"
+        );
+        assert!(
+            wait < other * 3 && other < wait * 3,
+            "wait of one thread was attributed to the stack of the other"
+        );
+    }
+}
@@ -11,13 +11,18 @@ use std::{
 
 use async_fs as fs;
 use lune_utils::{
-    path::{LuauModulePath, constants::FILE_CHUNK_PREFIX},
+    coverage::{CoverageReport, CoverageTracker},
+    path::{EmbeddedAssets, EmbeddedFiles, LuauModulePath, constants::FILE_CHUNK_PREFIX},
     process::{ProcessArgs, ProcessEnv, ProcessJitEnablement},
 };
+use mlua::Compiler as LuaCompiler;
 use mlua::prelude::*;
 use mlua_luau_scheduler::{Functions, Scheduler};
 
-use super::{RuntimeError, RuntimeResult};
+use super::{
+    RuntimeError, RuntimeResult,
+    profiler::{Profile, Profiler},
+};
 
 /**
     Values returned by running a Lune runtime until completion.
@@ -69,6 +74,8 @@ pub struct Runtime {
     args: ProcessArgs,
     env: ProcessEnv,
     jit: ProcessJitEnablement,
+    embedded: EmbeddedFiles,
+    profiler: Option<Profiler>,
 }
 
 impl Runtime {
@@ -137,6 +144,7 @@ impl Runtime {
         let args = ProcessArgs::current();
         let env = ProcessEnv::current();
         let jit = ProcessJitEnablement::default();
+        let embedded = EmbeddedFiles::default();
 
         Ok(Self {
             lua,
@@ -144,6 +152,8 @@ impl Runtime {
             args,
             env,
             jit,
+            embedded,
+            profiler: None,
         })
     }
 
@@ -190,6 +200,117 @@ impl Runtime {
         self
     }
 
+    /**
+        Sets the files embedded into a standalone binary, making them available through `require`.
+
+        Modules with embedded paths, such as `$/src/main`, are resolved using the given
+        files instead of the real filesystem. See [`Runtime::run_embedded`] for running
+        an embedded module, and [`EmbeddedFiles`] for more information about embedded paths.
+
+        # Errors
+
+        Returns an error if the `require` global fails to be created.
+    */
+    pub fn with_embedded_files(mut self, files: EmbeddedFiles) -> LuaResult<Self> {
+        self.lua.set_app_data(files.clone());
+        self.embedded = files;
+
+        // The require global reads embedded files when it is created,
+        // so it needs to be created again now that we have them
+        #[cfg(any(
+            feature = "std-datetime",
+            feature = "std-fs",
+            feature = "std-luau",
+            feature = "std-net",
+            feature = "std-process",
+            feature = "std-regex",
+            feature = "std-roblox",
+            feature = "std-serde",
+            feature = "std-stdio",
+            feature = "std-task",
+        ))]
+        {
+            let require = lune_std::LuneStandardGlobal::Require;
+            self.lua
+                .globals()
+                .set(require.name(), require.create(self.lua.clone())?)?;
+        }
+
+        Ok(self)
+    }
+
+    /**
+        Sets the asset files embedded into a standalone binary, making them
+        available to the `fs` library through paths such as `@embedded/config.json`.
+
+        Without any embedded assets, such paths are instead read from the
+        current directory, so that scripts work the same when not built.
+    */
+    #[must_use]
+    pub fn with_embedded_assets(self, files: EmbeddedFiles) -> Self {
+        self.lua.set_app_data(EmbeddedAssets::new(files));
+        self
+    }
+
+    /**
+        Enables or disables line coverage collection.
+
+        When enabled, all scripts and modules loaded from files are compiled with
+        coverage enabled, and their coverage can be read using [`Runtime::coverage`].
+    */
+    #[must_use]
+    pub fn with_coverage(self, enabled: bool) -> Self {
+        if enabled {
+            self.lua
+                .set_compiler(LuaCompiler::new().set_coverage_level(1));
+            self.lua.set_app_data(CoverageTracker::new());
+        } else {
+            self.lua.set_compiler(LuaCompiler::new());
+            self.lua.remove_app_data::<CoverageTracker>();
+        }
+        self
+    }
+
+    /**
+        Collects line coverage for all scripts and modules that
+        have been loaded from files, if coverage is enabled.
+
+        See [`Runtime::with_coverage`] for more information.
+    */
+    #[must_use]
+    pub fn coverage(&self) -> Option<CoverageReport> {
+        self.lua
+            .app_data_ref::<CoverageTracker>()
+            .map(|tracker| tracker.report())
+    }
+
+    /**
+        Enables or disables sampling of the Luau call stack.
+
+        When enabled, the call stacks of all threads are sampled at a fixed interval,
+        and the aggregated samples can be read using [`Runtime::profile`]. Time spent
+        in async functions, such as `fs.readFile`, is attributed to the calling function.
+    */
+    #[must_use]
+    pub fn with_profiling(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.profiler = Some(Profiler::start(&self.lua));
+        } else if self.profiler.take().is_some() {
+            self.lua.remove_interrupt();
+        }
+        self
+    }
+
+    /**
+        Returns all call stacks sampled so far, if profiling is enabled.
+
+        See [`Runtime::with_profiling`] for more information.
+    */
+    #[must_use]
+    pub fn profile(&self) -> Option<Profile> {
+        self.profiler.as_ref().map(Profiler::profile)
+    }
+
     /**
         Adds a custom library to the runtime, making it available through `require`.
 
@@ -257,6 +378,40 @@ impl Runtime {
         Ok(self)
     }
 
+    /**
+        Adds custom globals to the runtime, using the keys and
+        values of the table created by the given function.
+
+        # Example Usage
+
+        ```rs
+        Runtime::new().with_globals(|lua| {
+            let t = lua.create_table()?;
+            t.set("answer", 42)?;
+            Ok(t)
+        });
+        ```
+
+        # Errors
+
+        Returns an error if:
+
+        - The provided `make_globals` function errors
+        - Any of the globals fail to be set
+    */
+    pub fn with_globals<F>(self, make_globals: F) -> RuntimeResult<Self>
+    where
+        F: FnOnce(&Lua) -> LuaResult<LuaTable>,
+    {
+        let globals = make_globals(&self.lua)?;
+        for pair in globals.pairs::<LuaValue, LuaValue>() {
+            let (key, value) = pair?;
+            self.lua.globals().set(key, value)?;
+        }
+
+        Ok(self)
+    }
+
     /**
         Runs some kind of custom input, inside of the current runtime.
 
@@ -276,7 +431,7 @@ impl Runtime {
         chunk_contents: impl AsRef<[u8]>,
     ) -> RuntimeResult<RuntimeReturnValues> {
         let chunk_name = format!("={}", chunk_name.as_ref());
-        self.run_inner(chunk_name, chunk_contents).await
+        self.run_inner(chunk_name, chunk_contents, None).await
     }
 
     /**
@@ -328,14 +483,56 @@ impl Runtime {
 
         let module_name = format!("{FILE_CHUNK_PREFIX}{module_path}");
         let module_contents = strip_shebang(contents);
+        let module_file = module_path.target().as_ref().to_path_buf();
+
+        self.run_inner(module_name, module_contents, Some(module_file))
+            .await
+    }
+
+    /**
+        Runs an embedded module, inside of the current runtime.
+
+        The module path is relative to the root of the embedded files, such as `src/main`,
+        and is resolved in the same way as module paths given to [`run_file`].
+
+        Embedded files must first be set using [`Runtime::with_embedded_files`].
+
+        # Errors
 
-        self.run_inner(module_name, module_contents).await
+        Returns an error if:
+
+        - The module does not exist in the embedded files
+        - The script fails to run (not if the script itself errors)
+    */
+    pub async fn run_embedded(
+        &mut self,
+        module: impl AsRef<str>,
+    ) -> RuntimeResult<RuntimeReturnValues> {
+        let module = module.as_ref();
+        let module_path =
+            LuauModulePath::resolve_embedded(EmbeddedFiles::embedded_path(module), &self.embedded)
+                .map_err(|e| LuaError::external(format!("{e:?}")))
+                .with_context(|_| format!("Failed to find embedded module \"{module}\""))?;
+
+        let contents = module_path
+            .target()
+            .as_file()
+            .and_then(|file| self.embedded.get(file))
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| {
+                LuaError::runtime(format!("Embedded module \"{module}\" is not a file"))
+            })?;
+
+        let module_name = format!("{FILE_CHUNK_PREFIX}{module_path}");
+
+        self.run_inner(module_name, contents, None).await
     }
 
     async fn run_inner(
         &mut self,
         chunk_name: impl AsRef<str>,
         chunk_contents: impl AsRef<[u8]>,
+        chunk_file: Option<PathBuf>,
     ) -> RuntimeResult<RuntimeReturnValues> {
         // Add error callback to format errors nicely + store status
         let got_any_error = Arc::new(AtomicBool::new(false));
@@ -375,7 +572,15 @@ impl Runtime {
         let main = self
             .lua
             .load(chunk_contents.as_ref())
-            .set_name(chunk_name.as_ref());
+            .set_name(chunk_name.as_ref())
+            .into_function()?;
+
+        // Track coverage for the main chunk too, if it was loaded from a file
+        if let Some(file) = chunk_file
+            && let Some(tracker) = self.lua.app_data_ref::<CoverageTracker>()
+        {
+            tracker.track(file, main.clone());
+        }
 
         // Run it on our scheduler until it and any other spawned threads complete
         let main_thread_id = self.sched.push_thread_back(main, ())?;
@@ -1,34 +1,49 @@
 use std::{env, path::PathBuf, sync::LazyLock};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use async_fs as fs;
+use lune_utils::path::EmbeddedFiles;
 use mlua::Compiler as LuaCompiler;
 
+use super::tracer::Trace;
+
 pub static CURRENT_EXE: LazyLock<PathBuf> =
     LazyLock::new(|| env::current_exe().expect("failed to get current exe"));
 const MAGIC: &[u8; 8] = b"cr3sc3nt";
+const FORMAT_VERSION: u8 = 1;
 
 /*
-    TODO: Right now all we do is append the bytecode to the end
-    of the binary, but we will need a more flexible solution in
-    the future to store many files as well as their metadata.
+    The metadata for a standalone binary is appended to the end of the
+    base executable, followed by its size and the magic bytes, so that
+    it can be found by reading the executable backwards:
+
+    [base executable] [payload] [payload size: u64] [magic: 8 bytes]
+
+    The payload itself stores the entrypoint, all embedded files, and all embedded assets:
 
-    The best solution here is most likely to use a well-supported
-    and rust-native binary serialization format with a stable
-    specification, one that also supports byte arrays well without
-    overhead, so the best solution seems to currently be Postcard:
+    [format version: u8]
+    [entrypoint length: u32] [entrypoint]
+    [file count: u32]
+    [path length: u32] [path] [contents length: u64] [contents] - for each file
+    [asset count: u32]
+    [path length: u32] [path] [contents length: u64] [contents] - for each asset
 
-    https://github.com/jamesmunns/postcard
-    https://crates.io/crates/postcard
+    All integers are big endian. Modules are stored as compiled bytecode,
+    and any other files, such as `.luaurc` files and assets, are stored as-is.
 */
 
 /**
     Metadata for a standalone Lune executable. Can be used to
-    discover and load the bytecode contained in a standalone binary.
+    discover and load the modules contained in a standalone binary.
 */
 #[derive(Debug, Clone)]
 pub struct Metadata {
-    pub bytecode: Vec<u8>,
+    /// The module path of the entrypoint, relative to the root of the embedded files.
+    pub entrypoint: String,
+    /// All embedded files, with modules compiled to bytecode.
+    pub files: EmbeddedFiles,
+    /// All embedded asset files, readable through `@embedded/` paths.
+    pub assets: EmbeddedFiles,
 }
 
 impl Metadata {
@@ -45,11 +60,12 @@ impl Metadata {
     }
 
     /**
-        Creates a patched standalone binary from the given script contents.
+        Creates a patched standalone binary from the given traced modules and asset files.
     */
     pub async fn create_env_patched_bin(
         base_exe_path: PathBuf,
-        script_contents: impl Into<Vec<u8>>,
+        trace: Trace,
+        assets: EmbeddedFiles,
     ) -> Result<Vec<u8>> {
         let compiler = LuaCompiler::new()
             .set_optimization_level(2)
@@ -58,11 +74,24 @@ impl Metadata {
 
         let mut patched_bin = fs::read(base_exe_path).await?;
 
-        // Compile luau input into bytecode
-        let bytecode = compiler.compile(script_contents.into())?;
+        // Compile all luau modules into bytecode, and embed configs as-is
+        let mut files = EmbeddedFiles::new();
+        for (path, source) in trace.modules {
+            let bytecode = compiler
+                .compile(source)
+                .with_context(|| format!("failed to compile module \"{path}\""))?;
+            files.insert(path, bytecode);
+        }
+        for (path, contents) in trace.configs {
+            files.insert(path, contents);
+        }
 
-        // Append the bytecode / metadata to the end
-        let meta = Self { bytecode };
+        // Append the metadata to the end
+        let meta = Self {
+            entrypoint: trace.entrypoint,
+            files,
+            assets,
+        };
         patched_bin.extend_from_slice(&meta.to_bytes());
 
         Ok(patched_bin)
@@ -77,25 +106,153 @@ impl Metadata {
             bail!("not a standalone binary")
         }
 
-        // Extract bytecode size
-        let bytecode_size_bytes = &bytes[bytes.len() - 16..bytes.len() - 8];
-        let bytecode_size =
-            usize::try_from(u64::from_be_bytes(bytecode_size_bytes.try_into().unwrap()))?;
+        // Extract payload size
+        let payload_size_bytes = &bytes[bytes.len() - 16..bytes.len() - 8];
+        let payload_size =
+            usize::try_from(u64::from_be_bytes(payload_size_bytes.try_into().unwrap()))?;
+        if payload_size > bytes.len() - 16 {
+            bail!("standalone binary is truncated")
+        }
 
-        // Extract bytecode
-        let bytecode = bytes[bytes.len() - 16 - bytecode_size..].to_vec();
+        // Extract payload
+        let mut reader = PayloadReader {
+            bytes: &bytes[bytes.len() - 16 - payload_size..bytes.len() - 16],
+        };
 
-        Ok(Self { bytecode })
+        let version = reader.read_u8()?;
+        if version != FORMAT_VERSION {
+            bail!("unsupported standalone binary format version {version}")
+        }
+
+        let entrypoint = reader.read_string()?;
+        let files = reader.read_files()?;
+        let assets = reader.read_files()?;
+
+        Ok(Self {
+            entrypoint,
+            files,
+            assets,
+        })
     }
 
     /**
         Writes the metadata chunk to a byte vector, to later bet read using `from_bytes`.
     */
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.bytecode);
-        bytes.extend_from_slice(&(self.bytecode.len() as u64).to_be_bytes());
+        let mut payload = vec![FORMAT_VERSION];
+        write_string(&mut payload, &self.entrypoint);
+        write_files(&mut payload, &self.files);
+        write_files(&mut payload, &self.assets);
+
+        let payload_size = payload.len() as u64;
+        let mut bytes = payload;
+        bytes.extend_from_slice(&payload_size.to_be_bytes());
         bytes.extend_from_slice(MAGIC);
         bytes
     }
 }
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend_from_slice(&(string.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+fn write_files(bytes: &mut Vec<u8>, files: &EmbeddedFiles) {
+    bytes.extend_from_slice(&(files.len() as u32).to_be_bytes());
+    for (path, contents) in files.iter() {
+        write_string(bytes, path);
+        bytes.extend_from_slice(&(contents.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(contents);
+    }
+}
+
+struct PayloadReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PayloadReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            bail!("standalone binary metadata is truncated")
+        }
+        let (read, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(read)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.read_bytes(4)?.try_into()?))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.read_bytes(8)?.try_into()?))
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let len = usize::try_from(self.read_u32()?)?;
+        Ok(String::from_utf8(self.read_bytes(len)?.to_vec())?)
+    }
+
+    fn read_files(&mut self) -> Result<EmbeddedFiles> {
+        let mut files = EmbeddedFiles::new();
+        for _ in 0..self.read_u32()? {
+            let path = self.read_string()?;
+            let contents_len = usize::try_from(self.read_u64()?)?;
+            files.insert(path, self.read_bytes(contents_len)?);
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let meta = Metadata {
+            entrypoint: "pipeline/main".to_string(),
+            files: [
+                ("pipeline/main.luau", b"main".to_vec()),
+                ("shared/init.luau", b"shared".to_vec()),
+                (".luaurc", b"{}".to_vec()),
+            ]
+            .into_iter()
+            .collect(),
+            assets: [("data/config.json", b"{}".to_vec())].into_iter().collect(),
+        };
+
+        let mut bin = b"base executable".to_vec();
+        bin.extend_from_slice(&meta.to_bytes());
+
+        let read = Metadata::from_bytes(&bin).unwrap();
+        assert_eq!(read.entrypoint, "pipeline/main");
+        assert_eq!(
+            read.files.iter().collect::<Vec<_>>(),
+            meta.files.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            read.assets.iter().collect::<Vec<_>>(),
+            meta.assets.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rejects_truncated() {
+        let meta = Metadata {
+            entrypoint: "main".to_string(),
+            files: [("main.luau", b"main".to_vec())].into_iter().collect(),
+            assets: EmbeddedFiles::new(),
+        };
+
+        let bytes = meta.to_bytes();
+        let mut truncated = bytes[..4].to_vec();
+        truncated.extend_from_slice(&bytes[bytes.len() - 16..]);
+
+        assert!(Metadata::from_bytes(&truncated).is_err());
+    }
+}
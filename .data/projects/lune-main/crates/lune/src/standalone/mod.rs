@@ -22,16 +22,19 @@ pub async fn check() -> Option<Vec<u8>> {
 }
 
 /**
-    Discovers, loads and executes the bytecode contained in a standalone binary.
+    Discovers, loads and executes the modules contained in a standalone binary.
 */
 pub async fn run(patched_bin: impl AsRef<[u8]>) -> Result<ExitCode> {
     // The first argument is the path to the current executable
     let args = env::args().skip(1).collect::<Vec<_>>();
     let meta = Metadata::from_bytes(patched_bin).expect("must be a standalone binary");
 
-    let mut rt = Runtime::new()?.with_args(args);
+    let mut rt = Runtime::new()?
+        .with_args(args)
+        .with_embedded_files(meta.files)?
+        .with_embedded_assets(meta.assets);
 
-    let result = rt.run_custom("STANDALONE", meta.bytecode).await;
+    let result = rt.run_embedded(&meta.entrypoint).await;
 
     Ok(match result {
         Err(err) => {
@@ -0,0 +1,429 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use lune_utils::path::{
+    LuauFilePath, LuauModulePath, clean_path, clean_path_and_make_absolute,
+    constants::FILE_NAME_CONFIG,
+};
+use mlua::prelude::LuaNavigateError;
+use serde_json::Value as JsonValue;
+
+mod scanner;
+
+use self::scanner::{RequireCall, find_require_calls};
+
+/**
+    All modules and configuration files traced from an entrypoint, ready to be embedded.
+
+    Paths are relative to the root directory that contains all traced files, and always
+    use `/` as the separator, so that they are the same regardless of the current platform.
+*/
+#[derive(Debug, Clone)]
+pub struct Trace {
+    /// The module path of the entrypoint, relative to the root directory.
+    pub entrypoint: String,
+    /// The source code of all traced modules, including the entrypoint.
+    pub modules: BTreeMap<String, Vec<u8>>,
+    /// The contents of all `.luaurc` files that were used when resolving aliases.
+    pub configs: BTreeMap<String, Vec<u8>>,
+    /// Requires that could not be traced, such as requires using non-literal paths.
+    pub warnings: Vec<String>,
+}
+
+impl Trace {
+    /**
+        Traces all modules required by the given entrypoint file or module path,
+        following requires using relative paths, `@self`, and aliases from `.luaurc` files.
+
+        Requires for standard libraries are ignored, and requires using anything
+        other than a string literal are listed as warnings, since they can not be traced.
+
+        # Errors
+
+        Errors if any module or configuration file could not be read,
+        or if any require using a string literal could not be resolved.
+    */
+    pub fn from_entrypoint(path: impl Into<PathBuf>) -> Result<Self> {
+        let module = clean_path_and_make_absolute(LuauModulePath::strip(path));
+        let file = resolve_module_file(&module)
+            .with_context(|| format!("failed to find entrypoint \"{}\"", module.display()))?;
+
+        let mut tracer = Tracer::default();
+        tracer.visit(&module, &file)?;
+
+        let root = common_ancestor(
+            tracer
+                .modules
+                .keys()
+                .chain(tracer.configs.keys())
+                .filter_map(|path| path.parent()),
+        )
+        .context("traced files do not share a common root directory")?;
+
+        let relative = |path: &Path| relative_key(&root, path);
+        let configs = tracer
+            .configs
+            .iter()
+            .map(|(path, contents)| {
+                let contents = rewrite_config_aliases(path, contents)?;
+                Ok((relative(path), contents))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            entrypoint: relative(&module),
+            modules: tracer
+                .modules
+                .iter()
+                .map(|(path, source)| (relative(path), source.clone()))
+                .collect(),
+            configs,
+            warnings: tracer.warnings,
+        })
+    }
+}
+
+type Aliases = BTreeMap<String, PathBuf>;
+
+#[derive(Debug, Default)]
+struct Tracer {
+    /// Absolute file path -> source code, for all traced modules.
+    modules: BTreeMap<PathBuf, Vec<u8>>,
+    /// Absolute file path -> contents, for all configuration files that were read.
+    configs: BTreeMap<PathBuf, Vec<u8>>,
+    /// Absolute directory path -> parsed aliases, if there is a config in the directory.
+    aliases: HashMap<PathBuf, Option<Aliases>>,
+    /// Absolute file paths of the modules currently being traced.
+    stack: Vec<PathBuf>,
+    warnings: Vec<String>,
+}
+
+impl Tracer {
+    fn visit(&mut self, module: &Path, file: &Path) -> Result<()> {
+        // Modules may require each other in a cycle, as long as at least one
+        // of the requires happens lazily, so we only need to visit them once
+        if self.stack.iter().any(|f| f == file) || self.modules.contains_key(file) {
+            return Ok(());
+        }
+
+        let source = fs::read(file)
+            .with_context(|| format!("failed to read module \"{}\"", file.display()))?;
+        let source = strip_shebang(source);
+
+        self.stack.push(file.to_path_buf());
+        for call in find_require_calls(&source) {
+            match call {
+                RequireCall::Literal { line, path } => {
+                    let required = self.resolve_require(module, &path).with_context(|| {
+                        format!(
+                            "failed to resolve require(\"{path}\") at {}:{line}",
+                            file.display()
+                        )
+                    })?;
+                    if let Some(required) = required {
+                        let required_file = resolve_module_file(&required).with_context(|| {
+                            format!(
+                                "failed to find module required at {}:{line}",
+                                file.display()
+                            )
+                        })?;
+                        self.visit(&required, &required_file)?;
+                    }
+                }
+                RequireCall::Dynamic { line } => {
+                    self.warnings.push(format!(
+                        "require at {}:{line} does not use a string literal and can not be traced",
+                        file.display()
+                    ));
+                }
+            }
+        }
+        self.stack.pop();
+
+        self.modules.insert(file.to_path_buf(), source);
+        Ok(())
+    }
+
+    /**
+        Resolves a require path to an absolute module path, following the same rules
+        as require-by-string, or `None` if the require is for a standard library.
+    */
+    fn resolve_require(&mut self, requirer: &Path, path: &str) -> Result<Option<PathBuf>> {
+        let mut segments = path.split('/');
+        let first = segments.next().unwrap_or_default();
+
+        let mut module = match first {
+            "." => parent_of(requirer)?,
+            ".." => parent_of(&parent_of(requirer)?)?,
+            "@self" => requirer.to_path_buf(),
+            "@lune" => return Ok(None),
+            other => match other.strip_prefix('@') {
+                Some(alias) => self
+                    .find_alias(requirer, alias)?
+                    .with_context(|| format!("no alias \"@{alias}\" was found in any config"))?,
+                None => bail!("require paths must start with \"./\", \"../\" or \"@\""),
+            },
+        };
+
+        for segment in segments {
+            match segment {
+                "" | "." => {}
+                ".." => module = parent_of(&module)?,
+                name => module.push(name),
+            }
+        }
+
+        Ok(Some(module))
+    }
+
+    /**
+        Finds an alias in the nearest configuration file that defines it,
+        searching from the requiring module and upwards through its parents.
+    */
+    fn find_alias(&mut self, requirer: &Path, alias: &str) -> Result<Option<PathBuf>> {
+        let alias = alias.to_ascii_lowercase();
+        for dir in requirer.ancestors() {
+            if let Some(aliases) = self.read_aliases(dir)?
+                && let Some(target) = aliases.get(&alias)
+            {
+                return Ok(Some(target.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_aliases(&mut self, dir: &Path) -> Result<Option<&Aliases>> {
+        if !self.aliases.contains_key(dir) {
+            let config_path = dir.join(FILE_NAME_CONFIG);
+            let aliases = if dir.is_dir() && config_path.is_file() {
+                let contents = fs::read(&config_path).with_context(|| {
+                    format!("failed to read config \"{}\"", config_path.display())
+                })?;
+                let aliases = parse_config_aliases(&contents)
+                    .with_context(|| {
+                        format!("failed to parse config \"{}\"", config_path.display())
+                    })?
+                    .into_iter()
+                    .map(|(alias, value)| (alias.to_ascii_lowercase(), clean_path(dir.join(value))))
+                    .collect();
+                self.configs.insert(config_path, contents);
+                Some(aliases)
+            } else {
+                None
+            };
+            self.aliases.insert(dir.to_path_buf(), aliases);
+        }
+        Ok(self.aliases[dir].as_ref())
+    }
+}
+
+fn resolve_module_file(module: &Path) -> Result<PathBuf> {
+    match LuauModulePath::resolve(module) {
+        Ok(resolved) => match resolved.target() {
+            LuauFilePath::File(file) => Ok(file.clone()),
+            LuauFilePath::Directory(_) => {
+                bail!(
+                    "\"{}\" is a directory without an init file",
+                    module.display()
+                )
+            }
+        },
+        Err(LuaNavigateError::Ambiguous) => {
+            bail!("\"{}\" matches more than one file", module.display())
+        }
+        Err(_) => bail!("\"{}\" does not exist", module.display()),
+    }
+}
+
+fn parent_of(path: &Path) -> Result<PathBuf> {
+    match path.parent() {
+        Some(parent) => Ok(parent.to_path_buf()),
+        None => bail!("\"{}\" has no parent directory", path.display()),
+    }
+}
+
+fn parse_config_aliases(contents: &[u8]) -> Result<BTreeMap<String, String>> {
+    let config = serde_json::from_slice::<JsonValue>(contents)?;
+    let Some(aliases) = config.get("aliases") else {
+        return Ok(BTreeMap::new());
+    };
+    let Some(aliases) = aliases.as_object() else {
+        bail!("aliases must be an object");
+    };
+    aliases
+        .iter()
+        .map(|(alias, value)| match value.as_str() {
+            Some(value) => Ok((alias.clone(), value.to_string())),
+            None => bail!("alias \"{alias}\" must be a string"),
+        })
+        .collect()
+}
+
+/**
+    Rewrites aliases using absolute paths to be relative to the config instead,
+    since absolute paths on the current system will not exist once embedded.
+
+    Configs without any absolute aliases are returned unchanged.
+*/
+fn rewrite_config_aliases(config_path: &Path, contents: &[u8]) -> Result<Vec<u8>> {
+    let dir = parent_of(config_path)?;
+    let mut config = serde_json::from_slice::<JsonValue>(contents)?;
+
+    let mut changed = false;
+    if let Some(aliases) = config.get_mut("aliases").and_then(JsonValue::as_object_mut) {
+        for value in aliases.values_mut() {
+            if let Some(path) = value.as_str().map(Path::new)
+                && path.is_absolute()
+            {
+                *value = JsonValue::String(relative_path_between(&dir, &clean_path(path)));
+                changed = true;
+            }
+        }
+    }
+
+    if changed {
+        Ok(serde_json::to_vec_pretty(&config)?)
+    } else {
+        Ok(contents.to_vec())
+    }
+}
+
+fn common_ancestor<'a>(mut paths: impl Iterator<Item = &'a Path>) -> Option<PathBuf> {
+    let mut ancestor = paths.next()?.to_path_buf();
+    for path in paths {
+        while !path.starts_with(&ancestor) {
+            if !ancestor.pop() {
+                return None;
+            }
+        }
+    }
+    Some(ancestor)
+}
+
+/**
+    Converts an absolute path inside of the root directory to a relative path with `/` separators.
+*/
+fn relative_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .expect("traced path is inside of root directory")
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/**
+    Creates a relative path with `/` separators, from one absolute directory to another path.
+*/
+fn relative_path_between(from: &Path, to: &Path) -> String {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let shared = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut segments = match from.len() - shared {
+        0 => vec![".".to_string()],
+        parents => vec!["..".to_string(); parents],
+    };
+    segments.extend(
+        to[shared..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+    segments.join("/")
+}
+
+fn strip_shebang(mut contents: Vec<u8>) -> Vec<u8> {
+    if contents.starts_with(b"#!") {
+        let line_end = contents
+            .iter()
+            .position(|b| *b == b'\n')
+            .unwrap_or(contents.len());
+        contents.drain(..line_end);
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use super::*;
+
+    fn write_files(root: &Path, files: &[(&str, &str)]) {
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn traces_require_graph() {
+        let root = temp_dir().join(format!("lune-tracer-test-{}", std::process::id()));
+        let shared = root.join("shared");
+        write_files(
+            &root,
+            &[
+                (
+                    ".luaurc",
+                    &format!(
+                        r#"{{ "aliases": {{ "shared": {}, "unused": "./unused" }} }}"#,
+                        serde_json::to_string(&shared.display().to_string()).unwrap()
+                    ),
+                ),
+                (
+                    "pipeline/main.luau",
+                    "#!/usr/bin/env lune\n\
+                    local fs = require(\"@lune/fs\")\n\
+                    local stage = require(\"./stage\")\n\
+                    local util = require(\"@shared/util\")\n\
+                    local dynamic = require(stage.name)\n",
+                ),
+                ("pipeline/stage.luau", "return require(\"./main\")"),
+                ("shared/util/init.luau", "return require(\"@self/nested\")"),
+                ("shared/util/nested.luau", "return {}"),
+                ("shared/unrelated.luau", "return {}"),
+            ],
+        );
+
+        let trace = Trace::from_entrypoint(root.join("pipeline/main.luau"));
+        fs::remove_dir_all(&root).unwrap();
+        let trace = trace.unwrap();
+
+        assert_eq!(trace.entrypoint, "pipeline/main");
+        assert_eq!(
+            trace.modules.keys().collect::<Vec<_>>(),
+            vec![
+                "pipeline/main.luau",
+                "pipeline/stage.luau",
+                "shared/util/init.luau",
+                "shared/util/nested.luau",
+            ]
+        );
+        assert!(trace.modules["pipeline/main.luau"].starts_with(b"\nlocal fs"));
+        assert_eq!(trace.warnings.len(), 1);
+
+        // Absolute aliases should be rewritten to be relative to the config
+        let config = serde_json::from_slice::<JsonValue>(&trace.configs[".luaurc"]).unwrap();
+        assert_eq!(config["aliases"]["shared"], "./shared");
+        assert_eq!(config["aliases"]["unused"], "./unused");
+    }
+
+    #[test]
+    fn relative_paths() {
+        let from = Path::new("/a/b/c");
+        assert_eq!(relative_path_between(from, Path::new("/a/b/c/d")), "./d");
+        assert_eq!(
+            relative_path_between(from, Path::new("/a/x/y")),
+            "../../x/y"
+        );
+        assert_eq!(relative_path_between(from, Path::new("/a/b/c")), ".");
+    }
+}
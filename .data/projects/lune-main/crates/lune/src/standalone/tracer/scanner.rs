@@ -0,0 +1,323 @@
+/**
+    A single call to `require` found in Luau source code.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequireCall {
+    /// A require using a string literal, which can be traced.
+    Literal { line: usize, path: String },
+    /// A require using any other kind of expression, which can not be traced.
+    Dynamic { line: usize },
+}
+
+/**
+    Finds all calls to the global `require` function in the given Luau source code.
+
+    This is not a full parser, but it skips over comments and strings, and does not
+    consider method calls or field accesses such as `module.require(...)` as requires.
+*/
+pub fn find_require_calls(source: &[u8]) -> Vec<RequireCall> {
+    let mut scanner = Scanner {
+        source,
+        position: 0,
+        line_ends: source
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &b)| (b == b'\n').then_some(index))
+            .collect(),
+    };
+    let mut calls = Vec::new();
+    let mut previous = None;
+
+    // Shebang lines are not valid Luau, but are allowed in scripts
+    if source.starts_with(b"#!") {
+        scanner.skip_line();
+    }
+
+    while let Some(byte) = scanner.peek() {
+        match byte {
+            b'-' if scanner.peek_at(1) == Some(b'-') => {
+                scanner.position += 2;
+                if scanner.long_bracket_level().is_some() {
+                    scanner.read_long_bracket();
+                } else {
+                    scanner.skip_line();
+                }
+                continue;
+            }
+            b'"' | b'\'' => {
+                scanner.read_quoted();
+            }
+            b'`' => scanner.skip_interpolated(),
+            b'[' if scanner.long_bracket_level().is_some() => {
+                scanner.read_long_bracket();
+            }
+            b if is_identifier_start(b) => {
+                let start = scanner.position;
+                let identifier = scanner.read_identifier();
+                let is_field = matches!(previous, Some(b'.' | b':'));
+                if identifier == b"require" && !is_field {
+                    let line = scanner.line_at(start);
+                    if let Some(call) = scanner.read_require_argument(line) {
+                        calls.push(call);
+                    }
+                }
+                previous = Some(b'a');
+                continue;
+            }
+            b if b.is_ascii_digit() => {
+                // Skip numbers, so that something like `1e5require` is never a require
+                while scanner
+                    .peek()
+                    .is_some_and(|b| is_identifier_char(b) || b == b'.')
+                {
+                    scanner.position += 1;
+                }
+                previous = Some(b'0');
+                continue;
+            }
+            b if b.is_ascii_whitespace() => {
+                scanner.position += 1;
+                continue;
+            }
+            _ => scanner.position += 1,
+        }
+        previous = Some(byte);
+    }
+
+    calls
+}
+
+struct Scanner<'a> {
+    source: &'a [u8],
+    position: usize,
+    /// Positions of all newlines in the source, used to find line numbers
+    line_ends: Vec<usize>,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.source.get(self.position + offset).copied()
+    }
+
+    fn line_at(&self, position: usize) -> usize {
+        1 + self.line_ends.partition_point(|&end| end < position)
+    }
+
+    fn skip_line(&mut self) {
+        while self.peek().is_some_and(|b| b != b'\n') {
+            self.position += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn read_identifier(&mut self) -> &[u8] {
+        let start = self.position;
+        while self.peek().is_some_and(is_identifier_char) {
+            self.position += 1;
+        }
+        &self.source[start..self.position]
+    }
+
+    /**
+        Reads a quoted string, returning its contents with simple escapes resolved.
+    */
+    fn read_quoted(&mut self) -> Vec<u8> {
+        let quote = self.peek().expect("Peeked quote");
+        self.position += 1;
+
+        let mut contents = Vec::new();
+        while let Some(byte) = self.peek() {
+            self.position += 1;
+            match byte {
+                b if b == quote => break,
+                b'\n' => break, // Unterminated string
+                b'\\' => {
+                    if let Some(escaped) = self.peek() {
+                        self.position += 1;
+                        contents.push(match escaped {
+                            b'n' => b'\n',
+                            b't' => b'\t',
+                            b'r' => b'\r',
+                            b => b,
+                        });
+                    }
+                }
+                b => contents.push(b),
+            }
+        }
+        contents
+    }
+
+    /**
+        Skips an interpolated string, which may not be traced.
+    */
+    fn skip_interpolated(&mut self) {
+        self.position += 1;
+        while let Some(byte) = self.peek() {
+            self.position += 1;
+            match byte {
+                b'`' => break,
+                b'\\' => self.position += 1,
+                _ => {}
+            }
+        }
+    }
+
+    /**
+        Returns the level of the long bracket starting at the current position,
+        if any, meaning the number of `=` characters in brackets like `[==[`.
+    */
+    fn long_bracket_level(&self) -> Option<usize> {
+        if self.peek() != Some(b'[') {
+            return None;
+        }
+        let mut level = 0;
+        while self.peek_at(1 + level) == Some(b'=') {
+            level += 1;
+        }
+        (self.peek_at(1 + level) == Some(b'[')).then_some(level)
+    }
+
+    /**
+        Reads a long string or comment, such as `[[ ... ]]` or `[==[ ... ]==]`, returning its contents.
+    */
+    fn read_long_bracket(&mut self) -> Vec<u8> {
+        let level = self.long_bracket_level().expect("Peeked long bracket");
+        self.position += level + 2;
+
+        let mut closing = vec![b']'];
+        closing.extend(std::iter::repeat_n(b'=', level));
+        closing.push(b']');
+
+        let rest = &self.source[self.position..];
+        if let Some(end) = rest.windows(closing.len()).position(|w| w == closing) {
+            self.position += end + closing.len();
+            rest[..end].to_vec()
+        } else {
+            self.position = self.source.len();
+            rest.to_vec()
+        }
+    }
+
+    /**
+        Reads the argument of a call to `require`, if the identifier that was just read is a call.
+
+        Both `require("path")` and `require "path"` are valid calls using a string literal.
+    */
+    fn read_require_argument(&mut self, line: usize) -> Option<RequireCall> {
+        self.skip_whitespace();
+
+        let has_parens = self.peek() == Some(b'(');
+        if has_parens {
+            self.position += 1;
+            self.skip_whitespace();
+        }
+
+        let path = match self.peek() {
+            Some(b'"' | b'\'') => self.read_quoted(),
+            Some(b'[') if self.long_bracket_level().is_some() => self.read_long_bracket(),
+            _ if has_parens => return Some(RequireCall::Dynamic { line }),
+            // Not a call, the require function is being used as a value
+            _ => return None,
+        };
+
+        // Anything other than a closing paren means that the literal is only
+        // part of a larger expression, such as `require("./" .. name)`
+        if has_parens {
+            self.skip_whitespace();
+            if self.peek() != Some(b')') {
+                return Some(RequireCall::Dynamic { line });
+            }
+            self.position += 1;
+        }
+
+        Some(RequireCall::Literal {
+            line,
+            path: String::from_utf8_lossy(&path).into_owned(),
+        })
+    }
+}
+
+fn is_identifier_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_'
+}
+
+fn is_identifier_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(line: usize, path: &str) -> RequireCall {
+        RequireCall::Literal {
+            line,
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn finds_literal_requires() {
+        let source = br#"
+local a = require("./a")
+local b = require './b'
+local c = require([[@self/c]])
+local d = require ( "@shared/d" )
+"#;
+        assert_eq!(
+            find_require_calls(source),
+            vec![
+                literal(2, "./a"),
+                literal(3, "./b"),
+                literal(4, "@self/c"),
+                literal(5, "@shared/d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_dynamic_requires() {
+        let source = br#"
+local a = require(path)
+local b = require("./" .. name)
+local c = require(`./{name}`)
+"#;
+        assert_eq!(
+            find_require_calls(source),
+            vec![
+                RequireCall::Dynamic { line: 2 },
+                RequireCall::Dynamic { line: 3 },
+                RequireCall::Dynamic { line: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_comments_strings_and_fields() {
+        let source = br#"#!/usr/bin/env lune
+-- require("./comment")
+--[==[
+    require("./long-comment")
+]==]
+local s = "require('./string')"
+local l = [[require("./long-string")]]
+local i = `{require}`
+local m = module.require("./field")
+local n = module:require("./method")
+local f = require
+local x = myrequire("./other")
+local r = require("./real")
+"#;
+        assert_eq!(find_require_calls(source), vec![literal(13, "./real")]);
+    }
+}
@@ -6,7 +6,7 @@ use anyhow::Result;
 use console::set_colors_enabled;
 use console::set_colors_enabled_stderr;
 
-use lune_utils::path::clean_path;
+use lune_utils::path::{EmbeddedFiles, clean_path};
 
 use crate::Runtime;
 
@@ -102,6 +102,7 @@ create_tests! {
     fs_files: "fs/files",
     fs_copy: "fs/copy",
     fs_dirs: "fs/dirs",
+    fs_embedded: "fs/embedded",
     fs_metadata: "fs/metadata",
     fs_move: "fs/move",
 }
@@ -189,6 +190,7 @@ create_tests! {
     roblox_datatype_uniqueid: "roblox/datatypes/UniqueId",
     roblox_datatype_region3: "roblox/datatypes/Region3",
     roblox_datatype_region3int16: "roblox/datatypes/Region3int16",
+    roblox_datatype_security_capabilities: "roblox/datatypes/SecurityCapabilities",
     roblox_datatype_vector2: "roblox/datatypes/Vector2",
     roblox_datatype_vector2int16: "roblox/datatypes/Vector2int16",
     roblox_datatype_vector3: "roblox/datatypes/Vector3",
@@ -196,6 +198,11 @@ create_tests! {
 
     roblox_files_deserialize_model: "roblox/files/deserializeModel",
     roblox_files_deserialize_place: "roblox/files/deserializePlace",
+    roblox_files_deserialize_place_filtered: "roblox/files/deserializePlaceFiltered",
+    roblox_files_inspect_document: "roblox/files/inspectDocument",
+    roblox_files_rojo_project: "roblox/files/rojoProject",
+    roblox_files_roundtrip_datatypes: "roblox/files/roundtripDatatypes",
+    roblox_files_serialize_canonical: "roblox/files/serializeCanonical",
     roblox_files_serialize_model: "roblox/files/serializeModel",
     roblox_files_serialize_place: "roblox/files/serializePlace",
 
@@ -205,6 +212,7 @@ create_tests! {
     roblox_instance_tags: "roblox/instance/tags",
 
     roblox_instance_classes_data_model: "roblox/instance/classes/DataModel",
+    roblox_instance_classes_model: "roblox/instance/classes/Model",
     roblox_instance_classes_workspace: "roblox/instance/classes/Workspace",
     roblox_instance_classes_terrain: "roblox/instance/classes/Terrain",
 
@@ -228,6 +236,7 @@ create_tests! {
     roblox_instance_methods_is_a: "roblox/instance/methods/IsA",
     roblox_instance_methods_is_ancestor_of: "roblox/instance/methods/IsAncestorOf",
     roblox_instance_methods_is_descendant_of: "roblox/instance/methods/IsDescendantOf",
+    roblox_instance_methods_query_descendants: "roblox/instance/methods/QueryDescendants",
 
     roblox_misc_typeof: "roblox/misc/typeof",
 
@@ -237,6 +246,16 @@ create_tests! {
     roblox_reflection_property: "roblox/reflection/property",
 }
 
+#[cfg(all(feature = "std-roblox", feature = "std-serde"))]
+create_tests! {
+    roblox_misc_diff: "roblox/misc/diff",
+}
+
+#[cfg(all(feature = "std-roblox", feature = "std-task"))]
+create_tests! {
+    roblox_instance_signals: "roblox/instance/signals",
+}
+
 #[cfg(feature = "std-serde")]
 create_tests! {
     serde_compression_files: "serde/compression/files",
@@ -268,3 +287,86 @@ create_tests! {
     task_spawn: "task/spawn",
     task_wait: "task/wait",
 }
+
+#[cfg(any(
+    feature = "std-datetime",
+    feature = "std-fs",
+    feature = "std-luau",
+    feature = "std-net",
+    feature = "std-process",
+    feature = "std-regex",
+    feature = "std-roblox",
+    feature = "std-serde",
+    feature = "std-stdio",
+    feature = "std-task",
+))]
+#[test]
+fn require_embedded() -> Result<ExitCode> {
+    async_io::block_on(async {
+        let files = [
+            (".luaurc", r#"{ "aliases": { "shared": "./shared" } }"#),
+            (
+                "pipeline/main.luau",
+                r#"
+                local stage = require("./stage")
+                local util = require("@shared/util")
+                assert(stage.value == 1, "relative require failed")
+                assert(util.value == 2, "alias require failed")
+                assert(util.nested.value == 3, "self require failed")
+                "#,
+            ),
+            ("pipeline/stage.luau", "return { value = 1 }"),
+            (
+                "shared/util/init.luau",
+                r#"return { value = 2, nested = require("@self/nested") }"#,
+            ),
+            ("shared/util/nested.luau", "return { value = 3 }"),
+        ]
+        .into_iter()
+        .collect::<EmbeddedFiles>();
+
+        let mut rt = Runtime::new()?.with_embedded_files(files)?;
+        let values = rt.run_embedded("pipeline/main").await?;
+
+        Ok(ExitCode::from(values.status()))
+    })
+}
+
+#[cfg(feature = "std-fs")]
+#[test]
+fn fs_embedded_assets() -> Result<ExitCode> {
+    async_io::block_on(async {
+        let files = [(
+            "main.luau",
+            r#"
+            local fs = require("@lune/fs")
+            assert(fs.readFile("@embedded/data/config.json") == "{}", "readFile failed")
+            assert(fs.isFile("@embedded/templates/frame.luau"), "isFile failed")
+            assert(fs.isDir("@embedded/templates"), "isDir failed")
+            assert(not fs.isFile("@embedded/missing.json"), "missing file should not exist")
+            local entries = fs.readDir("@embedded/")
+            table.sort(entries)
+            assert(table.concat(entries, ",") == "data,templates", "readDir failed")
+            local meta = fs.metadata("@embedded/data/config.json")
+            assert(meta.kind == "file" and meta.permissions.readOnly, "metadata failed")
+            assert(not pcall(fs.readFile, "@embedded/missing.json"), "missing file should error")
+            assert(not pcall(fs.writeFile, "@embedded/data/config.json", ""), "write should error")
+            "#,
+        )]
+        .into_iter()
+        .collect::<EmbeddedFiles>();
+        let assets = [
+            ("data/config.json", "{}"),
+            ("templates/frame.luau", "return {}"),
+        ]
+        .into_iter()
+        .collect::<EmbeddedFiles>();
+
+        let mut rt = Runtime::new()?
+            .with_embedded_files(files)?
+            .with_embedded_assets(assets);
+        let values = rt.run_embedded("main").await?;
+
+        Ok(ExitCode::from(values.status()))
+    })
+}
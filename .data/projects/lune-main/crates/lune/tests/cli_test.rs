@@ -0,0 +1,72 @@
+use std::{fs, path::Path, process::Command};
+
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/test");
+
+fn attribute<'a>(xml: &'a str, element: &str, name: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{element} "))?;
+    let element = &xml[start..start + xml[start..].find('>')?];
+    let value = &element[element.find(&format!(" {name}=\""))? + name.len() + 3..];
+    Some(&value[..value.find('"')?])
+}
+
+#[test]
+fn test_command_reports_results() {
+    let junit = Path::new(env!("CARGO_TARGET_TMPDIR")).join("lune-test-junit.xml");
+    let _ = fs::remove_file(&junit);
+
+    // NOTE: The fixtures are not named as test files, so that running
+    // `lune test` in this repository does not pick up the failing one
+    let output = Command::new(env!("CARGO_BIN_EXE_lune"))
+        .current_dir(FIXTURES_DIR)
+        .args(["test", "passing.luau", "failing.luau", "--junit"])
+        .arg(&junit)
+        .output()
+        .expect("failed to run lune test");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "failing tests should exit with 1"
+    );
+    assert!(
+        stdout.contains("Tests: 1 failed, 1 passed, 2 total"),
+        "summary should count passed and failed tests, got:\n{stdout}"
+    );
+    assert!(
+        stdout.contains("Files: 1 failed, 1 passed, 2 total"),
+        "summary should count passed and failed files, got:\n{stdout}"
+    );
+    assert!(
+        stdout.contains("Expected 1 to be 2"),
+        "failed tests should print their errors, got:\n{stdout}"
+    );
+
+    let xml = fs::read_to_string(&junit).expect("lune test should write JUnit XML");
+    assert_eq!(attribute(&xml, "testsuites", "tests"), Some("2"));
+    assert_eq!(attribute(&xml, "testsuites", "failures"), Some("1"));
+    assert_eq!(attribute(&xml, "testsuites", "errors"), Some("0"));
+    assert!(xml.contains(r#"<testcase name="math &gt; adds" classname="passing.luau""#));
+    assert!(xml.contains(r#"<testcase name="math &gt; compares" classname="failing.luau""#));
+    assert!(xml.contains(r#"<failure message=""#));
+}
+
+#[test]
+fn test_command_succeeds_without_failures() {
+    let output = Command::new(env!("CARGO_BIN_EXE_lune"))
+        .current_dir(FIXTURES_DIR)
+        .args(["test", "passing.luau"])
+        .output()
+        .expect("failed to run lune test");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "passing tests should exit with 0"
+    );
+    assert!(
+        stdout.contains("Tests: 1 passed, 1 total"),
+        "summary should count passed tests, got:\n{stdout}"
+    );
+}